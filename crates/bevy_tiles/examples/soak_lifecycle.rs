@@ -0,0 +1,294 @@
+//! Randomly spawns and despawns maps, chunks, and tiles from several systems every frame, checking the
+//! bookkeeping between maps and their chunks after each frame.
+//!
+//! Commands regularly target maps and chunks that were despawned earlier in the same frame, which is where
+//! lifecycle bugs (stale chunk ids, edits to despawned maps) tend to hide.
+//! Runs for `SOAK_FRAMES` frames (10,000 by default), set it to 0 to run until stopped.
+
+use std::{
+    any::TypeId,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use bevy::{log::LogPlugin, prelude::*};
+use bevy_tiles::{
+    chunks::{ChunkCoord, ChunkData, ChunkTypes, InMap},
+    commands::{MissingMapPolicy, TileCommandExt},
+    maps::{AutoDespawnEmptyChunks, TileDims, TileMap, UseTransforms},
+    TilesPlugin,
+};
+
+const CHUNK_SIZE: usize = 4;
+const MAX_MAPS: usize = 4;
+/// Tiles are edited between `-EXTENT` and `EXTENT` on every axis.
+const EXTENT: i32 = 24;
+
+fn main() {
+    let frames = std::env::var("SOAK_FRAMES")
+        .ok()
+        .and_then(|frames| frames.parse().ok())
+        .unwrap_or(10_000);
+    App::new()
+        .add_plugins((MinimalPlugins, LogPlugin::default(), TilesPlugin))
+        .insert_resource(MissingMapPolicy::Ignore)
+        .insert_resource(Soak {
+            frame: 0,
+            frames,
+            maps: Vec::new(),
+        })
+        .add_systems(
+            Update,
+            (
+                churn_maps,
+                churn_chunks,
+                churn_tiles,
+                churn_tiles,
+                churn_tiles,
+            ),
+        )
+        .add_systems(Last, check_invariants)
+        .run();
+}
+
+#[derive(Resource)]
+struct Soak {
+    frame: u64,
+    frames: u64,
+    /// Every map spawned so far, including despawned ones so commands keep targeting them.
+    maps: Vec<Entity>,
+}
+
+/// A small xorshift generator, every system gets its own stream.
+struct Rng(u64);
+
+static SEEDS: AtomicU64 = AtomicU64::new(0x9E37_79B9_7F4A_7C15);
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self(SEEDS.fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed) | 1)
+    }
+}
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn chance(&mut self, p: f64) -> bool {
+        (self.next() % 10_000) as f64 / 10_000.0 < p
+    }
+
+    fn range(&mut self, min: i32, max: i32) -> i32 {
+        min + (self.next() % (max - min + 1) as u64) as i32
+    }
+
+    fn tile_c(&mut self) -> [i32; 2] {
+        [self.range(-EXTENT, EXTENT), self.range(-EXTENT, EXTENT)]
+    }
+
+    fn chunk_c(&mut self) -> [i32; 2] {
+        let extent = EXTENT / CHUNK_SIZE as i32;
+        [self.range(-extent, extent), self.range(-extent, extent)]
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> Option<T> {
+        (!items.is_empty()).then(|| items[self.next() as usize % items.len()])
+    }
+}
+
+/// Spawns and despawns whole maps.
+fn churn_maps(
+    mut commands: Commands,
+    mut rng: Local<Rng>,
+    mut soak: ResMut<Soak>,
+    maps: Query<Entity, With<TileMap>>,
+) {
+    let live: Vec<Entity> = maps.iter().collect();
+    if live.len() < MAX_MAPS && rng.chance(0.2) {
+        let mut map = TileCommandExt::<2>::spawn_map(&mut commands, CHUNK_SIZE);
+        match rng.next() % 3 {
+            0 => {
+                map.insert(AutoDespawnEmptyChunks);
+            }
+            1 => {
+                map.insert((UseTransforms, TileDims([1.0, 1.0])));
+            }
+            _ => {}
+        }
+        soak.maps.push(map.id());
+    }
+    if rng.chance(0.02) {
+        if let Some(map_id) = rng.pick(&live) {
+            TileCommandExt::<2>::despawn_map(&mut commands, map_id);
+        }
+    }
+}
+
+/// Spawns, despawns, swaps, and fills chunks of random maps.
+fn churn_chunks(mut commands: Commands, mut rng: Local<Rng>, soak: Res<Soak>) {
+    for _ in 0..8 {
+        let Some(map_id) = rng.pick(&soak.maps) else {
+            return;
+        };
+        let chunk_c = rng.chunk_c();
+        match rng.next() % 8 {
+            0 => commands.spawn_chunk(map_id, chunk_c),
+            1 => {
+                commands.despawn_chunk(map_id, chunk_c);
+            }
+            2 => {
+                let other_c = rng.chunk_c();
+                commands.swap_chunks(map_id, chunk_c, other_c);
+            }
+            3 => {
+                commands.fill_chunk(map_id, chunk_c, rng.next() as u8);
+            }
+            4 => {
+                commands.clear_chunk::<u8>(map_id, chunk_c);
+            }
+            5 => {
+                commands.reserve_chunks_around(map_id, chunk_c, 1);
+            }
+            6 => {
+                let other_c = rng.chunk_c();
+                commands.despawn_chunk_batch(map_id, [chunk_c, other_c]);
+            }
+            _ => {
+                commands.insert_chunk_bundle(map_id, chunk_c, Name::new("dirty"));
+            }
+        }
+    }
+}
+
+/// Inserts, removes, and moves tiles of random maps.
+fn churn_tiles(mut commands: Commands, mut rng: Local<Rng>, soak: Res<Soak>) {
+    for _ in 0..32 {
+        let Some(map_id) = rng.pick(&soak.maps) else {
+            return;
+        };
+        let tile_c = rng.tile_c();
+        let other_c = rng.tile_c();
+        match rng.next() % 9 {
+            0 | 1 => commands.spawn_tile(map_id, tile_c, rng.next() as u8),
+            2 => commands.spawn_tile(map_id, tile_c, rng.chance(0.5)),
+            3 => {
+                commands.remove_tile::<u8>(map_id, tile_c);
+            }
+            4 => {
+                commands.remove_tile_batch::<bool, _>(map_id, [tile_c, other_c]);
+            }
+            5 => {
+                let value = rng.next() as u8;
+                commands.spawn_tile_batch(map_id, [tile_c, other_c], move |_| value);
+            }
+            6 => {
+                commands.move_tile::<u8>(map_id, tile_c, other_c);
+            }
+            7 => {
+                commands.swap_tiles::<bool>(map_id, tile_c, other_c);
+            }
+            _ => {
+                let shift = [tile_c[0] + 1, tile_c[1]];
+                commands.move_tile_batch::<u8, _>(map_id, [(tile_c, shift), (other_c, tile_c)]);
+            }
+        }
+    }
+}
+
+/// Panics if a map and its chunks disagree, and stops the app once enough frames have run.
+fn check_invariants(
+    mut soak: ResMut<Soak>,
+    maps: Query<(Entity, &TileMap)>,
+    chunks: Query<(
+        Entity,
+        &InMap,
+        &ChunkCoord<2>,
+        &ChunkTypes,
+        Option<&Parent>,
+        Has<ChunkData<u8>>,
+        Has<ChunkData<bool>>,
+    )>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let frame = soak.frame;
+    for (map_id, map) in maps.iter() {
+        let mut occupied: Option<([i32; 2], [i32; 2])> = None;
+        for (chunk_c, chunk_id) in map.get_chunks() {
+            let Ok((_, in_map, coord, _, parent, has_u8, _)) = chunks.get(*chunk_id) else {
+                panic!(
+                    "frame {frame}: map {map_id} has a stale chunk id {chunk_id} at {chunk_c:?}"
+                );
+            };
+            assert_eq!(
+                **in_map, map_id,
+                "frame {frame}: chunk {chunk_id} is in another map"
+            );
+            assert_eq!(
+                coord, chunk_c,
+                "frame {frame}: chunk {chunk_id} has the wrong coordinate"
+            );
+            assert_eq!(
+                parent.map(|parent| parent.get()),
+                Some(map_id),
+                "frame {frame}: chunk {chunk_id} isn't a child of its map"
+            );
+            if has_u8 {
+                let chunk_c: [i32; 2] = (*chunk_c).into();
+                let (min, max) = occupied.get_or_insert((chunk_c, chunk_c));
+                for i in 0..2 {
+                    min[i] = min[i].min(chunk_c[i]);
+                    max[i] = max[i].max(chunk_c[i]);
+                }
+            }
+        }
+        let expected = occupied.map(|(min, max)| {
+            (
+                min.map(|c| c * CHUNK_SIZE as i32),
+                max.map(|c| (c + 1) * CHUNK_SIZE as i32 - 1),
+            )
+        });
+        assert_eq!(
+            map.occupied_bounds::<u8>(),
+            expected,
+            "frame {frame}: map {map_id} has the wrong occupied bounds"
+        );
+    }
+
+    for (chunk_id, in_map, coord, types, _, has_u8, has_bool) in chunks.iter() {
+        let Ok((_, map)) = maps.get(**in_map) else {
+            panic!("frame {frame}: chunk {chunk_id} outlived its map");
+        };
+        assert_eq!(
+            map.get_from_chunk(*coord),
+            Some(chunk_id),
+            "frame {frame}: chunk {chunk_id} isn't in its map at {coord:?}"
+        );
+        assert_eq!(
+            types.0.contains(&TypeId::of::<u8>()),
+            has_u8,
+            "frame {frame}: chunk {chunk_id} has the wrong u8 layer type"
+        );
+        assert_eq!(
+            types.0.contains(&TypeId::of::<bool>()),
+            has_bool,
+            "frame {frame}: chunk {chunk_id} has the wrong bool layer type"
+        );
+    }
+
+    soak.frame += 1;
+    if soak.frame.is_multiple_of(1_000) {
+        info!(
+            "Frame {}: {} maps, {} chunks",
+            soak.frame,
+            maps.iter().len(),
+            chunks.iter().len()
+        );
+    }
+    if soak.frame == soak.frames {
+        info!("Soaked for {} frames, no invariants broken.", soak.frames);
+        exit.send(AppExit::Success);
+    }
+}
@@ -0,0 +1,130 @@
+//! Streams a 10 million tile world through a headless app.
+//!
+//! A focus point walks across the world, chunks within range of it are generated from noise and chunks that
+//! fall out of range are despawned, while the tiles around the focus are edited every frame.
+//! Nothing is rendered, so this doubles as a stress test and a starting point for large worlds.
+
+use bevy::{
+    diagnostic::{
+        Diagnostic, DiagnosticPath, Diagnostics, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin,
+        RegisterDiagnostic,
+    },
+    log::LogPlugin,
+    prelude::*,
+};
+use bevy_tiles::{
+    commands::TileCommandExt,
+    coords::{calculate_tile_index, CoordIterator},
+    maps::TileMap,
+    noise::NoiseConfig,
+    TilesPlugin,
+};
+
+const CHUNK_SIZE: usize = 32;
+const VIEW_RADIUS: i32 = 8;
+const WORLD_TILES: usize = 10_000_000;
+
+const RESIDENT_CHUNKS: DiagnosticPath = DiagnosticPath::const_new("world/resident_chunks");
+const GENERATED_TILES: DiagnosticPath = DiagnosticPath::const_new("world/generated_tiles");
+
+fn main() {
+    App::new()
+        .add_plugins((
+            MinimalPlugins,
+            LogPlugin::default(),
+            TilesPlugin,
+            FrameTimeDiagnosticsPlugin,
+            LogDiagnosticsPlugin::default(),
+        ))
+        .register_diagnostic(Diagnostic::new(RESIDENT_CHUNKS))
+        .register_diagnostic(Diagnostic::new(GENERATED_TILES))
+        .add_systems(Startup, spawn)
+        .add_systems(Update, (stream_chunks, edit_tiles, report).chain())
+        .run();
+}
+
+#[derive(Resource)]
+struct Stream {
+    map_id: Entity,
+    focus: [i32; 2],
+    generated: usize,
+    noise: NoiseConfig,
+}
+
+fn spawn(mut commands: Commands) {
+    let map_id = TileCommandExt::<2>::spawn_map(&mut commands, CHUNK_SIZE).id();
+    commands.insert_resource(Stream {
+        map_id,
+        focus: [0, 0],
+        generated: 0,
+        noise: NoiseConfig::with_seed(42),
+    });
+}
+
+/// Moves the focus a chunk each frame, generating chunks coming into range and despawning the rest.
+fn stream_chunks(
+    mut commands: Commands,
+    mut stream: ResMut<Stream>,
+    maps: Query<&TileMap>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if stream.generated >= WORLD_TILES {
+        info!("Streamed {} tiles, done.", stream.generated);
+        exit.send(AppExit::Success);
+        return;
+    }
+    stream.focus[0] += 1;
+    let focus = stream.focus;
+    let Ok(map) = maps.get(stream.map_id) else {
+        return;
+    };
+    let mut map_commands = TileCommandExt::<2>::tile_map(&mut commands, stream.map_id).unwrap();
+
+    let in_range = |chunk_c: [i32; 2]| (0..2).all(|i| (chunk_c[i] - focus[i]).abs() <= VIEW_RADIUS);
+    for chunk_c in map
+        .get_chunks()
+        .keys()
+        .filter(|chunk_c| !in_range(***chunk_c))
+    {
+        map_commands.despawn_chunk(**chunk_c);
+    }
+
+    let min = focus.map(|c| c - VIEW_RADIUS);
+    let max = focus.map(|c| c + VIEW_RADIUS);
+    for chunk_c in CoordIterator::new(min, max) {
+        if map.get_from_chunk(IVec2::from(chunk_c).into()).is_none() {
+            map_commands.set_chunk_dense(chunk_c, generate(&stream.noise, chunk_c));
+            stream.generated += CHUNK_SIZE.pow(2);
+        }
+    }
+}
+
+/// Heights from noise for every tile in a chunk, in tile index order.
+fn generate(noise: &NoiseConfig, chunk_c: [i32; 2]) -> Vec<u8> {
+    let mut tiles = vec![0; CHUNK_SIZE.pow(2)];
+    let min = chunk_c.map(|c| c * CHUNK_SIZE as i32);
+    let max = min.map(|c| c + CHUNK_SIZE as i32 - 1);
+    for tile_c in CoordIterator::new(min, max) {
+        let height = (noise.sample_tile(tile_c) + 1.0) * 127.5;
+        tiles[calculate_tile_index(tile_c, CHUNK_SIZE)] = height as u8;
+    }
+    tiles
+}
+
+/// Digs a crater at the focus, with a hole in the middle.
+fn edit_tiles(mut commands: Commands, stream: Res<Stream>) {
+    let center = stream.focus.map(|c| c * CHUNK_SIZE as i32);
+    let mut map = TileCommandExt::<2>::tile_map(&mut commands, stream.map_id).unwrap();
+    for tile_c in CoordIterator::new(center.map(|c| c - 4), center.map(|c| c + 4)) {
+        map.insert_tile(tile_c, 0u8);
+    }
+    map.remove_tile::<u8>(center);
+}
+
+fn report(mut diagnostics: Diagnostics, stream: Res<Stream>, maps: Query<&TileMap>) {
+    let Ok(map) = maps.get(stream.map_id) else {
+        return;
+    };
+    diagnostics.add_measurement(&RESIDENT_CHUNKS, || map.get_chunks().len() as f64);
+    diagnostics.add_measurement(&GENERATED_TILES, || stream.generated as f64);
+}
@@ -0,0 +1,102 @@
+use crate::{coords::LineIterator, labels::RegionLabels, tiles::TileQuery};
+
+/// How the tilemap sits between a sound emitter and a listener.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SoundPath {
+    /// Number of blocking tiles on the line between the listener and emitter, not counting the endpoints.
+    pub blocking: usize,
+    /// Region the listener is standing in.
+    pub listener_region: Option<u32>,
+    /// Region the emitter is standing in.
+    pub emitter_region: Option<u32>,
+}
+
+impl SoundPath {
+    /// True if the emitter has an unblocked line to the listener.
+    #[inline]
+    pub fn is_clear(&self) -> bool {
+        self.blocking == 0
+    }
+
+    /// True if the listener and emitter are in the same labeled region, such as the same room.
+    #[inline]
+    pub fn shares_region(&self) -> bool {
+        self.listener_region.is_some() && self.listener_region == self.emitter_region
+    }
+}
+
+/// Counts the tiles matching `blocks` on the line between `listener` and `emitter`, not counting the endpoints.
+pub fn occlusion<T, F, const N: usize>(
+    tiles: &TileQuery<'_, '_, '_, &T, N>,
+    listener: impl Into<[i32; N]>,
+    emitter: impl Into<[i32; N]>,
+    blocks: F,
+) -> usize
+where
+    T: Send + Sync + 'static,
+    F: Fn(&T) -> bool,
+{
+    let listener = listener.into();
+    let emitter = emitter.into();
+    LineIterator::new(listener, emitter)
+        .filter(|tile_c| *tile_c != listener && *tile_c != emitter)
+        .filter(|tile_c| tiles.get_at(*tile_c).is_some_and(&blocks))
+        .count()
+}
+
+/// Calculates the [`SoundPath`] between a `listener` and an `emitter`, using `regions` to find the enclosing
+/// region (usually rooms labeled with [`crate::labels::label_regions`]) of each end.
+/// # Note
+/// The labels aren't recalculated here, so they should be refreshed when the layer they were made from changes.
+pub fn sound_path<T, F, const N: usize>(
+    tiles: &TileQuery<'_, '_, '_, &T, N>,
+    regions: &RegionLabels<N>,
+    listener: impl Into<[i32; N]>,
+    emitter: impl Into<[i32; N]>,
+    blocks: F,
+) -> SoundPath
+where
+    T: Send + Sync + 'static,
+    F: Fn(&T) -> bool,
+{
+    let listener = listener.into();
+    let emitter = emitter.into();
+    SoundPath {
+        blocking: occlusion(tiles, listener, emitter, blocks),
+        listener_region: regions.get(listener),
+        emitter_region: regions.get(emitter),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{system::SystemState, world::World};
+
+    use crate::{coords::CoordIterator, labels::label_regions, testing, tiles::TileMapQuery};
+
+    use super::*;
+
+    #[test]
+    fn walls_occlude_and_split_rooms() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            // Two rooms of floor split by a wall at x = 0.
+            for tile_c in CoordIterator::new([-5, -2], [5, 2]) {
+                map.insert_tile(tile_c, tile_c[0] == 0);
+            }
+        });
+
+        let mut state = SystemState::<TileMapQuery<&bool>>::new(&mut world);
+        let tile_maps = state.get(&world);
+        let tiles = tile_maps.get_map(map_id).unwrap();
+        let rooms = label_regions(&tiles, [-5, -2], [5, 2], |wall| !*wall);
+
+        let same_room = sound_path(&tiles, &rooms, [-4, 0], [-1, 1], |wall| *wall);
+        assert!(same_room.is_clear());
+        assert!(same_room.shares_region());
+
+        let next_room = sound_path(&tiles, &rooms, [-4, 0], [4, 0], |wall| *wall);
+        assert_eq!(next_room.blocking, 1);
+        assert!(!next_room.shares_region());
+    }
+}
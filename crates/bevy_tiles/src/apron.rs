@@ -0,0 +1,212 @@
+#[cfg(feature = "trace")]
+use bevy::log::info_span;
+use bevy::{
+    ecs::{entity::Entity, system::Query},
+    tasks::{ComputeTaskPool, TaskPool},
+};
+
+use crate::{
+    chunks::ChunkData,
+    coords::{calculate_tile_index, CoordIterator},
+    maps::TileMap,
+};
+
+/// A read only view of a layer over a chunk and a border (the apron) of its neighbors.
+pub struct Apron<'a, T, const N: usize> {
+    min: [i32; N],
+    max: [i32; N],
+    values: Vec<Option<&'a T>>,
+}
+
+impl<'a, T, const N: usize> Apron<'a, T, N> {
+    /// The lowest corner of the apron.
+    pub fn min(&self) -> [i32; N] {
+        self.min
+    }
+
+    /// The highest corner of the apron.
+    pub fn max(&self) -> [i32; N] {
+        self.max
+    }
+
+    /// Get a tile, returns [`None`] if the tile is empty or outside of the apron.
+    #[inline]
+    pub fn get(&self, tile_c: impl Into<[i32; N]>) -> Option<&'a T> {
+        let tile_c = tile_c.into();
+        let mut index = 0;
+        let mut stride = 1;
+        for (i, c) in tile_c.into_iter().enumerate() {
+            if c < self.min[i] || c > self.max[i] {
+                return None;
+            }
+            index += (c - self.min[i]) as usize * stride;
+            stride *= (self.max[i] - self.min[i] + 1) as usize;
+        }
+        self.values[index]
+    }
+}
+
+/// A writable copy of the tiles of a single chunk.
+pub struct ChunkView<T, const N: usize> {
+    chunk_c: [i32; N],
+    chunk_size: usize,
+    tiles: Vec<Option<T>>,
+    changed: bool,
+}
+
+impl<T, const N: usize> ChunkView<T, N> {
+    /// The coordinate of the chunk.
+    pub fn chunk_c(&self) -> [i32; N] {
+        self.chunk_c
+    }
+
+    /// Iterate over the coordinates of every tile in the chunk.
+    pub fn tiles(&self) -> CoordIterator<N> {
+        let min = self.chunk_c.map(|c| c * self.chunk_size as i32);
+        CoordIterator::new(min, min.map(|c| c + self.chunk_size as i32 - 1))
+    }
+
+    /// Get a tile, returns [`None`] if the tile is empty or outside of the chunk.
+    #[inline]
+    pub fn get(&self, tile_c: impl Into<[i32; N]>) -> Option<&T> {
+        let tile_c = tile_c.into();
+        self.contains(tile_c)
+            .then(|| self.tiles[calculate_tile_index(tile_c, self.chunk_size)].as_ref())
+            .flatten()
+    }
+
+    /// Set or clear a tile, returning the replaced value.  Tiles outside of the chunk are ignored.
+    #[inline]
+    pub fn set(&mut self, tile_c: impl Into<[i32; N]>, value: Option<T>) -> Option<T> {
+        let tile_c = tile_c.into();
+        if !self.contains(tile_c) {
+            return None;
+        }
+        self.changed = true;
+        let tile_i = calculate_tile_index(tile_c, self.chunk_size);
+        std::mem::replace(&mut self.tiles[tile_i], value)
+    }
+
+    #[inline]
+    fn contains(&self, tile_c: [i32; N]) -> bool {
+        let size = self.chunk_size as i32;
+        tile_c
+            .iter()
+            .zip(self.chunk_c)
+            .all(|(c, chunk_c)| (chunk_c * size..(chunk_c + 1) * size).contains(c))
+    }
+}
+
+/// Run a function over every chunk of a map with `T` data, in parallel.  The function gets a read only view of
+/// the chunk padded by `apron` tiles of its neighbors, and a writable copy of the chunk.
+/// # Note
+/// Every function reads the layer as it was before any chunk was written, so cellular automata, blurs, and autotiling
+/// see consistent values across chunk edges.  Writes are applied once every chunk is done, and only to chunks that
+/// already had `T` data.
+pub fn for_each_chunk_with_apron<T, F, const N: usize>(
+    map: &TileMap<N>,
+    chunks: &mut Query<&mut ChunkData<T>>,
+    apron: u32,
+    f: F,
+) where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&Apron<T, N>, &mut ChunkView<T, N>) + Sync,
+{
+    #[cfg(feature = "trace")]
+    let _span = info_span!(
+        "for_each_chunk_with_apron",
+        layer = std::any::type_name::<T>(),
+        chunks = map.get_chunks().len()
+    )
+    .entered();
+    let chunk_size = map.get_chunk_size();
+    let apron = apron as i32;
+    let f = &f;
+    let read = &*chunks;
+
+    let written: Vec<(Entity, Vec<Option<T>>)> = ComputeTaskPool::get_or_init(TaskPool::default)
+        .scope(|scope| {
+            for (chunk_c, chunk_id) in map.get_chunks() {
+                let Ok(core) = read.get(*chunk_id) else {
+                    continue;
+                };
+                scope.spawn(async move {
+                    let min = chunk_c.map(|c| c * chunk_size as i32 - apron);
+                    let max = chunk_c.map(|c| (c + 1) * chunk_size as i32 - 1 + apron);
+                    let values = CoordIterator::new(min, max)
+                        .map(|tile_c| {
+                            read.get(map.get_from_tile(tile_c)?)
+                                .ok()?
+                                .get(calculate_tile_index(tile_c, chunk_size))
+                        })
+                        .collect();
+                    let apron = Apron { min, max, values };
+                    let mut view = ChunkView {
+                        chunk_c: chunk_c.0,
+                        chunk_size,
+                        tiles: core.tiles.clone(),
+                        changed: false,
+                    };
+                    f(&apron, &mut view);
+                    view.changed.then_some((*chunk_id, view.tiles))
+                });
+            }
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+
+    for (chunk_id, tiles) in written {
+        if let Ok(mut chunk) = chunks.get_mut(chunk_id) {
+            *chunk = ChunkData::from_tiles(tiles);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{system::SystemState, world::World};
+
+    use crate::{testing, tiles::TileMapQuery};
+
+    use super::*;
+
+    #[test]
+    fn life_across_chunk_edges() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            for x in -4..8 {
+                for y in -4..8 {
+                    map.insert_tile([x, y], false);
+                }
+            }
+            // A blinker straddling the corner of four chunks.
+            for tile_c in [[-1, 0], [0, 0], [1, 0]] {
+                map.insert_tile(tile_c, true);
+            }
+        });
+
+        let mut state =
+            SystemState::<(Query<&TileMap<2>>, Query<&mut ChunkData<bool>>)>::new(&mut world);
+        for _ in 0..3 {
+            let (maps, mut chunks) = state.get_mut(&mut world);
+            for_each_chunk_with_apron(maps.get(map_id).unwrap(), &mut chunks, 1, |apron, view| {
+                for [x, y] in view.tiles() {
+                    let alive = CoordIterator::new([x - 1, y - 1], [x + 1, y + 1])
+                        .filter(|c| *c != [x, y] && apron.get(*c) == Some(&true))
+                        .count();
+                    let next = matches!((apron.get([x, y]), alive), (Some(true), 2) | (_, 3));
+                    view.set([x, y], Some(next));
+                }
+            });
+        }
+
+        let mut state = SystemState::<TileMapQuery<&bool>>::new(&mut world);
+        let tile_maps = state.get(&world);
+        let tiles = tile_maps.get_map(map_id).unwrap();
+        let alive: Vec<[i32; 2]> = CoordIterator::new([-4, -4], [7, 7])
+            .filter(|c| tiles.get_at(*c) == Some(&true))
+            .collect();
+        assert_eq!(alive, vec![[0, -1], [0, 0], [0, 1]]);
+    }
+}
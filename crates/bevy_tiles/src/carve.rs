@@ -0,0 +1,116 @@
+use bevy::utils::HashMap;
+
+use crate::coords::CoordIterator;
+
+/// The shape of the brush dragged along a path when carving.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathBrush {
+    /// Distance from the path where tiles are fully affected (weight of 1).
+    pub radius: f32,
+    /// Distance past `radius` over which the weight fades linearly to 0.
+    pub falloff: f32,
+}
+
+impl PathBrush {
+    /// Create a brush with a hard edge.
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius,
+            falloff: 0.0,
+        }
+    }
+
+    /// Set the falloff distance of this brush.
+    pub fn with_falloff(mut self, falloff: f32) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    /// Calculate the weight of a tile given it's distance from the path.
+    #[inline]
+    pub fn weight(&self, distance: f32) -> f32 {
+        if distance <= self.radius {
+            1.0
+        } else if self.falloff > 0.0 && distance < self.radius + self.falloff {
+            1.0 - (distance - self.radius) / self.falloff
+        } else {
+            0.0
+        }
+    }
+
+    /// Calculate the weight of every tile touched by this brush when dragged along the given path.
+    /// # Note
+    /// Path points are given in tile coordinates, with each tile sitting on its integer coordinate.
+    /// Tiles touched by multiple segments keep their highest weight.
+    pub fn stroke<const N: usize>(
+        &self,
+        path: impl IntoIterator<Item = [f32; N]>,
+    ) -> HashMap<[i32; N], f32> {
+        let reach = self.radius + self.falloff;
+        let mut weights = HashMap::new();
+        let mut points = path.into_iter();
+        let Some(mut start) = points.next() else {
+            return weights;
+        };
+        let mut stroke_segment = |start: [f32; N], end: [f32; N]| {
+            let mut corner_1 = [0; N];
+            let mut corner_2 = [0; N];
+            for i in 0..N {
+                corner_1[i] = (start[i].min(end[i]) - reach).floor() as i32;
+                corner_2[i] = (start[i].max(end[i]) + reach).ceil() as i32;
+            }
+            for tile_c in CoordIterator::new(corner_1, corner_2) {
+                let weight = self.weight(distance_to_segment(tile_c.map(|c| c as f32), start, end));
+                if weight > 0.0 {
+                    let entry = weights.entry(tile_c).or_insert(0.0);
+                    *entry = weight.max(*entry);
+                }
+            }
+        };
+
+        stroke_segment(start, start);
+        for end in points {
+            stroke_segment(start, end);
+            start = end;
+        }
+        weights
+    }
+}
+
+#[inline]
+fn distance_to_segment<const N: usize>(point: [f32; N], start: [f32; N], end: [f32; N]) -> f32 {
+    let mut length_sq = 0.0;
+    let mut projection = 0.0;
+    for i in 0..N {
+        let segment = end[i] - start[i];
+        length_sq += segment * segment;
+        projection += (point[i] - start[i]) * segment;
+    }
+    let t = if length_sq > 0.0 {
+        (projection / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let mut distance_sq = 0.0;
+    for i in 0..N {
+        let closest = start[i] + (end[i] - start[i]) * t;
+        distance_sq += (point[i] - closest) * (point[i] - closest);
+    }
+    distance_sq.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stroke_weights() {
+        let brush = PathBrush::new(1.0).with_falloff(2.0);
+        let weights = brush.stroke([[0.0, 0.0], [10.0, 0.0]]);
+        assert_eq!(weights.get(&[5, 0]), Some(&1.0));
+        assert_eq!(weights.get(&[5, 1]), Some(&1.0));
+        assert_eq!(weights.get(&[5, 2]), Some(&0.5));
+        assert_eq!(weights.get(&[5, 3]), None);
+        assert_eq!(weights.get(&[-2, 0]), Some(&0.5));
+    }
+}
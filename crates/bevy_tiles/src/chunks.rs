@@ -1,4 +1,7 @@
-use std::any::TypeId;
+use std::{
+    any::TypeId,
+    ops::{Add, Mul, Neg, Sub},
+};
 
 use bevy::{
     ecs::{component::Component, entity::Entity},
@@ -7,6 +10,8 @@ use bevy::{
     utils::HashSet,
 };
 
+use crate::coords::{calculate_chunk_coordinate, CoordIterator};
+
 mod chunk_query;
 
 pub use chunk_query::*;
@@ -27,6 +32,112 @@ pub struct InMap(pub(crate) Entity);
 #[derive(Component, Deref, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct ChunkCoord<const N: usize>(pub(crate) [i32; N]);
 
+impl<const N: usize> ChunkCoord<N> {
+    /// Create a chunk coordinate.
+    #[inline]
+    pub fn new(chunk_c: impl Into<[i32; N]>) -> Self {
+        Self(chunk_c.into())
+    }
+
+    /// Get the chunk coordinate of the chunk a tile is in.
+    #[inline]
+    pub fn from_tile(tile_c: impl Into<[i32; N]>, chunk_size: usize) -> Self {
+        Self(calculate_chunk_coordinate(tile_c, chunk_size))
+    }
+
+    /// Get the chunk coordinate shifted by `dir` chunks.
+    #[inline]
+    pub fn offset(&self, dir: impl Into<[i32; N]>) -> Self {
+        *self + dir.into()
+    }
+
+    /// Iterate over the chunks touching this one, including diagonals.
+    pub fn neighbors(&self) -> impl Iterator<Item = ChunkCoord<N>> {
+        let center = *self;
+        CoordIterator::new(self.0.map(|c| c - 1), self.0.map(|c| c + 1))
+            .map(ChunkCoord)
+            .filter(move |chunk_c| *chunk_c != center)
+    }
+
+    /// Get the coordinate of the lowest tile in this chunk.
+    #[inline]
+    pub fn to_tile_origin(&self, chunk_size: usize) -> [i32; N] {
+        self.0.map(|c| c * chunk_size as i32)
+    }
+
+    /// Check if a tile is in this chunk.
+    #[inline]
+    pub fn contains_tile(&self, tile_c: impl Into<[i32; N]>, chunk_size: usize) -> bool {
+        calculate_chunk_coordinate(tile_c, chunk_size) == self.0
+    }
+}
+
+impl<const N: usize> From<[i32; N]> for ChunkCoord<N> {
+    fn from(value: [i32; N]) -> Self {
+        Self(value)
+    }
+}
+
+impl<const N: usize> From<ChunkCoord<N>> for [i32; N] {
+    fn from(value: ChunkCoord<N>) -> Self {
+        value.0
+    }
+}
+
+impl<const N: usize> Add<[i32; N]> for ChunkCoord<N> {
+    type Output = Self;
+
+    fn add(mut self, rhs: [i32; N]) -> Self::Output {
+        for (c, o) in self.0.iter_mut().zip(rhs) {
+            *c += o;
+        }
+        self
+    }
+}
+
+impl<const N: usize> Add for ChunkCoord<N> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self + rhs.0
+    }
+}
+
+impl<const N: usize> Sub<[i32; N]> for ChunkCoord<N> {
+    type Output = Self;
+
+    fn sub(mut self, rhs: [i32; N]) -> Self::Output {
+        for (c, o) in self.0.iter_mut().zip(rhs) {
+            *c -= o;
+        }
+        self
+    }
+}
+
+impl<const N: usize> Sub for ChunkCoord<N> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self - rhs.0
+    }
+}
+
+impl<const N: usize> Mul<i32> for ChunkCoord<N> {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        Self(self.0.map(|c| c * rhs))
+    }
+}
+
+impl<const N: usize> Neg for ChunkCoord<N> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self * -1
+    }
+}
+
 impl From<IVec2> for ChunkCoord<2> {
     fn from(value: IVec2) -> Self {
         Self(value.into())
@@ -54,6 +165,12 @@ impl<T> ChunkData<T> {
         Self { tiles, count: 0 }
     }
 
+    /// Create a ChunkData from a full buffer of tiles.
+    pub fn from_tiles(tiles: Vec<Option<T>>) -> Self {
+        let count = tiles.iter().filter(|tile| tile.is_some()).count();
+        Self { tiles, count }
+    }
+
     /// Get tile data at a given index.
     pub fn get(&self, tile_i: usize) -> Option<&T> {
         self.tiles.get(tile_i).and_then(|f| f.as_ref())
@@ -64,6 +181,16 @@ impl<T> ChunkData<T> {
         self.tiles.get_mut(tile_i).and_then(|f| f.as_mut())
     }
 
+    /// Iterate over the tile data of every tile, in tile index order.
+    pub fn iter(&self) -> impl Iterator<Item = Option<&T>> {
+        self.tiles.iter().map(Option::as_ref)
+    }
+
+    /// Iterate over the tile data of every tile, in tile index order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = Option<&mut T>> {
+        self.tiles.iter_mut().map(Option::as_mut)
+    }
+
     pub(crate) fn get_mut_raw(&mut self, tile_i: usize) -> &mut Option<T> {
         self.tiles.get_mut(tile_i).expect("Out of index {}")
     }
@@ -78,7 +205,7 @@ impl<T> ChunkData<T> {
     /// Insert the value at this index.
     pub fn insert(&mut self, tile_i: usize, value: T) -> Option<T> {
         let target = self.get_mut_raw(tile_i);
-        let replaced = std::mem::replace(target, Some(value));
+        let replaced = target.replace(value);
         replaced.is_none().then(|| self.count += 1);
         replaced
     }
@@ -87,9 +214,45 @@ impl<T> ChunkData<T> {
     pub fn get_count(&self) -> usize {
         self.count
     }
+
+    /// Estimate the memory used by this chunk data in bytes.
+    pub fn memory_estimate(&self) -> usize {
+        size_of::<Self>() + self.tiles.capacity() * size_of::<Option<T>>()
+    }
 }
 
 /// Holds a registry of all data types on a chunk, used to decide
 /// if a chunk deserves to live :).
 #[derive(Component, Default, Debug)]
 pub struct ChunkTypes(pub HashSet<TypeId>);
+
+/// Marks a chunk that keeps its [`ChunkData`] components when they're emptied, see [`crate::maps::MapLayers`].
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct FixedLayers;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_coord_math() {
+        let chunk_c = ChunkCoord::new([1, -2]);
+        assert_eq!(chunk_c.offset([0, 1]), ChunkCoord([1, -1]));
+        assert_eq!(chunk_c - ChunkCoord([1, 1]), ChunkCoord([0, -3]));
+        assert_eq!(-chunk_c * 2, ChunkCoord([-2, 4]));
+
+        let neighbors: Vec<_> = chunk_c.neighbors().collect();
+        assert_eq!(neighbors.len(), 8);
+        assert!(!neighbors.contains(&chunk_c));
+        assert!(neighbors.contains(&ChunkCoord([0, -3])));
+
+        assert_eq!(chunk_c.to_tile_origin(4), [4, -8]);
+        assert!(chunk_c.contains_tile([7, -5], 4));
+        assert!(!chunk_c.contains_tile([8, -5], 4));
+        assert_eq!(ChunkCoord::<2>::from_tile([7, -5], 4), chunk_c);
+        assert_eq!(
+            ChunkCoord::<3>::from_tile([-1, 0, 5], 3),
+            ChunkCoord([-1, 0, 1])
+        );
+    }
+}
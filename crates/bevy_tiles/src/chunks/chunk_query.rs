@@ -1,8 +1,17 @@
+use std::fmt;
+#[cfg(feature = "strict-safety")]
+use std::marker::PhantomData;
+
+#[cfg(feature = "strict-safety")]
+use bevy::utils::HashMap;
 use bevy::{
     ecs::{
         entity::Entity,
         prelude::With,
-        query::{QueryData, QueryFilter, WorldQuery},
+        query::{
+            QueryData, QueryFilter, QueryItem, QuerySingleError, ROQueryItem, ReadOnlyQueryData,
+            WorldQuery,
+        },
         system::SystemParam,
     },
     prelude::Query,
@@ -16,43 +25,213 @@ use crate::{
 
 use super::ChunkTypes;
 
+/// Why a map couldn't be resolved by a map query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapQueryError {
+    /// The entity doesn't exist, or isn't a map with the queried dimensions.
+    NotAMap(Entity),
+    /// No maps matched the query.
+    NoMaps,
+    /// More than one map matched the query.
+    MultipleMaps,
+}
+
+impl fmt::Display for MapQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapQueryError::NotAMap(map_id) => {
+                write!(f, "entity {map_id} isn't a map matching the query")
+            }
+            MapQueryError::NoMaps => write!(f, "no maps matched the query"),
+            MapQueryError::MultipleMaps => write!(f, "more than one map matched the query"),
+        }
+    }
+}
+
+impl std::error::Error for MapQueryError {}
+
+impl From<QuerySingleError> for MapQueryError {
+    fn from(value: QuerySingleError) -> Self {
+        match value {
+            QuerySingleError::NoEntities(_) => MapQueryError::NoMaps,
+            QuerySingleError::MultipleEntities(_) => MapQueryError::MultipleMaps,
+        }
+    }
+}
+
 /// Used to query chunks from any tile map.
 /// This query also implicitly queries maps
 /// in order to properly resolve chunks.
+/// # Note
+/// `F` filters chunks, and `MF` filters maps (ex: `With<GameLayer>`), so
+/// [`ChunkMapQuery::get_single_map`] can resolve one map out of many.
+/// `M` is readonly data fetched from the map alongside chunks (ex: `(&TileDims<2>, Option<&TileSpacing<2>>)`).
 #[derive(SystemParam)]
-pub struct ChunkMapQuery<'w, 's, Q, F = (), const N: usize = 2>
+pub struct ChunkMapQuery<'w, 's, Q, F = (), MF = (), M = (), const N: usize = 2>
 where
     Q: QueryData + 'static,
     F: QueryFilter + 'static,
+    MF: QueryFilter + 'static,
+    M: ReadOnlyQueryData + 'static,
 {
-    chunk_q: Query<'w, 's, Q, (F, With<InMap>, With<ChunkTypes>)>,
-    map_q: Query<'w, 's, &'static TileMap<N>>,
+    chunk_q: Query<'w, 's, (Entity, Q, &'static InMap), (F, With<InMap>, With<ChunkTypes>)>,
+    map_q: Query<'w, 's, (&'static TileMap<N>, M), MF>,
 }
 
-impl<'w, 's, Q, F, const N: usize> ChunkMapQuery<'w, 's, Q, F, N>
+impl<'w, 's, Q, F, MF, M, const N: usize> ChunkMapQuery<'w, 's, Q, F, MF, M, N>
 where
     Q: QueryData + 'static,
     F: QueryFilter + 'static,
+    MF: QueryFilter + 'static,
+    M: ReadOnlyQueryData + 'static,
 {
     /// Gets the query for a given map.
-    pub fn get_map(&self, map_id: Entity) -> Option<ChunkQuery<'_, '_, 's, Q::ReadOnly, F, N>> {
-        let map = self.map_q.get(map_id).ok()?;
+    pub fn get_map(
+        &self,
+        map_id: Entity,
+    ) -> Result<ChunkQuery<'_, '_, 's, Q::ReadOnly, F, N>, MapQueryError> {
+        let (map, _) = self
+            .map_q
+            .get(map_id)
+            .map_err(|_| MapQueryError::NotAMap(map_id))?;
 
-        Some(ChunkQuery {
+        Ok(ChunkQuery {
             chunk_q: self.chunk_q.to_readonly(),
             map,
         })
     }
 
     /// Gets the query for a given map.
-    pub fn get_map_mut(&mut self, map_id: Entity) -> Option<ChunkQuery<'_, '_, 's, Q, F, N>> {
-        let map = self.map_q.get(map_id).ok()?;
+    pub fn get_map_mut(
+        &mut self,
+        map_id: Entity,
+    ) -> Result<ChunkQuery<'_, '_, 's, Q, F, N>, MapQueryError> {
+        let (map, _) = self
+            .map_q
+            .get(map_id)
+            .map_err(|_| MapQueryError::NotAMap(map_id))?;
 
-        Some(ChunkQuery {
+        Ok(ChunkQuery {
             chunk_q: self.chunk_q.reborrow(),
             map,
         })
     }
+
+    /// Gets the query for the only map, returns an error if there isn't exactly one.
+    /// # Note
+    /// The returned query holds onto the map, keep it around for the rest of the system
+    /// instead of looking the map up again.
+    pub fn get_single_map(
+        &self,
+    ) -> Result<ChunkQuery<'_, '_, 's, Q::ReadOnly, F, N>, MapQueryError> {
+        let (map, _) = self.map_q.get_single()?;
+
+        Ok(ChunkQuery {
+            chunk_q: self.chunk_q.to_readonly(),
+            map,
+        })
+    }
+
+    /// Gets the query for the only map, returns an error if there isn't exactly one.
+    pub fn get_single_map_mut(&mut self) -> Result<ChunkQuery<'_, '_, 's, Q, F, N>, MapQueryError> {
+        let (map, _) = self.map_q.get_single()?;
+
+        Ok(ChunkQuery {
+            chunk_q: self.chunk_q.reborrow(),
+            map,
+        })
+    }
+
+    /// Gets the query for the only map.
+    /// # Panics
+    /// Panics if there isn't exactly one map.
+    pub fn single_map(&self) -> ChunkQuery<'_, '_, 's, Q::ReadOnly, F, N> {
+        self.get_single_map()
+            .unwrap_or_else(|err| panic!("Couldn't get single map: {err}"))
+    }
+
+    /// Gets the query for the only map.
+    /// # Panics
+    /// Panics if there isn't exactly one map.
+    pub fn single_map_mut(&mut self) -> ChunkQuery<'_, '_, 's, Q, F, N> {
+        self.get_single_map_mut()
+            .unwrap_or_else(|err| panic!("Couldn't get single map: {err}"))
+    }
+
+    /// Gets the `M` data of a given map.
+    pub fn get_map_data(&self, map_id: Entity) -> Result<ROQueryItem<'_, M>, MapQueryError> {
+        self.map_q
+            .get(map_id)
+            .map(|(_, data)| data)
+            .map_err(|_| MapQueryError::NotAMap(map_id))
+    }
+
+    /// Gets the query for a given map, along with the map's `M` data.
+    pub fn get_map_with_data(
+        &self,
+        map_id: Entity,
+    ) -> Result<
+        (
+            ChunkQuery<'_, '_, 's, Q::ReadOnly, F, N>,
+            ROQueryItem<'_, M>,
+        ),
+        MapQueryError,
+    > {
+        let (map, data) = self
+            .map_q
+            .get(map_id)
+            .map_err(|_| MapQueryError::NotAMap(map_id))?;
+
+        Ok((
+            ChunkQuery {
+                chunk_q: self.chunk_q.to_readonly(),
+                map,
+            },
+            data,
+        ))
+    }
+
+    /// Gets the query for a given map, along with the map's `M` data.
+    pub fn get_map_with_data_mut(
+        &mut self,
+        map_id: Entity,
+    ) -> Result<(ChunkQuery<'_, '_, 's, Q, F, N>, ROQueryItem<'_, M>), MapQueryError> {
+        let (map, data) = self
+            .map_q
+            .get(map_id)
+            .map_err(|_| MapQueryError::NotAMap(map_id))?;
+
+        Ok((
+            ChunkQuery {
+                chunk_q: self.chunk_q.reborrow(),
+                map,
+            },
+            data,
+        ))
+    }
+
+    /// Iterate over every chunk of every map matching the query, along with the `M` data of the chunk's map.
+    pub fn iter_with_map_data(
+        &self,
+    ) -> impl Iterator<Item = (ROQueryItem<'_, Q>, ROQueryItem<'_, M>)> + '_ {
+        self.chunk_q.iter().filter_map(|(_, chunk, in_map)| {
+            let (_, data) = self.map_q.get(in_map.0).ok()?;
+            Some((chunk, data))
+        })
+    }
+
+    /// Iterate over every chunk of every map matching the query, along with the `M` data of the chunk's map.
+    pub fn iter_with_map_data_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (QueryItem<'_, Q>, ROQueryItem<'_, M>)> + '_ {
+        let map_q = &self.map_q;
+        self.chunk_q
+            .iter_mut()
+            .filter_map(move |(_, chunk, in_map)| {
+                let (_, data) = map_q.get(in_map.0).ok()?;
+                Some((chunk, data))
+            })
+    }
 }
 
 /// Used to query chunks from a tile map.
@@ -63,7 +242,7 @@ where
     Q: QueryData + 'static,
     F: QueryFilter + 'static,
 {
-    chunk_q: Query<'w, 's, Q, (F, With<InMap>, With<ChunkTypes>)>,
+    chunk_q: Query<'w, 's, (Entity, Q, &'static InMap), (F, With<InMap>, With<ChunkTypes>)>,
     /// The map being read.
     pub map: &'a TileMap<N>,
 }
@@ -100,7 +279,7 @@ where
         let chunk_c = chunk_c.into();
         let chunk_id = self.map.get_from_chunk(ChunkCoord(chunk_c))?;
 
-        self.chunk_q.get(chunk_id).ok()
+        self.chunk_q.get(chunk_id).ok().map(|(_, chunk, _)| chunk)
     }
 
     /// Get's the query item for the given chunk.
@@ -108,6 +287,7 @@ where
     /// This function makes it possible to violate Rust's aliasing guarantees: please use responsibly.
     /// # Note
     /// Coordinates are for these calls are in chunk coordinates.
+    /// Prefer [`ChunkQuery::get_at_mut`] where possible.
     #[inline]
     pub unsafe fn get_at_unchecked(
         &self,
@@ -116,7 +296,10 @@ where
         let chunk_c = chunk_c.into();
         let chunk_id = self.map.get_from_chunk(ChunkCoord(chunk_c))?;
 
-        self.chunk_q.get_unchecked(chunk_id).ok()
+        self.chunk_q
+            .get_unchecked(chunk_id)
+            .ok()
+            .map(|(_, chunk, _)| chunk)
     }
 
     /// Iterate over all the chunks in a given space, starting at `corner_1`
@@ -131,8 +314,16 @@ where
     ) -> ChunkQueryIter<'_, 's, Q::ReadOnly, F, N> {
         let corner_1 = corner_1.into();
         let corner_2 = corner_2.into();
+        #[cfg(not(feature = "strict-safety"))]
         // SAFETY: This thing is uses manual mem management
-        unsafe { ChunkQueryIter::from_owned(self.to_readonly(), corner_1, corner_2) }
+        return unsafe { ChunkQueryIter::from_owned(self.to_readonly(), corner_1, corner_2) };
+        #[cfg(feature = "strict-safety")]
+        return ChunkQueryIter::from_chunks(
+            self.collect_in(corner_1, corner_2)
+                .into_iter()
+                .map(|(_, chunk)| chunk)
+                .collect(),
+        );
     }
 
     /// Get's the query item for the given tile.
@@ -146,7 +337,10 @@ where
         let chunk_c = chunk_c.into();
         let chunk_id = self.map.get_from_chunk(ChunkCoord(chunk_c))?;
 
-        self.chunk_q.get_mut(chunk_id).ok()
+        self.chunk_q
+            .get_mut(chunk_id)
+            .ok()
+            .map(|(_, chunk, _)| chunk)
     }
 
     /// Iterate over all the chunks in a given space, starting at `corner_1`
@@ -161,15 +355,76 @@ where
     ) -> ChunkQueryIter<'_, 's, Q, F, N> {
         let corner_1 = corner_1.into();
         let corner_2 = corner_2.into();
+        #[cfg(not(feature = "strict-safety"))]
         // SAFETY: This thing is uses manual mem management
-        unsafe { ChunkQueryIter::from_owned(self.reborrow(), corner_1, corner_2) }
+        return unsafe { ChunkQueryIter::from_owned(self.reborrow(), corner_1, corner_2) };
+        #[cfg(feature = "strict-safety")]
+        return ChunkQueryIter::from_chunks(
+            self.collect_in_mut(corner_1, corner_2)
+                .into_iter()
+                .map(|(_, chunk)| chunk)
+                .collect(),
+        );
+    }
+
+    /// The chunks in a region along with their coordinates, in iteration order.
+    #[cfg(feature = "strict-safety")]
+    fn region(&self, corner_1: [i32; N], corner_2: [i32; N]) -> Vec<([i32; N], Entity)> {
+        CoordIterator::new(corner_1, corner_2)
+            .filter_map(|chunk_c| Some((chunk_c, self.map.get_from_chunk(ChunkCoord(chunk_c))?)))
+            .collect()
+    }
+
+    /// Collect the readonly query items for the chunks in a region, in iteration order.
+    #[cfg(feature = "strict-safety")]
+    pub(crate) fn collect_in(
+        &self,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+    ) -> Vec<([i32; N], ROQueryItem<'_, Q>)> {
+        self.region(corner_1, corner_2)
+            .into_iter()
+            .filter_map(|(chunk_c, chunk_id)| {
+                let (_, chunk, _) = self.chunk_q.get(chunk_id).ok()?;
+                Some((chunk_c, chunk))
+            })
+            .collect()
+    }
+
+    /// Collect the query items for the chunks in a region, in iteration order.
+    /// # Note
+    /// This walks every chunk matching the query, not just the ones in the region.
+    #[cfg(feature = "strict-safety")]
+    pub(crate) fn collect_in_mut(
+        &mut self,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+    ) -> Vec<([i32; N], QueryItem<'_, Q>)> {
+        let region = self.region(corner_1, corner_2);
+        let order: HashMap<Entity, usize> = region
+            .iter()
+            .enumerate()
+            .map(|(i, (_, chunk_id))| (*chunk_id, i))
+            .collect();
+        let mut chunks: Vec<(usize, QueryItem<'_, Q>)> = self
+            .chunk_q
+            .iter_mut()
+            .filter_map(|(chunk_id, chunk, _)| Some((*order.get(&chunk_id)?, chunk)))
+            .collect();
+        chunks.sort_unstable_by_key(|(i, _)| *i);
+        chunks
+            .into_iter()
+            .map(|(i, chunk)| (region[i].0, chunk))
+            .collect()
     }
 }
 // Everything below here is astoundingly unsafe but I think it's sound
 // If we're iterating over a readonly query, we're manually managing the lifetime of
 // the readonly query by making the TileQueryIter own it as a reference.
+// With the `strict-safety` feature the items are collected up front instead.
 
 /// Iterates over all the tiles in a region.
+#[cfg(not(feature = "strict-safety"))]
 pub struct ChunkQueryIter<'a, 's, Q, F, const N: usize>
 where
     Q: QueryData + 'static,
@@ -178,6 +433,19 @@ where
     coord_iter: CoordIterator<N>,
     chunk_q: ChunkQuery<'a, 'a, 's, Q, F, N>,
 }
+
+/// Iterates over all the tiles in a region.
+#[cfg(feature = "strict-safety")]
+pub struct ChunkQueryIter<'a, 's, Q, F, const N: usize>
+where
+    Q: QueryData + 'static,
+    F: QueryFilter + 'static,
+{
+    chunks: std::vec::IntoIter<Q::Item<'a>>,
+    marker: PhantomData<fn() -> (&'s (), F, [(); N])>,
+}
+
+#[cfg(not(feature = "strict-safety"))]
 impl<'a, 's, Q, F, const N: usize> ChunkQueryIter<'a, 's, Q, F, N>
 where
     Q: QueryData + 'static,
@@ -195,6 +463,21 @@ where
     }
 }
 
+#[cfg(feature = "strict-safety")]
+impl<'a, 's, Q, F, const N: usize> ChunkQueryIter<'a, 's, Q, F, N>
+where
+    Q: QueryData + 'static,
+    F: QueryFilter + 'static,
+{
+    fn from_chunks(chunks: Vec<Q::Item<'a>>) -> Self {
+        Self {
+            chunks: chunks.into_iter(),
+            marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(not(feature = "strict-safety"))]
 impl<'a, 's: 'a, Q, F, const N: usize> Iterator for ChunkQueryIter<'a, 's, Q, F, N>
 where
     Q: QueryData + 'static,
@@ -224,3 +507,51 @@ where
         None
     }
 }
+
+#[cfg(feature = "strict-safety")]
+impl<'a, 's: 'a, Q, F, const N: usize> Iterator for ChunkQueryIter<'a, 's, Q, F, N>
+where
+    Q: QueryData + 'static,
+    F: QueryFilter + 'static,
+{
+    type Item = Q::Item<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{system::SystemState, world::World};
+
+    use crate::{chunks::ChunkData, maps::TileDims, testing};
+
+    use super::*;
+
+    #[test]
+    fn chunks_with_map_data() {
+        let mut world = World::new();
+        let map_ids = [1.0, 2.0].map(|size| {
+            testing::spawn_map(&mut world, 4, |map| {
+                map.insert(TileDims([size, size]));
+                map.insert_tile([0, 0], 1u8);
+                map.insert_tile([5, 0], 1u8);
+            })
+        });
+
+        let mut state =
+            SystemState::<ChunkMapQuery<&mut ChunkData<u8>, (), (), &TileDims<2>>>::new(&mut world);
+        let mut chunk_maps = state.get_mut(&mut world);
+        let mut total = 0.0;
+        for (mut chunk, dims) in chunk_maps.iter_with_map_data_mut() {
+            chunk.insert(1, 2);
+            total += dims[0];
+        }
+        assert_eq!(total, 6.0);
+
+        let (chunks, dims) = chunk_maps.get_map_with_data(map_ids[1]).unwrap();
+        assert_eq!(dims[0], 2.0);
+        assert_eq!(chunks.get_at([1, 0]).unwrap().get(1), Some(&2));
+    }
+}
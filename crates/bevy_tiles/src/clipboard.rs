@@ -0,0 +1,476 @@
+use std::{
+    any::Any,
+    fmt::{self, Write},
+    str::FromStr,
+};
+
+use bevy::ecs::{entity::Entity, world::World};
+
+use crate::{
+    chunks::ChunkData,
+    commands::{insert_tile_batch, missing_map, TempRemove},
+    coords::{calculate_tile_index, CoordIterator},
+    maps::TileMap,
+    queries::TileComponent,
+};
+
+/// The tiles of one layer in a [`TileClipboard`].
+trait ClipLayer<const N: usize>: Send + Sync + 'static {
+    fn paste(&self, world: &mut World, map_id: Entity, offset: [i32; N]);
+
+    fn len(&self) -> usize;
+
+    fn clone_box(&self) -> Box<dyn ClipLayer<N>>;
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+struct LayerClip<T, const N: usize> {
+    tiles: Vec<([i32; N], T)>,
+}
+
+impl<T: TileComponent + Clone, const N: usize> ClipLayer<N> for LayerClip<T, N> {
+    fn paste(&self, world: &mut World, map_id: Entity, offset: [i32; N]) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(map_id) else {
+            return missing_map(world, map_id);
+        };
+        let (tile_cs, tiles): (Vec<[i32; N]>, Vec<T>) = self
+            .tiles
+            .iter()
+            .map(|(tile_c, tile)| {
+                let mut tile_c = *tile_c;
+                for (c, o) in tile_c.iter_mut().zip(offset) {
+                    *c += o;
+                }
+                (tile_c, tile.clone())
+            })
+            .unzip();
+        let _ = insert_tile_batch::<T, N>(&mut map, tile_cs, tiles);
+    }
+
+    fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    fn clone_box(&self) -> Box<dyn ClipLayer<N>> {
+        Box::new(LayerClip {
+            tiles: self.tiles.clone(),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Tile data copied out of a region of a map, detached from any map so it can be pasted anywhere.
+/// # Note
+/// Tiles are stored relative to the lowest corner of the copied region.
+pub struct TileClipboard<const N: usize = 2> {
+    size: [i32; N],
+    layers: Vec<Box<dyn ClipLayer<N>>>,
+}
+
+impl<const N: usize> Clone for TileClipboard<N> {
+    fn clone(&self) -> Self {
+        Self {
+            size: self.size,
+            layers: self.layers.iter().map(|layer| layer.clone_box()).collect(),
+        }
+    }
+}
+
+impl<const N: usize> TileClipboard<N> {
+    /// The size of the copied region along each axis.
+    pub fn size(&self) -> [i32; N] {
+        self.size
+    }
+
+    /// The number of tiles copied, over every layer.
+    pub fn len(&self) -> usize {
+        self.layers.iter().map(|layer| layer.len()).sum()
+    }
+
+    /// Check if no tiles were copied.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the copied tiles of a layer, with coordinates relative to the lowest corner of the region.
+    pub fn tiles<T: 'static>(&self) -> impl Iterator<Item = &([i32; N], T)> {
+        self.layers
+            .iter()
+            .filter_map(|layer| layer.as_any().downcast_ref::<LayerClip<T, N>>())
+            .flat_map(|layer| layer.tiles.iter())
+    }
+}
+
+/// The tile data types copied by [`copy_region`] (ex: `(Terrain, Decoration)`).
+pub trait ClipboardLayers: Send + Sync + 'static {
+    /// Copy the tiles of each layer in a region into a clipboard.
+    fn copy_into<const N: usize>(
+        world: &World,
+        map: &TileMap<N>,
+        min: [i32; N],
+        max: [i32; N],
+        clipboard: &mut TileClipboard<N>,
+    );
+}
+
+macro_rules! impl_clipboard_layers {
+    ($($t:ident),*) => {
+        impl<$($t: TileComponent + Clone),*> ClipboardLayers for ($($t,)*) {
+            fn copy_into<const N: usize>(
+                world: &World,
+                map: &TileMap<N>,
+                min: [i32; N],
+                max: [i32; N],
+                clipboard: &mut TileClipboard<N>,
+            ) {
+                $(clipboard.layers.push(copy_layer::<$t, N>(world, map, min, max));)*
+            }
+        }
+    };
+}
+
+impl_clipboard_layers!(A);
+impl_clipboard_layers!(A, B);
+impl_clipboard_layers!(A, B, C);
+impl_clipboard_layers!(A, B, C, D);
+impl_clipboard_layers!(A, B, C, D, E);
+impl_clipboard_layers!(A, B, C, D, E, F);
+impl_clipboard_layers!(A, B, C, D, E, F, G);
+impl_clipboard_layers!(A, B, C, D, E, F, G, H);
+
+fn copy_layer<T: TileComponent + Clone, const N: usize>(
+    world: &World,
+    map: &TileMap<N>,
+    min: [i32; N],
+    max: [i32; N],
+) -> Box<dyn ClipLayer<N>> {
+    let chunk_size = map.get_chunk_size();
+    let tiles = CoordIterator::new(min, max)
+        .filter_map(|tile_c| {
+            let tile = world
+                .get::<ChunkData<T>>(map.get_from_tile(tile_c)?)?
+                .get(calculate_tile_index(tile_c, chunk_size))?;
+            let mut relative_c = tile_c;
+            for (c, m) in relative_c.iter_mut().zip(min) {
+                *c -= m;
+            }
+            Some((relative_c, tile.clone()))
+        })
+        .collect();
+    Box::new(LayerClip::<T, N> { tiles })
+}
+
+/// Copy the `L` layers of every tile in the region between `corner_1` and `corner_2` (inclusive) of a map.
+/// Returns [`None`] if the map doesn't exist.
+pub fn copy_region<L: ClipboardLayers, const N: usize>(
+    world: &World,
+    map_id: Entity,
+    corner_1: impl Into<[i32; N]>,
+    corner_2: impl Into<[i32; N]>,
+) -> Option<TileClipboard<N>> {
+    let map = world.get::<TileMap<N>>(map_id)?;
+    let (corner_1, corner_2) = (corner_1.into(), corner_2.into());
+    let mut min = corner_1;
+    let mut size = [0; N];
+    for i in 0..N {
+        min[i] = corner_1[i].min(corner_2[i]);
+        size[i] = (corner_1[i] - corner_2[i]).abs() + 1;
+    }
+    let max = std::array::from_fn(|i| min[i] + size[i] - 1);
+
+    let mut clipboard = TileClipboard {
+        size,
+        layers: Vec::new(),
+    };
+    L::copy_into(world, map, min, max, &mut clipboard);
+    Some(clipboard)
+}
+
+/// Paste a clipboard into a map with its lowest corner at `tile_c`, replacing any tiles it covers.
+/// # Note
+/// Only tiles that were copied are written, so tiles under empty parts of the clipboard are kept.
+pub fn paste_region<const N: usize>(
+    world: &mut World,
+    map_id: Entity,
+    clipboard: &TileClipboard<N>,
+    tile_c: [i32; N],
+) {
+    for layer in clipboard.layers.iter() {
+        layer.paste(world, map_id, tile_c);
+    }
+}
+
+/// The version of the format written by [`TileClipboard::to_stamp`].
+pub const STAMP_VERSION: u32 = 1;
+
+const STAMP_HEADER: &str = "bevy_tiles stamp";
+
+/// Something that went wrong while reading a stamp with [`TileClipboard::from_stamp`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StampError {
+    /// The text doesn't start with a stamp header.
+    MissingHeader,
+    /// The stamp was written by a newer version of the format.
+    UnsupportedVersion(u32),
+    /// No layer was registered with the given name.
+    UnknownLayer(String),
+    /// The line with the given number (starting at 1) couldn't be parsed.
+    InvalidLine(usize),
+}
+
+impl fmt::Display for StampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StampError::MissingHeader => write!(f, "missing `{STAMP_HEADER}` header"),
+            StampError::UnsupportedVersion(version) => {
+                write!(f, "unsupported stamp version {version}")
+            }
+            StampError::UnknownLayer(layer) => write!(f, "unknown layer `{layer}`"),
+            StampError::InvalidLine(line) => write!(f, "invalid line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for StampError {}
+
+type WriteLayerFn<const N: usize> = fn(&TileClipboard<N>, &str, &mut String);
+type ReadLayerFn<const N: usize> =
+    fn(&mut TileClipboard<N>, &[(usize, [i32; N], &str)]) -> Result<(), StampError>;
+
+/// The tile data layers a stamp can hold, by name, see [`TileClipboard::to_stamp`].
+pub struct StampLayers<const N: usize = 2> {
+    layers: Vec<(String, WriteLayerFn<N>, ReadLayerFn<N>)>,
+}
+
+impl<const N: usize> Default for StampLayers<N> {
+    fn default() -> Self {
+        Self { layers: Vec::new() }
+    }
+}
+
+impl<const N: usize> StampLayers<N> {
+    /// Create an empty set of layers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store tiles of type `T` under the given name, values are printed with [`ToString`] and parsed with [`FromStr`].
+    /// # Note
+    /// Values can't contain line breaks.
+    pub fn with<T>(mut self, name: impl Into<String>) -> Self
+    where
+        T: TileComponent + FromStr + ToString + Clone,
+    {
+        self.layers
+            .push((name.into(), write_layer::<T, N>, read_layer::<T, N>));
+        self
+    }
+}
+
+fn write_layer<T: ToString + 'static, const N: usize>(
+    clipboard: &TileClipboard<N>,
+    name: &str,
+    out: &mut String,
+) {
+    let tiles: Vec<_> = clipboard.tiles::<T>().collect();
+    if tiles.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "layer {name} {}", tiles.len());
+    for (tile_c, tile) in tiles {
+        for c in tile_c {
+            let _ = write!(out, "{c} ");
+        }
+        let _ = writeln!(out, "{}", tile.to_string());
+    }
+}
+
+fn read_layer<T: TileComponent + FromStr + Clone, const N: usize>(
+    clipboard: &mut TileClipboard<N>,
+    lines: &[(usize, [i32; N], &str)],
+) -> Result<(), StampError> {
+    let tiles = lines
+        .iter()
+        .map(|(line, tile_c, value)| {
+            T::from_str(value)
+                .map(|tile| (*tile_c, tile))
+                .map_err(|_| StampError::InvalidLine(*line))
+        })
+        .collect::<Result<_, _>>()?;
+    clipboard.layers.push(Box::new(LayerClip::<T, N> { tiles }));
+    Ok(())
+}
+
+impl<const N: usize> TileClipboard<N> {
+    /// Write the clipboard as a small versioned text format that can be shared between projects,
+    /// and read back with [`TileClipboard::from_stamp`].
+    /// # Note
+    /// Only layers in `layers` are written, any other copied layers are left out.
+    pub fn to_stamp(&self, layers: &StampLayers<N>) -> String {
+        let mut out = format!("{STAMP_HEADER} {STAMP_VERSION}\nsize");
+        for c in self.size {
+            let _ = write!(out, " {c}");
+        }
+        out.push('\n');
+        for (name, write, _) in layers.layers.iter() {
+            write(self, name, &mut out);
+        }
+        out
+    }
+
+    /// Read a clipboard written by [`TileClipboard::to_stamp`].
+    pub fn from_stamp(stamp: &str, layers: &StampLayers<N>) -> Result<Self, StampError> {
+        let mut lines = stamp
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        let (_, header) = lines.next().ok_or(StampError::MissingHeader)?;
+        let version = header
+            .strip_prefix(STAMP_HEADER)
+            .and_then(|version| version.trim().parse::<u32>().ok())
+            .ok_or(StampError::MissingHeader)?;
+        if version > STAMP_VERSION {
+            return Err(StampError::UnsupportedVersion(version));
+        }
+
+        let (line, size) = lines.next().ok_or(StampError::InvalidLine(2))?;
+        let size = size
+            .strip_prefix("size")
+            .and_then(|size| parse_coord::<N>(&mut size.split_whitespace()))
+            .ok_or(StampError::InvalidLine(line))?;
+        let mut clipboard = TileClipboard {
+            size,
+            layers: Vec::new(),
+        };
+
+        while let Some((line, layer)) = lines.next() {
+            let mut args = layer.split_whitespace();
+            let (Some("layer"), Some(name), Some(count), None) =
+                (args.next(), args.next(), args.next(), args.next())
+            else {
+                return Err(StampError::InvalidLine(line));
+            };
+            let count: usize = count.parse().map_err(|_| StampError::InvalidLine(line))?;
+            let (_, _, read) = layers
+                .layers
+                .iter()
+                .find(|(layer, _, _)| layer == name)
+                .ok_or_else(|| StampError::UnknownLayer(name.into()))?;
+
+            let mut tiles = Vec::with_capacity(count);
+            for _ in 0..count {
+                let (line, tile) = lines.next().ok_or(StampError::InvalidLine(line + 1))?;
+                let mut args = tile.splitn(N + 1, char::is_whitespace);
+                let tile_c = parse_coord::<N>(&mut args).ok_or(StampError::InvalidLine(line))?;
+                let value = args.next().ok_or(StampError::InvalidLine(line))?;
+                tiles.push((line, tile_c, value.trim()));
+            }
+            read(&mut clipboard, &tiles)?;
+        }
+        Ok(clipboard)
+    }
+}
+
+fn parse_coord<'a, const N: usize>(args: &mut impl Iterator<Item = &'a str>) -> Option<[i32; N]> {
+    let mut coord = [0; N];
+    for c in coord.iter_mut() {
+        *c = args.next()?.parse().ok()?;
+    }
+    Some(coord)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use crate::{testing, tiles::TileMapQuery};
+
+    use super::*;
+
+    #[test]
+    fn copy_and_stamp() {
+        let mut world = World::new();
+        let src = testing::spawn_map(&mut world, 4, |map| {
+            map.insert_tile([-1, 2], 1u8);
+            map.insert_tile([3, 3], 2u8);
+            map.insert_tile([3, 3], 7u16);
+            map.insert_tile([4, 4], 3u8);
+        });
+        let dst = testing::spawn_map(&mut world, 4, |map| {
+            map.insert_tile([10, 10], 9u8);
+            map.insert_tile([11, 10], 9u8);
+        });
+
+        let clipboard = copy_region::<(u8, u16), 2>(&world, src, [3, 3], [-1, 0]).unwrap();
+        assert_eq!(clipboard.size(), [5, 4]);
+        assert_eq!(clipboard.len(), 3);
+        let mut u8s: Vec<_> = clipboard.tiles::<u8>().copied().collect();
+        u8s.sort();
+        assert_eq!(u8s, vec![([0, 2], 1), ([4, 3], 2)]);
+
+        paste_region(&mut world, dst, &clipboard, [10, 8]);
+        paste_region(&mut world, dst, &clipboard, [-20, -20]);
+
+        let mut state = SystemState::<TileMapQuery<&u8>>::new(&mut world);
+        let tile_maps = state.get(&world);
+        let tiles = tile_maps.get_map(dst).unwrap();
+        assert_eq!(tiles.get_at([10, 10]), Some(&1));
+        assert_eq!(tiles.get_at([11, 10]), Some(&9));
+        assert_eq!(tiles.get_at([14, 11]), Some(&2));
+        assert_eq!(tiles.get_at([-16, -17]), Some(&2));
+
+        let mut state = SystemState::<TileMapQuery<&u16>>::new(&mut world);
+        let tile_maps = state.get(&world);
+        assert_eq!(tile_maps.get_map(dst).unwrap().get_at([14, 11]), Some(&7));
+        assert!(copy_region::<(u8,), 2>(&world, Entity::PLACEHOLDER, [0, 0], [1, 1]).is_none());
+    }
+
+    #[test]
+    fn stamp_round_trip() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            map.insert_tile([0, 0], 1u8);
+            map.insert_tile([2, 1], 200u8);
+            map.insert_tile([1, 1], true);
+            map.insert_tile([1, 1], 7u16);
+        });
+
+        let clipboard = copy_region::<(u8, bool, u16), 2>(&world, map_id, [0, 0], [2, 1]).unwrap();
+        let layers = StampLayers::<2>::new()
+            .with::<u8>("terrain")
+            .with::<bool>("wall");
+        let stamp = clipboard.to_stamp(&layers);
+        assert!(stamp.starts_with("bevy_tiles stamp 1\nsize 3 2\n"));
+
+        let pasted = TileClipboard::<2>::from_stamp(&stamp, &layers).unwrap();
+        assert_eq!(pasted.size(), [3, 2]);
+        assert_eq!(pasted.len(), 3);
+        let mut u8s: Vec<_> = pasted.tiles::<u8>().copied().collect();
+        u8s.sort();
+        assert_eq!(u8s, vec![([0, 0], 1), ([2, 1], 200)]);
+        assert_eq!(pasted.tiles::<bool>().next(), Some(&([1, 1], true)));
+        assert_eq!(pasted.tiles::<u16>().next(), None);
+
+        let only_walls = StampLayers::<2>::new().with::<bool>("wall");
+        assert_eq!(
+            TileClipboard::<2>::from_stamp(&stamp, &only_walls).err(),
+            Some(StampError::UnknownLayer("terrain".into()))
+        );
+        let newer = stamp.replacen("stamp 1", "stamp 2", 1);
+        assert_eq!(
+            TileClipboard::<2>::from_stamp(&newer, &layers).err(),
+            Some(StampError::UnsupportedVersion(2))
+        );
+        let broken = stamp.replacen("200", "x", 1);
+        assert!(matches!(
+            TileClipboard::<2>::from_stamp(&broken, &layers),
+            Err(StampError::InvalidLine(_))
+        ));
+    }
+}
@@ -1,31 +1,63 @@
 use std::ops::{Deref, DerefMut};
 
 use crate::{
-    chunks::{ChunkCoord, ChunkTypes, InMap},
-    coords::{calculate_chunk_coordinate, calculate_tile_index},
-    maps::{TileDims, TileMap, TileSpacing, UseTransforms},
+    carve::PathBrush,
+    chunks::{ChunkCoord, ChunkData, ChunkTypes, InMap},
+    clipboard::TileClipboard,
+    coords::{calculate_chunk_coordinate, calculate_tile_index, chunk_bounds, CoordIterator},
+    distance::DistanceMetric,
+    filters::TileFilter,
+    index::{edit_layer_indexes, ChunkIndexEdit, LayerIndex},
+    layers::{LayerSet, TileMapHandle},
+    maps::{
+        AutoDespawnEmptyChunks, MapLayers, MapOrigin, TileDims, TileMap, TileSpacing, UseTransforms,
+    },
+    merge::MergePolicy,
+    noise::NoiseConfig,
+    observers::{trigger_on_map, OnChunkDespawned, OnChunkSpawned, OnTileInserted, OnTileRemoved},
     queries::TileComponent,
 };
 
 use bevy::{
-    ecs::system::EntityCommands,
-    math::Vec3,
+    ecs::system::{EntityCommands, Resource},
+    log::warn,
+    math::{Ray3d, Vec3},
     prelude::{
-        BuildChildren, Bundle, Commands, Deref, DerefMut, DespawnRecursiveExt, Entity,
+        BuildChildren, Bundle, Command, Commands, Deref, DerefMut, DespawnRecursiveExt, Entity,
         EntityWorldMut, InheritedVisibility, Transform, Visibility, World,
     },
     utils::hashbrown::{hash_map::Entry, HashMap},
 };
+#[cfg(feature = "trace")]
+use bevy::{log::info_span, utils::tracing::field};
 
-// mod chunk_batch;
+mod chunk_batch;
 mod chunk_single;
-// mod tile_batch;
+mod chunk_writer;
+mod map_merge;
+mod tile_batch;
+mod tile_carve;
+mod tile_clipboard;
+mod tile_distance;
+mod tile_filter;
+mod tile_noise;
+mod tile_ray;
 mod tile_single;
+mod tile_update;
 
-// use chunk_batch::*;
+use chunk_batch::*;
 use chunk_single::*;
-// use tile_batch::*;
+pub use chunk_writer::ChunkWriter;
+use map_merge::*;
+use tile_batch::*;
+use tile_carve::*;
+use tile_clipboard::*;
+use tile_distance::*;
+use tile_filter::*;
+use tile_noise::*;
+use tile_ray::*;
 use tile_single::*;
+use tile_update::*;
 
 /// Applies commands to a specific tile map.
 #[derive(Deref, DerefMut)]
@@ -42,18 +74,46 @@ impl<'a, const N: usize> TileMapCommands<'a, N> {
         self.commands.commands().spawn_tile(id, tile_c, bundle);
     }
 
-    // /// Spawns tiles from the given iterator using the given function.
-    // /// This will despawn any tile that already exists in this coordinate
-    // pub fn spawn_tile_batch<F, B, IC>(&mut self, tile_cs: IC, bundle_f: F) -> &mut Self
-    // where
-    //     F: Fn([i32; N]) -> B + Send + 'static,
-    //     B: Bundle + Send + 'static,
-    //     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
-    // {
-    //     self.commands
-    //         .spawn_tile_batch(self.map_id, tile_cs, bundle_f);
-    //     self
-    // }
+    /// Inserts a tile only if the map doesn't already have `B` data at this coordinate.
+    /// # Note
+    /// The check happens when the command is applied, so it can't race with other commands editing the same tile.
+    pub fn insert_tile_if_empty<B: TileComponent>(
+        &mut self,
+        tile_c: impl Into<[i32; N]>,
+        bundle: B,
+    ) -> &mut Self {
+        let tile_c = tile_c.into();
+        let map_id = self.id();
+        self.commands().insert_tile_if_empty(map_id, tile_c, bundle);
+        self
+    }
+
+    /// Inserts tiles at every coordinate from the given iterator, using the given function to create each tile.
+    /// This will replace any tile that already exists in these coordinates.
+    /// # Note
+    /// Tiles are grouped by chunk and inserted in a single command.
+    pub fn insert_tile_batch<F, B, IC>(&mut self, tile_cs: IC, bundle_f: F) -> &mut Self
+    where
+        F: Fn([i32; N]) -> B + Send + 'static,
+        B: TileComponent,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        let id = self.commands.id();
+        self.commands
+            .commands()
+            .spawn_tile_batch(id, tile_cs, bundle_f);
+        self
+    }
+
+    /// Inserts a copy of the same tile at every coordinate from the given iterator.
+    /// This will replace any tile that already exists in these coordinates.
+    pub fn insert_tile_batch_cloned<B, IC>(&mut self, tile_cs: IC, bundle: B) -> &mut Self
+    where
+        B: TileComponent + Clone,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        self.insert_tile_batch(tile_cs, move |_| bundle.clone())
+    }
 
     /// Despawns a tile.
     pub fn remove_tile<B: TileComponent>(&mut self, tile_c: impl Into<[i32; N]>) -> &mut Self {
@@ -63,14 +123,172 @@ impl<'a, const N: usize> TileMapCommands<'a, N> {
         self
     }
 
-    // /// Despawns tiles from the given iterator.
-    // pub fn despawn_tile_batch<IC>(&mut self, tile_cs: IC) -> &mut Self
-    // where
-    //     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
-    // {
-    //     self.commands.despawn_tile_batch(self.map_id, tile_cs);
-    //     self
-    // }
+    /// Removes the `B` data of a tile and passes it to `take_f` once the command is applied,
+    /// or [`None`] if the tile didn't have any.
+    pub fn take_tile<B, F>(&mut self, tile_c: impl Into<[i32; N]>, take_f: F) -> &mut Self
+    where
+        B: TileComponent,
+        F: FnOnce(Option<B>) + Send + 'static,
+    {
+        let tile_c = tile_c.into();
+        let map_id = self.id();
+        self.commands().take_tile::<B, F>(map_id, tile_c, take_f);
+        self
+    }
+
+    /// Fills every tile in the region between `corner_1` and `corner_2` (inclusive) with
+    /// noise sampled at the tile's coordinate, overwriting any existing `B` data.
+    /// If the map has a [`crate::maps::MapSeed`], it is mixed into the noise seed.
+    pub fn fill_noise<B: TileComponent + From<f32>>(
+        &mut self,
+        corner_1: impl Into<[i32; N]>,
+        corner_2: impl Into<[i32; N]>,
+        noise: NoiseConfig,
+    ) -> &mut Self {
+        let corner_1 = corner_1.into();
+        let corner_2 = corner_2.into();
+        let id = self.commands.id();
+        self.commands
+            .commands()
+            .fill_noise::<B>(id, corner_1, corner_2, noise);
+        self
+    }
+
+    /// Runs a filter over every existing `B` tile in the region between `corner_1` and `corner_2` (inclusive).
+    /// Tiles outside of the region (including ones in neighboring chunks) are read but not written.
+    pub fn filter_tiles<B: TileComponent + Copy + Into<f32> + From<f32>>(
+        &mut self,
+        corner_1: impl Into<[i32; N]>,
+        corner_2: impl Into<[i32; N]>,
+        filter: TileFilter,
+    ) -> &mut Self {
+        let corner_1 = corner_1.into();
+        let corner_2 = corner_2.into();
+        let id = self.commands.id();
+        self.commands
+            .commands()
+            .filter_tiles::<B>(id, corner_1, corner_2, filter);
+        self
+    }
+
+    /// Runs `update_f` on every existing `B` tile in the region between `corner_1` and `corner_2` (inclusive)
+    /// in a single command.
+    pub fn update_region<B, F>(
+        &mut self,
+        corner_1: impl Into<[i32; N]>,
+        corner_2: impl Into<[i32; N]>,
+        update_f: F,
+    ) -> &mut Self
+    where
+        B: TileComponent,
+        F: FnMut([i32; N], &mut B) + Send + 'static,
+    {
+        let corner_1 = corner_1.into();
+        let corner_2 = corner_2.into();
+        let map_id = self.id();
+        self.commands()
+            .update_region::<B, F>(map_id, corner_1, corner_2, update_f);
+        self
+    }
+
+    /// Drags a brush along a path (in tile coordinates), calling `carve_f` with the current `B` value
+    /// and brush weight of every touched tile.  Returning [`Some`] overwrites the tile, [`None`] leaves it as is.
+    pub fn carve_path<B, F>(
+        &mut self,
+        path: impl IntoIterator<Item = [f32; N]>,
+        brush: PathBrush,
+        carve_f: F,
+    ) -> &mut Self
+    where
+        B: TileComponent,
+        F: Fn(Option<&B>, f32) -> Option<B> + Send + 'static,
+    {
+        let path = path.into_iter().collect();
+        let id = self.commands.id();
+        self.commands
+            .commands()
+            .carve_path::<B, F>(id, path, brush, carve_f);
+        self
+    }
+
+    /// Writes the distance from every tile in the region between `corner_1` and `corner_2` (inclusive)
+    /// to the nearest `S` tile matching `is_source` into the `D` layer, capped at `max_distance`.
+    /// # Note
+    /// The region is worked through a chunk at a time, each reading only the sources within `max_distance` of it.
+    /// Sources outside of the region are taken into account, so after editing `S` only the edited area grown by
+    /// `max_distance` needs to be recomputed.
+    pub fn distance_field<S, D, F>(
+        &mut self,
+        corner_1: impl Into<[i32; N]>,
+        corner_2: impl Into<[i32; N]>,
+        metric: DistanceMetric,
+        max_distance: f32,
+        is_source: F,
+    ) -> &mut Self
+    where
+        S: TileComponent,
+        D: TileComponent + From<f32>,
+        F: Fn(&S) -> bool + Send + 'static,
+    {
+        let corner_1 = corner_1.into();
+        let corner_2 = corner_2.into();
+        let id = self.commands.id();
+        self.commands.commands().distance_field::<S, D, F>(
+            id,
+            corner_1,
+            corner_2,
+            metric,
+            max_distance,
+            is_source,
+        );
+        self
+    }
+
+    /// Despawns the `B` data of every tile from the given iterator.
+    /// # Note
+    /// Tiles are grouped by chunk and removed in a single command.
+    pub fn remove_tile_batch<B, IC>(&mut self, tile_cs: IC) -> &mut Self
+    where
+        B: TileComponent,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        let id = self.commands.id();
+        self.commands
+            .commands()
+            .remove_tile_batch::<B, IC>(id, tile_cs);
+        self
+    }
+
+    /// Moves the `B` data of every `(from, to)` pair of tiles from the given iterator in a single command,
+    /// overwriting any `B` data at the destinations.
+    /// # Note
+    /// Every tile is taken before any are inserted, so moves can chain into each other (ex: shifting a row over by one).
+    /// Tiles that are moved away from and not moved into are left empty.
+    /// Moves are applied in order: a tile listed as a source more than once only moves along its first pair,
+    /// and when several tiles move to the same destination the later one wins.
+    /// Overwritten tiles are passed to [`TileComponent::discard_tile`].
+    pub fn move_tile_batch<B, IC>(&mut self, tile_cs: IC) -> &mut Self
+    where
+        B: TileComponent,
+        IC: IntoIterator<Item = ([i32; N], [i32; N])> + Send + 'static,
+    {
+        let map_id = self.id();
+        self.commands().move_tile_batch::<B, IC>(map_id, tile_cs);
+        self
+    }
+
+    /// Pastes a [`TileClipboard`] into the map with its lowest corner at `tile_c`, replacing any tiles it covers.
+    /// See [`crate::clipboard::paste_region`].
+    pub fn paste_region(
+        &mut self,
+        clipboard: TileClipboard<N>,
+        tile_c: impl Into<[i32; N]>,
+    ) -> &mut Self {
+        let tile_c = tile_c.into();
+        let map_id = self.id();
+        self.commands().paste_region(map_id, clipboard, tile_c);
+        self
+    }
 
     /// Manually spawn a chunk entity, note that this will overwrite and despawn existing chunks at this location.
     pub fn spawn_chunk(&mut self, chunk_c: impl Into<[i32; N]>) {
@@ -79,18 +297,90 @@ impl<'a, const N: usize> TileMapCommands<'a, N> {
         self.commands.commands().spawn_chunk(id, chunk_c)
     }
 
-    // /// Spawns chunks from the given iterator using the given function.
-    // /// This will despawn any chunks (and their tiles) that already exists in this coordinate
-    // pub fn spawn_chunk_batch_with<F, B, IC>(&mut self, chunk_cs: IC, bundle_f: F) -> &mut Self
-    // where
-    //     F: Fn([i32; N]) -> B + Send + 'static,
-    //     B: Bundle + Send + 'static,
-    //     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
-    // {
-    //     self.commands
-    //         .spawn_chunk_batch_with(self.map_id, chunk_cs, bundle_f);
-    //     self
-    // }
+    /// Spawns chunks at every coordinate from the given iterator, keeping chunks that already exist.
+    pub fn spawn_chunk_batch<IC>(&mut self, chunk_cs: IC) -> &mut Self
+    where
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        self.spawn_chunk_batch_with(chunk_cs, |_| ())
+    }
+
+    /// Spawns every chunk within `radius` chunks (along every axis) of `chunk_c` without any tiles,
+    /// keeping chunks that already exist.
+    /// # Note
+    /// This is meant for streaming, so per chunk components can be added to chunks before their tiles are.
+    pub fn reserve_chunks_around(
+        &mut self,
+        chunk_c: impl Into<[i32; N]>,
+        radius: u32,
+    ) -> &mut Self {
+        let chunk_c = chunk_c.into();
+        let map_id = self.id();
+        self.commands()
+            .reserve_chunks_around(map_id, chunk_c, radius);
+        self
+    }
+
+    /// Spawns chunks at every coordinate from the given iterator, inserting a bundle made by the given function
+    /// into each one (including chunks that already exist).
+    pub fn spawn_chunk_batch_with<F, B, IC>(&mut self, chunk_cs: IC, bundle_f: F) -> &mut Self
+    where
+        F: Fn([i32; N]) -> B + Send + 'static,
+        B: Bundle,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        let map_id = self.id();
+        self.commands()
+            .spawn_chunk_batch_with(map_id, chunk_cs, bundle_f);
+        self
+    }
+
+    /// Runs the map's [`crate::generation::GenPipeline`] for a chunk.
+    pub fn generate_chunk(&mut self, chunk_c: impl Into<[i32; N]>) -> &mut Self {
+        let chunk_c = chunk_c.into();
+        let map_id = self.id();
+        self.commands().generate_chunk(map_id, chunk_c);
+        self
+    }
+
+    /// Replaces all the `B` data of a chunk at once, spawning the chunk if needed.
+    /// `tiles` must have one entry per tile in the chunk, in tile index order, see [`ChunkWriter::set_data`].
+    pub fn set_chunk_data<B: TileComponent>(
+        &mut self,
+        chunk_c: impl Into<[i32; N]>,
+        tiles: Vec<Option<B>>,
+    ) -> &mut Self {
+        let chunk_c = chunk_c.into();
+        let map_id = self.id();
+        self.commands().set_chunk_data::<B>(map_id, chunk_c, tiles);
+        self
+    }
+
+    /// Fills all the `B` data of a chunk at once, spawning the chunk if needed.
+    /// `tiles` must have one entry per tile in the chunk, in tile index order, see [`ChunkWriter::set_data`].
+    pub fn set_chunk_dense<B: TileComponent>(
+        &mut self,
+        chunk_c: impl Into<[i32; N]>,
+        tiles: Vec<B>,
+    ) -> &mut Self {
+        self.set_chunk_data(chunk_c, tiles.into_iter().map(Some).collect())
+    }
+
+    /// Swaps two chunks and all their tiles, moving a chunk into an empty spot if only one of them exists.
+    /// # Note
+    /// Any [`LayerIndex`] of the map is updated, tile data that stores its own coordinate can be updated by observing
+    /// [`crate::observers::OnChunkMoved`].
+    pub fn swap_chunks(
+        &mut self,
+        chunk_c_0: impl Into<[i32; N]>,
+        chunk_c_1: impl Into<[i32; N]>,
+    ) -> &mut Self {
+        let chunk_c_0 = chunk_c_0.into();
+        let chunk_c_1 = chunk_c_1.into();
+        let map_id = self.id();
+        self.commands().swap_chunks(map_id, chunk_c_0, chunk_c_1);
+        self
+    }
 
     /// Recursively despawn a chunk and all it's tiles.
     pub fn despawn_chunk(&mut self, chunk_c: impl Into<[i32; N]>) -> &mut Self {
@@ -100,24 +390,358 @@ impl<'a, const N: usize> TileMapCommands<'a, N> {
         self
     }
 
-    // /// Despawns chunks (and their tiles) from the given iterator.
-    // pub fn despawn_chunk_batch<IC>(&mut self, chunk_cs: IC) -> &mut Self
-    // where
-    //     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
-    // {
-    //     self.commands.despawn_chunk_batch(self.map_id, chunk_cs);
-    //     self
-    // }
+    /// Moves the registered layers of another map into this one, shifted by `offset`, then despawns the other map.
+    /// See [`crate::merge::merge_maps`].
+    pub fn merge_from(
+        &mut self,
+        src_id: Entity,
+        offset: impl Into<[i32; N]>,
+        policy: MergePolicy,
+    ) -> &mut Self {
+        let offset = offset.into();
+        let map_id = self.id();
+        self.commands().merge_maps(map_id, src_id, offset, policy);
+        self
+    }
+
+    /// Recursively despawns chunks (and their tiles) from the given iterator.
+    pub fn despawn_chunk_batch<IC>(&mut self, chunk_cs: IC) -> &mut Self
+    where
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        let map_id = self.id();
+        self.commands().despawn_chunk_batch(map_id, chunk_cs);
+        self
+    }
 
-    // /// Recursively despawns a map and all it's chunks and tiles.
-    // pub fn despawn_map(self) {
-    //     TileCommandExt::<N>::despawn_map(self.commands, self.map_id);
-    // }
+    /// Shows or hides every existing chunk overlapping the region between `corner_1` and `corner_2` (inclusive),
+    /// by setting the chunk's [`Visibility`] (ex: hiding a roof map while the player is indoors).
+    /// # Note
+    /// Chunks of maps without [`UseTransforms`] get a [`Visibility`] as well, so renderers of data only maps can check it.
+    pub fn set_chunks_visible(
+        &mut self,
+        corner_1: impl Into<[i32; N]>,
+        corner_2: impl Into<[i32; N]>,
+        visible: bool,
+    ) -> &mut Self {
+        let corner_1 = corner_1.into();
+        let corner_2 = corner_2.into();
+        let map_id = self.id();
+        self.commands()
+            .set_chunks_visible(map_id, corner_1, corner_2, visible);
+        self
+    }
 
-    // /// Get the id of the map.
-    // pub fn id(&self) -> Entity {
-    //     self.map_id
-    // }
+    /// Get commands for this map that shift every tile coordinate by `offset`, so tiles can be placed relative to
+    /// an origin (ex: stamping a prefab at an arbitrary position).
+    pub fn with_origin(&mut self, offset: impl Into<[i32; N]>) -> OffsetTileMapCommands<'_, 'a, N> {
+        OffsetTileMapCommands {
+            map: self,
+            offset: offset.into(),
+        }
+    }
+
+    /// Get commands scoped to a single chunk of this map, for attaching per chunk data and editing whole chunks.
+    /// # Note
+    /// The chunk is looked up when each command is applied, so it doesn't have to exist yet.
+    pub fn chunk(&mut self, chunk_c: impl Into<[i32; N]>) -> ChunkCommands<'_, 'a, N> {
+        ChunkCommands {
+            map: self,
+            chunk_c: chunk_c.into(),
+        }
+    }
+
+    /// Recursively despawns a map and all it's chunks and tiles.
+    pub fn despawn_map(mut self) {
+        let map_id = self.id();
+        TileCommandExt::<N>::despawn_map(&mut self.commands.commands(), map_id);
+    }
+
+    /// Get the id of the map.
+    #[inline]
+    pub fn id(&self) -> Entity {
+        self.commands.id()
+    }
+}
+
+impl<'a> TileMapCommands<'a, 3> {
+    /// Removes the `B` data of the first `B` tile hit by a ray, see [`crate::picking::raycast_tiles_3d`].
+    /// # Note
+    /// The map needs [`TileDims`] for the ray to be traced.
+    pub fn break_tile_at_ray<B: TileComponent>(
+        &mut self,
+        ray: Ray3d,
+        max_distance: f32,
+    ) -> &mut Self {
+        let map_id = self.id();
+        self.commands().queue(BreakTileAtRay::<B> {
+            map_id,
+            ray,
+            max_distance,
+            bundle: Default::default(),
+        });
+        self
+    }
+
+    /// Places a tile against the face of the first `B` tile hit by a ray, if there isn't a `B` tile there already
+    /// (ex: placing a block where the player is looking).
+    /// # Note
+    /// The map needs [`TileDims`] for the ray to be traced.
+    pub fn place_tile_at_ray<B: TileComponent>(
+        &mut self,
+        ray: Ray3d,
+        max_distance: f32,
+        bundle: B,
+    ) -> &mut Self {
+        let map_id = self.id();
+        self.commands().queue(PlaceTileAtRay::<B> {
+            map_id,
+            ray,
+            max_distance,
+            bundle,
+        });
+        self
+    }
+}
+
+/// Applies commands to a single chunk of a tile map, see [`TileMapCommands::chunk`].
+pub struct ChunkCommands<'c, 'a, const N: usize> {
+    map: &'c mut TileMapCommands<'a, N>,
+    chunk_c: [i32; N],
+}
+
+impl<'c, 'a, const N: usize> ChunkCommands<'c, 'a, N> {
+    /// The coordinate of the chunk.
+    pub fn chunk_c(&self) -> [i32; N] {
+        self.chunk_c
+    }
+
+    /// Get the commands for the whole map.
+    pub fn map(&mut self) -> &mut TileMapCommands<'a, N> {
+        self.map
+    }
+
+    /// Spawns the chunk if it doesn't exist yet.
+    pub fn spawn(&mut self) -> &mut Self {
+        let map_id = self.map.id();
+        self.map
+            .commands()
+            .spawn_chunk_batch(map_id, [self.chunk_c]);
+        self
+    }
+
+    /// Inserts a bundle into the chunk entity (ex: biome tags or dirty flags), spawning the chunk if needed.
+    pub fn insert_chunk_bundle<B: Bundle>(&mut self, bundle: B) -> &mut Self {
+        let map_id = self.map.id();
+        self.map
+            .commands()
+            .insert_chunk_bundle(map_id, self.chunk_c, bundle);
+        self
+    }
+
+    /// Removes a bundle from the chunk entity if the chunk exists.
+    pub fn remove_chunk_bundle<B: Bundle>(&mut self) -> &mut Self {
+        let map_id = self.map.id();
+        self.map
+            .commands()
+            .remove_chunk_bundle::<B>(map_id, self.chunk_c);
+        self
+    }
+
+    /// Sets the `B` data of every tile in the chunk to `value`, spawning the chunk if needed.
+    pub fn fill<B: TileComponent + Clone>(&mut self, value: B) -> &mut Self {
+        let map_id = self.map.id();
+        self.map.commands().fill_chunk(map_id, self.chunk_c, value);
+        self
+    }
+
+    /// Removes the `B` data of every tile in the chunk.
+    pub fn clear<B: TileComponent>(&mut self) -> &mut Self {
+        let map_id = self.map.id();
+        self.map.commands().clear_chunk::<B>(map_id, self.chunk_c);
+        self
+    }
+
+    /// Recursively despawn the chunk and all it's tiles.
+    pub fn despawn(&mut self) {
+        self.map.despawn_chunk(self.chunk_c);
+    }
+}
+
+/// Applies tile commands to a specific tile map, with every tile coordinate shifted by a constant offset.
+/// See [`TileMapCommands::with_origin`].
+/// # Note
+/// Coordinates passed to callbacks are relative to the origin as well.
+/// Commands addressed by chunk coordinate aren't shifted, so they're only available on [`TileMapCommands`].
+pub struct OffsetTileMapCommands<'c, 'a, const N: usize> {
+    map: &'c mut TileMapCommands<'a, N>,
+    offset: [i32; N],
+}
+
+impl<'c, 'a, const N: usize> OffsetTileMapCommands<'c, 'a, N> {
+    /// The offset added to every tile coordinate.
+    pub fn offset(&self) -> [i32; N] {
+        self.offset
+    }
+
+    /// Get the unshifted commands for the map.
+    pub fn map(&mut self) -> &mut TileMapCommands<'a, N> {
+        self.map
+    }
+
+    /// Inserts a tile, see [`TileMapCommands::insert_tile`].
+    pub fn insert_tile<B: TileComponent>(
+        &mut self,
+        tile_c: impl Into<[i32; N]>,
+        bundle: B,
+    ) -> &mut Self {
+        let tile_c = shift(tile_c.into(), self.offset);
+        self.map.insert_tile(tile_c, bundle);
+        self
+    }
+
+    /// Inserts a tile if there isn't one, see [`TileMapCommands::insert_tile_if_empty`].
+    pub fn insert_tile_if_empty<B: TileComponent>(
+        &mut self,
+        tile_c: impl Into<[i32; N]>,
+        bundle: B,
+    ) -> &mut Self {
+        let tile_c = shift(tile_c.into(), self.offset);
+        self.map.insert_tile_if_empty(tile_c, bundle);
+        self
+    }
+
+    /// Inserts tiles at every coordinate from the given iterator, see [`TileMapCommands::insert_tile_batch`].
+    pub fn insert_tile_batch<F, B, IC>(&mut self, tile_cs: IC, bundle_f: F) -> &mut Self
+    where
+        F: Fn([i32; N]) -> B + Send + 'static,
+        B: TileComponent,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        let offset = self.offset;
+        let back = offset.map(|o| -o);
+        self.map
+            .insert_tile_batch(self.shift_all(tile_cs), move |tile_c| {
+                bundle_f(shift(tile_c, back))
+            });
+        self
+    }
+
+    /// Inserts a copy of the same tile at every coordinate from the given iterator,
+    /// see [`TileMapCommands::insert_tile_batch_cloned`].
+    pub fn insert_tile_batch_cloned<B, IC>(&mut self, tile_cs: IC, bundle: B) -> &mut Self
+    where
+        B: TileComponent + Clone,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        let tile_cs = self.shift_all(tile_cs);
+        self.map.insert_tile_batch_cloned(tile_cs, bundle);
+        self
+    }
+
+    /// Despawns a tile, see [`TileMapCommands::remove_tile`].
+    pub fn remove_tile<B: TileComponent>(&mut self, tile_c: impl Into<[i32; N]>) -> &mut Self {
+        let tile_c = shift(tile_c.into(), self.offset);
+        self.map.remove_tile::<B>(tile_c);
+        self
+    }
+
+    /// Despawns the `B` data of every tile from the given iterator, see [`TileMapCommands::remove_tile_batch`].
+    pub fn remove_tile_batch<B, IC>(&mut self, tile_cs: IC) -> &mut Self
+    where
+        B: TileComponent,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        let tile_cs = self.shift_all(tile_cs);
+        self.map.remove_tile_batch::<B, _>(tile_cs);
+        self
+    }
+
+    /// Removes the `B` data of a tile and passes it to `take_f`, see [`TileMapCommands::take_tile`].
+    pub fn take_tile<B, F>(&mut self, tile_c: impl Into<[i32; N]>, take_f: F) -> &mut Self
+    where
+        B: TileComponent,
+        F: FnOnce(Option<B>) + Send + 'static,
+    {
+        let tile_c = shift(tile_c.into(), self.offset);
+        self.map.take_tile::<B, F>(tile_c, take_f);
+        self
+    }
+
+    /// Moves the `B` data of every `(from, to)` pair of tiles, see [`TileMapCommands::move_tile_batch`].
+    pub fn move_tile_batch<B, IC>(&mut self, tile_cs: IC) -> &mut Self
+    where
+        B: TileComponent,
+        IC: IntoIterator<Item = ([i32; N], [i32; N])> + Send + 'static,
+    {
+        let offset = self.offset;
+        let tile_cs: Vec<_> = tile_cs
+            .into_iter()
+            .map(|(from, to)| (shift(from, offset), shift(to, offset)))
+            .collect();
+        self.map.move_tile_batch::<B, _>(tile_cs);
+        self
+    }
+
+    /// Runs `update_f` on every existing `B` tile in a region, see [`TileMapCommands::update_region`].
+    pub fn update_region<B, F>(
+        &mut self,
+        corner_1: impl Into<[i32; N]>,
+        corner_2: impl Into<[i32; N]>,
+        mut update_f: F,
+    ) -> &mut Self
+    where
+        B: TileComponent,
+        F: FnMut([i32; N], &mut B) + Send + 'static,
+    {
+        let corner_1 = shift(corner_1.into(), self.offset);
+        let corner_2 = shift(corner_2.into(), self.offset);
+        let back = self.offset.map(|o| -o);
+        self.map
+            .update_region::<B, _>(corner_1, corner_2, move |tile_c, tile: &mut B| {
+                update_f(shift(tile_c, back), tile)
+            });
+        self
+    }
+
+    /// Pastes a [`TileClipboard`] with its lowest corner at `tile_c`, see [`TileMapCommands::paste_region`].
+    pub fn paste_region(
+        &mut self,
+        clipboard: TileClipboard<N>,
+        tile_c: impl Into<[i32; N]>,
+    ) -> &mut Self {
+        let tile_c = shift(tile_c.into(), self.offset);
+        self.map.paste_region(clipboard, tile_c);
+        self
+    }
+
+    /// Shows or hides every existing chunk overlapping a region, see [`TileMapCommands::set_chunks_visible`].
+    pub fn set_chunks_visible(
+        &mut self,
+        corner_1: impl Into<[i32; N]>,
+        corner_2: impl Into<[i32; N]>,
+        visible: bool,
+    ) -> &mut Self {
+        let corner_1 = shift(corner_1.into(), self.offset);
+        let corner_2 = shift(corner_2.into(), self.offset);
+        self.map.set_chunks_visible(corner_1, corner_2, visible);
+        self
+    }
+
+    fn shift_all(&self, tile_cs: impl IntoIterator<Item = [i32; N]>) -> Vec<[i32; N]> {
+        tile_cs
+            .into_iter()
+            .map(|tile_c| shift(tile_c, self.offset))
+            .collect()
+    }
+}
+
+#[inline]
+fn shift<const N: usize>(mut tile_c: [i32; N], offset: [i32; N]) -> [i32; N] {
+    for (c, o) in tile_c.iter_mut().zip(offset) {
+        *c += o;
+    }
+    tile_c
 }
 
 /// Helper method for creating map specific commands.
@@ -129,44 +753,266 @@ pub trait TileCommandExt<'w, 's, const N: usize> {
     /// This will despawn any tile that already exists in this coordinate
     fn spawn_tile<B: TileComponent>(&mut self, map_id: Entity, tile_c: [i32; N], bundle: B);
 
-    // /// Spawns tiles from the given iterator using the given function.
-    // /// This will despawn any tile that already exists in this coordinate
-    // fn spawn_tile_batch<F, B, IC>(&mut self, map_id: Entity, tile_cs: IC, bundle_f: F)
-    // where
-    //     F: Fn([i32; N]) -> B + Send + 'static,
-    //     B: Bundle + Send + 'static,
-    //     IC: IntoIterator<Item = [i32; N]> + Send + 'static;
+    /// Inserts a tile only if the map doesn't already have `B` data at this coordinate.
+    /// # Note
+    /// The check happens when the command is applied, so it can't race with other commands editing the same tile.
+    fn insert_tile_if_empty<B: TileComponent>(
+        &mut self,
+        map_id: Entity,
+        tile_c: [i32; N],
+        bundle: B,
+    ) -> &mut Self;
+
+    /// Spawns tiles from the given iterator using the given function.
+    /// This will replace any tile that already exists in these coordinates.
+    fn spawn_tile_batch<F, B, IC>(&mut self, map_id: Entity, tile_cs: IC, bundle_f: F) -> &mut Self
+    where
+        F: Fn([i32; N]) -> B + Send + 'static,
+        B: TileComponent,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static;
 
     /// Despawns a tile.
     fn remove_tile<B: TileComponent>(&mut self, map_id: Entity, tile_c: [i32; N]) -> &mut Self;
 
-    // /// Despawns tiles from the given iterator.
-    // fn despawn_tile_batch<IC>(&mut self, map_id: Entity, tile_cs: IC)
-    // where
-    //     IC: IntoIterator<Item = [i32; N]> + Send + 'static;
+    /// Removes the `B` data of a tile and passes it to `take_f` once the command is applied,
+    /// or [`None`] if the tile didn't have any.
+    fn take_tile<B, F>(&mut self, map_id: Entity, tile_c: [i32; N], take_f: F) -> &mut Self
+    where
+        B: TileComponent,
+        F: FnOnce(Option<B>) + Send + 'static;
+
+    /// Fills every tile in the region between `corner_1` and `corner_2` (inclusive) with
+    /// noise sampled at the tile's coordinate, overwriting any existing `B` data.
+    /// If the map has a [`crate::maps::MapSeed`], it is mixed into the noise seed.
+    fn fill_noise<B: TileComponent + From<f32>>(
+        &mut self,
+        map_id: Entity,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+        noise: NoiseConfig,
+    ) -> &mut Self;
+
+    /// Runs a filter over every existing `B` tile in the region between `corner_1` and `corner_2` (inclusive).
+    /// Tiles outside of the region (including ones in neighboring chunks) are read but not written.
+    fn filter_tiles<B: TileComponent + Copy + Into<f32> + From<f32>>(
+        &mut self,
+        map_id: Entity,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+        filter: TileFilter,
+    ) -> &mut Self;
+
+    /// Runs `update_f` on every existing `B` tile in the region between `corner_1` and `corner_2` (inclusive)
+    /// in a single command.
+    fn update_region<B, F>(
+        &mut self,
+        map_id: Entity,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+        update_f: F,
+    ) -> &mut Self
+    where
+        B: TileComponent,
+        F: FnMut([i32; N], &mut B) + Send + 'static;
+
+    /// Drags a brush along a path (in tile coordinates), calling `carve_f` with the current `B` value
+    /// and brush weight of every touched tile.  Returning [`Some`] overwrites the tile, [`None`] leaves it as is.
+    fn carve_path<B, F>(
+        &mut self,
+        map_id: Entity,
+        path: Vec<[f32; N]>,
+        brush: PathBrush,
+        carve_f: F,
+    ) -> &mut Self
+    where
+        B: TileComponent,
+        F: Fn(Option<&B>, f32) -> Option<B> + Send + 'static;
+
+    /// Writes the distance from every tile in the region between `corner_1` and `corner_2` (inclusive)
+    /// to the nearest `S` tile matching `is_source` into the `D` layer, capped at `max_distance`.
+    /// # Note
+    /// The region is worked through a chunk at a time, each reading only the sources within `max_distance` of it.
+    /// Sources outside of the region are taken into account, so after editing `S` only the edited area grown by
+    /// `max_distance` needs to be recomputed.
+    fn distance_field<S, D, F>(
+        &mut self,
+        map_id: Entity,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+        metric: DistanceMetric,
+        max_distance: f32,
+        is_source: F,
+    ) -> &mut Self
+    where
+        S: TileComponent,
+        D: TileComponent + From<f32>,
+        F: Fn(&S) -> bool + Send + 'static;
+
+    /// Despawns the `B` data of every tile from the given iterator.
+    fn remove_tile_batch<B, IC>(&mut self, map_id: Entity, tile_cs: IC) -> &mut Self
+    where
+        B: TileComponent,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static;
+
+    /// Moves the `B` data of a tile to another coordinate, overwriting any `B` data there.
+    /// Does nothing if the old coordinate has no `B` data.
+    fn move_tile<B: TileComponent>(
+        &mut self,
+        map_id: Entity,
+        old_c: [i32; N],
+        new_c: [i32; N],
+    ) -> &mut Self;
+
+    /// Swaps the `B` data of two tiles, or moves it if only one of them has any.
+    fn swap_tiles<B: TileComponent>(
+        &mut self,
+        map_id: Entity,
+        tile_c_0: [i32; N],
+        tile_c_1: [i32; N],
+    ) -> &mut Self;
+
+    /// Moves the `B` data of every `(from, to)` pair of tiles from the given iterator in a single command,
+    /// overwriting any `B` data at the destinations.
+    /// # Note
+    /// Every tile is taken before any are inserted, so moves can chain into each other (ex: shifting a row over by one).
+    /// Tiles that are moved away from and not moved into are left empty.
+    /// Moves are applied in order: a tile listed as a source more than once only moves along its first pair,
+    /// and when several tiles move to the same destination the later one wins.
+    /// Overwritten tiles are passed to [`TileComponent::discard_tile`].
+    fn move_tile_batch<B, IC>(&mut self, map_id: Entity, tile_cs: IC) -> &mut Self
+    where
+        B: TileComponent,
+        IC: IntoIterator<Item = ([i32; N], [i32; N])> + Send + 'static;
+
+    /// Pastes a [`TileClipboard`] into a map with its lowest corner at `tile_c`, replacing any tiles it covers.
+    fn paste_region(
+        &mut self,
+        map_id: Entity,
+        clipboard: TileClipboard<N>,
+        tile_c: [i32; N],
+    ) -> &mut Self;
 
     /// Manually spawn a chunk entity, note that this will overwrite and despawn existing chunks at this location.
     fn spawn_chunk(&mut self, map_id: Entity, chunk_c: [i32; N]);
 
-    // /// Spawns chunks from the given iterator using the given function.
-    // /// This will despawn any chunks (and their tiles) that already exists in this coordinate
-    // fn spawn_chunk_batch_with<F, B, IC>(&mut self, map_id: Entity, chunk_cs: IC, bundle_f: F)
-    // where
-    //     F: Fn([i32; N]) -> B + Send + 'static,
-    //     B: Bundle + Send + 'static,
-    //     IC: IntoIterator<Item = [i32; N]> + Send + 'static;
+    /// Spawns chunks at every coordinate from the given iterator, keeping chunks that already exist.
+    fn spawn_chunk_batch<IC>(&mut self, map_id: Entity, chunk_cs: IC) -> &mut Self
+    where
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static;
+
+    /// Spawns every chunk within `radius` chunks (along every axis) of `chunk_c` without any tiles,
+    /// keeping chunks that already exist.
+    fn reserve_chunks_around(
+        &mut self,
+        map_id: Entity,
+        chunk_c: [i32; N],
+        radius: u32,
+    ) -> &mut Self;
+
+    /// Spawns chunks at every coordinate from the given iterator, inserting a bundle made by the given function
+    /// into each one (including chunks that already exist).
+    fn spawn_chunk_batch_with<F, B, IC>(
+        &mut self,
+        map_id: Entity,
+        chunk_cs: IC,
+        bundle_f: F,
+    ) -> &mut Self
+    where
+        F: Fn([i32; N]) -> B + Send + 'static,
+        B: Bundle,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static;
+
+    /// Runs the map's [`crate::generation::GenPipeline`] for a chunk, maps without a pipeline are skipped with a warning.
+    fn generate_chunk(&mut self, map_id: Entity, chunk_c: [i32; N]) -> &mut Self;
+
+    /// Replaces all the `B` data of a chunk at once, spawning the chunk if needed.
+    /// `tiles` must have one entry per tile in the chunk, in tile index order, see [`ChunkWriter::set_data`].
+    fn set_chunk_data<B: TileComponent>(
+        &mut self,
+        map_id: Entity,
+        chunk_c: [i32; N],
+        tiles: Vec<Option<B>>,
+    ) -> &mut Self;
+
+    /// Swaps two chunks and all their tiles, moving a chunk into an empty spot if only one of them exists.
+    fn swap_chunks(
+        &mut self,
+        map_id: Entity,
+        chunk_c_0: [i32; N],
+        chunk_c_1: [i32; N],
+    ) -> &mut Self;
+
+    /// Inserts a bundle into a chunk entity (ex: biome tags or dirty flags), spawning the chunk if needed.
+    fn insert_chunk_bundle<B: Bundle>(
+        &mut self,
+        map_id: Entity,
+        chunk_c: [i32; N],
+        bundle: B,
+    ) -> &mut Self;
+
+    /// Removes a bundle from a chunk entity if the chunk exists.
+    fn remove_chunk_bundle<B: Bundle>(&mut self, map_id: Entity, chunk_c: [i32; N]) -> &mut Self;
+
+    /// Sets the `B` data of every tile in a chunk to `value`, spawning the chunk if needed.
+    fn fill_chunk<B: TileComponent + Clone>(
+        &mut self,
+        map_id: Entity,
+        chunk_c: [i32; N],
+        value: B,
+    ) -> &mut Self;
+
+    /// Removes the `B` data of every tile in a chunk.
+    fn clear_chunk<B: TileComponent>(&mut self, map_id: Entity, chunk_c: [i32; N]) -> &mut Self;
 
     /// Recursively despawn a chunk and all it's tiles.
     fn despawn_chunk(&mut self, map_id: Entity, chunk_c: [i32; N]) -> &mut Self;
 
-    // /// Despawns chunks (and their tiles) from the given iterator.
-    // fn despawn_chunk_batch<IC>(&mut self, map_id: Entity, chunk_cs: IC)
-    // where
-    //     IC: IntoIterator<Item = [i32; N]> + Send + 'static;
+    /// Recursively despawns chunks (and their tiles) from the given iterator.
+    fn despawn_chunk_batch<IC>(&mut self, map_id: Entity, chunk_cs: IC) -> &mut Self
+    where
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static;
+
+    /// Shows or hides every existing chunk overlapping the region between `corner_1` and `corner_2` (inclusive),
+    /// by setting the chunk's [`Visibility`] (ex: hiding a roof map while the player is indoors).
+    /// # Note
+    /// Chunks of maps without [`UseTransforms`] get a [`Visibility`] as well, so renderers of data only maps can check it.
+    fn set_chunks_visible(
+        &mut self,
+        map_id: Entity,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+        visible: bool,
+    ) -> &mut Self;
+
+    /// Moves the registered layers of the source map into the destination map, shifted by `offset`,
+    /// then despawns the source map.  See [`crate::merge::merge_maps`].
+    fn merge_maps(
+        &mut self,
+        dst_id: Entity,
+        src_id: Entity,
+        offset: [i32; N],
+        policy: MergePolicy,
+    ) -> &mut Self;
 
     /// Spawn a new map.
+    /// # Panics
+    /// If `chunk_size` is 0, or a chunk would hold more than [`crate::maps::MAX_CHUNK_TILES`] tiles.
+    /// # Note
+    /// Power of two chunk sizes use faster coordinate math.
     fn spawn_map(&mut self, chunk_size: usize) -> TileMapCommands<'_, N>;
 
+    /// Spawn a new map with the given bundle (ex: `(UseTransforms, TileDims([16.0, 16.0]))`),
+    /// so it's configured before any other command sees it.  See [`crate::maps::MapBuilder`] as well.
+    /// # Panics
+    /// If `chunk_size` is 0, or a chunk would hold more than [`crate::maps::MAX_CHUNK_TILES`] tiles.
+    fn spawn_map_with(&mut self, chunk_size: usize, bundle: impl Bundle) -> TileMapCommands<'_, N>;
+
+    /// Spawn a new map with a declared set of layers (ex: `(Terrain, Moisture)`), its chunks spawn with
+    /// storage for every layer and the returned handle only accepts those layers.
+    /// # Panics
+    /// If `chunk_size` is 0, or a chunk would hold more than [`crate::maps::MAX_CHUNK_TILES`] tiles.
+    fn spawn_map_with_layers<L: LayerSet>(&mut self, chunk_size: usize) -> TileMapHandle<L, N>;
+
     /// Recursively despawns a map and all it's chunks and tiles.
     fn despawn_map(&mut self, map_id: Entity) -> &mut Self;
 }
@@ -187,20 +1033,38 @@ impl<'w, 's, const N: usize> TileCommandExt<'w, 's, N> for Commands<'w, 's> {
         });
     }
 
-    // /// Spawns tiles from the given iterator using the given function.
-    // /// This will despawn any tile that already exists in this coordinate
-    // fn spawn_tile_batch<F, B, IC>(&mut self, map_id: Entity, tile_cs: IC, bundle_f: F)
-    // where
-    //     F: Fn([i32; N]) -> B + Send + 'static,
-    //     B: Bundle + Send + 'static,
-    //     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
-    // {
-    //     self.add(SpawnTileBatch::<F, B, IC, N> {
-    //         map_id,
-    //         tile_cs,
-    //         bundle_f,
-    //     });
-    // }
+    /// Inserts a tile only if the map doesn't already have `B` data at this coordinate.
+    /// # Note
+    /// The check happens when the command is applied, so it can't race with other commands editing the same tile.
+    fn insert_tile_if_empty<B: TileComponent>(
+        &mut self,
+        map_id: Entity,
+        tile_c: [i32; N],
+        bundle: B,
+    ) -> &mut Self {
+        self.queue(InsertTileIfEmpty::<B, N> {
+            map_id,
+            tile_c,
+            bundle,
+        });
+        self
+    }
+
+    /// Spawns tiles from the given iterator using the given function.
+    /// This will replace any tile that already exists in these coordinates.
+    fn spawn_tile_batch<F, B, IC>(&mut self, map_id: Entity, tile_cs: IC, bundle_f: F) -> &mut Self
+    where
+        F: Fn([i32; N]) -> B + Send + 'static,
+        B: TileComponent,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        self.queue(InsertTileBatch::<F, B, IC, N> {
+            map_id,
+            tile_cs,
+            bundle_f,
+        });
+        self
+    }
 
     /// Despawns a tile.
     fn remove_tile<B: TileComponent>(&mut self, map_id: Entity, tile_c: [i32; N]) -> &mut Self {
@@ -212,25 +1076,350 @@ impl<'w, 's, const N: usize> TileCommandExt<'w, 's, N> for Commands<'w, 's> {
         self
     }
 
+    /// Removes the `B` data of a tile and passes it to `take_f` once the command is applied,
+    /// or [`None`] if the tile didn't have any.
+    fn take_tile<B, F>(&mut self, map_id: Entity, tile_c: [i32; N], take_f: F) -> &mut Self
+    where
+        B: TileComponent,
+        F: FnOnce(Option<B>) + Send + 'static,
+    {
+        self.queue(TakeTile::<B, F, N> {
+            map_id,
+            tile_c,
+            take_f,
+            bundle: Default::default(),
+        });
+        self
+    }
+
+    /// Despawns the `B` data of every tile from the given iterator.
+    fn remove_tile_batch<B, IC>(&mut self, map_id: Entity, tile_cs: IC) -> &mut Self
+    where
+        B: TileComponent,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        self.queue(RemoveTileBatch::<B, IC, N> {
+            map_id,
+            tile_cs,
+            bundle: Default::default(),
+        });
+        self
+    }
+
+    /// Moves the `B` data of a tile to another coordinate, overwriting any `B` data there.
+    fn move_tile<B: TileComponent>(
+        &mut self,
+        map_id: Entity,
+        old_c: [i32; N],
+        new_c: [i32; N],
+    ) -> &mut Self {
+        self.queue(MoveTile::<B, N> {
+            map_id,
+            old_c,
+            new_c,
+            bundle: Default::default(),
+        });
+        self
+    }
+
+    /// Swaps the `B` data of two tiles, or moves it if only one of them has any.
+    fn swap_tiles<B: TileComponent>(
+        &mut self,
+        map_id: Entity,
+        tile_c_0: [i32; N],
+        tile_c_1: [i32; N],
+    ) -> &mut Self {
+        self.queue(SwapTiles::<B, N> {
+            map_id,
+            tile_c_0,
+            tile_c_1,
+            bundle: Default::default(),
+        });
+        self
+    }
+
+    /// Moves the `B` data of every `(from, to)` pair of tiles from the given iterator in a single command,
+    /// overwriting any `B` data at the destinations.
+    /// # Note
+    /// Every tile is taken before any are inserted, so moves can chain into each other (ex: shifting a row over by one).
+    /// Tiles that are moved away from and not moved into are left empty.
+    /// Moves are applied in order: a tile listed as a source more than once only moves along its first pair,
+    /// and when several tiles move to the same destination the later one wins.
+    /// Overwritten tiles are passed to [`TileComponent::discard_tile`].
+    fn move_tile_batch<B, IC>(&mut self, map_id: Entity, tile_cs: IC) -> &mut Self
+    where
+        B: TileComponent,
+        IC: IntoIterator<Item = ([i32; N], [i32; N])> + Send + 'static,
+    {
+        self.queue(MoveTileBatch::<B, IC, N> {
+            map_id,
+            tile_cs,
+            bundle: Default::default(),
+        });
+        self
+    }
+
+    /// Fills every tile in the region between `corner_1` and `corner_2` (inclusive) with
+    /// noise sampled at the tile's coordinate, overwriting any existing `B` data.
+    /// If the map has a [`crate::maps::MapSeed`], it is mixed into the noise seed.
+    fn fill_noise<B: TileComponent + From<f32>>(
+        &mut self,
+        map_id: Entity,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+        noise: NoiseConfig,
+    ) -> &mut Self {
+        self.queue(FillNoise::<B, N> {
+            map_id,
+            corner_1,
+            corner_2,
+            noise,
+            bundle: Default::default(),
+        });
+        self
+    }
+
+    /// Runs a filter over every existing `B` tile in the region between `corner_1` and `corner_2` (inclusive).
+    /// Tiles outside of the region (including ones in neighboring chunks) are read but not written.
+    fn filter_tiles<B: TileComponent + Copy + Into<f32> + From<f32>>(
+        &mut self,
+        map_id: Entity,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+        filter: TileFilter,
+    ) -> &mut Self {
+        self.queue(FilterTiles::<B, N> {
+            map_id,
+            corner_1,
+            corner_2,
+            filter,
+            bundle: Default::default(),
+        });
+        self
+    }
+
+    /// Runs `update_f` on every existing `B` tile in the region between `corner_1` and `corner_2` (inclusive)
+    /// in a single command.
+    fn update_region<B, F>(
+        &mut self,
+        map_id: Entity,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+        update_f: F,
+    ) -> &mut Self
+    where
+        B: TileComponent,
+        F: FnMut([i32; N], &mut B) + Send + 'static,
+    {
+        self.queue(UpdateRegion::<B, F, N> {
+            map_id,
+            corner_1,
+            corner_2,
+            update_f,
+            bundle: Default::default(),
+        });
+        self
+    }
+
+    /// Drags a brush along a path (in tile coordinates), calling `carve_f` with the current `B` value
+    /// and brush weight of every touched tile.  Returning [`Some`] overwrites the tile, [`None`] leaves it as is.
+    fn carve_path<B, F>(
+        &mut self,
+        map_id: Entity,
+        path: Vec<[f32; N]>,
+        brush: PathBrush,
+        carve_f: F,
+    ) -> &mut Self
+    where
+        B: TileComponent,
+        F: Fn(Option<&B>, f32) -> Option<B> + Send + 'static,
+    {
+        self.queue(CarvePath::<B, F, N> {
+            map_id,
+            path,
+            brush,
+            carve_f,
+            bundle: Default::default(),
+        });
+        self
+    }
+
+    /// Writes the distance from every tile in the region between `corner_1` and `corner_2` (inclusive)
+    /// to the nearest `S` tile matching `is_source` into the `D` layer, capped at `max_distance`.
+    fn distance_field<S, D, F>(
+        &mut self,
+        map_id: Entity,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+        metric: DistanceMetric,
+        max_distance: f32,
+        is_source: F,
+    ) -> &mut Self
+    where
+        S: TileComponent,
+        D: TileComponent + From<f32>,
+        F: Fn(&S) -> bool + Send + 'static,
+    {
+        self.queue(DistanceField::<S, D, F, N> {
+            map_id,
+            corner_1,
+            corner_2,
+            metric,
+            max_distance,
+            is_source,
+            layers: Default::default(),
+        });
+        self
+    }
+
+    /// Pastes a [`TileClipboard`] into a map with its lowest corner at `tile_c`, replacing any tiles it covers.
+    fn paste_region(
+        &mut self,
+        map_id: Entity,
+        clipboard: TileClipboard<N>,
+        tile_c: [i32; N],
+    ) -> &mut Self {
+        self.queue(PasteRegion::<N> {
+            map_id,
+            clipboard,
+            tile_c,
+        });
+        self
+    }
+
     /// Manually spawn a chunk entity, note that this will overwrite and despawn existing chunks at this location.
     fn spawn_chunk(&mut self, map_id: Entity, chunk_c: [i32; N]) {
         self.queue(SpawnChunk::<N> { map_id, chunk_c });
     }
 
-    // /// Spawns chunks from the given iterator using the given function.
-    // /// This will despawn any chunks (and their tiles) that already exists in this coordinate
-    // fn spawn_chunk_batch_with<F, B, IC>(&mut self, map_id: Entity, chunk_cs: IC, bundle_f: F)
-    // where
-    //     F: Fn([i32; N]) -> B + Send + 'static,
-    //     B: Bundle + Send + 'static,
-    //     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
-    // {
-    //     self.add(SpawnChunkBatch::<F, B, IC, N> {
-    //         map_id,
-    //         chunk_cs,
-    //         bundle_f,
-    //     });
-    // }
+    /// Spawns chunks at every coordinate from the given iterator, keeping chunks that already exist.
+    fn spawn_chunk_batch<IC>(&mut self, map_id: Entity, chunk_cs: IC) -> &mut Self
+    where
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        TileCommandExt::<N>::spawn_chunk_batch_with(self, map_id, chunk_cs, |_| ())
+    }
+
+    /// Spawns every chunk within `radius` chunks (along every axis) of `chunk_c` without any tiles,
+    /// keeping chunks that already exist.
+    fn reserve_chunks_around(
+        &mut self,
+        map_id: Entity,
+        chunk_c: [i32; N],
+        radius: u32,
+    ) -> &mut Self {
+        let radius = radius as i32;
+        let chunk_cs = CoordIterator::new(chunk_c.map(|c| c - radius), chunk_c.map(|c| c + radius));
+        TileCommandExt::<N>::spawn_chunk_batch(self, map_id, chunk_cs)
+    }
+
+    /// Spawns chunks at every coordinate from the given iterator, inserting a bundle made by the given function
+    /// into each one (including chunks that already exist).
+    fn spawn_chunk_batch_with<F, B, IC>(
+        &mut self,
+        map_id: Entity,
+        chunk_cs: IC,
+        bundle_f: F,
+    ) -> &mut Self
+    where
+        F: Fn([i32; N]) -> B + Send + 'static,
+        B: Bundle,
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        self.queue(SpawnChunkBatch::<F, B, IC, N> {
+            map_id,
+            chunk_cs,
+            bundle_f,
+        });
+        self
+    }
+
+    /// Runs the map's [`crate::generation::GenPipeline`] for a chunk.
+    fn generate_chunk(&mut self, map_id: Entity, chunk_c: [i32; N]) -> &mut Self {
+        self.queue(GenerateChunk::<N> { map_id, chunk_c });
+        self
+    }
+
+    /// Replaces all the `B` data of a chunk at once, spawning the chunk if needed.
+    /// `tiles` must have one entry per tile in the chunk, in tile index order, see [`ChunkWriter::set_data`].
+    fn set_chunk_data<B: TileComponent>(
+        &mut self,
+        map_id: Entity,
+        chunk_c: [i32; N],
+        tiles: Vec<Option<B>>,
+    ) -> &mut Self {
+        self.queue(SetChunkData::<B, N> {
+            map_id,
+            chunk_c,
+            tiles,
+        });
+        self
+    }
+
+    /// Swaps two chunks and all their tiles, moving a chunk into an empty spot if only one of them exists.
+    fn swap_chunks(
+        &mut self,
+        map_id: Entity,
+        chunk_c_0: [i32; N],
+        chunk_c_1: [i32; N],
+    ) -> &mut Self {
+        self.queue(SwapChunks::<N> {
+            map_id,
+            chunk_c_0,
+            chunk_c_1,
+        });
+        self
+    }
+
+    /// Inserts a bundle into a chunk entity (ex: biome tags or dirty flags), spawning the chunk if needed.
+    fn insert_chunk_bundle<B: Bundle>(
+        &mut self,
+        map_id: Entity,
+        chunk_c: [i32; N],
+        bundle: B,
+    ) -> &mut Self {
+        self.queue(InsertChunkBundle::<B, N> {
+            map_id,
+            chunk_c,
+            bundle,
+        });
+        self
+    }
+
+    /// Removes a bundle from a chunk entity if the chunk exists.
+    fn remove_chunk_bundle<B: Bundle>(&mut self, map_id: Entity, chunk_c: [i32; N]) -> &mut Self {
+        self.queue(RemoveChunkBundle::<B, N> {
+            map_id,
+            chunk_c,
+            bundle: Default::default(),
+        });
+        self
+    }
+
+    /// Sets the `B` data of every tile in a chunk to `value`, spawning the chunk if needed.
+    fn fill_chunk<B: TileComponent + Clone>(
+        &mut self,
+        map_id: Entity,
+        chunk_c: [i32; N],
+        value: B,
+    ) -> &mut Self {
+        self.queue(FillChunk::<B, N> {
+            map_id,
+            chunk_c,
+            value,
+        });
+        self
+    }
+
+    /// Removes the `B` data of every tile in a chunk.
+    fn clear_chunk<B: TileComponent>(&mut self, map_id: Entity, chunk_c: [i32; N]) -> &mut Self {
+        self.queue(ClearChunk::<B, N> {
+            map_id,
+            chunk_c,
+            bundle: Default::default(),
+        });
+        self
+    }
 
     /// Recursively despawn a chunk and all it's tiles.
     fn despawn_chunk(&mut self, map_id: Entity, chunk_c: [i32; N]) -> &mut Self {
@@ -238,26 +1427,71 @@ impl<'w, 's, const N: usize> TileCommandExt<'w, 's, N> for Commands<'w, 's> {
         self
     }
 
-    // /// Despawns chunks (and their tiles) from the given iterator.
-    // fn despawn_chunk_batch<IC>(&mut self, map_id: Entity, chunk_cs: IC)
-    // where
-    //     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
-    // {
-    //     self.add(DespawnChunkBatch::<IC, N> { map_id, chunk_cs });
-    // }
+    /// Recursively despawns chunks (and their tiles) from the given iterator.
+    fn despawn_chunk_batch<IC>(&mut self, map_id: Entity, chunk_cs: IC) -> &mut Self
+    where
+        IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+    {
+        self.queue(DespawnChunkBatch::<IC, N> { map_id, chunk_cs });
+        self
+    }
+
+    /// Shows or hides every existing chunk overlapping the region between `corner_1` and `corner_2` (inclusive),
+    /// by setting the chunk's [`Visibility`] (ex: hiding a roof map while the player is indoors).
+    /// # Note
+    /// Chunks of maps without [`UseTransforms`] get a [`Visibility`] as well, so renderers of data only maps can check it.
+    fn set_chunks_visible(
+        &mut self,
+        map_id: Entity,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+        visible: bool,
+    ) -> &mut Self {
+        self.queue(SetChunksVisible::<N> {
+            map_id,
+            corner_1,
+            corner_2,
+            visible,
+        });
+        self
+    }
+
+    /// Moves the registered layers of the source map into the destination map, shifted by `offset`,
+    /// then despawns the source map.
+    fn merge_maps(
+        &mut self,
+        dst_id: Entity,
+        src_id: Entity,
+        offset: [i32; N],
+        policy: MergePolicy,
+    ) -> &mut Self {
+        self.queue(MergeMaps::<N> {
+            dst_id,
+            src_id,
+            offset,
+            policy,
+        });
+        self
+    }
 
     /// Spawn a new map.
     fn spawn_map(&mut self, chunk_size: usize) -> TileMapCommands<'_, N> {
+        TileCommandExt::<N>::spawn_map_with(self, chunk_size, ())
+    }
+
+    /// Spawn a new map with the given bundle.
+    fn spawn_map_with(&mut self, chunk_size: usize, bundle: impl Bundle) -> TileMapCommands<'_, N> {
         TileMapCommands {
-            commands: self.spawn((
-                TileMap::<N>::with_chunk_size(chunk_size),
-                Visibility::default(),
-                InheritedVisibility::default(),
-                Transform::default(),
-            )),
+            commands: self.spawn((map_bundle::<N>(chunk_size), bundle)),
         }
     }
 
+    fn spawn_map_with_layers<L: LayerSet>(&mut self, chunk_size: usize) -> TileMapHandle<L, N> {
+        let mut map = TileCommandExt::<N>::spawn_map(self, chunk_size);
+        map.insert(L::map_layers());
+        TileMapHandle::from_id(map.id())
+    }
+
     /// Recursively despawns a map and all it's chunks and tiles.
     fn despawn_map(&mut self, map_id: Entity) -> &mut Self {
         self.reborrow().entity(map_id).despawn_recursive();
@@ -265,6 +1499,184 @@ impl<'w, 's, const N: usize> TileCommandExt<'w, 's, N> for Commands<'w, 's> {
     }
 }
 
+/// Edits tilemaps directly through the [`World`], for exclusive systems and tests that don't want to
+/// queue commands and flush them.
+/// # Note
+/// Edits are applied immediately, so unlike [`TileCommandExt`] replaced and removed tiles are returned.
+/// Edits to maps that don't exist are handled by the [`MissingMapPolicy`], the same as commands.
+pub trait TileWorldExt<const N: usize> {
+    /// Spawn a new map.
+    fn spawn_map(&mut self, chunk_size: usize) -> Entity;
+
+    /// Inserts a tile, returning the tile it replaced.
+    fn insert_tile<B: TileComponent>(
+        &mut self,
+        map_id: Entity,
+        tile_c: [i32; N],
+        bundle: B,
+    ) -> Option<B>;
+
+    /// Inserts tiles at every coordinate from the given iterator, using the given function to create each tile.
+    /// Returns the tiles that were replaced.
+    fn insert_tile_batch<F, B, IC>(&mut self, map_id: Entity, tile_cs: IC, bundle_f: F) -> Vec<B>
+    where
+        F: Fn([i32; N]) -> B,
+        B: TileComponent,
+        IC: IntoIterator<Item = [i32; N]>;
+
+    /// Gets the `B` data of a tile.
+    fn get_tile<B: TileComponent>(&self, map_id: Entity, tile_c: [i32; N]) -> Option<&B>;
+
+    /// Gets the `B` data of a tile, inserting the tile made by `default_f` first if there isn't one.
+    /// Returns [`None`] if the map doesn't exist, or if `B` is a tuple (its elements are kept in separate layers).
+    fn get_or_insert_tile<B, F>(
+        &mut self,
+        map_id: Entity,
+        tile_c: [i32; N],
+        default_f: F,
+    ) -> Option<&mut B>
+    where
+        B: TileComponent,
+        F: FnOnce() -> B;
+
+    /// Removes the `B` data of a tile and returns it.
+    fn take_tile<B: TileComponent>(&mut self, map_id: Entity, tile_c: [i32; N]) -> Option<B>;
+
+    /// Removes the `B` data of every tile from the given iterator, returning the tiles that existed with their coordinates.
+    fn take_tile_batch<B, IC>(&mut self, map_id: Entity, tile_cs: IC) -> Vec<([i32; N], B)>
+    where
+        B: TileComponent,
+        IC: IntoIterator<Item = [i32; N]>;
+
+    /// Spawns a chunk if it doesn't exist yet and returns its id.
+    fn spawn_chunk(&mut self, map_id: Entity, chunk_c: [i32; N]) -> Option<Entity>;
+
+    /// Recursively despawn a chunk and all it's tiles.
+    fn despawn_chunk(&mut self, map_id: Entity, chunk_c: [i32; N]);
+
+    /// Recursively despawns a map and all it's chunks and tiles.
+    fn despawn_map(&mut self, map_id: Entity);
+}
+
+impl<const N: usize> TileWorldExt<N> for World {
+    fn spawn_map(&mut self, chunk_size: usize) -> Entity {
+        self.spawn(map_bundle::<N>(chunk_size)).id()
+    }
+
+    fn insert_tile<B: TileComponent>(
+        &mut self,
+        map_id: Entity,
+        tile_c: [i32; N],
+        bundle: B,
+    ) -> Option<B> {
+        edit_map::<_, N>(self, map_id, None, |map| {
+            insert_tile::<B, N>(map, tile_c, bundle)
+        })
+    }
+
+    fn insert_tile_batch<F, B, IC>(&mut self, map_id: Entity, tile_cs: IC, bundle_f: F) -> Vec<B>
+    where
+        F: Fn([i32; N]) -> B,
+        B: TileComponent,
+        IC: IntoIterator<Item = [i32; N]>,
+    {
+        edit_map::<_, N>(self, map_id, Vec::new(), |map| {
+            let tile_cs: Vec<[i32; N]> = tile_cs.into_iter().collect();
+            let bundles: Vec<B> = tile_cs.iter().map(|tile_c| bundle_f(*tile_c)).collect();
+            insert_tile_batch::<B, N>(map, tile_cs, bundles).collect()
+        })
+    }
+
+    fn get_tile<B: TileComponent>(&self, map_id: Entity, tile_c: [i32; N]) -> Option<&B> {
+        let map = self.get::<TileMap<N>>(map_id)?;
+        self.get::<ChunkData<B>>(map.get_from_tile(tile_c)?)?
+            .get(calculate_tile_index(tile_c, map.get_chunk_size()))
+    }
+
+    fn get_or_insert_tile<B, F>(
+        &mut self,
+        map_id: Entity,
+        tile_c: [i32; N],
+        default_f: F,
+    ) -> Option<&mut B>
+    where
+        B: TileComponent,
+        F: FnOnce() -> B,
+    {
+        edit_map::<_, N>(self, map_id, None, |map| {
+            if !has_tile::<B, N>(map, tile_c) {
+                insert_tile::<B, N>(map, tile_c, default_f());
+            }
+            Some(())
+        })?;
+        let map = self.get::<TileMap<N>>(map_id)?;
+        let tile_i = calculate_tile_index(tile_c, map.get_chunk_size());
+        let chunk_id = map.get_from_tile(tile_c)?;
+        self.get_mut::<ChunkData<B>>(chunk_id)?
+            .into_inner()
+            .get_mut(tile_i)
+    }
+
+    fn take_tile<B: TileComponent>(&mut self, map_id: Entity, tile_c: [i32; N]) -> Option<B> {
+        edit_map::<_, N>(self, map_id, None, |map| take_tile::<B, N>(map, tile_c))
+    }
+
+    fn take_tile_batch<B, IC>(&mut self, map_id: Entity, tile_cs: IC) -> Vec<([i32; N], B)>
+    where
+        B: TileComponent,
+        IC: IntoIterator<Item = [i32; N]>,
+    {
+        edit_map::<_, N>(self, map_id, Vec::new(), |map| {
+            take_tile_batch::<B, N>(map, tile_cs).collect()
+        })
+    }
+
+    fn spawn_chunk(&mut self, map_id: Entity, chunk_c: [i32; N]) -> Option<Entity> {
+        edit_map::<_, N>(self, map_id, None, |map| {
+            Some(get_or_spawn_chunk::<N>(map, chunk_c).id())
+        })
+    }
+
+    fn despawn_chunk(&mut self, map_id: Entity, chunk_c: [i32; N]) {
+        DespawnChunk::<N> { map_id, chunk_c }.apply(self);
+        self.flush();
+    }
+
+    fn despawn_map(&mut self, map_id: Entity) {
+        if let Ok(map) = self.get_entity_mut(map_id) {
+            map.despawn_recursive();
+        }
+    }
+}
+
+/// The components every map is spawned with.
+#[inline]
+fn map_bundle<const N: usize>(chunk_size: usize) -> impl Bundle {
+    (
+        TileMap::<N>::with_chunk_size(chunk_size),
+        Visibility::default(),
+        InheritedVisibility::default(),
+        Transform::default(),
+    )
+}
+
+/// Edits a map through the [`World`], then applies the commands queued by the edit (ex: observer triggers).
+fn edit_map<R, const N: usize>(
+    world: &mut World,
+    map_id: Entity,
+    missing: R,
+    edit: impl FnOnce(&mut TempRemoved<'_, TileMap<N>>) -> R,
+) -> R {
+    let Some(mut map) = world.temp_remove::<TileMap<N>>(map_id) else {
+        missing_map(world, map_id);
+        return missing;
+    };
+    let out = edit(&mut map);
+    drop(map);
+    world.flush();
+    out
+}
+
 /// Spawns a chunk in the world if needed, inserts the info into the map, and returns
 /// and id for reinsertion
 #[inline]
@@ -295,20 +1707,22 @@ fn get_or_spawn_chunk<'a, const N: usize>(
         .get::<ChunkCoord<N>>(&ChunkCoord(chunk_c))
         .cloned();
 
-    let (use_transforms, tile_dims, tile_spacing) = map
+    let (use_transforms, tile_dims, tile_spacing, origin) = map
         .world
         .query::<(
             Option<&UseTransforms>,
             Option<&TileDims<N>>,
             Option<&TileSpacing<N>>,
+            Option<&MapOrigin<N>>,
         )>()
         .get(map.world, map.source)
         .unwrap();
 
-    let (use_transforms, tile_dims, tile_spacing) = (
+    let (use_transforms, tile_dims, tile_spacing, origin) = (
         use_transforms.cloned(),
         tile_dims.cloned(),
         tile_spacing.cloned(),
+        origin.cloned().unwrap_or_default(),
     );
 
     if let Some(chunk_id) = chunk_id {
@@ -318,15 +1732,28 @@ fn get_or_spawn_chunk<'a, const N: usize>(
         }
     }
 
+    assert_chunk_in_bounds(map.get_chunk_size(), chunk_c);
+
     spawn_chunk(
         map,
         chunk_c,
         use_transforms.is_some(),
         tile_dims,
         tile_spacing,
+        origin,
     )
 }
 
+/// Panics if a chunk coordinate is outside of the [`chunk_bounds`] of a map.
+#[inline]
+fn assert_chunk_in_bounds<const N: usize>(chunk_size: usize, chunk_c: [i32; N]) {
+    let (min, max) = chunk_bounds(chunk_size);
+    assert!(
+        chunk_c.iter().all(|c| (min..=max).contains(c)),
+        "Chunk {chunk_c:?} is outside of the map's bounds, see `coords::tile_bounds`."
+    );
+}
+
 #[inline]
 fn spawn_chunk<'a, const N: usize>(
     map: &'a mut TempRemoved<'_, TileMap<N>>,
@@ -334,6 +1761,7 @@ fn spawn_chunk<'a, const N: usize>(
     use_transforms: bool,
     tile_dims: Option<TileDims<N>>,
     tile_spacing: Option<TileSpacing<N>>,
+    origin: MapOrigin<N>,
 ) -> EntityWorldMut<'a> {
     let chunk_c = ChunkCoord(chunk_c);
 
@@ -341,19 +1769,61 @@ fn spawn_chunk<'a, const N: usize>(
         (true, Some(size)) => {
             let translation = match N {
                 1 => Vec3::new(
-                    calc_chunk_trans_dim(0, map.get_chunk_size(), chunk_c, size, tile_spacing),
+                    calc_chunk_trans_dim(
+                        0,
+                        map.get_chunk_size(),
+                        chunk_c,
+                        size,
+                        tile_spacing,
+                        origin,
+                    ),
                     0.0,
                     0.0,
                 ),
                 2 => Vec3::new(
-                    calc_chunk_trans_dim(0, map.get_chunk_size(), chunk_c, size, tile_spacing),
-                    calc_chunk_trans_dim(1, map.get_chunk_size(), chunk_c, size, tile_spacing),
+                    calc_chunk_trans_dim(
+                        0,
+                        map.get_chunk_size(),
+                        chunk_c,
+                        size,
+                        tile_spacing,
+                        origin,
+                    ),
+                    calc_chunk_trans_dim(
+                        1,
+                        map.get_chunk_size(),
+                        chunk_c,
+                        size,
+                        tile_spacing,
+                        origin,
+                    ),
                     0.0,
                 ),
                 3 => Vec3::new(
-                    calc_chunk_trans_dim(0, map.get_chunk_size(), chunk_c, size, tile_spacing),
-                    calc_chunk_trans_dim(1, map.get_chunk_size(), chunk_c, size, tile_spacing),
-                    calc_chunk_trans_dim(2, map.get_chunk_size(), chunk_c, size, tile_spacing),
+                    calc_chunk_trans_dim(
+                        0,
+                        map.get_chunk_size(),
+                        chunk_c,
+                        size,
+                        tile_spacing,
+                        origin,
+                    ),
+                    calc_chunk_trans_dim(
+                        1,
+                        map.get_chunk_size(),
+                        chunk_c,
+                        size,
+                        tile_spacing,
+                        origin,
+                    ),
+                    calc_chunk_trans_dim(
+                        2,
+                        map.get_chunk_size(),
+                        chunk_c,
+                        size,
+                        tile_spacing,
+                        origin,
+                    ),
                 ),
                 _ => {
                     panic!("Can't use transforms on tilemaps with more than 3 dimensions :)");
@@ -386,7 +1856,21 @@ fn spawn_chunk<'a, const N: usize>(
     };
 
     map.get_chunks_mut().insert(chunk_c, chunk_id);
-    map.world.get_entity_mut(chunk_id).unwrap()
+    trigger_on_map(
+        map.world,
+        map.source,
+        [OnChunkSpawned {
+            chunk_c: chunk_c.0,
+            chunk_id,
+        }],
+    );
+    let layers = map.world.get::<MapLayers>(map.source).cloned();
+    let tiles = map.get_chunk_size().pow(N as u32);
+    let mut chunk = map.world.get_entity_mut(chunk_id).unwrap();
+    if let Some(layers) = layers {
+        layers.insert_into(&mut chunk, tiles);
+    }
+    chunk
 }
 
 #[inline]
@@ -396,8 +1880,9 @@ fn calc_chunk_trans_dim<const N: usize>(
     chunk_c: ChunkCoord<N>,
     dims: TileDims<N>,
     spacing: Option<TileSpacing<N>>,
+    origin: MapOrigin<N>,
 ) -> f32 {
-    let coord = chunk_dims as f32 * chunk_c.0[dim] as f32;
+    let coord = (chunk_dims as i64 * chunk_c.0[dim] as i64 - origin.0[dim] as i64) as f32;
     dims.0[dim] * coord + spacing.map(|spacing| spacing.0[dim] * coord).unwrap_or(0.0)
 }
 
@@ -433,7 +1918,7 @@ pub fn insert_tile<B: TileComponent, const N: usize>(
     // Insert the tile
     let tile_i = calculate_tile_index(tile_c, chunk_size);
 
-    tile_bundle.insert_tile_into_chunk::<N>(
+    let replaced = tile_bundle.insert_tile_into_chunk::<N>(
         chunk,
         chunk_c,
         chunk_size,
@@ -442,7 +1927,11 @@ pub fn insert_tile<B: TileComponent, const N: usize>(
         tile_spacing,
         tile_c,
         tile_i,
-    )
+    );
+    B::update_occupied(map, chunk_c);
+    B::update_layer_index(map, &[tile_c]);
+    B::trigger_inserted(map, &[tile_c]);
+    replaced
 }
 
 /// Inserts a batch of tiles into the given map.
@@ -454,19 +1943,39 @@ pub fn insert_tile_batch<B: TileComponent, const N: usize>(
     tile_cs: impl IntoIterator<Item = [i32; N]>,
     tile_bundles: impl IntoIterator<Item = B>,
 ) -> impl Iterator<Item = B> {
+    #[cfg(feature = "trace")]
+    let _span = info_span!(
+        "insert_tile_batch",
+        layer = std::any::type_name::<B>(),
+        tiles = field::Empty,
+        chunks = field::Empty
+    )
+    .entered();
     let chunk_size = map.get_chunk_size();
-    let mut tiles = tile_bundles.into_iter();
 
     let mut chunk_cs = HashMap::new();
+    let mut inserted_cs = Vec::new();
 
-    for tile_c in tile_cs {
+    for (tile_c, tile) in tile_cs.into_iter().zip(tile_bundles) {
+        inserted_cs.push(tile_c);
         let chunk_c = calculate_chunk_coordinate(tile_c, chunk_size);
-        let tiles = match chunk_cs.entry(chunk_c) {
+        let (tile_is, tiles) = match chunk_cs.entry(chunk_c) {
             Entry::Occupied(occupied_entry) => occupied_entry.into_mut(),
-            Entry::Vacant(vacant_entry) => vacant_entry.insert(Vec::new()),
+            Entry::Vacant(vacant_entry) => vacant_entry.insert((Vec::new(), Vec::new())),
         };
-        tiles.push((tile_c, calculate_tile_index(tile_c, chunk_size)));
+        tile_is.push((tile_c, calculate_tile_index(tile_c, chunk_size)));
+        tiles.push(tile);
     }
+    #[cfg(feature = "trace")]
+    _span
+        .record(
+            "tiles",
+            chunk_cs
+                .values()
+                .map(|(_, tiles)| tiles.len())
+                .sum::<usize>(),
+        )
+        .record("chunks", chunk_cs.len());
 
     let mut replaced_vals = Vec::new();
 
@@ -486,10 +1995,10 @@ pub fn insert_tile_batch<B: TileComponent, const N: usize>(
         tile_spacing.cloned(),
     );
 
-    for (chunk_c, tile_is) in chunk_cs {
+    for (chunk_c, (tile_is, tiles)) in chunk_cs {
         let chunk = get_or_spawn_chunk::<N>(map, chunk_c);
         for replaced in B::insert_tile_batch_into_chunk::<N>(
-            &mut tiles,
+            tiles.into_iter(),
             chunk,
             chunk_c,
             chunk_size,
@@ -500,10 +2009,38 @@ pub fn insert_tile_batch<B: TileComponent, const N: usize>(
         ) {
             replaced_vals.push(replaced);
         }
+        B::update_occupied(map, chunk_c);
     }
+    B::update_layer_index(map, &inserted_cs);
+    B::trigger_inserted(map, &inserted_cs);
     replaced_vals.into_iter()
 }
 
+/// Gets a reference to the tile data at the given coordinate if it exists.
+#[inline]
+pub fn get_tile<'a, B: TileComponent, const N: usize>(
+    map: &'a TempRemoved<'_, TileMap<N>>,
+    tile_c: [i32; N],
+) -> Option<&'a B> {
+    let chunk_size = map.get_chunk_size();
+    let chunk_id = map.get_from_tile(tile_c)?;
+    map.world
+        .get::<ChunkData<B>>(chunk_id)?
+        .get(calculate_tile_index(tile_c, chunk_size))
+}
+
+/// Whether the given map has every layer of `B` at the given coordinate.
+#[inline]
+pub fn has_tile<B: TileComponent, const N: usize>(
+    map: &mut TempRemoved<'_, TileMap<N>>,
+    tile_c: [i32; N],
+) -> bool {
+    let tile_i = calculate_tile_index(tile_c, map.get_chunk_size());
+    map.get_from_tile(tile_c)
+        .and_then(|chunk_id| map.world.get_entity_mut(chunk_id).ok())
+        .is_some_and(|chunk| B::chunk_has_tile(&chunk, tile_i))
+}
+
 /// Removes a tile from the given map if it exists.
 #[inline]
 pub fn take_tile<B: TileComponent, const N: usize>(
@@ -520,7 +2057,189 @@ pub fn take_tile<B: TileComponent, const N: usize>(
     // Insert the tile
     let tile_i = calculate_tile_index(tile_c, chunk_size);
 
-    B::take_tile_from_chunk(&mut chunk_e, tile_i)
+    let taken = B::take_tile_from_chunk(&mut chunk_e, tile_i);
+    B::update_occupied(map, chunk_c.0);
+    B::update_layer_index(map, &[tile_c]);
+    if taken.is_some() {
+        B::trigger_removed(map, &[tile_c]);
+    }
+    despawn_if_empty(map, chunk_c.0);
+    taken
+}
+
+/// Removes a batch of tiles from the given map, returning the ones that existed with their coordinates.
+#[inline]
+pub fn take_tile_batch<B: TileComponent, const N: usize>(
+    map: &mut TempRemoved<'_, TileMap<N>>,
+    tile_cs: impl IntoIterator<Item = [i32; N]>,
+) -> impl Iterator<Item = ([i32; N], B)> {
+    #[cfg(feature = "trace")]
+    let _span = info_span!(
+        "take_tile_batch",
+        layer = std::any::type_name::<B>(),
+        tiles = field::Empty,
+        chunks = field::Empty
+    )
+    .entered();
+    let chunk_size = map.get_chunk_size();
+
+    let mut chunk_cs: HashMap<[i32; N], Vec<[i32; N]>> = HashMap::new();
+    for tile_c in tile_cs {
+        chunk_cs
+            .entry(calculate_chunk_coordinate(tile_c, chunk_size))
+            .or_default()
+            .push(tile_c);
+    }
+
+    #[cfg(feature = "trace")]
+    _span
+        .record("tiles", chunk_cs.values().map(Vec::len).sum::<usize>())
+        .record("chunks", chunk_cs.len());
+
+    let mut taken_vals = Vec::new();
+    let mut taken_cs = Vec::new();
+    let mut emptied = Vec::new();
+    for (chunk_c, tile_cs) in chunk_cs {
+        let Some(chunk_id) = map.get_from_chunk(ChunkCoord(chunk_c)) else {
+            continue;
+        };
+        let Ok(mut chunk_e) = map.world.get_entity_mut(chunk_id) else {
+            continue;
+        };
+        for tile_c in tile_cs {
+            let tile_i = calculate_tile_index(tile_c, chunk_size);
+            if let Some(taken) = B::take_tile_from_chunk(&mut chunk_e, tile_i) {
+                taken_vals.push((tile_c, taken));
+                taken_cs.push(tile_c);
+            }
+        }
+        B::update_occupied(map, chunk_c);
+        emptied.push(chunk_c);
+    }
+    B::update_layer_index(map, &taken_cs);
+    B::trigger_removed(map, &taken_cs);
+    for chunk_c in emptied {
+        despawn_if_empty(map, chunk_c);
+    }
+    taken_vals.into_iter()
+}
+
+/// Triggers [`OnChunkDespawned`] for a chunk that was just despawned.
+#[inline]
+fn chunk_despawned<const N: usize>(
+    map: &mut TempRemoved<'_, TileMap<N>>,
+    chunk_c: [i32; N],
+    chunk_id: Entity,
+) {
+    trigger_on_map(
+        map.world,
+        map.source,
+        [OnChunkDespawned { chunk_c, chunk_id }],
+    );
+}
+
+/// Removes a chunk from the map's bookkeeping (occupied bounds and any [`LayerIndex`]) once it's despawned.
+#[inline]
+fn forget_chunk<const N: usize>(map: &mut TempRemoved<'_, TileMap<N>>, chunk_c: [i32; N]) {
+    map.get_chunks_mut().remove(&ChunkCoord(chunk_c));
+    map.clear_occupied(chunk_c);
+    let chunk_size = map.get_chunk_size();
+    edit_layer_indexes(
+        map.world,
+        map.source,
+        ChunkIndexEdit::Remove(chunk_c),
+        chunk_size,
+    );
+}
+
+/// Despawns a chunk that has no tile data left, if the map has [`AutoDespawnEmptyChunks`].
+#[inline]
+fn despawn_if_empty<const N: usize>(map: &mut TempRemoved<'_, TileMap<N>>, chunk_c: [i32; N]) {
+    if map
+        .world
+        .get::<AutoDespawnEmptyChunks>(map.source)
+        .is_none()
+    {
+        return;
+    }
+    let Some(chunk_id) = map.get_from_chunk(ChunkCoord(chunk_c)) else {
+        return;
+    };
+    if map
+        .world
+        .get::<ChunkTypes>(chunk_id)
+        .is_some_and(|types| types.0.is_empty())
+    {
+        if let Ok(chunk) = map.world.get_entity_mut(chunk_id) {
+            chunk.despawn_recursive();
+        }
+        forget_chunk(map, chunk_c);
+        chunk_despawned(map, chunk_c, chunk_id);
+    }
+}
+
+/// Updates the map's [`TileMap::occupied_bounds`] for `B` with whether a chunk still has `B` data.
+#[inline]
+pub(crate) fn update_occupied<B: Send + Sync + 'static, const N: usize>(
+    map: &mut TempRemoved<'_, TileMap<N>>,
+    chunk_c: [i32; N],
+) {
+    let occupied = map
+        .get_from_chunk(ChunkCoord(chunk_c))
+        .and_then(|chunk_id| map.world.get::<ChunkData<B>>(chunk_id))
+        .is_some_and(|data| data.get_count() > 0);
+    map.set_occupied::<B>(chunk_c, occupied);
+}
+
+/// Updates the map's [`LayerIndex`] for `B` (if it has one) with the current value of some tiles.
+#[inline]
+pub(crate) fn update_layer_index<B: TileComponent, const N: usize>(
+    map: &mut TempRemoved<'_, TileMap<N>>,
+    tile_cs: impl IntoIterator<Item = [i32; N]>,
+) {
+    let chunk_size = map.get_chunk_size();
+    let Some(index) = map.world.get::<LayerIndex<B, N>>(map.source) else {
+        return;
+    };
+    let keys: Vec<([i32; N], Option<u64>)> = tile_cs
+        .into_iter()
+        .map(|tile_c| {
+            (
+                tile_c,
+                get_tile::<B, N>(map, tile_c).and_then(|value| index.key(value)),
+            )
+        })
+        .collect();
+    let mut index = map.world.get_mut::<LayerIndex<B, N>>(map.source).unwrap();
+    for (tile_c, key) in keys {
+        index.update(tile_c, key, chunk_size);
+    }
+}
+
+/// Triggers [`OnTileInserted`] for `B` on some tiles of the map.
+#[inline]
+pub(crate) fn trigger_inserted<B: TileComponent, const N: usize>(
+    map: &mut TempRemoved<'_, TileMap<N>>,
+    tile_cs: impl IntoIterator<Item = [i32; N]>,
+) {
+    trigger_on_map(
+        map.world,
+        map.source,
+        tile_cs.into_iter().map(OnTileInserted::<B, N>::new),
+    );
+}
+
+/// Triggers [`OnTileRemoved`] for `B` on some tiles of the map.
+#[inline]
+pub(crate) fn trigger_removed<B: TileComponent, const N: usize>(
+    map: &mut TempRemoved<'_, TileMap<N>>,
+    tile_cs: impl IntoIterator<Item = [i32; N]>,
+) {
+    trigger_on_map(
+        map.world,
+        map.source,
+        tile_cs.into_iter().map(OnTileRemoved::<B, N>::new),
+    );
 }
 
 /// Temporarily removed bundle from the world.
@@ -563,6 +2282,34 @@ impl<'w, T: Bundle> DerefMut for TempRemoved<'w, T> {
     }
 }
 
+/// How tile commands handle a map that was despawned before they were applied.
+/// Insert this as a resource to change it, without one commands panic.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissingMapPolicy {
+    /// Panic with "No tilemap found!".
+    #[default]
+    Panic,
+    /// Log a warning and skip the command.
+    Warn,
+    /// Silently skip the command.
+    Ignore,
+}
+
+/// Handles a command's map not being found, according to the world's [`MissingMapPolicy`].
+/// # Panics
+/// If the policy is [`MissingMapPolicy::Panic`].
+pub fn missing_map(world: &World, map_id: Entity) {
+    match world
+        .get_resource::<MissingMapPolicy>()
+        .copied()
+        .unwrap_or_default()
+    {
+        MissingMapPolicy::Panic => panic!("No tilemap found!"),
+        MissingMapPolicy::Warn => warn!("No tilemap found for {map_id}, skipping command."),
+        MissingMapPolicy::Ignore => {}
+    }
+}
+
 /// Temporarily remove a given group of components from an entity
 /// and put them back when done using them automatically.
 pub trait TempRemove {
@@ -1,11 +1,23 @@
-use bevy::ecs::{bundle::Bundle, entity::Entity, system::Command, world::World};
+#[cfg(feature = "trace")]
+use bevy::log::info_span;
+use bevy::{
+    ecs::{bundle::Bundle, entity::Entity, world::World},
+    prelude::{Command, DespawnRecursiveExt, Visibility},
+};
 
-use super::{insert_chunk_batch, take_chunk_batch_despawn_tiles};
+use crate::{
+    coords::{calculate_chunk_coordinate, CoordIterator},
+    maps::TileMap,
+};
+
+use super::{
+    chunk_despawned, forget_chunk, get_chunk, get_or_spawn_chunk, missing_map, TempRemove,
+};
 
 pub struct SpawnChunkBatch<F, B, IC, const N: usize = 2>
 where
     F: Fn([i32; N]) -> B + Send + 'static,
-    B: Bundle + Send + 'static,
+    B: Bundle,
     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
 {
     pub map_id: Entity,
@@ -16,22 +28,19 @@ where
 impl<F, B, IC, const N: usize> Command for SpawnChunkBatch<F, B, IC, N>
 where
     F: Fn([i32; N]) -> B + Send + 'static,
-    B: Bundle + Send + 'static,
+    B: Bundle,
     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
 {
     fn apply(self, world: &mut World) {
-        let (chunk_cs, bundles): (Vec<[i32; N]>, Vec<B>) = self
-            .chunk_cs
-            .into_iter()
-            .map(|coord| (coord, (self.bundle_f)(coord)))
-            .unzip();
-
-        let chunks = chunk_cs
-            .into_iter()
-            .zip(world.spawn_batch(bundles))
-            .collect::<Vec<([i32; N], Entity)>>();
-
-        insert_chunk_batch::<N>(world, self.map_id, chunks);
+        #[cfg(feature = "trace")]
+        let _span = info_span!("spawn_chunk_batch").entered();
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        for chunk_c in self.chunk_cs {
+            get_or_spawn_chunk::<N>(&mut map, chunk_c).insert((self.bundle_f)(chunk_c));
+        }
     }
 }
 
@@ -48,8 +57,49 @@ where
     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
 {
     fn apply(self, world: &mut World) {
-        for (_, tile_id) in take_chunk_batch_despawn_tiles::<N>(world, self.map_id, self.chunk_cs) {
-            world.despawn(tile_id);
+        #[cfg(feature = "trace")]
+        let _span = info_span!("despawn_chunk_batch").entered();
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        for chunk_c in self.chunk_cs {
+            if let Some(chunk) = get_chunk::<N>(&mut map, chunk_c) {
+                let chunk_id = chunk.id();
+                chunk.try_despawn_recursive();
+                chunk_despawned(&mut map, chunk_c, chunk_id);
+            }
+            forget_chunk(&mut map, chunk_c);
+        }
+    }
+}
+
+pub struct SetChunksVisible<const N: usize = 2> {
+    pub map_id: Entity,
+    pub corner_1: [i32; N],
+    pub corner_2: [i32; N],
+    pub visible: bool,
+}
+
+impl<const N: usize> Command for SetChunksVisible<N> {
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        let visibility = if self.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        let chunk_size = map.get_chunk_size();
+        for chunk_c in CoordIterator::new(
+            calculate_chunk_coordinate(self.corner_1, chunk_size),
+            calculate_chunk_coordinate(self.corner_2, chunk_size),
+        ) {
+            if let Some(mut chunk) = get_chunk::<N>(&mut map, chunk_c) {
+                chunk.insert(visibility);
+            }
         }
     }
 }
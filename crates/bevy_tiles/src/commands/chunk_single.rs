@@ -1,15 +1,25 @@
+use std::marker::PhantomData;
+
 use bevy::{
-    ecs::{entity::Entity, world::World},
-    prelude::{Command, DespawnRecursiveExt},
+    ecs::{bundle::Bundle, entity::Entity, world::World},
+    log::warn,
+    prelude::{Command, DespawnRecursiveExt, Transform},
 };
 
 use crate::{
     chunks::ChunkCoord,
     commands::get_chunk,
+    generation::GenPipeline,
+    index::{edit_layer_indexes, ChunkIndexEdit},
     maps::{TileDims, TileMap, TileSpacing},
+    observers::{trigger_on_map, OnChunkMoved},
+    queries::TileComponent,
 };
 
-use super::{get_or_spawn_chunk, TempRemove};
+use super::{
+    assert_chunk_in_bounds, chunk_despawned, forget_chunk, get_or_spawn_chunk, missing_map,
+    ChunkWriter, TempRemove,
+};
 
 pub struct SpawnChunk<const N: usize = 2> {
     pub map_id: Entity,
@@ -19,7 +29,7 @@ pub struct SpawnChunk<const N: usize = 2> {
 impl<const N: usize> Command for SpawnChunk<N> {
     fn apply(self, world: &mut World) {
         let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
-            panic!("No tilemap found!")
+            return missing_map(world, self.map_id);
         };
 
         get_or_spawn_chunk::<N>(&mut map, self.chunk_c);
@@ -34,12 +44,299 @@ pub struct DespawnChunk<const N: usize> {
 impl<const N: usize> Command for DespawnChunk<N> {
     fn apply(self, world: &mut World) {
         let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
-            panic!("No tilemap found!")
+            return missing_map(world, self.map_id);
         };
 
         if let Some(chunk) = get_chunk::<N>(&mut map, self.chunk_c) {
+            let chunk_id = chunk.id();
             chunk.try_despawn_recursive();
+            chunk_despawned(&mut map, self.chunk_c, chunk_id);
+        }
+        forget_chunk(&mut map, self.chunk_c);
+    }
+}
+
+pub struct SetChunkData<B, const N: usize> {
+    pub map_id: Entity,
+    pub chunk_c: [i32; N],
+    pub tiles: Vec<Option<B>>,
+}
+
+impl<B: TileComponent, const N: usize> Command for SetChunkData<B, N> {
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        ChunkWriter::new(&mut map, self.chunk_c).set_data(self.tiles);
+    }
+}
+
+pub struct SwapChunks<const N: usize> {
+    pub map_id: Entity,
+    pub chunk_c_0: [i32; N],
+    pub chunk_c_1: [i32; N],
+}
+
+impl<const N: usize> Command for SwapChunks<N> {
+    fn apply(self, world: &mut World) {
+        if self.chunk_c_0 == self.chunk_c_1 {
+            return;
+        }
+
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        let chunk_size = map.get_chunk_size();
+        assert_chunk_in_bounds(chunk_size, self.chunk_c_0);
+        assert_chunk_in_bounds(chunk_size, self.chunk_c_1);
+
+        let (chunk_c_0, chunk_c_1) = (ChunkCoord(self.chunk_c_0), ChunkCoord(self.chunk_c_1));
+        let chunk_0 = map.get_chunks_mut().remove(&chunk_c_0);
+        let chunk_1 = map.get_chunks_mut().remove(&chunk_c_1);
+        if let Some(chunk_0) = chunk_0 {
+            map.get_chunks_mut().insert(chunk_c_1, chunk_0);
+        }
+        if let Some(chunk_1) = chunk_1 {
+            map.get_chunks_mut().insert(chunk_c_0, chunk_1);
+        }
+        map.swap_occupied(self.chunk_c_0, self.chunk_c_1);
+        edit_layer_indexes(
+            map.world,
+            map.source,
+            ChunkIndexEdit::Swap(self.chunk_c_0, self.chunk_c_1),
+            chunk_size,
+        );
+
+        // Chunks are moved by the distance between the two coordinates, so chunks rebased by a floating origin stay put.
+        let pitch = map.world.get::<TileDims<N>>(map.source).map(|dims| {
+            let spacing = map.world.get::<TileSpacing<N>>(map.source);
+            std::array::from_fn::<f32, N, _>(|i| {
+                dims.0[i] + spacing.map(|spacing| spacing.0[i]).unwrap_or(0.0)
+            })
+        });
+        let mut moved = Vec::new();
+        for (chunk_id, from, to) in [
+            (chunk_0, self.chunk_c_0, self.chunk_c_1),
+            (chunk_1, self.chunk_c_1, self.chunk_c_0),
+        ] {
+            let Some(mut chunk) = chunk_id.and_then(|id| map.world.get_entity_mut(id).ok()) else {
+                continue;
+            };
+            chunk.insert(ChunkCoord(to));
+            if let (Some(pitch), Some(mut chunk_t)) = (pitch, chunk.get_mut::<Transform>()) {
+                for i in 0..N.min(3) {
+                    chunk_t.translation[i] +=
+                        (to[i] - from[i]) as f32 * chunk_size as f32 * pitch[i];
+                }
+            }
+            moved.push(OnChunkMoved {
+                from,
+                to,
+                chunk_id: chunk.id(),
+            });
+        }
+        trigger_on_map(map.world, map.source, moved);
+    }
+}
+
+pub struct InsertChunkBundle<B, const N: usize>
+where
+    B: Bundle,
+{
+    pub map_id: Entity,
+    pub chunk_c: [i32; N],
+    pub bundle: B,
+}
+
+impl<B: Bundle, const N: usize> Command for InsertChunkBundle<B, N> {
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        get_or_spawn_chunk::<N>(&mut map, self.chunk_c).insert(self.bundle);
+    }
+}
+
+pub struct RemoveChunkBundle<B, const N: usize>
+where
+    B: Bundle,
+{
+    pub map_id: Entity,
+    pub chunk_c: [i32; N],
+    pub bundle: PhantomData<B>,
+}
+
+impl<B: Bundle, const N: usize> Command for RemoveChunkBundle<B, N> {
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        if let Some(mut chunk) = get_chunk::<N>(&mut map, self.chunk_c) {
+            chunk.remove::<B>();
+        }
+    }
+}
+
+pub struct FillChunk<B, const N: usize>
+where
+    B: TileComponent + Clone,
+{
+    pub map_id: Entity,
+    pub chunk_c: [i32; N],
+    pub value: B,
+}
+
+impl<B: TileComponent + Clone, const N: usize> Command for FillChunk<B, N> {
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        ChunkWriter::new(&mut map, self.chunk_c).fill(self.value);
+    }
+}
+
+pub struct ClearChunk<B, const N: usize>
+where
+    B: TileComponent,
+{
+    pub map_id: Entity,
+    pub chunk_c: [i32; N],
+    pub bundle: PhantomData<B>,
+}
+
+impl<B: TileComponent, const N: usize> Command for ClearChunk<B, N> {
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        ChunkWriter::new(&mut map, self.chunk_c).clear::<B>();
+    }
+}
+
+pub struct GenerateChunk<const N: usize> {
+    pub map_id: Entity,
+    pub chunk_c: [i32; N],
+}
+
+impl<const N: usize> Command for GenerateChunk<N> {
+    fn apply(self, world: &mut World) {
+        if world.get::<TileMap<N>>(self.map_id).is_none() {
+            return missing_map(world, self.map_id);
+        }
+        let Some(pipeline) = world.get::<GenPipeline<N>>(self.map_id).cloned() else {
+            warn!(
+                "No generation pipeline found for {}, skipping command.",
+                self.map_id
+            );
+            return;
+        };
+        pipeline.generate(world, self.map_id, self.chunk_c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chunks::ChunkData, commands::TileWorldExt, index::LayerIndex, maps::UseTransforms, testing,
+    };
+
+    use super::*;
+
+    #[test]
+    fn set_whole_chunks() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 2, |map| {
+            map.insert(LayerIndex::<u8, 2>::new(|value| Some(*value as u64)));
+            map.insert_tile([0, 0], 9u8);
+            map.set_chunk_dense([0, 0], vec![1u8, 2, 3, 4]);
+            map.set_chunk_data([-1, 0], vec![None, Some(5u8), None, None]);
+            map.set_chunk_data::<u16>([3, 3], vec![None; 4]);
+        });
+
+        let map = world.get::<TileMap<2>>(map_id).unwrap();
+        let chunk_id = map.get_from_chunk(ChunkCoord([0, 0])).unwrap();
+        let other_id = map.get_from_chunk(ChunkCoord([-1, 0])).unwrap();
+        let chunk = world.get::<ChunkData<u8>>(chunk_id).unwrap();
+        assert_eq!(chunk.get_count(), 4);
+        assert_eq!(chunk.get(3), Some(&4));
+        assert_eq!(world.get::<ChunkData<u8>>(other_id).unwrap().get_count(), 1);
+        assert!(map.get_from_chunk(ChunkCoord([3, 3])).is_none());
+
+        let index = world.get::<LayerIndex<u8, 2>>(map_id).unwrap();
+        assert_eq!(index.key_of([0, 0]), Some(1));
+        assert_eq!(index.key_of([-1, 0]), Some(5));
+        assert_eq!(index.count(9), 0);
+    }
+
+    #[test]
+    fn swap_chunks_and_empty_chunks() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 2, |map| {
+            map.insert((UseTransforms, TileDims([1.0, 1.0]), TileSpacing([1.0, 0.0])));
+            map.insert(LayerIndex::<u8, 2>::new(|value| Some(*value as u64)));
+            map.insert_tile([0, 0], 1u8);
+            map.insert_tile([5, 1], 2u8);
+        });
+        let map = world.get::<TileMap<2>>(map_id).unwrap();
+        let chunk_0 = map.get_from_chunk(ChunkCoord([0, 0])).unwrap();
+        let chunk_1 = map.get_from_chunk(ChunkCoord([2, 0])).unwrap();
+
+        SwapChunks::<2> {
+            map_id,
+            chunk_c_0: [0, 0],
+            chunk_c_1: [2, 0],
+        }
+        .apply(&mut world);
+        let map = world.get::<TileMap<2>>(map_id).unwrap();
+        assert_eq!(map.get_from_chunk(ChunkCoord([0, 0])), Some(chunk_1));
+        assert_eq!(map.get_from_chunk(ChunkCoord([2, 0])), Some(chunk_0));
+        assert_eq!(
+            world.get::<ChunkCoord<2>>(chunk_0),
+            Some(&ChunkCoord([2, 0]))
+        );
+        let translation = |world: &World, id| world.get::<Transform>(id).unwrap().translation;
+        assert_eq!(translation(&world, chunk_0).x, 8.0);
+        assert_eq!(translation(&world, chunk_1).x, 0.0);
+        assert_eq!(map.occupied_bounds::<u8>(), Some(([0, 0], [5, 1])));
+        let index = world.get::<LayerIndex<u8, 2>>(map_id).unwrap();
+        assert_eq!(index.coords(1).collect::<Vec<_>>(), vec![[4, 0]]);
+        assert_eq!(index.coords(2).collect::<Vec<_>>(), vec![[1, 1]]);
+        assert_eq!(index.count_in_chunk(1, [2, 0]), 1);
+
+        SwapChunks::<2> {
+            map_id,
+            chunk_c_0: [0, 0],
+            chunk_c_1: [-3, 1],
+        }
+        .apply(&mut world);
+        let map = world.get::<TileMap<2>>(map_id).unwrap();
+        assert_eq!(map.get_from_chunk(ChunkCoord([0, 0])), None);
+        assert_eq!(map.get_from_chunk(ChunkCoord([-3, 1])), Some(chunk_1));
+        assert_eq!(map.occupied_bounds::<u8>(), Some(([-6, 0], [5, 3])));
+        assert_eq!(translation(&world, chunk_1).y, 2.0);
+        let index = world.get::<LayerIndex<u8, 2>>(map_id).unwrap();
+        assert_eq!(index.coords(2).collect::<Vec<_>>(), vec![[-5, 3]]);
+        assert_eq!(index.chunks(2).collect::<Vec<_>>(), vec![[-3, 1]]);
+        assert_eq!(index.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "is outside of the map's bounds")]
+    fn swap_chunks_out_of_bounds() {
+        let mut world = World::new();
+        let map_id = TileWorldExt::<2>::spawn_map(&mut world, 4);
+        TileWorldExt::<2>::insert_tile(&mut world, map_id, [0, 0], 1u8);
+        SwapChunks::<2> {
+            map_id,
+            chunk_c_0: [0, 0],
+            chunk_c_1: [i32::MAX, 0],
         }
-        map.get_chunks_mut().remove(&ChunkCoord(self.chunk_c));
+        .apply(&mut world);
     }
 }
@@ -0,0 +1,279 @@
+use std::any::TypeId;
+
+use bevy::ecs::entity::Entity;
+
+use crate::{
+    chunks::{ChunkCoord, ChunkData, ChunkTypes, FixedLayers},
+    coords::{
+        calculate_chunk_coordinate, calculate_tile_coordinate, calculate_tile_index, CoordIterator,
+    },
+    maps::TileMap,
+    queries::TileComponent,
+    tags::TagLayer,
+};
+
+use super::{
+    get_chunk, get_or_spawn_chunk, insert_tile, insert_tile_batch, take_tile, take_tile_batch,
+    update_layer_index, TempRemoved,
+};
+
+/// Write access to the layers of a single chunk of a map, by tile index.
+/// # Note
+/// Chunk commands and generation stages write whole chunks through this, so loaders should too.
+/// Writes keep the chunk's [`ChunkTypes`], the map's occupied bounds, and any [`crate::index::LayerIndex`] up to date.
+/// The chunk isn't spawned until something is written to it.
+pub struct ChunkWriter<'m, 'w, const N: usize> {
+    map: &'m mut TempRemoved<'w, TileMap<N>>,
+    chunk_c: [i32; N],
+    chunk_size: usize,
+}
+
+impl<'m, 'w, const N: usize> ChunkWriter<'m, 'w, N> {
+    /// Get a writer for a chunk of a map.
+    pub fn new(map: &'m mut TempRemoved<'w, TileMap<N>>, chunk_c: impl Into<[i32; N]>) -> Self {
+        let chunk_size = map.get_chunk_size();
+        Self {
+            map,
+            chunk_c: chunk_c.into(),
+            chunk_size,
+        }
+    }
+
+    /// Get the coordinate of the chunk.
+    pub fn chunk_c(&self) -> [i32; N] {
+        self.chunk_c
+    }
+
+    /// Get the chunk entity, if it's spawned.
+    pub fn id(&self) -> Option<Entity> {
+        self.map.get_from_chunk(ChunkCoord(self.chunk_c))
+    }
+
+    /// Number of tiles in the chunk.
+    pub fn tile_count(&self) -> usize {
+        self.chunk_size.pow(N as u32)
+    }
+
+    /// Iterate over the coordinates of every tile in the chunk, in tile index order.
+    pub fn tiles(&self) -> CoordIterator<N> {
+        let min = ChunkCoord(self.chunk_c).to_tile_origin(self.chunk_size);
+        let max = min.map(|c| c + self.chunk_size as i32 - 1);
+        CoordIterator::new(min, max)
+    }
+
+    /// Get the coordinate of the tile at an index of the chunk.
+    pub fn tile_c(&self, tile_i: usize) -> [i32; N] {
+        assert!(
+            tile_i < self.tile_count(),
+            "Tile index {tile_i} is outside the chunk."
+        );
+        calculate_tile_coordinate(self.chunk_c, tile_i, self.chunk_size)
+    }
+
+    /// Get the index of a tile in the chunk, [`None`] if the tile is in another chunk.
+    pub fn tile_i(&self, tile_c: impl Into<[i32; N]>) -> Option<usize> {
+        let tile_c = tile_c.into();
+        (calculate_chunk_coordinate(tile_c, self.chunk_size) == self.chunk_c)
+            .then(|| calculate_tile_index(tile_c, self.chunk_size))
+    }
+
+    /// Get the `T` data of the tile at an index.
+    pub fn get<T: TileComponent>(&self, tile_i: usize) -> Option<&T> {
+        self.map.world.get::<ChunkData<T>>(self.id()?)?.get(tile_i)
+    }
+
+    /// Set the `T` data of the tile at an index, returning the replaced data.
+    pub fn set<T: TileComponent>(&mut self, tile_i: usize, value: T) -> Option<T> {
+        let tile_c = self.tile_c(tile_i);
+        insert_tile::<T, N>(self.map, tile_c, value)
+    }
+
+    /// Remove the `T` data of the tile at an index.
+    pub fn remove<T: TileComponent>(&mut self, tile_i: usize) -> Option<T> {
+        let tile_c = self.tile_c(tile_i);
+        take_tile::<T, N>(self.map, tile_c)
+    }
+
+    /// Replace all the `T` data of the chunk, `tiles` must have one entry per tile in tile index order.
+    /// # Note
+    /// [`TileComponent::PLAIN`] data is moved into the chunk as is, without triggering
+    /// [`crate::observers::OnTileInserted`] or [`crate::observers::OnTileRemoved`].
+    /// Other tiles (ex: entities) are set or removed one at a time, and the tiles they replace are passed to
+    /// [`TileComponent::discard_tile`].
+    pub fn set_data<T: TileComponent>(&mut self, tiles: Vec<Option<T>>) {
+        assert_eq!(
+            tiles.len(),
+            self.tile_count(),
+            "Chunk data must have one entry per tile in the chunk."
+        );
+
+        if !T::PLAIN {
+            for (tile_i, tile) in tiles.into_iter().enumerate() {
+                let replaced = match tile {
+                    Some(tile) => self.set(tile_i, tile),
+                    None => self.remove(tile_i),
+                };
+                if let Some(replaced) = replaced {
+                    replaced.discard_tile::<N>(self.map.world);
+                }
+            }
+            return;
+        }
+
+        let chunk_data = ChunkData::from_tiles(tiles);
+        if chunk_data.get_count() == 0 {
+            // Clearing a chunk that doesn't exist yet shouldn't spawn it.
+            if let Some(mut chunk) = get_chunk::<N>(self.map, self.chunk_c) {
+                if !chunk.contains::<FixedLayers>() {
+                    chunk.remove::<ChunkData<T>>();
+                    chunk
+                        .get_mut::<ChunkTypes>()
+                        .unwrap()
+                        .0
+                        .remove(&TypeId::of::<T>());
+                } else if chunk.contains::<ChunkData<T>>() {
+                    chunk.insert(chunk_data);
+                }
+            }
+        } else {
+            let mut chunk = get_or_spawn_chunk::<N>(self.map, self.chunk_c);
+            chunk.insert(chunk_data);
+            chunk
+                .get_mut::<ChunkTypes>()
+                .unwrap()
+                .0
+                .insert(TypeId::of::<T>());
+        }
+
+        T::update_occupied(self.map, self.chunk_c);
+        let tiles = self.tiles();
+        update_layer_index::<T, N>(self.map, tiles);
+    }
+
+    /// Replace all the `T` data of the chunk with one value per tile, see [`Self::set_data`].
+    pub fn set_dense<T: TileComponent>(&mut self, tiles: Vec<T>) {
+        self.set_data(tiles.into_iter().map(Some).collect());
+    }
+
+    /// Set the `T` data of every tile in the chunk to a value.
+    pub fn fill<T: TileComponent + Clone>(&mut self, value: T) {
+        let tiles = self.tiles();
+        let values = std::iter::repeat_n(value, self.tile_count());
+        let replaced: Vec<T> = insert_tile_batch::<T, N>(self.map, tiles, values).collect();
+        self.discard(replaced);
+    }
+
+    /// Remove the `T` data of every tile in the chunk.
+    pub fn clear<T: TileComponent>(&mut self) {
+        let tiles = self.tiles();
+        let taken: Vec<T> = take_tile_batch::<T, N>(self.map, tiles)
+            .map(|(_, tile)| tile)
+            .collect();
+        self.discard(taken);
+    }
+
+    /// Set the `T` data of every tile of the chunk that's in a tag layer to a value.
+    pub fn fill_tagged<T: TileComponent + Clone>(&mut self, tags: &TagLayer<N>, value: T) {
+        let tiles = self.tagged(tags);
+        let values = std::iter::repeat_n(value, tiles.len());
+        let replaced: Vec<T> = insert_tile_batch::<T, N>(self.map, tiles, values).collect();
+        self.discard(replaced);
+    }
+
+    /// Remove the `T` data of every tile of the chunk that's in a tag layer.
+    pub fn clear_tagged<T: TileComponent>(&mut self, tags: &TagLayer<N>) {
+        let tiles = self.tagged(tags);
+        let taken: Vec<T> = take_tile_batch::<T, N>(self.map, tiles)
+            .map(|(_, tile)| tile)
+            .collect();
+        self.discard(taken);
+    }
+
+    /// Pass tiles that were replaced or taken to [`TileComponent::discard_tile`].
+    fn discard<T: TileComponent>(&mut self, tiles: Vec<T>) {
+        for tile in tiles {
+            tile.discard_tile::<N>(self.map.world);
+        }
+    }
+
+    /// The coordinates of the tiles of the chunk in a tag layer, in tile index order.
+    fn tagged(&self, tags: &TagLayer<N>) -> Vec<[i32; N]> {
+        assert_eq!(
+            tags.get_chunk_size(),
+            self.chunk_size,
+            "Tag layers must have the same chunk size as the map to write with."
+        );
+        let Some(bits) = tags.chunk_bits(self.chunk_c) else {
+            return Vec::new();
+        };
+        let mut tiles = Vec::new();
+        for (word_i, word) in bits.iter().enumerate() {
+            let mut word = *word;
+            while word != 0 {
+                let tile_i = word_i * 64 + word.trailing_zeros() as usize;
+                tiles.push(calculate_tile_coordinate(
+                    self.chunk_c,
+                    tile_i,
+                    self.chunk_size,
+                ));
+                word &= word - 1;
+            }
+        }
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::world::World;
+
+    use crate::commands::{TempRemove, TileWorldExt};
+
+    use super::*;
+
+    #[test]
+    fn write_chunk_layers() {
+        let mut world = World::new();
+        let map_id = TileWorldExt::<3>::spawn_map(&mut world, 4);
+        let mut map = world.temp_remove::<TileMap<3>>(map_id).unwrap();
+        let mut writer = ChunkWriter::new(&mut map, [1, -1, 0]);
+        assert_eq!(writer.id(), None);
+        assert_eq!(writer.tile_count(), 64);
+        assert_eq!(writer.tiles().count(), 64);
+        for (tile_i, tile_c) in writer.tiles().enumerate() {
+            assert_eq!(writer.tile_c(tile_i), tile_c);
+            assert_eq!(writer.tile_i(tile_c), Some(tile_i));
+        }
+        assert_eq!(writer.tile_i([0, 0, 0]), None);
+
+        writer.set_data::<u8>(vec![None; 64]);
+        assert_eq!(writer.id(), None);
+
+        writer.fill(1u8);
+        assert!(writer.id().is_some());
+        assert_eq!(writer.set(63, 2u8), Some(1));
+        assert_eq!(writer.get::<u8>(63), Some(&2));
+        assert_eq!(writer.remove::<u8>(0), Some(1));
+        assert_eq!(writer.get::<u8>(0), None);
+
+        let mut tags = TagLayer::<3>::new(4);
+        tags.set([4, -4, 0], true)
+            .set([7, -1, 3], true)
+            .set([0, 0, 0], true);
+        writer.clear::<u8>();
+        writer.fill_tagged(&tags, 3u8);
+        assert_eq!(writer.get::<u8>(0), Some(&3));
+        assert_eq!(writer.get::<u8>(63), Some(&3));
+        writer.set_dense((0..64).map(|i| i as u16).collect());
+        writer.clear_tagged::<u16>(&tags);
+        assert_eq!(writer.get::<u16>(0), None);
+        assert_eq!(writer.get::<u16>(21), Some(&21));
+        drop(map);
+
+        assert_eq!(world.get_tile::<u8>(map_id, [7, -1, 3]), Some(&3));
+        assert_eq!(world.get_tile::<u8>(map_id, [0, 0, 0]), None);
+        assert_eq!(world.get_tile::<u16>(map_id, [5, -3, 1]), Some(&21));
+        let map = world.get::<TileMap<3>>(map_id).unwrap();
+        assert_eq!(map.occupied_bounds::<u8>(), Some(([4, -4, 0], [7, -1, 3])));
+    }
+}
@@ -0,0 +1,23 @@
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    log::warn,
+    prelude::Command,
+};
+
+use crate::merge::{merge_maps, MergePolicy};
+
+pub struct MergeMaps<const N: usize> {
+    pub dst_id: Entity,
+    pub src_id: Entity,
+    pub offset: [i32; N],
+    pub policy: MergePolicy,
+}
+
+impl<const N: usize> Command for MergeMaps<N> {
+    fn apply(self, world: &mut World) {
+        if let Err(err) = merge_maps::<N>(world, self.dst_id, self.src_id, self.offset, self.policy)
+        {
+            warn!("Maps weren't merged: {err}");
+        }
+    }
+}
@@ -1,15 +1,19 @@
+use std::marker::PhantomData;
+
 use bevy::{
-    ecs::{bundle::Bundle, entity::Entity, system::Command, world::World},
+    ecs::{entity::Entity, world::World},
+    prelude::Command,
     utils::HashMap,
 };
-use bimap::BiMap;
 
-use super::{insert_tile_batch, take_tile_batch};
+use crate::{maps::TileMap, queries::TileComponent};
+
+use super::{insert_tile_batch, missing_map, take_tile_batch, TempRemove};
 
-pub struct SpawnTileBatch<F, B, IC, const N: usize = 2>
+pub struct InsertTileBatch<F, B, IC, const N: usize = 2>
 where
     F: Fn([i32; N]) -> B + Send + 'static,
-    B: Bundle + Send + 'static,
+    B: TileComponent,
     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
 {
     pub map_id: Entity,
@@ -17,117 +21,143 @@ where
     pub bundle_f: F,
 }
 
-impl<F, B, IC, const N: usize> Command for SpawnTileBatch<F, B, IC, N>
+impl<F, B, IC, const N: usize> Command for InsertTileBatch<F, B, IC, N>
 where
     F: Fn([i32; N]) -> B + Send + 'static,
-    B: Bundle + Send + 'static,
+    B: TileComponent,
     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
 {
     fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
         let (tile_cs, bundles): (Vec<[i32; N]>, Vec<B>) = self
             .tile_cs
             .into_iter()
-            .map(|coord| (coord, (self.bundle_f)(coord)))
+            .map(|tile_c| (tile_c, (self.bundle_f)(tile_c)))
             .unzip();
 
-        let tiles = tile_cs
-            .into_iter()
-            .zip(world.spawn_batch(bundles))
-            .collect::<Vec<([i32; N], Entity)>>();
-
-        insert_tile_batch::<N>(world, self.map_id, tiles);
+        let _ = insert_tile_batch::<B, N>(&mut map, tile_cs, bundles);
     }
 }
 
-pub struct DespawnTileBatch<IC, const N: usize = 2>
+pub struct RemoveTileBatch<B, IC, const N: usize = 2>
 where
+    B: TileComponent,
     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
 {
     pub map_id: Entity,
     pub tile_cs: IC,
+    pub bundle: PhantomData<B>,
 }
 
-impl<IC, const N: usize> Command for DespawnTileBatch<IC, N>
+impl<B, IC, const N: usize> Command for RemoveTileBatch<B, IC, N>
 where
+    B: TileComponent,
     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
 {
     fn apply(self, world: &mut World) {
-        for (_, tile_id) in take_tile_batch::<N>(world, self.map_id, self.tile_cs) {
-            world.despawn(tile_id);
-        }
-    }
-}
-
-pub struct MoveTileBatch<IC, const N: usize = 2>
-where
-    IC: IntoIterator<Item = ([i32; N], [i32; N])> + Send + 'static,
-{
-    pub map_id: Entity,
-    pub tile_cs: IC,
-}
-
-impl<IC, const N: usize> Command for MoveTileBatch<IC, N>
-where
-    IC: IntoIterator<Item = ([i32; N], [i32; N])> + Send + 'static,
-{
-    fn apply(self, world: &mut World) {
-        const ERR_MESSAGE: &str =
-            "Couldn't find tile coord in batch move.  Maybe repeated tile coord in command.";
-
-        let mut tile_cs = self
-            .tile_cs
-            .into_iter()
-            .collect::<HashMap<[i32; N], [i32; N]>>();
-
-        let removed = take_tile_batch::<N>(
-            world,
-            self.map_id,
-            tile_cs.keys().cloned().collect::<Vec<[i32; N]>>(),
-        )
-        .into_iter()
-        .map(|(tile_c, tile_id)| (tile_cs.remove(&tile_c).expect(ERR_MESSAGE), tile_id));
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
 
-        insert_tile_batch::<N>(world, self.map_id, removed);
+        let _ = take_tile_batch::<B, N>(&mut map, self.tile_cs);
     }
 }
 
-pub struct SwapTileBatch<IC, const N: usize = 2>
+pub struct MoveTileBatch<B, IC, const N: usize = 2>
 where
+    B: TileComponent,
     IC: IntoIterator<Item = ([i32; N], [i32; N])> + Send + 'static,
 {
     pub map_id: Entity,
     pub tile_cs: IC,
+    pub bundle: PhantomData<B>,
 }
 
-impl<IC, const N: usize> Command for SwapTileBatch<IC, N>
+impl<B, IC, const N: usize> Command for MoveTileBatch<B, IC, N>
 where
+    B: TileComponent,
     IC: IntoIterator<Item = ([i32; N], [i32; N])> + Send + 'static,
 {
     fn apply(self, world: &mut World) {
-        const ERR_MESSAGE: &str =
-            "Couldn't find tile coord in batch move.  Maybe repeated tile coord in command.";
-
-        let tile_cs = self
-            .tile_cs
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        // Moves are applied in input order, so a source only moves along its first pair,
+        // and the later of two moves to the same destination wins.
+        let moves = self.tile_cs.into_iter().collect::<Vec<_>>();
+        let mut taken = take_tile_batch::<B, N>(&mut map, moves.iter().map(|(old_c, _)| *old_c))
+            .collect::<HashMap<_, _>>();
+        let (new_cs, tiles): (Vec<_>, Vec<_>) = moves
             .into_iter()
-            .collect::<BiMap<[i32; N], [i32; N]>>();
-
-        let removed_left = take_tile_batch::<N>(
-            world,
-            self.map_id,
-            tile_cs.left_values().cloned().collect::<Vec<[i32; N]>>(),
-        )
-        .into_iter()
-        .map(|(tile_c, tile_id)| (*tile_cs.get_by_left(&tile_c).expect(ERR_MESSAGE), tile_id));
-
-        let removed_right = take_tile_batch::<N>(
-            world,
-            self.map_id,
-            tile_cs.right_values().cloned().collect::<Vec<[i32; N]>>(),
-        )
-        .into_iter()
-        .map(|(tile_c, tile_id)| (*tile_cs.get_by_right(&tile_c).expect(ERR_MESSAGE), tile_id));
-
-        insert_tile_batch::<N>(world, self.map_id, removed_left.chain(removed_right));
+            .filter_map(|(old_c, new_c)| Some((new_c, taken.remove(&old_c)?)))
+            .unzip();
+        let replaced = insert_tile_batch::<B, N>(&mut map, new_cs, tiles).collect::<Vec<_>>();
+        for tile in replaced {
+            tile.discard_tile::<N>(map.world);
+        }
     }
 }
+
+// pub struct DespawnTileBatch<IC, const N: usize = 2>
+// where
+//     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+// {
+//     pub map_id: Entity,
+//     pub tile_cs: IC,
+// }
+//
+// impl<IC, const N: usize> Command for DespawnTileBatch<IC, N>
+// where
+//     IC: IntoIterator<Item = [i32; N]> + Send + 'static,
+// {
+//     fn apply(self, world: &mut World) {
+//         for (_, tile_id) in take_tile_batch::<N>(world, self.map_id, self.tile_cs) {
+//             world.despawn(tile_id);
+//         }
+//     }
+// }
+//
+// pub struct SwapTileBatch<IC, const N: usize = 2>
+// where
+//     IC: IntoIterator<Item = ([i32; N], [i32; N])> + Send + 'static,
+// {
+//     pub map_id: Entity,
+//     pub tile_cs: IC,
+// }
+//
+// impl<IC, const N: usize> Command for SwapTileBatch<IC, N>
+// where
+//     IC: IntoIterator<Item = ([i32; N], [i32; N])> + Send + 'static,
+// {
+//     fn apply(self, world: &mut World) {
+//         const ERR_MESSAGE: &str =
+//             "Couldn't find tile coord in batch move.  Maybe repeated tile coord in command.";
+//
+//         let tile_cs = self
+//             .tile_cs
+//             .into_iter()
+//             .collect::<BiMap<[i32; N], [i32; N]>>();
+//
+//         let removed_left = take_tile_batch::<N>(
+//             world,
+//             self.map_id,
+//             tile_cs.left_values().cloned().collect::<Vec<[i32; N]>>(),
+//         )
+//         .into_iter()
+//         .map(|(tile_c, tile_id)| (*tile_cs.get_by_left(&tile_c).expect(ERR_MESSAGE), tile_id));
+//
+//         let removed_right = take_tile_batch::<N>(
+//             world,
+//             self.map_id,
+//             tile_cs.right_values().cloned().collect::<Vec<[i32; N]>>(),
+//         )
+//         .into_iter()
+//         .map(|(tile_c, tile_id)| (*tile_cs.get_by_right(&tile_c).expect(ERR_MESSAGE), tile_id));
+//
+//         insert_tile_batch::<N>(world, self.map_id, removed_left.chain(removed_right));
+//     }
+// }
@@ -0,0 +1,46 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    prelude::Command,
+};
+
+use crate::{carve::PathBrush, maps::TileMap, queries::TileComponent};
+
+use super::{get_tile, insert_tile_batch, missing_map, TempRemove};
+
+pub struct CarvePath<B, F, const N: usize>
+where
+    B: TileComponent,
+    F: Fn(Option<&B>, f32) -> Option<B> + Send + 'static,
+{
+    pub map_id: Entity,
+    pub path: Vec<[f32; N]>,
+    pub brush: PathBrush,
+    pub carve_f: F,
+    pub bundle: PhantomData<B>,
+}
+
+impl<B, F, const N: usize> Command for CarvePath<B, F, N>
+where
+    B: TileComponent,
+    F: Fn(Option<&B>, f32) -> Option<B> + Send + 'static,
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        let (tile_cs, tiles): (Vec<[i32; N]>, Vec<B>) = self
+            .brush
+            .stroke(self.path)
+            .into_iter()
+            .filter_map(|(tile_c, weight)| {
+                let carved = (self.carve_f)(get_tile::<B, N>(&map, tile_c), weight)?;
+                Some((tile_c, carved))
+            })
+            .unzip();
+
+        let _ = insert_tile_batch::<B, N>(&mut map, tile_cs, tiles);
+    }
+}
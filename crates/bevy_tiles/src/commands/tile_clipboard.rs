@@ -0,0 +1,18 @@
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    prelude::Command,
+};
+
+use crate::clipboard::{paste_region, TileClipboard};
+
+pub struct PasteRegion<const N: usize> {
+    pub map_id: Entity,
+    pub clipboard: TileClipboard<N>,
+    pub tile_c: [i32; N],
+}
+
+impl<const N: usize> Command for PasteRegion<N> {
+    fn apply(self, world: &mut World) {
+        paste_region::<N>(world, self.map_id, &self.clipboard, self.tile_c);
+    }
+}
@@ -0,0 +1,116 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    prelude::Command,
+};
+
+use crate::{
+    coords::{calculate_chunk_coordinate, CoordIterator},
+    distance::{distance_transform, DistanceMetric},
+    filters::TileWindow,
+    maps::TileMap,
+    queries::TileComponent,
+};
+
+use super::{get_tile, insert_tile_batch, missing_map, TempRemove};
+
+pub struct DistanceField<S, D, F, const N: usize>
+where
+    S: TileComponent,
+    D: TileComponent + From<f32>,
+    F: Fn(&S) -> bool + Send + 'static,
+{
+    pub map_id: Entity,
+    pub corner_1: [i32; N],
+    pub corner_2: [i32; N],
+    pub metric: DistanceMetric,
+    pub max_distance: f32,
+    pub is_source: F,
+    pub layers: PhantomData<(S, D)>,
+}
+
+impl<S, D, F, const N: usize> Command for DistanceField<S, D, F, N>
+where
+    S: TileComponent,
+    D: TileComponent + From<f32>,
+    F: Fn(&S) -> bool + Send + 'static,
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        let chunk_size = map.get_chunk_size();
+        let region = TileWindow::<N>::new(self.corner_1, self.corner_2);
+        let sources = map.occupied_bounds::<S>();
+        // Sources further than max_distance away can't affect a chunk, and there aren't any outside of the layer's
+        // bounds, so every chunk only reads the part of its apron that could hold sources.
+        let apron = self.max_distance.max(0.0).ceil() as i32;
+        let mut tile_cs = Vec::new();
+        let mut tiles = Vec::new();
+        for chunk_c in CoordIterator::new(
+            calculate_chunk_coordinate(region.min(), chunk_size),
+            calculate_chunk_coordinate(region.max(), chunk_size),
+        ) {
+            let min: [i32; N] =
+                std::array::from_fn(|i| (chunk_c[i] * chunk_size as i32).max(region.min()[i]));
+            let max: [i32; N] = std::array::from_fn(|i| {
+                (chunk_c[i] * chunk_size as i32 + chunk_size as i32 - 1).min(region.max()[i])
+            });
+            let (mut read_min, mut read_max) = (min, max);
+            if let Some((sources_min, sources_max)) = sources {
+                for i in 0..N {
+                    read_min[i] = read_min[i].min(min[i].saturating_sub(apron).max(sources_min[i]));
+                    read_max[i] = read_max[i].max(max[i].saturating_add(apron).min(sources_max[i]));
+                }
+            }
+            let distances = distance_transform(
+                read_min,
+                read_max,
+                self.metric,
+                self.max_distance,
+                |tile_c| get_tile::<S, N>(&map, tile_c).is_some_and(&self.is_source),
+            );
+            for tile_c in CoordIterator::new(min, max) {
+                if let Some(distance) = distances.get(tile_c) {
+                    tile_cs.push(tile_c);
+                    tiles.push(D::from(distance));
+                }
+            }
+        }
+
+        let _ = insert_tile_batch::<D, N>(&mut map, tile_cs, tiles);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::TileWorldExt;
+
+    use super::*;
+
+    #[test]
+    fn distance_field_by_chunk() {
+        let mut world = World::new();
+        let map_id = TileWorldExt::<2>::spawn_map(&mut world, 4);
+        TileWorldExt::<2>::insert_tile(&mut world, map_id, [1, 1], true);
+        for max_distance in [3.0, f32::INFINITY] {
+            DistanceField::<bool, f32, _, 2> {
+                map_id,
+                corner_1: [-6, 0],
+                corner_2: [9, 1],
+                metric: DistanceMetric::Chebyshev,
+                max_distance,
+                is_source: |wall: &bool| *wall,
+                layers: PhantomData,
+            }
+            .apply(&mut world);
+            let distance = |tile_c| TileWorldExt::<2>::get_tile::<f32>(&world, map_id, tile_c);
+            assert_eq!(distance([1, 1]), Some(&0.0));
+            assert_eq!(distance([-2, 0]), Some(&3.0));
+            assert_eq!(distance([5, 1]), Some(&max_distance.min(4.0)));
+            assert_eq!(distance([9, 0]), Some(&max_distance.min(8.0)));
+        }
+    }
+}
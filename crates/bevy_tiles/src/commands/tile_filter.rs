@@ -0,0 +1,116 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    prelude::Command,
+};
+
+use crate::{
+    chunks::{ChunkCoord, ChunkData},
+    coords::{calculate_chunk_coordinate, calculate_tile_index, CoordIterator},
+    filters::{TileFilter, TileWindow},
+    maps::TileMap,
+    queries::TileComponent,
+};
+
+use super::{missing_map, update_layer_index, TempRemove, TempRemoved};
+
+pub struct FilterTiles<B, const N: usize>
+where
+    B: TileComponent + Copy + Into<f32> + From<f32>,
+{
+    pub map_id: Entity,
+    pub corner_1: [i32; N],
+    pub corner_2: [i32; N],
+    pub filter: TileFilter,
+    pub bundle: PhantomData<B>,
+}
+
+impl<B, const N: usize> Command for FilterTiles<B, N>
+where
+    B: TileComponent + Copy + Into<f32> + From<f32>,
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        // Read the region plus an apron, this pulls in tiles from neighboring chunks.
+        let region = TileWindow::<N>::new(self.corner_1, self.corner_2);
+        let apron = self.filter.apron() as i32;
+        let window = read_window::<B, N>(
+            &map,
+            region.min().map(|c| c - apron),
+            region.max().map(|c| c + apron),
+        );
+
+        let filtered: Vec<([i32; N], f32)> = CoordIterator::new(region.min(), region.max())
+            .filter_map(|tile_c| Some((tile_c, self.filter.sample(&window, tile_c)?)))
+            .collect();
+
+        write_values::<B, N>(&mut map, filtered);
+    }
+}
+
+/// Copies the values of a numeric layer in a region into a [`TileWindow`].
+pub(crate) fn read_window<B, const N: usize>(
+    map: &TempRemoved<'_, TileMap<N>>,
+    min: [i32; N],
+    max: [i32; N],
+) -> TileWindow<N>
+where
+    B: TileComponent + Copy + Into<f32>,
+{
+    let chunk_size = map.get_chunk_size();
+    let mut window = TileWindow::new(min, max);
+    for chunk_c in CoordIterator::new(
+        calculate_chunk_coordinate(min, chunk_size),
+        calculate_chunk_coordinate(max, chunk_size),
+    ) {
+        let Some(chunk_data) = map
+            .get_from_chunk(ChunkCoord(chunk_c))
+            .and_then(|chunk_id| map.world.get::<ChunkData<B>>(chunk_id))
+        else {
+            continue;
+        };
+
+        let mut chunk_min = [0; N];
+        let mut chunk_max = [0; N];
+        for i in 0..N {
+            let origin = chunk_c[i] * chunk_size as i32;
+            chunk_min[i] = origin.max(min[i]);
+            chunk_max[i] = (origin + chunk_size as i32 - 1).min(max[i]);
+        }
+        for tile_c in CoordIterator::new(chunk_min, chunk_max) {
+            let value = chunk_data
+                .get(calculate_tile_index(tile_c, chunk_size))
+                .map(|value| (*value).into());
+            window.set(tile_c, value);
+        }
+    }
+    window
+}
+
+/// Overwrites existing tiles of a numeric layer in place, updating its [`crate::index::LayerIndex`].
+pub(crate) fn write_values<B, const N: usize>(
+    map: &mut TempRemoved<'_, TileMap<N>>,
+    values: impl IntoIterator<Item = ([i32; N], f32)>,
+) where
+    B: TileComponent + From<f32>,
+{
+    let chunk_size = map.get_chunk_size();
+    let mut written = Vec::new();
+    for (tile_c, value) in values {
+        let chunk_c = calculate_chunk_coordinate(tile_c, chunk_size);
+        let Some(chunk_id) = map.get_from_chunk(ChunkCoord(chunk_c)) else {
+            continue;
+        };
+        if let Some(mut chunk_data) = map.world.get_mut::<ChunkData<B>>(chunk_id) {
+            if let Some(tile) = chunk_data.get_mut(calculate_tile_index(tile_c, chunk_size)) {
+                *tile = B::from(value);
+                written.push(tile_c);
+            }
+        }
+    }
+    update_layer_index::<B, N>(map, written);
+}
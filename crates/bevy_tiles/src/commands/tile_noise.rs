@@ -0,0 +1,128 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    prelude::Command,
+    tasks::{ComputeTaskPool, TaskPool},
+};
+
+use crate::{
+    coords::{calculate_chunk_coordinate, CoordIterator},
+    maps::{MapSeed, TileMap},
+    noise::NoiseConfig,
+    queries::TileComponent,
+};
+
+use super::{insert_tile_batch, missing_map, TempRemove};
+
+pub struct FillNoise<B, const N: usize>
+where
+    B: TileComponent + From<f32>,
+{
+    pub map_id: Entity,
+    pub corner_1: [i32; N],
+    pub corner_2: [i32; N],
+    pub noise: NoiseConfig,
+    pub bundle: PhantomData<B>,
+}
+
+impl<B, const N: usize> Command for FillNoise<B, N>
+where
+    B: TileComponent + From<f32>,
+{
+    fn apply(mut self, world: &mut World) {
+        if let Some(map_seed) = world.get::<MapSeed>(self.map_id) {
+            self.noise.seed = map_seed.mix(self.noise.seed);
+        }
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        let chunk_size = map.get_chunk_size();
+        let mut min = self.corner_1;
+        let mut max = self.corner_2;
+        for i in 0..N {
+            if min[i] > max[i] {
+                std::mem::swap(&mut min[i], &mut max[i]);
+            }
+        }
+
+        // Evaluate each chunk's slice of the region in parallel, noise is a pure function of the coordinate.
+        let noise = self.noise;
+        let chunks = ComputeTaskPool::get_or_init(TaskPool::default).scope(|scope| {
+            for chunk_c in CoordIterator::new(
+                calculate_chunk_coordinate(min, chunk_size),
+                calculate_chunk_coordinate(max, chunk_size),
+            ) {
+                scope.spawn(async move {
+                    let mut chunk_min = [0; N];
+                    let mut chunk_max = [0; N];
+                    for i in 0..N {
+                        let origin = chunk_c[i] * chunk_size as i32;
+                        chunk_min[i] = origin.max(min[i]);
+                        chunk_max[i] = (origin + chunk_size as i32 - 1).min(max[i]);
+                    }
+                    CoordIterator::new(chunk_min, chunk_max)
+                        .map(|tile_c| (tile_c, noise.sample_tile(tile_c)))
+                        .collect::<Vec<_>>()
+                });
+            }
+        });
+
+        let (tile_cs, values): (Vec<[i32; N]>, Vec<f32>) = chunks.into_iter().flatten().unzip();
+        let _ = insert_tile_batch::<B, N>(&mut map, tile_cs, values.into_iter().map(B::from));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::world::World;
+
+    use crate::{
+        chunks::ChunkData,
+        maps::{MapSeed, TileMap},
+        noise::NoiseConfig,
+        testing,
+    };
+
+    #[test]
+    fn fill_noise_covers_region() {
+        let mut world = World::new();
+        let noise = NoiseConfig::with_seed(7);
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            map.fill_noise::<f32>([-3, -3], [5, 2], noise);
+        });
+
+        let mut tiles = 0;
+        let mut chunks = world.query::<&ChunkData<f32>>();
+        for chunk in chunks.iter(&world) {
+            tiles += chunk.get_count();
+        }
+        assert_eq!(tiles, 9 * 6);
+        assert!(world.get_entity(map_id).is_ok());
+    }
+
+    #[test]
+    fn fill_noise_uses_map_seed() {
+        let mut world = World::new();
+        let noise = NoiseConfig::with_seed(7);
+        let map_ids = [1, 1, 2].map(|seed| {
+            testing::spawn_map(&mut world, 4, |map| {
+                map.insert(MapSeed(seed));
+                map.fill_noise::<f32>([0, 0], [3, 3], noise);
+            })
+        });
+
+        let values = map_ids.map(|map_id| {
+            let map = world.get::<TileMap<2>>(map_id).unwrap();
+            let chunk = world
+                .get::<ChunkData<f32>>(map.get_from_tile([0, 0]).unwrap())
+                .unwrap();
+            (0..16)
+                .map(|i| *chunk.get(i).unwrap())
+                .collect::<Vec<f32>>()
+        });
+        assert_eq!(values[0], values[1]);
+        assert_ne!(values[0], values[2]);
+    }
+}
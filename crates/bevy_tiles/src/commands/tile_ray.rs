@@ -0,0 +1,74 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    math::Ray3d,
+    prelude::Command,
+};
+
+use crate::{convert::MapSpace, maps::TileMap, picking::raycast_grid_3d, queries::TileComponent};
+
+use super::{get_tile, insert_tile, missing_map, take_tile, TempRemove, TempRemoved};
+
+/// Finds the first `B` tile hit by a ray, along with the tile in front of the face it was hit on.
+fn ray_hit<B: TileComponent>(
+    map: &TempRemoved<'_, TileMap<3>>,
+    ray: Ray3d,
+    max_distance: f32,
+) -> Option<([i32; 3], [i32; 3])> {
+    let space = MapSpace::<3>::from_entity(map.world.entity(map.source))?;
+    let (tile_c, face, _) = raycast_grid_3d(ray, max_distance, &space, |tile_c| {
+        get_tile::<B, 3>(map, tile_c).is_some()
+    })?;
+    let mut front_c = tile_c;
+    for (c, o) in front_c.iter_mut().zip(face.offset()) {
+        *c += o;
+    }
+    Some((tile_c, front_c))
+}
+
+pub struct BreakTileAtRay<B>
+where
+    B: TileComponent,
+{
+    pub map_id: Entity,
+    pub ray: Ray3d,
+    pub max_distance: f32,
+    pub bundle: PhantomData<B>,
+}
+
+impl<B: TileComponent> Command for BreakTileAtRay<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<3>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        if let Some((tile_c, _)) = ray_hit::<B>(&map, self.ray, self.max_distance) {
+            take_tile::<B, 3>(&mut map, tile_c);
+        }
+    }
+}
+
+pub struct PlaceTileAtRay<B>
+where
+    B: TileComponent,
+{
+    pub map_id: Entity,
+    pub ray: Ray3d,
+    pub max_distance: f32,
+    pub bundle: B,
+}
+
+impl<B: TileComponent> Command for PlaceTileAtRay<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<3>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        if let Some((_, front_c)) = ray_hit::<B>(&map, self.ray, self.max_distance) {
+            if get_tile::<B, 3>(&map, front_c).is_none() {
+                insert_tile::<B, 3>(&mut map, front_c, self.bundle);
+            }
+        }
+    }
+}
@@ -7,7 +7,7 @@ use bevy::{
 
 use crate::{maps::TileMap, queries::TileComponent};
 
-use super::{insert_tile, take_tile, TempRemove};
+use super::{has_tile, insert_tile, missing_map, take_tile, TempRemove};
 
 pub struct InsertTile<B, const N: usize>
 where
@@ -21,13 +21,34 @@ where
 impl<B: TileComponent, const N: usize> Command for InsertTile<B, N> {
     fn apply(self, world: &mut World) {
         let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
-            panic!("No tilemap found!")
+            return missing_map(world, self.map_id);
         };
 
         insert_tile::<B, N>(&mut map, self.tile_c, self.bundle);
     }
 }
 
+pub struct InsertTileIfEmpty<B, const N: usize>
+where
+    B: TileComponent,
+{
+    pub map_id: Entity,
+    pub tile_c: [i32; N],
+    pub bundle: B,
+}
+
+impl<B: TileComponent, const N: usize> Command for InsertTileIfEmpty<B, N> {
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        if !has_tile::<B, N>(&mut map, self.tile_c) {
+            insert_tile::<B, N>(&mut map, self.tile_c, self.bundle);
+        }
+    }
+}
+
 pub struct RemoveTile<B, const N: usize>
 where
     B: TileComponent,
@@ -43,9 +64,99 @@ where
 {
     fn apply(self, world: &mut World) {
         let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
-            panic!("No tilemap found!")
+            return missing_map(world, self.map_id);
         };
 
         take_tile::<B, N>(&mut map, self.tile_c);
     }
 }
+
+pub struct TakeTile<B, F, const N: usize>
+where
+    B: TileComponent,
+    F: FnOnce(Option<B>) + Send + 'static,
+{
+    pub map_id: Entity,
+    pub tile_c: [i32; N],
+    pub take_f: F,
+    pub bundle: PhantomData<B>,
+}
+
+impl<B, F, const N: usize> Command for TakeTile<B, F, N>
+where
+    B: TileComponent,
+    F: FnOnce(Option<B>) + Send + 'static,
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        (self.take_f)(take_tile::<B, N>(&mut map, self.tile_c));
+    }
+}
+
+pub struct MoveTile<B, const N: usize>
+where
+    B: TileComponent,
+{
+    pub map_id: Entity,
+    pub old_c: [i32; N],
+    pub new_c: [i32; N],
+    pub bundle: PhantomData<B>,
+}
+
+impl<B, const N: usize> Command for MoveTile<B, N>
+where
+    B: TileComponent,
+{
+    fn apply(self, world: &mut World) {
+        if self.old_c == self.new_c {
+            return;
+        }
+
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        if let Some(tile) = take_tile::<B, N>(&mut map, self.old_c) {
+            insert_tile::<B, N>(&mut map, self.new_c, tile);
+        }
+    }
+}
+
+pub struct SwapTiles<B, const N: usize>
+where
+    B: TileComponent,
+{
+    pub map_id: Entity,
+    pub tile_c_0: [i32; N],
+    pub tile_c_1: [i32; N],
+    pub bundle: PhantomData<B>,
+}
+
+impl<B, const N: usize> Command for SwapTiles<B, N>
+where
+    B: TileComponent,
+{
+    fn apply(self, world: &mut World) {
+        if self.tile_c_0 == self.tile_c_1 {
+            return;
+        }
+
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        let tile_0 = take_tile::<B, N>(&mut map, self.tile_c_0);
+        let tile_1 = take_tile::<B, N>(&mut map, self.tile_c_1);
+
+        if let Some(tile_0) = tile_0 {
+            insert_tile::<B, N>(&mut map, self.tile_c_1, tile_0);
+        }
+
+        if let Some(tile_1) = tile_1 {
+            insert_tile::<B, N>(&mut map, self.tile_c_0, tile_1);
+        }
+    }
+}
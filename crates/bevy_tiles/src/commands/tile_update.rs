@@ -0,0 +1,100 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    prelude::Command,
+};
+
+use crate::{
+    chunks::{ChunkCoord, ChunkData},
+    coords::{calculate_chunk_coordinate, calculate_tile_index, CoordIterator},
+    maps::{TileDims, TileMap, TileSpacing, UseTransforms},
+    queries::TileComponent,
+};
+
+use super::{missing_map, TempRemove};
+
+pub struct UpdateRegion<B, F, const N: usize>
+where
+    B: TileComponent,
+    F: FnMut([i32; N], &mut B) + Send + 'static,
+{
+    pub map_id: Entity,
+    pub corner_1: [i32; N],
+    pub corner_2: [i32; N],
+    pub update_f: F,
+    pub bundle: PhantomData<B>,
+}
+
+impl<B, F, const N: usize> Command for UpdateRegion<B, F, N>
+where
+    B: TileComponent,
+    F: FnMut([i32; N], &mut B) + Send + 'static,
+{
+    fn apply(mut self, world: &mut World) {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+            return missing_map(world, self.map_id);
+        };
+
+        let chunk_size = map.get_chunk_size();
+        let (use_transforms, tile_dims, tile_spacing) = (
+            map.world.get::<UseTransforms>(map.source).is_some(),
+            map.world.get::<TileDims<N>>(map.source).cloned(),
+            map.world.get::<TileSpacing<N>>(map.source).cloned(),
+        );
+        let min: [i32; N] = std::array::from_fn(|i| self.corner_1[i].min(self.corner_2[i]));
+        let max: [i32; N] = std::array::from_fn(|i| self.corner_1[i].max(self.corner_2[i]));
+        let mut updated = Vec::new();
+        for chunk_c in CoordIterator::new(
+            calculate_chunk_coordinate(min, chunk_size),
+            calculate_chunk_coordinate(max, chunk_size),
+        ) {
+            let Some(chunk_id) = map.get_from_chunk(ChunkCoord(chunk_c)) else {
+                continue;
+            };
+
+            let mut chunk_min = [0; N];
+            let mut chunk_max = [0; N];
+            for i in 0..N {
+                let origin = chunk_c[i] * chunk_size as i32;
+                chunk_min[i] = origin.max(min[i]);
+                chunk_max[i] = (origin + chunk_size as i32 - 1).min(max[i]);
+            }
+            let tiles = CoordIterator::new(chunk_min, chunk_max)
+                .map(|tile_c| (tile_c, calculate_tile_index(tile_c, chunk_size)));
+
+            // Layers kept in one `ChunkData` are updated in place, tuples are taken out and put back.
+            if let Some(mut chunk_data) = map.world.get_mut::<ChunkData<B>>(chunk_id) {
+                for (tile_c, tile_i) in tiles {
+                    if let Some(tile) = chunk_data.get_mut(tile_i) {
+                        (self.update_f)(tile_c, tile);
+                        updated.push(tile_c);
+                    }
+                }
+                continue;
+            }
+            for (tile_c, tile_i) in tiles {
+                let mut chunk = map.world.entity_mut(chunk_id);
+                if !B::chunk_has_tile(&chunk, tile_i) {
+                    continue;
+                }
+                let Some(mut tile) = B::take_tile_from_chunk(&mut chunk, tile_i) else {
+                    continue;
+                };
+                (self.update_f)(tile_c, &mut tile);
+                let _ = tile.insert_tile_into_chunk::<N>(
+                    chunk,
+                    chunk_c,
+                    chunk_size,
+                    use_transforms,
+                    tile_dims,
+                    tile_spacing,
+                    tile_c,
+                    tile_i,
+                );
+                updated.push(tile_c);
+            }
+        }
+        B::update_layer_index(&mut map, &updated);
+    }
+}
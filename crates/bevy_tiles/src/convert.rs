@@ -0,0 +1,232 @@
+use bevy::{
+    ecs::{
+        entity::Entity,
+        query::With,
+        system::{Query, SystemParam},
+        world::EntityRef,
+    },
+    math::Vec3,
+    render::primitives::Aabb,
+    transform::components::GlobalTransform,
+};
+
+use crate::{
+    coords::CoordIterator,
+    maps::{MapOrigin, TileDims, TileMap, TileSpacing},
+};
+
+/// Where the tiles of a map are in world space.
+/// # Note
+/// Only the first three axes of a map are placed in world space, any others are left at zero.
+#[derive(Clone, Copy, Debug)]
+pub struct MapSpace<const N: usize> {
+    /// The transform of the map, [`GlobalTransform::IDENTITY`] for maps without one.
+    pub transform: GlobalTransform,
+    /// The size of the map's tiles.
+    pub dims: TileDims<N>,
+    /// The spacing between the map's tiles.
+    pub spacing: Option<TileSpacing<N>>,
+    /// The tile the map's local space is relative to.
+    pub origin: MapOrigin<N>,
+}
+
+impl<const N: usize> MapSpace<N> {
+    /// Get where the tiles of a map are in world space from the map's entity, returns [`None`] if the map
+    /// doesn't have [`TileDims`].
+    pub fn from_entity(map: EntityRef) -> Option<Self> {
+        Some(MapSpace {
+            transform: map.get::<GlobalTransform>().copied().unwrap_or_default(),
+            dims: *map.get::<TileDims<N>>()?,
+            spacing: map.get::<TileSpacing<N>>().copied(),
+            origin: map.get::<MapOrigin<N>>().copied().unwrap_or_default(),
+        })
+    }
+
+    /// The distance between the corners of neighboring tiles along an axis.
+    #[inline]
+    pub fn pitch(&self, axis: usize) -> f32 {
+        self.dims.0[axis] + self.spacing.map(|spacing| spacing.0[axis]).unwrap_or(0.0)
+    }
+
+    /// Get the world space position of the center of a tile.
+    pub fn tile_to_world(&self, tile_c: impl Into<[i32; N]>) -> Vec3 {
+        self.tile_anchor_to_world(tile_c, [0.5; N])
+    }
+
+    /// Get the world space position of a point in a tile, where `anchor` is `0.0` at the tile's lowest corner
+    /// and `1.0` at its highest along each axis.
+    pub fn tile_anchor_to_world(&self, tile_c: impl Into<[i32; N]>, anchor: [f32; N]) -> Vec3 {
+        let tile_c = tile_c.into();
+        let mut local = Vec3::ZERO;
+        for i in 0..N.min(3) {
+            local[i] = ((tile_c[i] - self.origin.0[i]) as f32 + anchor[i]) * self.pitch(i);
+        }
+        self.transform.transform_point(local)
+    }
+
+    /// Get the tile a world space position is in.
+    pub fn world_to_tile(&self, world: Vec3) -> [i32; N] {
+        self.world_to_tile_pos(world).map(|c| c.floor() as i32)
+    }
+
+    /// Get the position of a world space point in tile units, where tile `c` covers `c..c + 1` along each axis.
+    pub fn world_to_tile_pos(&self, world: Vec3) -> [f32; N] {
+        let local = self.transform.affine().inverse().transform_point3(world);
+        let mut tile_pos = self.origin.0.map(|c| c as f32);
+        for (i, c) in tile_pos.iter_mut().enumerate().take(3) {
+            *c += local[i] / self.pitch(i);
+        }
+        tile_pos
+    }
+
+    /// Get the world space bounding box of a chunk, covering `chunk_size` tiles from its lowest corner along each axis.
+    /// # Note
+    /// The box is axis aligned in world space, so it grows to fit chunks of rotated maps.
+    pub fn chunk_world_aabb(&self, chunk_size: usize, chunk_c: impl Into<[i32; N]>) -> Aabb {
+        let low = chunk_c.into().map(|c| c * chunk_size as i32);
+        let mut min = Vec3::INFINITY;
+        let mut max = Vec3::NEG_INFINITY;
+        for corner in CoordIterator::new([0; N], [1; N]) {
+            let corner =
+                self.tile_anchor_to_world(low, corner.map(|c| (c * chunk_size as i32) as f32));
+            min = min.min(corner);
+            max = max.max(corner);
+        }
+        Aabb::from_min_max(min, max)
+    }
+
+    /// Iterate over the world space bounding boxes of every chunk in a map (see [`MapSpace::chunk_world_aabb`]).
+    pub fn chunk_aabbs<'a>(
+        &'a self,
+        map: &'a TileMap<N>,
+    ) -> impl Iterator<Item = ([i32; N], Aabb)> + 'a {
+        map.get_chunks().keys().map(|chunk_c| {
+            (
+                chunk_c.0,
+                self.chunk_world_aabb(map.get_chunk_size(), chunk_c.0),
+            )
+        })
+    }
+}
+
+/// Translate a tile coordinate from one map to the tile of another map at the same place in world space.
+/// # Note
+/// Goes through the center of the tile, so a tile of a coarse map lands on one of the tiles it covers in a finer map.
+pub fn convert_coord<const N: usize, const M: usize>(
+    from: &MapSpace<N>,
+    to: &MapSpace<M>,
+    tile_c: impl Into<[i32; N]>,
+) -> [i32; M] {
+    to.world_to_tile(from.tile_to_world(tile_c))
+}
+
+/// Used to convert coordinates between maps placed in world space with [`TileDims`].
+#[derive(SystemParam)]
+pub struct MapSpaces<'w, 's, const N: usize = 2> {
+    map_q: Query<
+        'w,
+        's,
+        (
+            Option<&'static GlobalTransform>,
+            &'static TileDims<N>,
+            Option<&'static TileSpacing<N>>,
+            Option<&'static MapOrigin<N>>,
+        ),
+        With<TileMap<N>>,
+    >,
+}
+
+impl<'w, 's, const N: usize> MapSpaces<'w, 's, N> {
+    /// Get where the tiles of a map are in world space, returns [`None`] if the map doesn't have [`TileDims`].
+    pub fn get(&self, map_id: Entity) -> Option<MapSpace<N>> {
+        let (transform, dims, spacing, origin) = self.map_q.get(map_id).ok()?;
+        Some(MapSpace {
+            transform: transform.copied().unwrap_or_default(),
+            dims: *dims,
+            spacing: spacing.copied(),
+            origin: origin.copied().unwrap_or_default(),
+        })
+    }
+
+    /// Translate a tile coordinate from one map to the tile of another map at the same place in world space.
+    pub fn convert_coord(
+        &self,
+        from_map: Entity,
+        to_map: Entity,
+        tile_c: impl Into<[i32; N]>,
+    ) -> Option<[i32; N]> {
+        Some(convert_coord(
+            &self.get(from_map)?,
+            &self.get(to_map)?,
+            tile_c,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::{system::SystemState, world::World},
+        math::Vec3A,
+        transform::components::Transform,
+    };
+
+    use crate::testing;
+
+    use super::*;
+
+    #[test]
+    fn coarse_to_fine_and_back() {
+        let mut world = World::new();
+        let [nav, terrain] = [
+            (TileDims([1.0, 1.0]), Vec3::ZERO),
+            (TileDims([0.5, 0.5]), Vec3::new(-2.0, 1.0, 0.0)),
+        ]
+        .map(|(dims, translation)| {
+            testing::spawn_map(&mut world, 4, |map| {
+                map.insert((
+                    dims,
+                    GlobalTransform::from(Transform::from_translation(translation)),
+                ));
+            })
+        });
+
+        let mut state = SystemState::<MapSpaces>::new(&mut world);
+        let spaces = state.get(&world);
+        // The center of nav tile [3, -1] is at (3.5, -0.5), or (5.5, -1.5) from the terrain map.
+        assert_eq!(spaces.convert_coord(nav, terrain, [3, -1]), Some([11, -3]));
+        assert_eq!(spaces.convert_coord(terrain, nav, [11, -3]), Some([3, -1]));
+        assert_eq!(spaces.convert_coord(terrain, nav, [10, -4]), Some([3, -1]));
+
+        let untiled = world.spawn_empty().id();
+        let spaces = state.get(&world);
+        assert_eq!(spaces.convert_coord(nav, untiled, [0, 0]), None);
+    }
+
+    #[test]
+    fn chunk_bounds() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            map.insert_tile([1, -1], 0u8);
+            map.insert_tile([-5, 6], 0u8);
+        });
+
+        let space = MapSpace {
+            transform: GlobalTransform::from(Transform::from_xyz(1.0, 0.0, 0.0)),
+            dims: TileDims([2.0, 2.0]),
+            spacing: None,
+            origin: MapOrigin::default(),
+        };
+        let aabb = space.chunk_world_aabb(4, [0, -1]);
+        assert_eq!(aabb.min(), Vec3A::new(1.0, -8.0, 0.0));
+        assert_eq!(aabb.max(), Vec3A::new(9.0, 0.0, 0.0));
+
+        let map = world.get::<TileMap>(map_id).unwrap();
+        let mut aabbs: Vec<_> = space.chunk_aabbs(map).collect();
+        aabbs.sort_by_key(|(chunk_c, _)| *chunk_c);
+        assert_eq!(aabbs.len(), 2);
+        assert_eq!(aabbs[0].0, [-2, 1]);
+        assert_eq!(aabbs[0].1.min(), Vec3A::new(-15.0, 8.0, 0.0));
+        assert_eq!(aabbs[1].0, [0, -1]);
+    }
+}
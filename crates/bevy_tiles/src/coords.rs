@@ -1,3 +1,5 @@
+use bevy::math::{IVec2, IVec3};
+
 use crate::maps::{TileDims, TileSpacing};
 
 /// Calculate the coordinate of a chunk from a given tile coordinate and chunk size
@@ -6,6 +8,10 @@ pub fn calculate_chunk_coordinate<const N: usize>(
     tile_c: impl Into<[i32; N]>,
     chunk_size: usize,
 ) -> [i32; N] {
+    if chunk_size.is_power_of_two() {
+        let shift = chunk_size.trailing_zeros();
+        return tile_c.into().map(|i| i >> shift);
+    }
     tile_c.into().map(|i| {
         if i < 0 {
             (i + 1) / (chunk_size as i32) - 1
@@ -37,6 +43,10 @@ pub fn calculate_chunk_relative_tile_coordinate<const N: usize>(
     tile_c: impl Into<[i32; N]>,
     chunk_size: usize,
 ) -> [i32; N] {
+    if chunk_size.is_power_of_two() {
+        let mask = chunk_size as i32 - 1;
+        return tile_c.into().map(|i| i & mask);
+    }
     tile_c.into().map(|mut i| {
         i %= chunk_size as i32;
         if i < 0 {
@@ -51,6 +61,13 @@ pub fn calculate_chunk_relative_tile_coordinate<const N: usize>(
 pub fn calculate_tile_index<const N: usize>(tile_c: [i32; N], chunk_size: usize) -> usize {
     let mut index = 0;
     let relative_tile_c = calculate_chunk_relative_tile_coordinate(tile_c, chunk_size);
+    if chunk_size.is_power_of_two() {
+        let shift = chunk_size.trailing_zeros() as usize;
+        for (i, c) in relative_tile_c.iter().enumerate() {
+            index |= (*c as usize) << (shift * i);
+        }
+        return index;
+    }
     for (i, c) in relative_tile_c.iter().enumerate() {
         index += (*c as usize) * chunk_size.pow(i as u32);
     }
@@ -69,7 +86,7 @@ pub fn calculate_tile_coordinate<const N: usize>(
         if i == 0 {
             *c += (tile_i % chunk_size) as i32;
         } else {
-            *c += (tile_i / chunk_size.pow(i as u32)) as i32;
+            *c += (tile_i / chunk_size.pow(i as u32) % chunk_size) as i32;
         }
     }
     chunk_world_c
@@ -78,11 +95,56 @@ pub fn calculate_tile_coordinate<const N: usize>(
 /// Find the highest index possible in a chunk.
 #[inline]
 pub fn max_tile_index<const N: usize>(chunk_size: usize) -> usize {
-    let mut index = 0;
-    for i in 1..=N {
-        index += chunk_size.pow(i as u32);
+    chunk_size.pow(N as u32) - 1
+}
+
+/// Get the lowest and highest chunk coordinate (on every axis) a map with the given chunk size can hold.
+/// # Note
+/// See [`tile_bounds`].
+#[inline]
+pub fn chunk_bounds(chunk_size: usize) -> (i32, i32) {
+    let chunk_size = chunk_size as i64;
+    (
+        (i32::MIN as i64 / chunk_size) as i32,
+        ((i32::MAX as i64 + 1) / chunk_size - 1) as i32,
+    )
+}
+
+/// Get the lowest and highest tile coordinate (on every axis) a map with the given chunk size can hold.
+/// # Note
+/// Tile coordinates are `i32`s, so these are just inside of `i32::MIN..=i32::MAX`, trimmed to whole chunks so every
+/// tile of every chunk can be addressed without overflowing.  Spawning a chunk outside of these bounds panics.
+///
+/// Worlds that need more room (ex: a fine grid over a solar system) should be split into several maps, each
+/// placed with its own [`bevy::prelude::Transform`].
+#[inline]
+pub fn tile_bounds(chunk_size: usize) -> (i32, i32) {
+    let (min, max) = chunk_bounds(chunk_size);
+    let chunk_size = chunk_size as i64;
+    (
+        (min as i64 * chunk_size) as i32,
+        ((max as i64 + 1) * chunk_size - 1) as i32,
+    )
+}
+
+/// Check if a tile coordinate is within the [`tile_bounds`] for the given chunk size.
+#[inline]
+pub fn in_tile_bounds<const N: usize>(tile_c: impl Into<[i32; N]>, chunk_size: usize) -> bool {
+    let (min, max) = tile_bounds(chunk_size);
+    tile_c.into().iter().all(|c| (min..=max).contains(c))
+}
+
+/// Offset a tile coordinate, returning [`None`] if any axis would overflow.
+#[inline]
+pub fn checked_offset<const N: usize>(
+    tile_c: impl Into<[i32; N]>,
+    offset: impl Into<[i32; N]>,
+) -> Option<[i32; N]> {
+    let mut tile_c = tile_c.into();
+    for (c, o) in tile_c.iter_mut().zip(offset.into()) {
+        *c = c.checked_add(o)?;
     }
-    index - 1
+    Some(tile_c)
 }
 
 /// Calculate the tile coordinate given a world coordinate
@@ -165,6 +227,355 @@ impl<const N: usize> Iterator for CoordIterator<N> {
     }
 }
 
+/// Allows for iteration over the tiles crossed by a line between two coordinates (inclusive).
+pub struct LineIterator<const N: usize> {
+    start: [i32; N],
+    delta: [i32; N],
+    steps: i32,
+    step: i32,
+}
+
+impl<const N: usize> LineIterator<N> {
+    /// Create an iterator that walks from `start` to `end`, moving at most one tile along each axis per step.
+    pub fn new(start: impl Into<[i32; N]>, end: impl Into<[i32; N]>) -> Self {
+        let start = start.into();
+        let end = end.into();
+        let mut delta = [0; N];
+        for i in 0..N {
+            delta[i] = end[i] - start[i];
+        }
+        let steps = delta.iter().map(|d| d.abs()).max().unwrap_or(0);
+
+        Self {
+            start,
+            delta,
+            steps,
+            step: 0,
+        }
+    }
+}
+
+impl<const N: usize> Iterator for LineIterator<N> {
+    type Item = [i32; N];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.step > self.steps {
+            return None;
+        }
+
+        let mut ret = self.start;
+        if self.steps > 0 {
+            for (c, d) in ret.iter_mut().zip(self.delta) {
+                // Round to the nearest tile, with halves rounding away from zero.
+                let offset = 2 * d * self.step;
+                *c += (offset + offset.signum() * self.steps) / (2 * self.steps);
+            }
+        }
+        self.step += 1;
+
+        Some(ret)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.steps + 1 - self.step).max(0) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+/// The four directions between tiles that share an edge on a 2d grid, where north is `+y` and east is `+x`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dir4 {
+    /// `+y`
+    North,
+    /// `+x`
+    East,
+    /// `-y`
+    South,
+    /// `-x`
+    West,
+}
+
+impl Dir4 {
+    /// Every direction, clockwise from north.
+    pub const ALL: [Dir4; 4] = [Dir4::North, Dir4::East, Dir4::South, Dir4::West];
+
+    /// The coordinate delta of one step in this direction.
+    #[inline]
+    pub fn offset(self) -> [i32; 2] {
+        match self {
+            Dir4::North => [0, 1],
+            Dir4::East => [1, 0],
+            Dir4::South => [0, -1],
+            Dir4::West => [-1, 0],
+        }
+    }
+
+    /// The direction pointing the other way.
+    #[inline]
+    pub fn opposite(self) -> Self {
+        self.rotate_cw().rotate_cw()
+    }
+
+    /// The direction a quarter turn clockwise from this one.
+    #[inline]
+    pub fn rotate_cw(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    /// The direction a quarter turn counter clockwise from this one.
+    #[inline]
+    pub fn rotate_ccw(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    /// Iterate over the tiles sharing an edge with a tile, along with the direction to each one.
+    pub fn neighbors(tile_c: impl Into<[i32; 2]>) -> impl Iterator<Item = (Dir4, [i32; 2])> {
+        let [x, y] = tile_c.into();
+        Self::ALL.into_iter().map(move |dir| {
+            let [dx, dy] = dir.offset();
+            (dir, [x + dx, y + dy])
+        })
+    }
+}
+
+impl From<Dir4> for [i32; 2] {
+    fn from(value: Dir4) -> Self {
+        value.offset()
+    }
+}
+
+impl From<Dir4> for IVec2 {
+    fn from(value: Dir4) -> Self {
+        value.offset().into()
+    }
+}
+
+impl TryFrom<[i32; 2]> for Dir4 {
+    type Error = [i32; 2];
+
+    /// Get the direction of a coordinate delta, fails unless the delta is a single step along one axis.
+    fn try_from(value: [i32; 2]) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|dir| dir.offset() == value)
+            .ok_or(value)
+    }
+}
+
+/// The eight directions between tiles that share an edge or corner on a 2d grid, where north is `+y` and east is `+x`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dir8 {
+    /// `+y`
+    North,
+    /// `+x`, `+y`
+    NorthEast,
+    /// `+x`
+    East,
+    /// `+x`, `-y`
+    SouthEast,
+    /// `-y`
+    South,
+    /// `-x`, `-y`
+    SouthWest,
+    /// `-x`
+    West,
+    /// `-x`, `+y`
+    NorthWest,
+}
+
+impl Dir8 {
+    /// Every direction, clockwise from north.
+    pub const ALL: [Dir8; 8] = [
+        Dir8::North,
+        Dir8::NorthEast,
+        Dir8::East,
+        Dir8::SouthEast,
+        Dir8::South,
+        Dir8::SouthWest,
+        Dir8::West,
+        Dir8::NorthWest,
+    ];
+
+    /// The coordinate delta of one step in this direction.
+    #[inline]
+    pub fn offset(self) -> [i32; 2] {
+        match self {
+            Dir8::North => [0, 1],
+            Dir8::NorthEast => [1, 1],
+            Dir8::East => [1, 0],
+            Dir8::SouthEast => [1, -1],
+            Dir8::South => [0, -1],
+            Dir8::SouthWest => [-1, -1],
+            Dir8::West => [-1, 0],
+            Dir8::NorthWest => [-1, 1],
+        }
+    }
+
+    /// The direction pointing the other way.
+    #[inline]
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+
+    /// The direction an eighth of a turn clockwise from this one.
+    #[inline]
+    pub fn rotate_cw(self) -> Self {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    /// The direction an eighth of a turn counter clockwise from this one.
+    #[inline]
+    pub fn rotate_ccw(self) -> Self {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    /// True for the directions that move along both axes.
+    #[inline]
+    pub fn is_diagonal(self) -> bool {
+        self as usize % 2 == 1
+    }
+
+    /// Iterate over the tiles sharing an edge or corner with a tile, along with the direction to each one.
+    pub fn neighbors(tile_c: impl Into<[i32; 2]>) -> impl Iterator<Item = (Dir8, [i32; 2])> {
+        let [x, y] = tile_c.into();
+        Self::ALL.into_iter().map(move |dir| {
+            let [dx, dy] = dir.offset();
+            (dir, [x + dx, y + dy])
+        })
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(value: Dir4) -> Self {
+        Self::ALL[value as usize * 2]
+    }
+}
+
+impl From<Dir8> for [i32; 2] {
+    fn from(value: Dir8) -> Self {
+        value.offset()
+    }
+}
+
+impl From<Dir8> for IVec2 {
+    fn from(value: Dir8) -> Self {
+        value.offset().into()
+    }
+}
+
+impl TryFrom<[i32; 2]> for Dir8 {
+    type Error = [i32; 2];
+
+    /// Get the direction of a coordinate delta, fails unless the delta is a single step along one or both axes.
+    fn try_from(value: [i32; 2]) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|dir| dir.offset() == value)
+            .ok_or(value)
+    }
+}
+
+/// The six directions between tiles that share a face on a 3d grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dir6 {
+    /// `+x`
+    PosX,
+    /// `+y`
+    PosY,
+    /// `+z`
+    PosZ,
+    /// `-x`
+    NegX,
+    /// `-y`
+    NegY,
+    /// `-z`
+    NegZ,
+}
+
+impl Dir6 {
+    /// Every direction, positive axes first.
+    pub const ALL: [Dir6; 6] = [
+        Dir6::PosX,
+        Dir6::PosY,
+        Dir6::PosZ,
+        Dir6::NegX,
+        Dir6::NegY,
+        Dir6::NegZ,
+    ];
+
+    /// The coordinate delta of one step in this direction.
+    #[inline]
+    pub fn offset(self) -> [i32; 3] {
+        let mut offset = [0; 3];
+        offset[self as usize % 3] = if (self as usize) < 3 { 1 } else { -1 };
+        offset
+    }
+
+    /// The direction pointing the other way.
+    #[inline]
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 3) % 6]
+    }
+
+    /// The direction a quarter turn clockwise from this one around the z axis (looking down from `+z`),
+    /// [`Dir6::PosZ`] and [`Dir6::NegZ`] are unchanged.
+    #[inline]
+    pub fn rotate_cw(self) -> Self {
+        match self {
+            Dir6::PosX => Dir6::NegY,
+            Dir6::NegY => Dir6::NegX,
+            Dir6::NegX => Dir6::PosY,
+            Dir6::PosY => Dir6::PosX,
+            dir => dir,
+        }
+    }
+
+    /// The direction a quarter turn counter clockwise from this one around the z axis (looking down from `+z`),
+    /// [`Dir6::PosZ`] and [`Dir6::NegZ`] are unchanged.
+    #[inline]
+    pub fn rotate_ccw(self) -> Self {
+        self.rotate_cw().rotate_cw().rotate_cw()
+    }
+
+    /// Iterate over the tiles sharing a face with a tile, along with the direction to each one.
+    pub fn neighbors(tile_c: impl Into<[i32; 3]>) -> impl Iterator<Item = (Dir6, [i32; 3])> {
+        let tile_c = tile_c.into();
+        Self::ALL.into_iter().map(move |dir| {
+            let mut neighbor_c = tile_c;
+            for (c, o) in neighbor_c.iter_mut().zip(dir.offset()) {
+                *c += o;
+            }
+            (dir, neighbor_c)
+        })
+    }
+}
+
+impl From<Dir6> for [i32; 3] {
+    fn from(value: Dir6) -> Self {
+        value.offset()
+    }
+}
+
+impl From<Dir6> for IVec3 {
+    fn from(value: Dir6) -> Self {
+        value.offset().into()
+    }
+}
+
+impl TryFrom<[i32; 3]> for Dir6 {
+    type Error = [i32; 3];
+
+    /// Get the direction of a coordinate delta, fails unless the delta is a single step along one axis.
+    fn try_from(value: [i32; 3]) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|dir| dir.offset() == value)
+            .ok_or(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
@@ -215,4 +626,114 @@ mod tests {
     fn tile_index_test(#[case] chunk_size: usize, #[case] tile_c: [i32; 2], #[case] index: usize) {
         assert_eq!(calculate_tile_index(tile_c, chunk_size), index)
     }
+
+    #[rstest]
+    #[case([0, 0], [3, 1], vec![[0, 0], [1, 0], [2, 1], [3, 1]])]
+    #[case([0, 0], [-2, -2], vec![[0, 0], [-1, -1], [-2, -2]])]
+    #[case([1, 1], [1, 1], vec![[1, 1]])]
+    #[case([0, 0], [0, -3], vec![[0, 0], [0, -1], [0, -2], [0, -3]])]
+    fn line_iter(#[case] start: [i32; 2], #[case] end: [i32; 2], #[case] expected: Vec<[i32; 2]>) {
+        assert_eq!(LineIterator::new(start, end).collect::<Vec<_>>(), expected);
+    }
+
+    #[rstest]
+    #[case(4, (i32::MIN, i32::MAX))]
+    #[case(16, (i32::MIN, i32::MAX))]
+    #[case(3, (-2147483646, 2147483645))]
+    fn bounds_test(#[case] chunk_size: usize, #[case] bounds: (i32, i32)) {
+        let (min, max) = tile_bounds(chunk_size);
+        assert_eq!((min, max), bounds);
+        assert!(in_tile_bounds([min, max], chunk_size));
+        assert_eq!(
+            calculate_chunk_coordinate([min, max], chunk_size),
+            <[i32; 2]>::from(chunk_bounds(chunk_size))
+        );
+        assert_eq!(calculate_tile_index([min], chunk_size), 0);
+        assert_eq!(calculate_tile_index([max], chunk_size), chunk_size - 1);
+    }
+
+    #[rstest]
+    #[case(4)]
+    #[case(5)]
+    #[case(16)]
+    fn power_of_two_matches(#[case] chunk_size: usize) {
+        for tile_c in CoordIterator::new([-40, -40, -40], [40, 40, 40]) {
+            let chunk_c = calculate_chunk_coordinate(tile_c, chunk_size);
+            let relative_c = calculate_chunk_relative_tile_coordinate(tile_c, chunk_size);
+            for i in 0..3 {
+                assert_eq!(chunk_c[i], tile_c[i].div_euclid(chunk_size as i32));
+                assert_eq!(relative_c[i], tile_c[i].rem_euclid(chunk_size as i32));
+            }
+            let index = relative_c
+                .iter()
+                .rev()
+                .fold(0, |index, c| index * chunk_size + *c as usize);
+            assert_eq!(calculate_tile_index(tile_c, chunk_size), index);
+            assert_eq!(
+                calculate_tile_coordinate(chunk_c, index, chunk_size),
+                tile_c
+            );
+        }
+        let size = chunk_size as i32;
+        assert_eq!(
+            calculate_tile_coordinate([1, -1, 0], max_tile_index::<3>(chunk_size), chunk_size),
+            [2 * size - 1, -1, size - 1]
+        );
+    }
+
+    #[rstest]
+    #[case(4, 15, 63)]
+    #[case(5, 24, 124)]
+    #[case(16, 255, 4095)]
+    fn max_tile_index_test(#[case] chunk_size: usize, #[case] max_2: usize, #[case] max_3: usize) {
+        assert_eq!(max_tile_index::<2>(chunk_size), max_2);
+        assert_eq!(max_tile_index::<3>(chunk_size), max_3);
+        let size = chunk_size as i32;
+        assert_eq!(
+            calculate_tile_coordinate([-1, 2], max_2, chunk_size),
+            [-1, 3 * size - 1]
+        );
+    }
+
+    #[test]
+    fn checked_offset_test() {
+        assert_eq!(checked_offset([1, -1], [2, -2]), Some([3, -3]));
+        assert_eq!(checked_offset([i32::MAX, 0], [1, 0]), None);
+        assert!(!in_tile_bounds([0, i32::MAX], 3));
+    }
+
+    #[test]
+    fn directions() {
+        assert_eq!(Dir4::North.rotate_cw(), Dir4::East);
+        assert_eq!(Dir4::West.rotate_cw(), Dir4::North);
+        assert_eq!(Dir4::North.rotate_ccw(), Dir4::West);
+        assert_eq!(Dir4::East.opposite(), Dir4::West);
+        assert_eq!(Dir4::try_from([0, -1]), Ok(Dir4::South));
+        assert_eq!(Dir4::try_from([1, 1]), Err([1, 1]));
+
+        assert_eq!(Dir8::from(Dir4::South), Dir8::South);
+        assert_eq!(Dir8::NorthEast.opposite(), Dir8::SouthWest);
+        assert_eq!(Dir8::NorthWest.rotate_cw(), Dir8::North);
+        assert_eq!(Dir8::try_from([1, -1]), Ok(Dir8::SouthEast));
+        assert!(Dir8::SouthEast.is_diagonal() && !Dir8::South.is_diagonal());
+
+        assert_eq!(Dir6::NegY.offset(), [0, -1, 0]);
+        assert_eq!(Dir6::PosZ.opposite(), Dir6::NegZ);
+        assert_eq!(Dir6::PosY.rotate_cw(), Dir6::PosX);
+        assert_eq!(Dir6::PosY.rotate_ccw(), Dir6::NegX);
+        assert_eq!(Dir6::try_from([0, 0, 1]), Ok(Dir6::PosZ));
+
+        for dir in Dir8::ALL {
+            assert_eq!(dir.rotate_cw().rotate_ccw(), dir);
+            assert_eq!(Dir8::try_from(dir.offset()), Ok(dir));
+        }
+        for dir in Dir6::ALL {
+            assert_eq!(dir.opposite().opposite(), dir);
+        }
+
+        let neighbors: Vec<_> = Dir4::neighbors([2, 3]).collect();
+        assert_eq!(neighbors[1], (Dir4::East, [3, 3]));
+        assert_eq!(checked_offset([2, 3], Dir8::SouthWest), Some([1, 2]));
+        assert_eq!(Dir6::neighbors([0, 0, 0]).count(), 6);
+    }
 }
@@ -0,0 +1,267 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    ecs::{
+        entity::Entity,
+        query::{Changed, QueryState},
+        removal_detection::RemovedComponents,
+        system::{Local, Resource, SystemState},
+        world::World,
+    },
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    chunks::{ChunkCoord, ChunkData, InMap},
+    commands::{insert_tile, take_tile, TempRemove},
+    coords::{calculate_tile_index, CoordIterator},
+    maps::TileMap,
+    queries::TileComponent,
+};
+
+/// Read access to the tiles of a source layer around the tile being derived.
+pub struct Neighborhood<'a, A, const N: usize> {
+    world: &'a World,
+    map: &'a TileMap<N>,
+    center: [i32; N],
+    layer: PhantomData<fn() -> A>,
+}
+
+impl<'a, A: Send + Sync + 'static, const N: usize> Neighborhood<'a, A, N> {
+    /// The coordinate of the tile being derived.
+    #[inline]
+    pub fn center(&self) -> [i32; N] {
+        self.center
+    }
+
+    /// The source value of the tile being derived.
+    #[inline]
+    pub fn value(&self) -> Option<&'a A> {
+        self.get([0; N])
+    }
+
+    /// Get the source value of a tile relative to the tile being derived.
+    #[inline]
+    pub fn get(&self, offset: impl Into<[i32; N]>) -> Option<&'a A> {
+        let mut tile_c = self.center;
+        for (c, o) in tile_c.iter_mut().zip(offset.into()) {
+            *c += o;
+        }
+        self.world
+            .get::<ChunkData<A>>(self.map.get_from_tile(tile_c)?)?
+            .get(calculate_tile_index(tile_c, self.map.get_chunk_size()))
+    }
+}
+
+/// How a derived layer is computed from its source layer.
+pub type DeriveFn<A, B, const N: usize> = fn(&Neighborhood<A, N>) -> Option<B>;
+
+/// Keeps a layer of `B` tiles derived from the `A` tiles around them (ex: collision from terrain).
+/// # Note
+/// Whenever a chunk's `A` data changes, is removed, or the chunk is despawned, every `B` tile within `radius` of the
+/// chunk is derived again, returning [`None`] removes the `B` tile.  Derived tiles are written in [`PostUpdate`], so edits are picked up the same frame.
+///
+/// Only one derivation from `A` to `B` can be added per `N`, and `B` shouldn't be edited by anything else.
+/// Layers derived from other derived layers can lag a frame behind unless [`derive_layer`] systems are ordered.
+pub struct DerivedLayerPlugin<A, B, const N: usize> {
+    /// How far from a tile the source layer is read, changes to a chunk also update tiles this far outside of it.
+    pub radius: i32,
+    /// Computes a derived tile from the source tiles around it.
+    pub derive: DeriveFn<A, B, N>,
+}
+
+impl<A, B, const N: usize> DerivedLayerPlugin<A, B, N> {
+    /// Create a plugin deriving `B` tiles from the `A` tiles within `radius` of them.
+    pub fn new(radius: i32, derive: DeriveFn<A, B, N>) -> Self {
+        Self { radius, derive }
+    }
+}
+
+impl<A, B, const N: usize> Plugin for DerivedLayerPlugin<A, B, N>
+where
+    A: TileComponent,
+    B: TileComponent,
+{
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DerivedLayer::<A, B, N> {
+            radius: self.radius,
+            derive: self.derive,
+        })
+        .add_systems(PostUpdate, derive_layer::<A, B, N>);
+    }
+}
+
+/// The derivation added by a [`DerivedLayerPlugin`].
+#[derive(Resource)]
+pub struct DerivedLayer<A, B, const N: usize> {
+    radius: i32,
+    derive: DeriveFn<A, B, N>,
+}
+
+/// Derives `B` tiles around every chunk whose `A` data changed or was removed since the last run.
+pub fn derive_layer<A: TileComponent, B: TileComponent, const N: usize>(
+    world: &mut World,
+    changed: &mut QueryState<(Entity, &ChunkCoord<N>, &InMap), Changed<ChunkData<A>>>,
+    removed: &mut SystemState<RemovedComponents<ChunkData<A>>>,
+    mut sources: Local<HashMap<Entity, (Entity, [i32; N])>>,
+) {
+    let Some(layer) = world.get_resource::<DerivedLayer<A, B, N>>() else {
+        return;
+    };
+    let (radius, derive) = (layer.radius, layer.derive);
+
+    // Despawned chunks can't be looked up anymore, so remember where every source chunk was.
+    let removed: Vec<Entity> = removed.get_mut(world).read().collect();
+    let mut sources_changed: Vec<(Entity, [i32; N])> = removed
+        .into_iter()
+        .filter_map(|chunk_id| {
+            let source = sources.remove(&chunk_id)?;
+            let chunk = world.get_entity(chunk_id).ok();
+            match chunk
+                .and_then(|chunk| Some((chunk.get::<InMap>()?, chunk.get::<ChunkCoord<N>>()?)))
+            {
+                Some((in_map, chunk_c)) => Some((in_map.0, chunk_c.0)),
+                None => Some(source),
+            }
+        })
+        .collect();
+    for (chunk_id, chunk_c, in_map) in changed.iter(world) {
+        sources.insert(chunk_id, (in_map.0, chunk_c.0));
+        sources_changed.push((in_map.0, chunk_c.0));
+    }
+
+    let mut dirty: HashMap<Entity, HashSet<[i32; N]>> = HashMap::new();
+    for (map_id, chunk_c) in sources_changed {
+        let Some(map) = world.get::<TileMap<N>>(map_id) else {
+            continue;
+        };
+        let chunk_size = map.get_chunk_size() as i32;
+        let min = chunk_c.map(|c| c * chunk_size - radius);
+        let max = chunk_c.map(|c| c * chunk_size + chunk_size - 1 + radius);
+        dirty
+            .entry(map_id)
+            .or_default()
+            .extend(CoordIterator::new(min, max));
+    }
+
+    for (map_id, tile_cs) in dirty {
+        let Some(map) = world.get::<TileMap<N>>(map_id) else {
+            continue;
+        };
+        let derived: Vec<([i32; N], Option<B>)> = tile_cs
+            .into_iter()
+            .map(|center| {
+                let neighborhood = Neighborhood::<A, N> {
+                    world,
+                    map,
+                    center,
+                    layer: PhantomData,
+                };
+                (center, derive(&neighborhood))
+            })
+            .collect();
+
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(map_id) else {
+            continue;
+        };
+        for (tile_c, value) in derived {
+            match value {
+                Some(value) => {
+                    insert_tile::<B, N>(&mut map, tile_c, value);
+                }
+                None => {
+                    take_tile::<B, N>(&mut map, tile_c);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use crate::{commands::TileWorldExt, testing, tiles::TileMapQuery};
+
+    use super::*;
+
+    /// Solid tiles are terrain above 0, with an edge where they touch open tiles.
+    fn edges(terrain: &Neighborhood<u8, 2>) -> Option<bool> {
+        if *terrain.value()? == 0 {
+            return None;
+        }
+        let open = [[1, 0], [-1, 0], [0, 1], [0, -1]]
+            .into_iter()
+            .any(|offset| terrain.get(offset).is_none_or(|t| *t == 0));
+        Some(open)
+    }
+
+    #[test]
+    fn derived_layer_follows_source() {
+        let mut app = App::new();
+        app.add_plugins(DerivedLayerPlugin::<u8, bool, 2>::new(1, edges));
+        let map_id = testing::spawn_map(app.world_mut(), 4, |map| {
+            for x in 2..=4 {
+                map.insert_tile([x, 0], 1u8);
+            }
+        });
+        app.update();
+
+        let mut state = SystemState::<TileMapQuery<&bool>>::new(app.world_mut());
+        let tile_maps = state.get(app.world());
+        let edge = tile_maps.get_map(map_id).unwrap();
+        assert_eq!(edge.get_at([2, 0]), Some(&true));
+        assert_eq!(edge.get_at([4, 0]), Some(&true));
+        assert_eq!(edge.get_at([5, 0]), None);
+
+        // Filling in around a tile in the next chunk over clears the edge.
+        testing::apply_map(app.world_mut(), map_id, |map| {
+            for tile_c in [[4, 1], [4, -1], [5, 0]] {
+                map.insert_tile(tile_c, 1u8);
+            }
+            map.remove_tile::<u8>([2, 0]);
+        });
+        app.update();
+
+        let tile_maps = state.get(app.world());
+        let edge = tile_maps.get_map(map_id).unwrap();
+        assert_eq!(edge.get_at([4, 0]), Some(&false));
+        assert_eq!(edge.get_at([3, 0]), Some(&true));
+        assert_eq!(edge.get_at([2, 0]), None);
+    }
+
+    /// Counts the terrain tiles next to a terrain tile.
+    fn neighbors(terrain: &Neighborhood<u8, 2>) -> Option<u16> {
+        terrain.value()?;
+        let count = [[1, 0], [-1, 0], [0, 1], [0, -1]]
+            .into_iter()
+            .filter(|offset| terrain.get(*offset).is_some())
+            .count();
+        Some(count as u16)
+    }
+
+    #[test]
+    fn derived_layer_follows_removed_sources() {
+        let mut app = App::new();
+        app.add_plugins(DerivedLayerPlugin::<u8, u16, 2>::new(1, neighbors));
+        let world = app.world_mut();
+        let map_id = TileWorldExt::<2>::spawn_map(world, 4);
+        TileWorldExt::<2>::insert_tile(world, map_id, [3, 0], 1u8);
+        TileWorldExt::<2>::insert_tile(world, map_id, [4, 0], 1u8);
+        app.update();
+        let count =
+            |app: &App| TileWorldExt::<2>::get_tile::<u16>(app.world(), map_id, [3, 0]).copied();
+        assert_eq!(count(&app), Some(1));
+
+        // Despawning the next chunk over updates the tiles next to it.
+        TileWorldExt::<2>::despawn_chunk(app.world_mut(), map_id, [1, 0]);
+        app.update();
+        assert_eq!(count(&app), Some(0));
+
+        // Taking the only terrain tile removes the chunk's terrain data.
+        TileWorldExt::<2>::take_tile::<u8>(app.world_mut(), map_id, [3, 0]);
+        app.update();
+        assert_eq!(count(&app), None);
+    }
+}
@@ -0,0 +1,220 @@
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    utils::HashMap,
+};
+
+use crate::{
+    chunks::ChunkData,
+    commands::{get_tile, insert_tile, take_tile, TempRemove},
+    coords::{calculate_tile_index, CoordIterator},
+    maps::TileMap,
+    merge::MergePolicy,
+    queries::TileComponent,
+};
+
+/// A change to a single tile between two versions of a map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileChange<T, const N: usize> {
+    /// The coordinate of the tile.
+    pub tile_c: [i32; N],
+    /// The tile before the change, [`None`] if it was added.
+    pub before: Option<T>,
+    /// The tile after the change, [`None`] if it was removed.
+    pub after: Option<T>,
+}
+
+/// A tile that was changed in the map a [`MapDiff`] was applied to since the diff was made.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileConflict<T, const N: usize> {
+    /// The change that couldn't be applied cleanly.
+    pub change: TileChange<T, N>,
+    /// The tile found in the map instead of [`TileChange::before`].
+    pub found: Option<T>,
+}
+
+/// The tiles of one layer that differ between two maps, see [`diff_maps`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapDiff<T, const N: usize = 2> {
+    changes: Vec<TileChange<T, N>>,
+}
+
+impl<T, const N: usize> MapDiff<T, N> {
+    /// The changed tiles, ordered by coordinate.
+    pub fn changes(&self) -> &[TileChange<T, N>] {
+        &self.changes
+    }
+
+    /// Number of changed tiles.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Whether the maps had the same tiles.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Swap the before and after of every change, giving a diff that undoes this one.
+    pub fn invert(mut self) -> Self {
+        for change in self.changes.iter_mut() {
+            std::mem::swap(&mut change.before, &mut change.after);
+        }
+        self
+    }
+}
+
+impl<T: TileComponent + Clone + PartialEq, const N: usize> MapDiff<T, N> {
+    /// Apply the diff to a map, returning the tiles that conflicted.
+    /// A change conflicts if the tile in the map isn't [`TileChange::before`], meaning someone else edited it.
+    /// # Note
+    /// Conflicting changes are skipped with [`MergePolicy::KeepDestination`] and applied anyway with [`MergePolicy::KeepSource`].
+    /// Changes that were already made in the map (the tile is already [`TileChange::after`]) don't conflict.
+    /// Does nothing if the entity isn't a map.
+    pub fn apply(
+        &self,
+        world: &mut World,
+        map_id: Entity,
+        policy: MergePolicy,
+    ) -> Vec<TileConflict<T, N>> {
+        let Some(mut map) = world.temp_remove::<TileMap<N>>(map_id) else {
+            return Vec::new();
+        };
+        let mut conflicts = Vec::new();
+        for change in self.changes.iter() {
+            let found = get_tile::<T, N>(&map, change.tile_c);
+            if found == change.after.as_ref() {
+                continue;
+            }
+            if found != change.before.as_ref() {
+                conflicts.push(TileConflict {
+                    change: change.clone(),
+                    found: found.cloned(),
+                });
+                if policy == MergePolicy::KeepDestination {
+                    continue;
+                }
+            }
+            match change.after.clone() {
+                Some(value) => {
+                    insert_tile::<T, N>(&mut map, change.tile_c, value);
+                }
+                None => {
+                    take_tile::<T, N>(&mut map, change.tile_c);
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+/// Compare the `T` tiles of two maps, giving the changes that turn map `a` into map `b`.
+/// # Note
+/// Used for version control like workflows, ex: diffing an edited copy of a level against the original,
+/// then applying the diff to a level someone else edited in the meantime.
+/// The maps don't need to have the same chunk size, a missing map is treated as empty.
+pub fn diff_maps<T: TileComponent + Clone + PartialEq, const N: usize>(
+    world: &World,
+    a: Entity,
+    b: Entity,
+) -> MapDiff<T, N> {
+    let mut tiles_a = layer_tiles::<T, N>(world, a);
+    let mut changes = Vec::new();
+    for (tile_c, after) in layer_tiles::<T, N>(world, b) {
+        match tiles_a.remove(&tile_c) {
+            Some(before) if before == after => {}
+            before => changes.push(TileChange {
+                tile_c,
+                before: before.cloned(),
+                after: Some(after.clone()),
+            }),
+        }
+    }
+    changes.extend(tiles_a.into_iter().map(|(tile_c, before)| TileChange {
+        tile_c,
+        before: Some(before.clone()),
+        after: None,
+    }));
+    changes.sort_by_key(|change| change.tile_c);
+    MapDiff { changes }
+}
+
+/// Every `T` tile in a map.
+fn layer_tiles<T: TileComponent, const N: usize>(
+    world: &World,
+    map_id: Entity,
+) -> HashMap<[i32; N], &T> {
+    let mut tiles = HashMap::new();
+    let Some(map) = world.get::<TileMap<N>>(map_id) else {
+        return tiles;
+    };
+    let chunk_size = map.get_chunk_size();
+    for (chunk_c, chunk_id) in map.get_chunks() {
+        let Some(data) = world.get::<ChunkData<T>>(*chunk_id) else {
+            continue;
+        };
+        let min = chunk_c.to_tile_origin(chunk_size);
+        let max = min.map(|c| c + chunk_size as i32 - 1);
+        for tile_c in CoordIterator::new(min, max) {
+            if let Some(value) = data.get(calculate_tile_index(tile_c, chunk_size)) {
+                tiles.insert(tile_c, value);
+            }
+        }
+    }
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::TileWorldExt;
+
+    use super::*;
+
+    #[test]
+    fn diff_and_apply_with_conflicts() {
+        let mut world = World::new();
+        let original = TileWorldExt::<2>::spawn_map(&mut world, 4);
+        world.insert_tile_batch(original, [[0, 0], [1, 0], [2, 0]], |_| 1u8);
+        let edited = TileWorldExt::<2>::spawn_map(&mut world, 8);
+        world.insert_tile_batch(edited, [[0, 0], [1, 0], [2, 0]], |_| 1u8);
+        world.insert_tile(edited, [1, 0], 2u8);
+        world.take_tile::<u8>(edited, [2, 0]);
+        world.insert_tile(edited, [-5, 9], 3u8);
+
+        let diff = diff_maps::<u8, 2>(&world, original, edited);
+        assert_eq!(
+            diff.changes()
+                .iter()
+                .map(|change| (change.tile_c, change.before, change.after))
+                .collect::<Vec<_>>(),
+            vec![
+                ([-5, 9], None, Some(3)),
+                ([1, 0], Some(1), Some(2)),
+                ([2, 0], Some(1), None),
+            ]
+        );
+        assert!(diff_maps::<u8, 2>(&world, edited, edited).is_empty());
+
+        // Someone else changed [1, 0] and [-5, 9] in the meantime.
+        world.insert_tile(original, [1, 0], 4u8);
+        world.insert_tile(original, [-5, 9], 3u8);
+        let conflicts = diff.apply(&mut world, original, MergePolicy::KeepDestination);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].change.tile_c, [1, 0]);
+        assert_eq!(conflicts[0].found, Some(4));
+        assert_eq!(world.get_tile::<u8>(original, [1, 0]), Some(&4));
+        assert_eq!(world.get_tile::<u8>(original, [2, 0]), None);
+
+        let conflicts = diff.apply(&mut world, original, MergePolicy::KeepSource);
+        assert_eq!(conflicts.len(), 1);
+        assert!(diff_maps::<u8, 2>(&world, original, edited).is_empty());
+
+        // Undoing the diff conflicts with nothing.
+        let conflicts = diff
+            .invert()
+            .apply(&mut world, original, MergePolicy::KeepDestination);
+        assert!(conflicts.is_empty());
+        assert_eq!(world.get_tile::<u8>(original, [1, 0]), Some(&1));
+        assert_eq!(world.get_tile::<u8>(original, [2, 0]), Some(&1));
+        assert_eq!(world.get_tile::<u8>(original, [-5, 9]), None);
+    }
+}
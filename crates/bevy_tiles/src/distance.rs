@@ -0,0 +1,110 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{coords::CoordIterator, filters::TileWindow};
+
+/// How distance between tiles is measured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DistanceMetric {
+    /// Steps along a single axis at a time (taxicab distance).
+    Manhattan,
+    /// Diagonal steps cost the same as straight steps.
+    Chebyshev,
+    /// Chamfer approximation of straight line distance, diagonal steps cost their true length.
+    Euclidean,
+}
+
+impl DistanceMetric {
+    /// The cost of a single step with the given offset, where each offset is in `-1..=1`.
+    /// Returns [`None`] if the step isn't allowed by this metric.
+    #[inline]
+    pub fn step_cost<const N: usize>(&self, offset: [i32; N]) -> Option<f32> {
+        let axes = offset.iter().filter(|o| **o != 0).count();
+        match (self, axes) {
+            (_, 0) => None,
+            (DistanceMetric::Manhattan, 1) => Some(1.0),
+            (DistanceMetric::Manhattan, _) => None,
+            (DistanceMetric::Chebyshev, _) => Some(1.0),
+            (DistanceMetric::Euclidean, axes) => Some((axes as f32).sqrt()),
+        }
+    }
+}
+
+/// Calculates the distance from every tile between `corner_1` and `corner_2` (inclusive) to the
+/// closest tile where `is_source` returns true, capped at `max_distance`.
+/// # Note
+/// Only sources inside of the corners are considered, to get correct values near the edges
+/// include an apron of `max_distance` tiles around the area of interest.
+pub fn distance_transform<const N: usize>(
+    corner_1: impl Into<[i32; N]>,
+    corner_2: impl Into<[i32; N]>,
+    metric: DistanceMetric,
+    max_distance: f32,
+    is_source: impl Fn([i32; N]) -> bool,
+) -> TileWindow<N> {
+    let mut window = TileWindow::new(corner_1, corner_2);
+    let (min, max) = (window.min(), window.max());
+
+    let mut heap = BinaryHeap::new();
+    for tile_c in CoordIterator::new(min, max) {
+        if is_source(tile_c) {
+            window.set(tile_c, Some(0.0));
+            heap.push(Reverse((0.0f32.to_bits(), tile_c)));
+        }
+    }
+
+    let steps: Vec<([i32; N], f32)> = CoordIterator::new([-1; N], [1; N])
+        .filter_map(|offset| Some((offset, metric.step_cost(offset)?)))
+        .collect();
+
+    // Distances are never negative, so their bit patterns sort the same way the floats do.
+    while let Some(Reverse((distance, tile_c))) = heap.pop() {
+        let distance = f32::from_bits(distance);
+        if window.get(tile_c).is_some_and(|best| best < distance) {
+            continue;
+        }
+        for (offset, cost) in steps.iter() {
+            let mut neighbor_c = tile_c;
+            for i in 0..N {
+                neighbor_c[i] += offset[i];
+            }
+            let next = distance + cost;
+            if next > max_distance
+                || (0..N).any(|i| neighbor_c[i] < min[i] || neighbor_c[i] > max[i])
+                || window.get(neighbor_c).is_some_and(|best| best <= next)
+            {
+                continue;
+            }
+            window.set(neighbor_c, Some(next));
+            heap.push(Reverse((next.to_bits(), neighbor_c)));
+        }
+    }
+
+    for tile_c in CoordIterator::new(min, max) {
+        if window.get(tile_c).is_none() {
+            window.set(tile_c, Some(max_distance));
+        }
+    }
+    window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics() {
+        let source = |tile_c: [i32; 2]| tile_c == [0, 0];
+        let manhattan =
+            distance_transform([-5, -5], [5, 5], DistanceMetric::Manhattan, 4.0, source);
+        let chebyshev =
+            distance_transform([-5, -5], [5, 5], DistanceMetric::Chebyshev, 4.0, source);
+        let euclidean =
+            distance_transform([-5, -5], [5, 5], DistanceMetric::Euclidean, 4.0, source);
+
+        assert_eq!(manhattan.get([2, 1]), Some(3.0));
+        assert_eq!(chebyshev.get([2, 1]), Some(2.0));
+        assert_eq!(euclidean.get([2, 1]), Some(1.0 + 2f32.sqrt()));
+        assert_eq!(manhattan.get([5, 5]), Some(4.0));
+        assert_eq!(chebyshev.get([0, 0]), Some(0.0));
+    }
+}
@@ -0,0 +1,216 @@
+use crate::coords::CoordIterator;
+
+/// Filters that can be run over a numeric tile layer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TileFilter {
+    /// Averages every tile within `radius` tiles (along each axis) of the target tile.
+    BoxBlur {
+        /// Size of the neighborhood along each axis.
+        radius: u32,
+    },
+    /// Binomially weighted average, an integer approximation of a gaussian blur.
+    Gaussian {
+        /// Size of the neighborhood along each axis.
+        radius: u32,
+    },
+    /// Replaces each tile with the median of its neighborhood, removes spikes while keeping edges.
+    Median {
+        /// Size of the neighborhood along each axis.
+        radius: u32,
+    },
+    /// A single step of thermal erosion, material slides from a tile to its face neighbors
+    /// when the difference between them is greater than `talus`.
+    ThermalErosion {
+        /// The largest height difference that is stable.
+        talus: f32,
+        /// Portion of the unstable difference that is moved each step.
+        rate: f32,
+    },
+}
+
+impl TileFilter {
+    /// How far outside of the filtered region tiles are read.
+    pub fn apron(&self) -> u32 {
+        match self {
+            TileFilter::BoxBlur { radius }
+            | TileFilter::Gaussian { radius }
+            | TileFilter::Median { radius } => *radius,
+            TileFilter::ThermalErosion { .. } => 1,
+        }
+    }
+
+    /// Calculates the filtered value of a tile, returns [`None`] if the tile is empty.
+    /// # Note
+    /// Empty neighbors are skipped, weights are renormalized over the tiles that exist.
+    pub fn sample<const N: usize>(&self, window: &TileWindow<N>, tile_c: [i32; N]) -> Option<f32> {
+        let center = window.get(tile_c)?;
+        match *self {
+            TileFilter::BoxBlur { radius } => {
+                let (sum, count) = neighborhood(tile_c, radius)
+                    .filter_map(|c| window.get(c))
+                    .fold((0.0, 0.0), |(sum, count), v| (sum + v, count + 1.0));
+                Some(sum / count)
+            }
+            TileFilter::Gaussian { radius } => {
+                let (sum, total) = neighborhood(tile_c, radius)
+                    .filter_map(|c| {
+                        let v = window.get(c)?;
+                        let mut weight = 1.0;
+                        for i in 0..N {
+                            let k = (c[i] - tile_c[i] + radius as i32) as u32;
+                            weight *= binomial(2 * radius, k);
+                        }
+                        Some((v, weight))
+                    })
+                    .fold((0.0, 0.0), |(sum, total), (v, w)| (sum + v * w, total + w));
+                Some(sum / total)
+            }
+            TileFilter::Median { radius } => {
+                let mut values: Vec<f32> = neighborhood(tile_c, radius)
+                    .filter_map(|c| window.get(c))
+                    .collect();
+                values.sort_by(f32::total_cmp);
+                Some(values[values.len() / 2])
+            }
+            TileFilter::ThermalErosion { talus, rate } => {
+                // Transfers are symmetric so material is conserved between tiles in the region.
+                let share = rate / (2 * N) as f32;
+                let mut delta = 0.0;
+                for i in 0..N {
+                    for step in [-1, 1] {
+                        let mut neighbor_c = tile_c;
+                        neighbor_c[i] += step;
+                        let Some(neighbor) = window.get(neighbor_c) else {
+                            continue;
+                        };
+                        let diff = center - neighbor;
+                        if diff > talus {
+                            delta -= share * (diff - talus);
+                        } else if -diff > talus {
+                            delta += share * (-diff - talus);
+                        }
+                    }
+                }
+                Some(center + delta)
+            }
+        }
+    }
+}
+
+/// A dense copy of a numeric layer over a region, used as the read side of filters.
+pub struct TileWindow<const N: usize> {
+    min: [i32; N],
+    max: [i32; N],
+    values: Vec<Option<f32>>,
+}
+
+impl<const N: usize> TileWindow<N> {
+    /// Create an empty window covering the region between two corners (inclusive).
+    pub fn new(corner_1: impl Into<[i32; N]>, corner_2: impl Into<[i32; N]>) -> Self {
+        let mut min = corner_1.into();
+        let mut max = corner_2.into();
+        let mut len = 1;
+        for i in 0..N {
+            if min[i] > max[i] {
+                std::mem::swap(&mut min[i], &mut max[i]);
+            }
+            len *= (max[i] - min[i] + 1) as usize;
+        }
+        Self {
+            min,
+            max,
+            values: vec![None; len],
+        }
+    }
+
+    /// The lowest corner of this window.
+    pub fn min(&self) -> [i32; N] {
+        self.min
+    }
+
+    /// The highest corner of this window.
+    pub fn max(&self) -> [i32; N] {
+        self.max
+    }
+
+    /// Get the value of a tile, returns [`None`] if the tile is empty or outside of the window.
+    #[inline]
+    pub fn get(&self, tile_c: impl Into<[i32; N]>) -> Option<f32> {
+        self.index(tile_c.into()).and_then(|i| self.values[i])
+    }
+
+    /// Set the value of a tile, does nothing if the tile is outside of the window.
+    #[inline]
+    pub fn set(&mut self, tile_c: impl Into<[i32; N]>, value: Option<f32>) {
+        if let Some(i) = self.index(tile_c.into()) {
+            self.values[i] = value;
+        }
+    }
+
+    #[inline]
+    fn index(&self, tile_c: [i32; N]) -> Option<usize> {
+        let mut index = 0;
+        let mut stride = 1;
+        for (i, c) in tile_c.into_iter().enumerate() {
+            if c < self.min[i] || c > self.max[i] {
+                return None;
+            }
+            index += (c - self.min[i]) as usize * stride;
+            stride *= (self.max[i] - self.min[i] + 1) as usize;
+        }
+        Some(index)
+    }
+}
+
+#[inline]
+fn neighborhood<const N: usize>(tile_c: [i32; N], radius: u32) -> CoordIterator<N> {
+    let radius = radius as i32;
+    CoordIterator::new(tile_c.map(|c| c - radius), tile_c.map(|c| c + radius))
+}
+
+#[inline]
+fn binomial(n: u32, k: u32) -> f32 {
+    let mut result = 1.0;
+    for i in 0..k.min(n - k) {
+        result = result * (n - i) as f32 / (i + 1) as f32;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step_window() -> TileWindow<2> {
+        let mut window = TileWindow::new([0, 0], [4, 0]);
+        for x in 0..=4 {
+            window.set([x, 0], Some(if x < 2 { 0.0 } else { 10.0 }));
+        }
+        window
+    }
+
+    #[test]
+    fn blur_skips_empty_tiles() {
+        let mut window = step_window();
+        window.set([1, 0], None);
+        let filter = TileFilter::BoxBlur { radius: 1 };
+        assert_eq!(filter.sample(&window, [0, 0]), Some(0.0));
+        assert_eq!(filter.sample(&window, [1, 0]), None);
+        assert_eq!(filter.sample(&window, [2, 0]), Some(10.0));
+    }
+
+    #[test]
+    fn thermal_erosion_conserves_material() {
+        let window = step_window();
+        let filter = TileFilter::ThermalErosion {
+            talus: 1.0,
+            rate: 0.5,
+        };
+        let before: f32 = (0..=4).map(|x| window.get([x, 0]).unwrap()).sum();
+        let after: f32 = (0..=4)
+            .map(|x| filter.sample(&window, [x, 0]).unwrap())
+            .sum();
+        assert!((before - after).abs() < 1e-4);
+        assert!(filter.sample(&window, [1, 0]).unwrap() > 0.0);
+    }
+}
@@ -0,0 +1,430 @@
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+};
+
+#[cfg(feature = "trace")]
+use bevy::log::info_span;
+use bevy::{
+    ecs::{component::Component, entity::Entity, world::World},
+    utils::HashMap,
+};
+
+use crate::{
+    commands::{get_tile, insert_tile, take_tile, ChunkWriter, TempRemove, TempRemoved},
+    coords::{calculate_tile_coordinate, CoordIterator},
+    maps::{MapSeed, TileMap},
+    noise::TileRng,
+    queries::TileComponent,
+};
+
+/// Runs a generation stage over the tiles in its context.
+pub type StageFn<const N: usize> = fn(&mut GenContext<'_, N>);
+
+/// A single step of a [`GenPipeline`] (ex: noise fill, erosion, structure placement).
+#[derive(Clone)]
+pub struct GenStage<const N: usize> {
+    name: Cow<'static, str>,
+    reads: Vec<TypeId>,
+    writes: Vec<TypeId>,
+    padding: i32,
+    run: StageFn<N>,
+}
+
+impl<const N: usize> GenStage<N> {
+    /// Create a stage that runs a function.
+    pub fn new(name: impl Into<Cow<'static, str>>, run: StageFn<N>) -> Self {
+        Self {
+            name: name.into(),
+            reads: Vec::new(),
+            writes: Vec::new(),
+            padding: 0,
+            run,
+        }
+    }
+
+    /// Declare that the stage reads tiles of type `T`.
+    pub fn reads<T: TileComponent>(mut self) -> Self {
+        self.reads.push(TypeId::of::<T>());
+        self
+    }
+
+    /// Declare that the stage writes tiles of type `T`.
+    pub fn writes<T: TileComponent>(mut self) -> Self {
+        self.writes.push(TypeId::of::<T>());
+        self
+    }
+
+    /// How far outside of a tile the stage reads (ex: 1 for a 3x3 filter).
+    pub fn padding(mut self, padding: i32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Get the name of the stage.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// An ordered list of generation stages that are run chunk by chunk, add this to a [`TileMap`] and generate
+/// chunks with [`crate::commands::TileCommandExt::generate_chunk`].
+/// # Note
+/// Stages run over the chunk being generated, padded out so that later stages reading with a
+/// [`GenStage::padding`] see finished tiles past the edge of the chunk.  Only layers a later stage
+/// [`GenStage::reads`] and an earlier stage [`GenStage::writes`] get padded.
+///
+/// Padded tiles are generated into a scratch copy that later stages read, only tiles inside of the chunk are written
+/// to the map.  Padded tiles are generated again with their own chunk, so stages must only depend on the tile
+/// coordinate and [`MapSeed`] (ex: [`GenContext::rng`]), not on what order chunks are generated in.
+#[derive(Component, Clone, Default)]
+pub struct GenPipeline<const N: usize> {
+    stages: Vec<GenStage<N>>,
+}
+
+impl<const N: usize> GenPipeline<N> {
+    /// Create an empty pipeline.
+    pub fn new() -> Self {
+        Self { stages: Vec::new() }
+    }
+
+    /// Add a stage to the end of the pipeline.
+    pub fn stage(mut self, stage: GenStage<N>) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Iterate over the stages of the pipeline in order.
+    pub fn stages(&self) -> impl Iterator<Item = &GenStage<N>> {
+        self.stages.iter()
+    }
+
+    /// How far outside of the chunk each stage has to run.
+    pub fn stage_padding(&self) -> Vec<i32> {
+        let mut padding = vec![0; self.stages.len()];
+        for i in (0..self.stages.len()).rev() {
+            for j in i + 1..self.stages.len() {
+                let later = &self.stages[j];
+                if later
+                    .reads
+                    .iter()
+                    .any(|layer| self.stages[i].writes.contains(layer))
+                {
+                    padding[i] = padding[i].max(padding[j] + later.padding);
+                }
+            }
+        }
+        padding
+    }
+
+    /// Run every stage of the pipeline for a chunk of a map.
+    pub fn generate(&self, world: &mut World, map_id: Entity, chunk_c: impl Into<[i32; N]>) {
+        let chunk_c = chunk_c.into();
+        #[cfg(feature = "trace")]
+        let _span =
+            info_span!("generate_chunk", chunk_c = ?chunk_c, stages = self.stages.len()).entered();
+        let seed = world.get::<MapSeed>(map_id).copied().unwrap_or_default();
+        let Some(chunk_size) = world
+            .get::<TileMap<N>>(map_id)
+            .map(|map| map.get_chunk_size() as i32)
+        else {
+            return;
+        };
+
+        let mut scratch = GenScratch::default();
+        for (stage, padding) in self.stages.iter().zip(self.stage_padding()) {
+            let Some(map) = world.temp_remove::<TileMap<N>>(map_id) else {
+                return;
+            };
+            let min = chunk_c.map(|c| c * chunk_size - padding);
+            let max = chunk_c.map(|c| c * chunk_size + chunk_size - 1 + padding);
+            let mut context = GenContext {
+                map,
+                stage,
+                seed,
+                chunk_c,
+                min,
+                max,
+                scratch: &mut scratch,
+            };
+            (stage.run)(&mut context);
+        }
+    }
+}
+
+/// Tiles generated outside of the chunk, kept out of the map so they don't overwrite finished neighbors.
+#[derive(Default)]
+struct GenScratch<const N: usize> {
+    layers: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl<const N: usize> GenScratch<N> {
+    /// Get a padded tile, [`None`] if no stage has written it, `Some(None)` if it was removed.
+    fn get<T: TileComponent>(&self, tile_c: [i32; N]) -> Option<Option<&T>> {
+        self.layers
+            .get(&TypeId::of::<T>())?
+            .downcast_ref::<HashMap<[i32; N], Option<T>>>()?
+            .get(&tile_c)
+            .map(Option::as_ref)
+    }
+
+    fn set<T: TileComponent>(&mut self, tile_c: [i32; N], value: Option<T>) -> Option<T> {
+        self.layers
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(HashMap::<[i32; N], Option<T>>::default()))
+            .downcast_mut::<HashMap<[i32; N], Option<T>>>()
+            .expect("Scratch layers are keyed by their type.")
+            .insert(tile_c, value)
+            .flatten()
+    }
+}
+
+/// Access to a map for a [`GenStage`].
+pub struct GenContext<'w, const N: usize> {
+    map: TempRemoved<'w, TileMap<N>>,
+    stage: &'w GenStage<N>,
+    seed: MapSeed,
+    chunk_c: [i32; N],
+    min: [i32; N],
+    max: [i32; N],
+    scratch: &'w mut GenScratch<N>,
+}
+
+impl<'w, const N: usize> GenContext<'w, N> {
+    /// The lowest and highest corners (inclusive) of the tiles the stage should generate.
+    pub fn region(&self) -> ([i32; N], [i32; N]) {
+        (self.min, self.max)
+    }
+
+    /// Iterate over every tile the stage should generate.
+    pub fn tiles(&self) -> CoordIterator<N> {
+        CoordIterator::new(self.min, self.max)
+    }
+
+    /// Get the seed of the map.
+    pub fn seed(&self) -> MapSeed {
+        self.seed
+    }
+
+    /// Get a random number generator for a tile, unique to the map seed and stage.
+    pub fn rng(&self, tile_c: impl Into<[i32; N]>) -> TileRng {
+        let stage = self
+            .stage
+            .name
+            .bytes()
+            .fold(0u64, |hash, byte| hash.rotate_left(5) ^ byte as u64);
+        TileRng::for_tile(self.seed.mix(stage), tile_c)
+    }
+
+    /// Get a tile, padded tiles written by earlier stages are read from the scratch copy.
+    pub fn get<T: TileComponent>(&self, tile_c: impl Into<[i32; N]>) -> Option<&T> {
+        let tile_c = tile_c.into();
+        if !self.in_chunk(tile_c) {
+            if let Some(tile) = self.scratch.get::<T>(tile_c) {
+                return tile;
+            }
+        }
+        get_tile::<T, N>(&self.map, tile_c)
+    }
+
+    /// Set a tile, `T` has to be declared with [`GenStage::writes`].
+    /// Tiles outside of the chunk are only seen by later stages, the map isn't changed.
+    pub fn set<T: TileComponent>(&mut self, tile_c: impl Into<[i32; N]>, value: T) {
+        self.check_writes::<T>();
+        let tile_c = tile_c.into();
+        if self.in_chunk(tile_c) {
+            insert_tile::<T, N>(&mut self.map, tile_c, value);
+        } else {
+            self.scratch.set(tile_c, Some(value));
+        }
+    }
+
+    /// Remove a tile, `T` has to be declared with [`GenStage::writes`].
+    /// Tiles outside of the chunk are only hidden from later stages, and only return values set during this generation.
+    pub fn remove<T: TileComponent>(&mut self, tile_c: impl Into<[i32; N]>) -> Option<T> {
+        self.check_writes::<T>();
+        let tile_c = tile_c.into();
+        if self.in_chunk(tile_c) {
+            take_tile::<T, N>(&mut self.map, tile_c)
+        } else {
+            self.scratch.set::<T>(tile_c, None)
+        }
+    }
+
+    /// Replace all the `T` data of a chunk, `T` has to be declared with [`GenStage::writes`].
+    /// See [`ChunkWriter::set_data`], other chunks are written to the scratch copy like [`GenContext::set`].
+    pub fn set_chunk_data<T: TileComponent>(
+        &mut self,
+        chunk_c: impl Into<[i32; N]>,
+        tiles: Vec<Option<T>>,
+    ) {
+        self.check_writes::<T>();
+        let chunk_c = chunk_c.into();
+        if chunk_c == self.chunk_c {
+            ChunkWriter::new(&mut self.map, chunk_c).set_data(tiles);
+            return;
+        }
+        let chunk_size = self.map.get_chunk_size();
+        for (tile_i, value) in tiles.into_iter().enumerate() {
+            let tile_c = calculate_tile_coordinate(chunk_c, tile_i, chunk_size);
+            self.scratch.set(tile_c, value);
+        }
+    }
+
+    /// Set the `T` data of every tile in a chunk, `T` has to be declared with [`GenStage::writes`].
+    /// Other chunks are written to the scratch copy like [`GenContext::set`].
+    pub fn fill_chunk<T: TileComponent + Clone>(&mut self, chunk_c: impl Into<[i32; N]>, value: T) {
+        self.check_writes::<T>();
+        let chunk_c = chunk_c.into();
+        if chunk_c == self.chunk_c {
+            ChunkWriter::new(&mut self.map, chunk_c).fill(value);
+            return;
+        }
+        let chunk_size = self.map.get_chunk_size();
+        for tile_i in 0..chunk_size.pow(N as u32) {
+            let tile_c = calculate_tile_coordinate(chunk_c, tile_i, chunk_size);
+            self.scratch.set(tile_c, Some(value.clone()));
+        }
+    }
+
+    #[inline]
+    fn in_chunk(&self, tile_c: [i32; N]) -> bool {
+        let size = self.map.get_chunk_size() as i32;
+        tile_c
+            .iter()
+            .zip(self.chunk_c)
+            .all(|(c, chunk_c)| (chunk_c * size..(chunk_c + 1) * size).contains(c))
+    }
+
+    #[inline]
+    fn check_writes<T: TileComponent>(&self) {
+        debug_assert!(
+            self.stage.writes.contains(&TypeId::of::<T>()),
+            "Generation stage `{}` wrote to `{}` without declaring it.",
+            self.stage.name,
+            std::any::type_name::<T>()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use crate::{chunks::ChunkCoord, commands::TileWorldExt, testing, tiles::TileMapQuery};
+
+    use super::*;
+
+    fn heights(context: &mut GenContext<2>) {
+        for tile_c in context.tiles() {
+            let height = context.rng(tile_c).range(0..10) as u8;
+            context.set(tile_c, height);
+        }
+    }
+
+    /// Marks tiles taller than all their neighbors.
+    fn peaks(context: &mut GenContext<2>) {
+        for [x, y] in context.tiles() {
+            let height = *context.get::<u8>([x, y]).unwrap();
+            let peak = [[x + 1, y], [x - 1, y], [x, y + 1], [x, y - 1]]
+                .into_iter()
+                .all(|neighbor| context.get::<u8>(neighbor).unwrap() < &height);
+            context.set([x, y], peak);
+        }
+    }
+
+    /// Lowers tiles to their lowest neighbor.
+    fn erode(context: &mut GenContext<2>) {
+        let eroded: Vec<([i32; 2], u8)> = context
+            .tiles()
+            .map(|[x, y]| {
+                let height = [[x, y], [x + 1, y], [x - 1, y], [x, y + 1], [x, y - 1]]
+                    .into_iter()
+                    .map(|tile_c| *context.get::<u8>(tile_c).unwrap())
+                    .min()
+                    .unwrap();
+                ([x, y], height)
+            })
+            .collect();
+        for (tile_c, height) in eroded {
+            context.set(tile_c, height);
+        }
+    }
+
+    fn generate(world: &mut World, map_id: Entity, chunk_c: [i32; 2]) {
+        let pipeline = world.get::<GenPipeline<2>>(map_id).unwrap().clone();
+        pipeline.generate(world, map_id, chunk_c);
+    }
+
+    #[test]
+    fn pipeline_pads_stages() {
+        let pipeline = GenPipeline::<2>::new()
+            .stage(GenStage::new("heights", heights).writes::<u8>())
+            .stage(
+                GenStage::new("peaks", peaks)
+                    .reads::<u8>()
+                    .writes::<bool>()
+                    .padding(1),
+            );
+        assert_eq!(pipeline.stage_padding(), vec![1, 0]);
+
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            map.insert((MapSeed(7), pipeline));
+            map.generate_chunk([0, 0]);
+            map.generate_chunk([1, 0]);
+        });
+
+        let map = world.get::<TileMap<2>>(map_id).unwrap();
+        assert_eq!(map.get_chunks().len(), 2);
+        let mut state = SystemState::<(TileMapQuery<&u8>, TileMapQuery<&bool>)>::new(&mut world);
+        let (heights, peaks) = state.get(&world);
+        let (heights, peaks) = (
+            heights.get_map(map_id).unwrap(),
+            peaks.get_map(map_id).unwrap(),
+        );
+        // Heights are generated a tile past each chunk for the peaks stage, but only written inside of them.
+        assert!(heights.get_at([-1, 0]).is_none());
+        assert!(heights.get_at([8, 3]).is_none());
+        assert!(peaks.get_at([-1, 0]).is_none());
+        for x in 1..7 {
+            for y in 1..3 {
+                let height = heights.get_at([x, y]).unwrap();
+                let peak = [[x + 1, y], [x - 1, y], [x, y + 1], [x, y - 1]]
+                    .into_iter()
+                    .all(|neighbor| heights.get_at(neighbor).unwrap() < height);
+                assert_eq!(peaks.get_at([x, y]), Some(&peak));
+            }
+        }
+    }
+
+    #[test]
+    fn neighbors_keep_finished_tiles() {
+        let pipeline = GenPipeline::<2>::new()
+            .stage(GenStage::new("heights", heights).writes::<u8>())
+            .stage(
+                GenStage::new("erode", erode)
+                    .reads::<u8>()
+                    .writes::<u8>()
+                    .padding(1),
+            );
+        let mut world = World::new();
+        let map_id = TileWorldExt::<2>::spawn_map(&mut world, 4);
+        world.entity_mut(map_id).insert((MapSeed(3), pipeline));
+
+        generate(&mut world, map_id, [0, 0]);
+        let border = |world: &mut World| -> Vec<Option<u8>> {
+            (0..4)
+                .map(|y| TileWorldExt::<2>::get_tile::<u8>(world, map_id, [3, y]).copied())
+                .collect()
+        };
+        let finished = border(&mut world);
+        assert!(finished.iter().all(Option::is_some));
+
+        generate(&mut world, map_id, [1, 0]);
+        assert_eq!(border(&mut world), finished);
+        let map = world.get::<TileMap<2>>(map_id).unwrap();
+        assert!(map.get_from_chunk(ChunkCoord([0, -1])).is_none());
+        assert!(map.get_from_chunk(ChunkCoord([2, 0])).is_none());
+    }
+}
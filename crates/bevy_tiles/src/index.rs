@@ -0,0 +1,396 @@
+use std::any::TypeId;
+
+use bevy::{
+    ecs::{
+        component::{Component, ComponentHooks, StorageType},
+        entity::Entity,
+        system::Query,
+        world::World,
+    },
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    chunks::ChunkData,
+    coords::{calculate_chunk_coordinate, calculate_tile_index, CoordIterator},
+    maps::TileMap,
+};
+
+/// A reverse index from keys to the coordinates of `T` tiles in a map, add this to a [`TileMap`] to
+/// answer questions like "where are all the ore tiles" without scanning every chunk.
+/// # Note
+/// The index is kept up to date by tile inserts and removals made through this crate's commands.
+/// Tiles that already existed when the index was added, or that were edited in place through queries,
+/// are only picked up by [`LayerIndex::rebuild`].
+///
+/// Tiles are also counted per chunk, so passes over rare values (ex: every lava tile) can skip
+/// chunks without any with [`LayerIndex::chunks`].
+/// Despawning a chunk removes its tiles from the index.
+pub struct LayerIndex<T, const N: usize> {
+    key: IndexKey<T>,
+    coords: HashMap<u64, HashSet<[i32; N]>>,
+    keys: HashMap<[i32; N], u64>,
+    chunk_counts: HashMap<u64, HashMap<[i32; N], usize>>,
+}
+
+impl<T: Send + Sync + 'static, const N: usize> Component for LayerIndex<T, N> {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_add(|mut world, map_id, _| {
+            world
+                .commands()
+                .queue(move |world: &mut World| register_index::<T, N>(world, map_id));
+        });
+    }
+}
+
+enum IndexKey<T> {
+    Value(fn(&T) -> Option<u64>),
+    Tag(fn(&T) -> bool),
+}
+
+impl<T, const N: usize> LayerIndex<T, N> {
+    /// The key tiles matching the predicate of a [`LayerIndex::tagged_with`] index are stored under.
+    pub const TAG: u64 = 0;
+
+    /// Index tiles by a key computed from their value, tiles with a [`None`] key aren't indexed.
+    pub fn new(key: fn(&T) -> Option<u64>) -> Self {
+        Self {
+            key: IndexKey::Value(key),
+            coords: Default::default(),
+            keys: Default::default(),
+            chunk_counts: Default::default(),
+        }
+    }
+
+    /// Index the tiles matching a predicate, they can be found with [`LayerIndex::tagged`].
+    pub fn tagged_with(predicate: fn(&T) -> bool) -> Self {
+        Self {
+            key: IndexKey::Tag(predicate),
+            coords: Default::default(),
+            keys: Default::default(),
+            chunk_counts: Default::default(),
+        }
+    }
+
+    /// Iterate over the coordinates of the tiles matching the predicate of a [`LayerIndex::tagged_with`] index.
+    pub fn tagged(&self) -> impl Iterator<Item = [i32; N]> + '_ {
+        self.coords(Self::TAG)
+    }
+
+    /// Iterate over the coordinates of the tiles with a key.
+    pub fn coords(&self, key: u64) -> impl Iterator<Item = [i32; N]> + '_ {
+        self.coords.get(&key).into_iter().flatten().copied()
+    }
+
+    /// Number of tiles with a key.
+    pub fn count(&self, key: u64) -> usize {
+        self.coords.get(&key).map(HashSet::len).unwrap_or(0)
+    }
+
+    /// Iterate over the coordinates of the chunks with at least one tile with a key.
+    pub fn chunks(&self, key: u64) -> impl Iterator<Item = [i32; N]> + '_ {
+        self.chunk_counts
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .map(|(chunk_c, _)| *chunk_c)
+    }
+
+    /// Number of tiles with a key in a chunk.
+    pub fn count_in_chunk(&self, key: u64, chunk_c: impl Into<[i32; N]>) -> usize {
+        self.chunk_counts
+            .get(&key)
+            .and_then(|counts| counts.get(&chunk_c.into()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Get the key a tile is indexed under.
+    pub fn key_of(&self, tile_c: impl Into<[i32; N]>) -> Option<u64> {
+        self.keys.get(&tile_c.into()).copied()
+    }
+
+    /// Iterate over every key with at least one tile.
+    pub fn keys(&self) -> impl Iterator<Item = u64> + '_ {
+        self.coords.keys().copied()
+    }
+
+    /// Number of indexed tiles.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether no tiles are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Get the key a value would be indexed under.
+    #[inline]
+    pub fn key(&self, value: &T) -> Option<u64> {
+        match self.key {
+            IndexKey::Value(key) => key(value),
+            IndexKey::Tag(predicate) => predicate(value).then_some(Self::TAG),
+        }
+    }
+
+    /// Record the new value of a tile, [`None`] if it was removed.
+    pub(crate) fn update(&mut self, tile_c: [i32; N], key: Option<u64>, chunk_size: usize) {
+        let chunk_c = calculate_chunk_coordinate(tile_c, chunk_size);
+        if let Some(old) = self.keys.remove(&tile_c) {
+            if let Some(coords) = self.coords.get_mut(&old) {
+                coords.remove(&tile_c);
+                if coords.is_empty() {
+                    self.coords.remove(&old);
+                }
+            }
+            if let Some(counts) = self.chunk_counts.get_mut(&old) {
+                if let Some(count) = counts.get_mut(&chunk_c) {
+                    *count -= 1;
+                    if *count == 0 {
+                        counts.remove(&chunk_c);
+                    }
+                }
+                if counts.is_empty() {
+                    self.chunk_counts.remove(&old);
+                }
+            }
+        }
+        if let Some(key) = key {
+            self.keys.insert(tile_c, key);
+            self.coords.entry(key).or_default().insert(tile_c);
+            *self
+                .chunk_counts
+                .entry(key)
+                .or_default()
+                .entry(chunk_c)
+                .or_default() += 1;
+        }
+    }
+
+    /// Remove every tile of a chunk from the index.
+    pub(crate) fn remove_chunk(&mut self, chunk_c: [i32; N], chunk_size: usize) {
+        if !self
+            .chunk_counts
+            .values()
+            .any(|counts| counts.contains_key(&chunk_c))
+        {
+            return;
+        }
+        let min = chunk_c.map(|c| c * chunk_size as i32);
+        let max = min.map(|c| c + chunk_size as i32 - 1);
+        for tile_c in CoordIterator::new(min, max) {
+            if self.keys.contains_key(&tile_c) {
+                self.update(tile_c, None, chunk_size);
+            }
+        }
+    }
+
+    /// Move the tiles of two chunks over to each other's coordinates.
+    pub(crate) fn swap_chunks(
+        &mut self,
+        chunk_c_0: [i32; N],
+        chunk_c_1: [i32; N],
+        chunk_size: usize,
+    ) {
+        let mut moved = Vec::new();
+        for (from, to) in [(chunk_c_0, chunk_c_1), (chunk_c_1, chunk_c_0)] {
+            if !self
+                .chunk_counts
+                .values()
+                .any(|counts| counts.contains_key(&from))
+            {
+                continue;
+            }
+            let min = from.map(|c| c * chunk_size as i32);
+            let max = min.map(|c| c + chunk_size as i32 - 1);
+            for tile_c in CoordIterator::new(min, max) {
+                if let Some(key) = self.keys.get(&tile_c).copied() {
+                    let moved_c =
+                        std::array::from_fn(|i| tile_c[i] + (to[i] - from[i]) * chunk_size as i32);
+                    moved.push((tile_c, moved_c, key));
+                }
+            }
+        }
+        for (tile_c, _, _) in &moved {
+            self.update(*tile_c, None, chunk_size);
+        }
+        for (_, moved_c, key) in moved {
+            self.update(moved_c, Some(key), chunk_size);
+        }
+    }
+
+    /// Clear the index and re-add every tile in the map.
+    pub fn rebuild(&mut self, map: &TileMap<N>, chunks: &Query<&ChunkData<T>>)
+    where
+        T: Send + Sync + 'static,
+    {
+        self.coords.clear();
+        self.keys.clear();
+        self.chunk_counts.clear();
+        let chunk_size = map.get_chunk_size() as i32;
+        for (chunk_c, chunk_id) in map.get_chunks() {
+            let Ok(chunk) = chunks.get(*chunk_id) else {
+                continue;
+            };
+            let min = chunk_c.map(|c| c * chunk_size);
+            let max = min.map(|c| c + chunk_size - 1);
+            for tile_c in CoordIterator::new(min, max) {
+                let key = chunk
+                    .get(calculate_tile_index(tile_c, chunk_size as usize))
+                    .and_then(|value| self.key(value));
+                self.update(tile_c, key, chunk_size as usize);
+            }
+        }
+    }
+}
+
+/// An edit made to whole chunks of a map that every [`LayerIndex`] on it has to follow.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ChunkIndexEdit<const N: usize> {
+    /// The chunk was despawned.
+    Remove([i32; N]),
+    /// The two chunks swapped coordinates.
+    Swap([i32; N], [i32; N]),
+}
+
+type IndexHook<const N: usize> = fn(&mut World, Entity, ChunkIndexEdit<N>, usize);
+
+/// The [`LayerIndex`] layers of a map, so chunk edits can reach them without knowing their types.
+#[derive(Component)]
+pub(crate) struct LayerIndexHooks<const N: usize>(Vec<(TypeId, IndexHook<N>)>);
+
+fn register_index<T: Send + Sync + 'static, const N: usize>(world: &mut World, map_id: Entity) {
+    let Ok(mut map) = world.get_entity_mut(map_id) else {
+        return;
+    };
+    let hook: IndexHook<N> = edit_index::<T, N>;
+    match map.get_mut::<LayerIndexHooks<N>>() {
+        Some(mut hooks) => {
+            if !hooks.0.iter().any(|(layer, _)| *layer == TypeId::of::<T>()) {
+                hooks.0.push((TypeId::of::<T>(), hook));
+            }
+        }
+        None => {
+            map.insert(LayerIndexHooks(vec![(TypeId::of::<T>(), hook)]));
+        }
+    }
+}
+
+fn edit_index<T: Send + Sync + 'static, const N: usize>(
+    world: &mut World,
+    map_id: Entity,
+    edit: ChunkIndexEdit<N>,
+    chunk_size: usize,
+) {
+    let Some(mut index) = world.get_mut::<LayerIndex<T, N>>(map_id) else {
+        return;
+    };
+    match edit {
+        ChunkIndexEdit::Remove(chunk_c) => index.remove_chunk(chunk_c, chunk_size),
+        ChunkIndexEdit::Swap(chunk_c_0, chunk_c_1) => {
+            index.swap_chunks(chunk_c_0, chunk_c_1, chunk_size)
+        }
+    }
+}
+
+/// Apply a chunk edit to every [`LayerIndex`] of a map.
+pub(crate) fn edit_layer_indexes<const N: usize>(
+    world: &mut World,
+    map_id: Entity,
+    edit: ChunkIndexEdit<N>,
+    chunk_size: usize,
+) {
+    let Some(hooks) = world.get::<LayerIndexHooks<N>>(map_id) else {
+        return;
+    };
+    for (_, hook) in hooks.0.clone() {
+        hook(world, map_id, edit, chunk_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{system::SystemState, world::World};
+
+    use crate::{commands::TileWorldExt, filters::TileFilter, testing};
+
+    use super::*;
+
+    #[test]
+    fn index_follows_inserts_and_takes() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            map.insert_tile([9, 9], 2u8);
+            map.insert(LayerIndex::<u8, 2>::new(|ore| {
+                (*ore > 0).then_some(*ore as u64)
+            }));
+            map.insert(LayerIndex::<bool, 2>::tagged_with(|explored| *explored));
+            map.insert_tile([0, 0], 1u8);
+            map.insert_tile([5, -3], 1u8);
+            map.insert_tile([2, 2], 2u8);
+            map.insert_tile([2, 2], 1u8);
+            map.insert_tile([7, 7], 0u8);
+            map.insert_tile([1, 1], true);
+            map.insert_tile([1, 2], false);
+            map.remove_tile::<u8>([0, 0]);
+        });
+
+        let index = world.get::<LayerIndex<u8, 2>>(map_id).unwrap();
+        let mut ones: Vec<[i32; 2]> = index.coords(1).collect();
+        ones.sort();
+        assert_eq!(ones, vec![[2, 2], [5, -3]]);
+        assert_eq!(index.count(2), 0);
+        assert_eq!(index.key_of([7, 7]), None);
+        assert_eq!(index.len(), 2);
+        let mut chunks: Vec<[i32; 2]> = index.chunks(1).collect();
+        chunks.sort();
+        assert_eq!(chunks, vec![[0, 0], [1, -1]]);
+        assert_eq!(index.count_in_chunk(1, [0, 0]), 1);
+        assert_eq!(index.count_in_chunk(2, [0, 0]), 0);
+
+        let explored = world.get::<LayerIndex<bool, 2>>(map_id).unwrap();
+        assert_eq!(explored.tagged().collect::<Vec<_>>(), vec![[1, 1]]);
+
+        // Tiles from before the index was added are found by rebuilding.
+        let mut state = SystemState::<Query<&ChunkData<u8>>>::new(&mut world);
+        let chunks = state.get(&world);
+        let mut index = LayerIndex::<u8, 2>::new(|ore| Some(*ore as u64));
+        index.rebuild(world.get::<TileMap<2>>(map_id).unwrap(), &chunks);
+        assert_eq!(index.coords(2).collect::<Vec<_>>(), vec![[9, 9]]);
+        assert_eq!(index.len(), 4);
+    }
+
+    #[test]
+    fn index_follows_chunk_despawns_and_filters() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            map.insert(LayerIndex::<f32, 2>::new(|height| Some(*height as u64)));
+            map.insert(LayerIndex::<u8, 2>::new(|ore| Some(*ore as u64)));
+            map.insert_tile_batch([[0, 0], [1, 0], [2, 0]], |_| 4.0f32);
+            map.insert_tile([1, 0], 1.0f32);
+            map.insert_tile([5, 0], 1u8);
+            map.insert_tile([6, 1], 1u8);
+            map.insert_tile([0, 0], 1u8);
+        });
+
+        testing::apply_map(&mut world, map_id, |map| {
+            map.filter_tiles::<f32>([0, 0], [2, 0], TileFilter::BoxBlur { radius: 1 });
+            map.despawn_chunk([1, 0]);
+        });
+
+        // Filtered tiles are re-keyed by their new values.
+        let heights = world.get::<LayerIndex<f32, 2>>(map_id).unwrap();
+        assert_eq!(heights.count(4), 0);
+        for tile_c in [[0, 0], [1, 0], [2, 0]] {
+            let height = world.get_tile::<f32>(map_id, tile_c).unwrap();
+            assert_eq!(heights.key_of(tile_c), Some(*height as u64));
+        }
+        let ores = world.get::<LayerIndex<u8, 2>>(map_id).unwrap();
+        assert_eq!(ores.coords(1).collect::<Vec<_>>(), vec![[0, 0]]);
+        assert_eq!(ores.chunks(1).collect::<Vec<_>>(), vec![[0, 0]]);
+        assert_eq!(ores.count_in_chunk(1, [1, 0]), 0);
+    }
+}
@@ -0,0 +1,335 @@
+use std::any::{type_name, TypeId};
+
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        entity::Entity,
+        query::With,
+        schedule::IntoSystemConfigs,
+        system::{Query, Res, ResMut, Resource},
+        world::World,
+    },
+    input::{mouse::MouseButton, ButtonInput},
+    prelude::{Camera, GlobalTransform, Name},
+    reflect::{PartialReflect, Reflect, ReflectMut},
+    utils::HashSet,
+    window::{PrimaryWindow, Window},
+};
+use bevy_egui::{egui, EguiContext, EguiContexts, EguiPlugin};
+
+use crate::{
+    chunks::{ChunkCoord, ChunkTypes},
+    commands::TileWorldExt,
+    convert::MapSpaces,
+    maps::TileMap,
+    queries::TileComponent,
+};
+
+/// Adds an egui panel for inspecting 2d maps and editing the tile data of registered layers.
+/// # Note
+/// Only layers registered with [`TileInspectorAppExt::register_inspectable_layer`] can be edited,
+/// other layers are listed by type id.
+pub struct TileInspectorPlugin;
+
+impl Plugin for TileInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+        app.init_resource::<TileInspector>()
+            .init_resource::<InspectableLayers>()
+            .add_systems(Update, (pick_tile, inspector_ui, chunk_labels).chain());
+    }
+}
+
+/// State of the tile inspector panel.
+#[derive(Resource, Debug)]
+pub struct TileInspector {
+    /// Whether the panel is shown.
+    pub open: bool,
+    /// Whether left clicking in the viewport selects a tile.
+    pub pick_with_mouse: bool,
+    /// The map being inspected.
+    pub selected_map: Option<Entity>,
+    /// The tile being inspected.
+    pub selected_tile: [i32; 2],
+    /// Whether every visible chunk is labeled with its coordinate and layer count.
+    pub show_chunk_labels: bool,
+}
+
+impl Default for TileInspector {
+    fn default() -> Self {
+        Self {
+            open: true,
+            pick_with_mouse: true,
+            selected_map: None,
+            selected_tile: [0, 0],
+            show_chunk_labels: false,
+        }
+    }
+}
+
+type LayerUi = fn(&mut World, Entity, [i32; 2], &mut egui::Ui);
+
+struct InspectableLayer {
+    type_id: TypeId,
+    name: &'static str,
+    ui: LayerUi,
+}
+
+#[derive(Resource, Default)]
+struct InspectableLayers(Vec<InspectableLayer>);
+
+/// Adds layer registration for the [`TileInspectorPlugin`].
+pub trait TileInspectorAppExt {
+    /// Allow the inspector to show and edit tiles of type `T`.
+    fn register_inspectable_layer<T: TileComponent + Reflect>(&mut self) -> &mut Self;
+}
+
+impl TileInspectorAppExt for App {
+    fn register_inspectable_layer<T: TileComponent + Reflect>(&mut self) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(InspectableLayers::default)
+            .0
+            .push(InspectableLayer {
+                type_id: TypeId::of::<T>(),
+                name: type_name::<T>(),
+                ui: layer_ui::<T>,
+            });
+        self
+    }
+}
+
+fn layer_ui<T: TileComponent + Reflect>(
+    world: &mut World,
+    map_id: Entity,
+    tile_c: [i32; 2],
+    ui: &mut egui::Ui,
+) {
+    let Some(map) = world.get::<TileMap<2>>(map_id) else {
+        return;
+    };
+    if map.get_from_tile(tile_c).is_none() {
+        ui.label("No chunk");
+        return;
+    }
+    let Some(tile) = TileWorldExt::<2>::get_tile::<T>(world, map_id, tile_c) else {
+        ui.label("Empty");
+        return;
+    };
+
+    // Edit a copy and write it back like any other tile edit, so indexes and observers see the change.
+    let mut edited = tile.clone_value();
+    if !reflect_ui(edited.as_mut(), ui) {
+        return;
+    }
+    if let Some(mut tile) = TileWorldExt::<2>::take_tile::<T>(world, map_id, tile_c) {
+        tile.apply(edited.as_ref());
+        TileWorldExt::<2>::insert_tile(world, map_id, tile_c, tile);
+    }
+}
+
+/// A small reflection based editor for plain data, returns whether the value was changed.
+fn reflect_ui(value: &mut dyn PartialReflect, ui: &mut egui::Ui) -> bool {
+    if let Some(value) = value.try_downcast_mut::<bool>() {
+        return ui.checkbox(value, "").changed();
+    }
+    if let Some(value) = value.try_downcast_mut::<String>() {
+        return ui.text_edit_singleline(value).changed();
+    }
+    macro_rules! drag {
+        ($($ty:ty),*) => {
+            $(if let Some(value) = value.try_downcast_mut::<$ty>() {
+                return ui.add(egui::DragValue::new(value)).changed();
+            })*
+        };
+    }
+    drag!(f32, f64, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+    let mut changed = false;
+    match value.reflect_mut() {
+        ReflectMut::Struct(value) => {
+            for i in 0..value.field_len() {
+                let name = value.name_at(i).unwrap_or_default().to_owned();
+                ui.horizontal(|ui| {
+                    ui.label(name);
+                    if let Some(field) = value.field_at_mut(i) {
+                        changed |= reflect_ui(field, ui);
+                    }
+                });
+            }
+        }
+        ReflectMut::TupleStruct(value) => {
+            for i in 0..value.field_len() {
+                if let Some(field) = value.field_mut(i) {
+                    changed |= reflect_ui(field, ui);
+                }
+            }
+        }
+        ReflectMut::Enum(value) => {
+            ui.label(value.variant_name().to_owned());
+            for i in 0..value.field_len() {
+                if let Some(field) = value.field_at_mut(i) {
+                    changed |= reflect_ui(field, ui);
+                }
+            }
+        }
+        _ => {
+            ui.label(format!("{value:?}"));
+        }
+    }
+    changed
+}
+
+fn pick_tile(
+    mut inspector: ResMut<TileInspector>,
+    mut contexts: EguiContexts,
+    buttons: Option<Res<ButtonInput<MouseButton>>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    map_spaces: MapSpaces<2>,
+) {
+    if !inspector.open || !inspector.pick_with_mouse {
+        return;
+    }
+    if !buttons.is_some_and(|buttons| buttons.just_pressed(MouseButton::Left)) {
+        return;
+    }
+    if contexts
+        .try_ctx_mut()
+        .is_some_and(|ctx| ctx.wants_pointer_input())
+    {
+        return;
+    }
+    let Some(cursor) = windows.get_single().ok().and_then(|w| w.cursor_position()) else {
+        return;
+    };
+    let Some(map_id) = inspector.selected_map else {
+        return;
+    };
+    let Some(map_space) = map_spaces.get(map_id) else {
+        return;
+    };
+    let Some(world_c) = cameras
+        .iter()
+        .find_map(|(camera, camera_t)| camera.viewport_to_world_2d(camera_t, cursor).ok())
+    else {
+        return;
+    };
+    inspector.selected_tile = map_space.world_to_tile(world_c.extend(0.0));
+}
+
+/// Draws the coordinate and layer count of every chunk (with a transform) over it.
+fn chunk_labels(
+    inspector: Res<TileInspector>,
+    mut contexts: EguiContexts,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    chunks: Query<(&ChunkCoord<2>, &ChunkTypes, &GlobalTransform)>,
+) {
+    if !inspector.open || !inspector.show_chunk_labels {
+        return;
+    }
+    let Some(ctx) = contexts.try_ctx_mut() else {
+        return;
+    };
+    let Some((camera, camera_t, viewport)) = cameras
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .find_map(|(camera, camera_t)| Some((camera, camera_t, camera.logical_viewport_rect()?)))
+    else {
+        return;
+    };
+
+    let painter = ctx.layer_painter(egui::LayerId::background());
+    for (chunk_c, types, chunk_t) in chunks.iter() {
+        let Ok(position) = camera.world_to_viewport(camera_t, chunk_t.translation()) else {
+            continue;
+        };
+        let position = position + viewport.min;
+        if !viewport.contains(position) {
+            continue;
+        }
+        painter.text(
+            egui::pos2(position.x, position.y),
+            egui::Align2::LEFT_BOTTOM,
+            format!("{:?}\n{} layers", **chunk_c, types.0.len()),
+            egui::FontId::monospace(12.0),
+            egui::Color32::WHITE,
+        );
+    }
+}
+
+fn inspector_ui(world: &mut World) {
+    if !world.resource::<TileInspector>().open {
+        return;
+    }
+    let Ok(mut egui_context) = world
+        .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+        .get_single_mut(world)
+    else {
+        return;
+    };
+    let ctx = egui_context.get_mut().clone();
+
+    let maps: Vec<(Entity, String, usize)> = world
+        .query::<(Entity, &TileMap<2>, Option<&Name>)>()
+        .iter(world)
+        .map(|(id, map, name)| {
+            let name = name.map(|name| name.to_string()).unwrap_or(id.to_string());
+            (id, name, map.stats().chunk_count)
+        })
+        .collect();
+
+    let mut inspector = world.resource_mut::<TileInspector>();
+    egui::Window::new("Tile Inspector").show(&ctx, |ui| {
+        ui.heading("Maps");
+        for (id, name, chunk_count) in maps.iter() {
+            let selected = inspector.selected_map == Some(*id);
+            if ui
+                .selectable_label(selected, format!("{name} ({chunk_count} chunks)"))
+                .clicked()
+            {
+                inspector.selected_map = Some(*id);
+            }
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Tile");
+            ui.add(egui::DragValue::new(&mut inspector.selected_tile[0]));
+            ui.add(egui::DragValue::new(&mut inspector.selected_tile[1]));
+        });
+        ui.checkbox(&mut inspector.pick_with_mouse, "Pick with mouse");
+        ui.checkbox(&mut inspector.show_chunk_labels, "Chunk labels");
+    });
+
+    let Some(map_id) = inspector.selected_map else {
+        return;
+    };
+    let tile_c = inspector.selected_tile;
+    let Some(map) = world.get::<TileMap<2>>(map_id) else {
+        return;
+    };
+
+    // Every layer stored on the map, registered or not.
+    let chunk_ids: Vec<Entity> = map.get_chunks().values().copied().collect();
+    let mut layer_ids = HashSet::new();
+    for chunk_id in chunk_ids {
+        if let Some(types) = world.get::<ChunkTypes>(chunk_id) {
+            layer_ids.extend(types.0.iter().copied());
+        }
+    }
+
+    world.resource_scope(|world, layers: bevy::ecs::world::Mut<InspectableLayers>| {
+        egui::Window::new("Tile Layers").show(&ctx, |ui| {
+            for layer in layers.0.iter() {
+                if !layer_ids.remove(&layer.type_id) {
+                    continue;
+                }
+                ui.collapsing(layer.name, |ui| (layer.ui)(world, map_id, tile_c, ui));
+            }
+            for layer_id in layer_ids.iter() {
+                ui.label(format!("Unregistered layer {layer_id:?}"));
+            }
+        });
+    });
+}
@@ -0,0 +1,279 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        system::{Commands, Query, Res},
+    },
+    time::Time,
+    transform::components::GlobalTransform,
+};
+
+use crate::{
+    commands::TileCommandExt,
+    convert::MapSpaces,
+    labels::{label_regions, RegionInfo},
+    tiles::{TileMapQuery, TileQuery},
+};
+
+/// Finds the enclosed region of tiles matching `is_open` that contains `tile_c` (ex: the floor of the room
+/// the player is standing in).  Returns [`None`] if `tile_c` isn't open, or if its region reaches further than
+/// `search_radius` tiles away along any axis, which is treated as being outdoors.
+/// # Note
+/// Missing tiles aren't open, so the region has to be closed off by tiles that don't match `is_open` or by gaps in the layer.
+pub fn find_interior<T, F, const N: usize>(
+    tiles: &TileQuery<'_, '_, '_, &T, N>,
+    tile_c: impl Into<[i32; N]>,
+    search_radius: i32,
+    is_open: F,
+) -> Option<RegionInfo<N>>
+where
+    T: Send + Sync + 'static,
+    F: Fn(&T) -> bool + Sync,
+{
+    let tile_c = tile_c.into();
+    let min = tile_c.map(|c| c - search_radius);
+    let max = tile_c.map(|c| c + search_radius);
+    let labels = label_regions(tiles, min, max, is_open);
+    let region = *labels.region_of(tile_c)?;
+    (0..N)
+        .all(|i| region.min[i] > min[i] && region.max[i] < max[i])
+        .then_some(region)
+}
+
+/// Hides the chunks of a roof map above the interior a probe entity is standing in, and shows them again once it leaves.
+/// # Note
+/// The probe's tile is found from its [`GlobalTransform`], so the floor map needs [`crate::maps::TileDims`].
+/// The interior is only searched for again when the probe moves onto another tile.
+#[derive(Component)]
+pub struct RoofProbe<T: Send + Sync + 'static, const N: usize = 2> {
+    /// The map searched for interiors.
+    pub floor_map: Entity,
+    /// The map hidden above interiors.
+    pub roof_map: Entity,
+    /// How far an interior can reach from the probe, see [`find_interior`].
+    pub search_radius: i32,
+    /// Which floor tiles are part of interiors.
+    pub is_open: fn(&T) -> bool,
+    tile_c: Option<[i32; N]>,
+    interior: Option<RegionInfo<N>>,
+}
+
+impl<T: Send + Sync + 'static, const N: usize> RoofProbe<T, N> {
+    /// Create a probe that hides `roof_map` over interiors of `floor_map`.
+    pub fn new(
+        floor_map: Entity,
+        roof_map: Entity,
+        search_radius: i32,
+        is_open: fn(&T) -> bool,
+    ) -> Self {
+        Self {
+            floor_map,
+            roof_map,
+            search_radius,
+            is_open,
+            tile_c: None,
+            interior: None,
+        }
+    }
+
+    /// The interior the probe is in, if any.
+    pub fn interior(&self) -> Option<&RegionInfo<N>> {
+        self.interior.as_ref()
+    }
+}
+
+/// Fades a roof map instead of hiding its chunks when a [`RoofProbe`] enters an interior.
+/// # Note
+/// This only keeps track of the alpha, renderers are expected to apply it to the roof tiles in [`RoofFade::region`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct RoofFade<const N: usize = 2> {
+    /// The opacity of the faded roof tiles, from `0.0` (hidden) to `1.0` (opaque).
+    pub alpha: f32,
+    /// How much the alpha changes per second.
+    pub speed: f32,
+    indoors: bool,
+    region: Option<([i32; N], [i32; N])>,
+}
+
+impl<const N: usize> RoofFade<N> {
+    /// Create an opaque roof that fades at `speed` alpha per second.
+    pub fn new(speed: f32) -> Self {
+        Self {
+            alpha: 1.0,
+            speed,
+            indoors: false,
+            region: None,
+        }
+    }
+
+    /// The lowest and highest corners of the last interior under the roof, kept while it fades back in.
+    pub fn region(&self) -> Option<([i32; N], [i32; N])> {
+        self.region
+    }
+}
+
+/// Hides roofs above the interiors [`RoofProbe`]s with `T` floors are in.
+pub struct RoofHidingPlugin<T, const N: usize = 2> {
+    layer: PhantomData<fn() -> T>,
+}
+
+impl<T, const N: usize> Default for RoofHidingPlugin<T, N> {
+    fn default() -> Self {
+        Self { layer: PhantomData }
+    }
+}
+
+impl<T: Send + Sync + 'static, const N: usize> Plugin for RoofHidingPlugin<T, N> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, (hide_roofs::<T, N>, fade_roofs::<N>));
+    }
+}
+
+/// Finds the interior of every [`RoofProbe`] that moved, and hides or fades the roofs above them.
+pub fn hide_roofs<T: Send + Sync + 'static, const N: usize>(
+    mut commands: Commands,
+    mut probes: Query<(&mut RoofProbe<T, N>, &GlobalTransform)>,
+    mut fades: Query<&mut RoofFade<N>>,
+    floors: TileMapQuery<&T, (), N>,
+    spaces: MapSpaces<N>,
+) {
+    for (mut probe, probe_t) in probes.iter_mut() {
+        let Some(space) = spaces.get(probe.floor_map) else {
+            continue;
+        };
+        let tile_c = space.world_to_tile(probe_t.translation());
+        if probe.tile_c == Some(tile_c) {
+            continue;
+        }
+        probe.tile_c = Some(tile_c);
+
+        let interior = floors
+            .get_map(probe.floor_map)
+            .ok()
+            .and_then(|tiles| find_interior(&tiles, tile_c, probe.search_radius, probe.is_open));
+        let bounds = |region: Option<RegionInfo<N>>| region.map(|region| (region.min, region.max));
+        if bounds(interior) == bounds(probe.interior) {
+            continue;
+        }
+
+        if let Ok(mut fade) = fades.get_mut(probe.roof_map) {
+            fade.indoors = interior.is_some();
+            if interior.is_some() {
+                fade.region = bounds(interior);
+            }
+        } else if let Some(mut roof) = TileCommandExt::<N>::tile_map(&mut commands, probe.roof_map)
+        {
+            if let Some(old) = probe.interior {
+                roof.set_chunks_visible(old.min, old.max, true);
+            }
+            if let Some(new) = interior {
+                roof.set_chunks_visible(new.min, new.max, false);
+            }
+        }
+        probe.interior = interior;
+    }
+}
+
+/// Moves the alpha of every [`RoofFade`] towards hidden or opaque.
+pub fn fade_roofs<const N: usize>(time: Res<Time>, mut fades: Query<&mut RoofFade<N>>) {
+    for mut fade in fades.iter_mut() {
+        let target = if fade.indoors { 0.0 } else { 1.0 };
+        let step = fade.speed * time.delta_secs();
+        if fade.alpha != target {
+            fade.alpha = if fade.alpha < target {
+                (fade.alpha + step).min(target)
+            } else {
+                (fade.alpha - step).max(target)
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::system::SystemState, render::view::Visibility, transform::components::Transform,
+    };
+
+    use crate::{
+        coords::CoordIterator,
+        maps::{TileDims, TileMap},
+        testing,
+    };
+
+    use super::*;
+
+    fn spawn_house(app: &mut App) -> (Entity, Entity) {
+        let floor_id = testing::spawn_map(app.world_mut(), 4, |floor| {
+            floor.insert(TileDims([1.0, 1.0]));
+            // Grass everywhere, with a walled house from (2, 2) to (6, 6).
+            for tile_c in CoordIterator::new([-10, -10], [10, 10]) {
+                let wall = (2..=6).contains(&tile_c[0])
+                    && (2..=6).contains(&tile_c[1])
+                    && [2, 6].iter().any(|c| tile_c.contains(c));
+                floor.insert_tile(tile_c, wall);
+            }
+        });
+        let roof_id = testing::spawn_map(app.world_mut(), 4, |roof| {
+            roof.insert_tile_batch_cloned(CoordIterator::new([2, 2], [6, 6]), 1u8);
+            roof.insert_tile([-8, -8], 1u8);
+        });
+        (floor_id, roof_id)
+    }
+
+    #[test]
+    fn detects_enclosed_rooms() {
+        let mut app = App::new();
+        let (floor_id, _) = spawn_house(&mut app);
+
+        let mut state = SystemState::<TileMapQuery<&bool>>::new(app.world_mut());
+        let tile_maps = state.get(app.world());
+        let tiles = tile_maps.get_map(floor_id).unwrap();
+        let room = find_interior(&tiles, [4, 3], 8, |wall| !*wall).unwrap();
+        assert_eq!((room.min, room.max, room.area), ([3, 3], [5, 5], 9));
+        assert!(find_interior(&tiles, [-4, 0], 8, |wall| !*wall).is_none());
+        assert!(find_interior(&tiles, [2, 2], 8, |wall| !*wall).is_none());
+        // A radius too small to see the walls can't tell the room is enclosed.
+        assert!(find_interior(&tiles, [4, 4], 1, |wall| !*wall).is_none());
+    }
+
+    #[test]
+    fn hides_roof_while_inside() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_plugins(RoofHidingPlugin::<bool, 2>::default());
+        let (floor_id, roof_id) = spawn_house(&mut app);
+        let player = app
+            .world_mut()
+            .spawn((
+                RoofProbe::<bool, 2>::new(floor_id, roof_id, 8, |wall| !*wall),
+                GlobalTransform::from(Transform::from_xyz(4.5, 4.5, 0.0)),
+            ))
+            .id();
+        app.update();
+        app.update();
+
+        let chunk_visibility = |app: &App, tile_c: [i32; 2]| {
+            let map = app.world().get::<TileMap<2>>(roof_id).unwrap();
+            let chunk_id = map.get_from_tile(tile_c).unwrap();
+            app.world().get::<Visibility>(chunk_id).copied()
+        };
+        assert!(app
+            .world()
+            .get::<RoofProbe<bool, 2>>(player)
+            .unwrap()
+            .interior()
+            .is_some());
+        assert_eq!(chunk_visibility(&app, [4, 4]), Some(Visibility::Hidden));
+        assert_eq!(chunk_visibility(&app, [-8, -8]), None);
+
+        *app.world_mut().get_mut::<GlobalTransform>(player).unwrap() =
+            GlobalTransform::from(Transform::from_xyz(-4.5, 0.5, 0.0));
+        app.update();
+        app.update();
+        assert_eq!(chunk_visibility(&app, [4, 4]), Some(Visibility::Inherited));
+    }
+}
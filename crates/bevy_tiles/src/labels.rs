@@ -0,0 +1,317 @@
+use bevy::{
+    tasks::{ComputeTaskPool, TaskPool},
+    utils::HashMap,
+};
+
+use crate::{
+    chunks::{ChunkCoord, ChunkData},
+    coords::{calculate_chunk_coordinate, calculate_tile_index, CoordIterator},
+    tiles::TileQuery,
+};
+
+/// Information about a single connected region.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegionInfo<const N: usize> {
+    /// The label of the region, as stored in [`RegionLabels`].
+    pub id: u32,
+    /// Number of tiles in the region.
+    pub area: usize,
+    /// The lowest corner of the region's bounding box.
+    pub min: [i32; N],
+    /// The highest corner of the region's bounding box.
+    pub max: [i32; N],
+}
+
+/// Result of labeling the connected regions of a layer.
+pub struct RegionLabels<const N: usize> {
+    chunk_size: usize,
+    chunks: HashMap<ChunkCoord<N>, ChunkData<u32>>,
+    /// Information on every region, indexed by region id.
+    pub regions: Vec<RegionInfo<N>>,
+}
+
+impl<const N: usize> RegionLabels<N> {
+    /// Get the region id of a tile, returns [`None`] if the tile wasn't part of a region.
+    pub fn get(&self, tile_c: impl Into<[i32; N]>) -> Option<u32> {
+        let tile_c = tile_c.into();
+        let chunk_c = ChunkCoord(calculate_chunk_coordinate(tile_c, self.chunk_size));
+        self.chunks
+            .get(&chunk_c)?
+            .get(calculate_tile_index(tile_c, self.chunk_size))
+            .copied()
+    }
+
+    /// Get the info of the region a tile is part of.
+    pub fn region_of(&self, tile_c: impl Into<[i32; N]>) -> Option<&RegionInfo<N>> {
+        self.get(tile_c)
+            .and_then(|id| self.regions.get(id as usize))
+    }
+
+    /// Get the per chunk label storage.
+    pub fn chunks(&self) -> &HashMap<ChunkCoord<N>, ChunkData<u32>> {
+        &self.chunks
+    }
+
+    /// Take the per chunk label storage.
+    pub fn into_chunks(self) -> HashMap<ChunkCoord<N>, ChunkData<u32>> {
+        self.chunks
+    }
+}
+
+/// Labels the face connected regions of tiles matching `predicate` between `corner_1` and `corner_2` (inclusive).
+/// # Note
+/// Chunks are labeled in parallel and stitched together in a final merge pass.
+pub fn label_regions<T, F, const N: usize>(
+    tiles: &TileQuery<'_, '_, '_, &T, N>,
+    corner_1: impl Into<[i32; N]>,
+    corner_2: impl Into<[i32; N]>,
+    predicate: F,
+) -> RegionLabels<N>
+where
+    T: Send + Sync + 'static,
+    F: Fn(&T) -> bool + Sync,
+{
+    let chunk_size = tiles.get_chunk_size();
+    let (min, max) = ordered(corner_1.into(), corner_2.into());
+
+    let predicate = &predicate;
+    let mut chunks = ComputeTaskPool::get_or_init(TaskPool::default).scope(|scope| {
+        for chunk_c in CoordIterator::new(
+            calculate_chunk_coordinate(min, chunk_size),
+            calculate_chunk_coordinate(max, chunk_size),
+        ) {
+            scope.spawn(async move {
+                let mut box_min = [0; N];
+                let mut box_max = [0; N];
+                for i in 0..N {
+                    let origin = chunk_c[i] * chunk_size as i32;
+                    box_min[i] = origin.max(min[i]);
+                    box_max[i] = (origin + chunk_size as i32 - 1).min(max[i]);
+                }
+                let solid = CoordIterator::new(box_min, box_max)
+                    .map(|tile_c| tiles.get_at(tile_c).is_some_and(predicate))
+                    .collect();
+                LocalLabels::new(chunk_c, box_min, box_max, solid)
+            });
+        }
+    });
+
+    // Give every chunk local label a global id.
+    let mut offsets = Vec::with_capacity(chunks.len());
+    let mut total = 0;
+    for chunk in chunks.iter() {
+        offsets.push(total);
+        total += chunk.count;
+    }
+    let chunk_lookup: HashMap<[i32; N], usize> = chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| (chunk.chunk_c, i))
+        .collect();
+
+    // Merge labels that touch across chunk faces.
+    let mut sets = DisjointSet::new(total);
+    for (chunk_i, chunk) in chunks.iter().enumerate() {
+        for axis in 0..N {
+            let mut neighbor_c = chunk.chunk_c;
+            neighbor_c[axis] += 1;
+            let Some(&neighbor_i) = chunk_lookup.get(&neighbor_c) else {
+                continue;
+            };
+            let neighbor = &chunks[neighbor_i];
+            let mut face_min = chunk.min;
+            face_min[axis] = chunk.max[axis];
+            for tile_c in CoordIterator::new(face_min, chunk.max) {
+                let mut across_c = tile_c;
+                across_c[axis] += 1;
+                if let (Some(a), Some(b)) = (chunk.label(tile_c), neighbor.label(across_c)) {
+                    sets.union(offsets[chunk_i] + a, offsets[neighbor_i] + b);
+                }
+            }
+        }
+    }
+
+    // Compact the merged sets into region ids and build the outputs.
+    let mut ids = HashMap::new();
+    let mut regions: Vec<RegionInfo<N>> = Vec::new();
+    let mut out = HashMap::with_capacity(chunks.len());
+    for (chunk_i, chunk) in chunks.drain(..).enumerate() {
+        let mut data = ChunkData::new(chunk_size.pow(N as u32));
+        for tile_c in CoordIterator::new(chunk.min, chunk.max) {
+            let Some(local) = chunk.label(tile_c) else {
+                continue;
+            };
+            let root = sets.find(offsets[chunk_i] + local);
+            let id = *ids.entry(root).or_insert_with(|| {
+                regions.push(RegionInfo {
+                    id: regions.len() as u32,
+                    area: 0,
+                    min: tile_c,
+                    max: tile_c,
+                });
+                regions.len() as u32 - 1
+            });
+            let region = &mut regions[id as usize];
+            region.area += 1;
+            for (i, c) in tile_c.into_iter().enumerate() {
+                region.min[i] = region.min[i].min(c);
+                region.max[i] = region.max[i].max(c);
+            }
+            data.insert(calculate_tile_index(tile_c, chunk_size), id);
+        }
+        if data.get_count() > 0 {
+            out.insert(ChunkCoord(chunk.chunk_c), data);
+        }
+    }
+
+    RegionLabels {
+        chunk_size,
+        chunks: out,
+        regions,
+    }
+}
+
+/// Labels for the part of a region inside of one chunk.
+struct LocalLabels<const N: usize> {
+    chunk_c: [i32; N],
+    min: [i32; N],
+    max: [i32; N],
+    /// Zero for empty tiles, otherwise the local label plus one.
+    labels: Vec<usize>,
+    count: usize,
+}
+
+impl<const N: usize> LocalLabels<N> {
+    fn new(chunk_c: [i32; N], min: [i32; N], max: [i32; N], solid: Vec<bool>) -> Self {
+        let mut local = Self {
+            chunk_c,
+            min,
+            max,
+            labels: vec![0; solid.len()],
+            count: 0,
+        };
+
+        let mut stack = Vec::new();
+        for (start_c, start_i) in CoordIterator::new(min, max).zip(0..) {
+            if !solid[start_i] || local.labels[start_i] != 0 {
+                continue;
+            }
+            local.count += 1;
+            local.labels[start_i] = local.count;
+            stack.push(start_c);
+            while let Some(tile_c) = stack.pop() {
+                for axis in 0..N {
+                    for step in [-1, 1] {
+                        let mut neighbor_c = tile_c;
+                        neighbor_c[axis] += step;
+                        let Some(neighbor_i) = local.index(neighbor_c) else {
+                            continue;
+                        };
+                        if solid[neighbor_i] && local.labels[neighbor_i] == 0 {
+                            local.labels[neighbor_i] = local.count;
+                            stack.push(neighbor_c);
+                        }
+                    }
+                }
+            }
+        }
+        local
+    }
+
+    #[inline]
+    fn label(&self, tile_c: [i32; N]) -> Option<usize> {
+        let label = self.labels[self.index(tile_c)?];
+        (label != 0).then(|| label - 1)
+    }
+
+    #[inline]
+    fn index(&self, tile_c: [i32; N]) -> Option<usize> {
+        let mut index = 0;
+        let mut stride = 1;
+        for (i, c) in tile_c.into_iter().enumerate() {
+            if c < self.min[i] || c > self.max[i] {
+                return None;
+            }
+            index += (c - self.min[i]) as usize * stride;
+            stride *= (self.max[i] - self.min[i] + 1) as usize;
+        }
+        Some(index)
+    }
+}
+
+struct DisjointSet {
+    parents: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let a = self.find(a);
+        let b = self.find(b);
+        if a != b {
+            self.parents[a.max(b)] = a.min(b);
+        }
+    }
+}
+
+#[inline]
+fn ordered<const N: usize>(mut min: [i32; N], mut max: [i32; N]) -> ([i32; N], [i32; N]) {
+    for i in 0..N {
+        if min[i] > max[i] {
+            std::mem::swap(&mut min[i], &mut max[i]);
+        }
+    }
+    (min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{system::SystemState, world::World};
+
+    use crate::{coords::CoordIterator, testing, tiles::TileMapQuery};
+
+    use super::*;
+
+    #[test]
+    fn regions_merge_across_chunks() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            // A bar crossing several chunks, with a gap of false tiles.
+            for tile_c in CoordIterator::new([-6, 0], [6, 0]) {
+                map.insert_tile(tile_c, tile_c[0] != 2);
+            }
+            // A bend reconnecting across a chunk corner.
+            for tile_c in CoordIterator::new([-6, -5], [-6, -1]) {
+                map.insert_tile(tile_c, true);
+            }
+            map.insert_tile([10, 10], true);
+        });
+
+        let mut state = SystemState::<TileMapQuery<&bool>>::new(&mut world);
+        let tile_maps = state.get(&world);
+        let tiles = tile_maps.get_map(map_id).unwrap();
+        let labels = label_regions(&tiles, [-10, -10], [10, 10], |solid| *solid);
+
+        assert_eq!(labels.regions.len(), 3);
+        assert_eq!(labels.get([-6, -5]), labels.get([1, 0]));
+        assert_ne!(labels.get([1, 0]), labels.get([3, 0]));
+        assert_eq!(labels.get([2, 0]), None);
+        let bar = labels.region_of([0, 0]).unwrap();
+        assert_eq!(bar.area, 8 + 5);
+        assert_eq!((bar.min, bar.max), ([-6, -5], [1, 0]));
+        assert_eq!(labels.region_of([10, 10]).unwrap().area, 1);
+    }
+}
@@ -0,0 +1,254 @@
+use std::marker::PhantomData;
+
+use bevy::ecs::{entity::Entity, query::QueryFilter, system::Commands};
+
+use crate::{
+    chunks::MapQueryError,
+    commands::{TileCommandExt, TileMapCommands},
+    maps::MapLayers,
+    queries::{TileComponent, TileData},
+    tiles::{TileMapQuery, TileQuery},
+};
+
+/// The position of a layer in a [`LayerSet`], picked by the compiler.
+pub struct At<const I: usize>;
+
+/// A tuple of tile data types a map is declared with, see [`TileCommandExt::spawn_map_with_layers`].
+pub trait LayerSet: Send + Sync + 'static {
+    /// The [`MapLayers`] chunks of the map are spawned with.
+    fn map_layers() -> MapLayers;
+}
+
+/// Implemented by layer sets that contain `T` (at index `I`).
+pub trait HasLayer<T, I> {}
+
+/// The tile data type a [`TileData`] query reads or writes.
+pub trait QueriedLayer {
+    /// The tile data type.
+    type Layer;
+}
+
+impl<T> QueriedLayer for &T {
+    type Layer = T;
+}
+
+impl<T> QueriedLayer for &mut T {
+    type Layer = T;
+}
+
+macro_rules! impl_layer_set {
+    ($(($t:ident, $i:literal)),*) => {
+        impl<$($t: TileComponent),*> LayerSet for ($($t,)*) {
+            fn map_layers() -> MapLayers {
+                MapLayers::new()$(.with::<$t>())*
+            }
+        }
+        impl_layer_set!(@has [$($t),*]; $(($t, $i)),*);
+    };
+    (@has $all:tt; $(($t:ident, $i:literal)),*) => {
+        $(impl_layer_set!(@one $all, $t, $i);)*
+    };
+    (@one [$($all:ident),*], $t:ident, $i:literal) => {
+        impl<$($all),*> HasLayer<$t, At<$i>> for ($($all,)*) {}
+    };
+}
+
+impl_layer_set!((A, 0));
+impl_layer_set!((A, 0), (B, 1));
+impl_layer_set!((A, 0), (B, 1), (C, 2));
+impl_layer_set!((A, 0), (B, 1), (C, 2), (D, 3));
+impl_layer_set!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4));
+impl_layer_set!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4), (F, 5));
+impl_layer_set!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4), (F, 5), (G, 6));
+impl_layer_set!(
+    (A, 0),
+    (B, 1),
+    (C, 2),
+    (D, 3),
+    (E, 4),
+    (F, 5),
+    (G, 6),
+    (H, 7)
+);
+
+/// A handle to a map with a declared [`LayerSet`], tiles can only be read and written through it for
+/// the declared layers.
+/// # Note
+/// Writing to a layer the map doesn't use is a compile error instead of a silently unused layer.
+pub struct TileMapHandle<L, const N: usize = 2> {
+    map_id: Entity,
+    layers: PhantomData<fn() -> L>,
+}
+
+impl<L, const N: usize> Clone for TileMapHandle<L, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<L, const N: usize> Copy for TileMapHandle<L, N> {}
+
+impl<L, const N: usize> PartialEq for TileMapHandle<L, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map_id == other.map_id
+    }
+}
+
+impl<L, const N: usize> Eq for TileMapHandle<L, N> {}
+
+impl<L, const N: usize> std::fmt::Debug for TileMapHandle<L, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TileMapHandle").field(&self.map_id).finish()
+    }
+}
+
+impl<L: LayerSet, const N: usize> TileMapHandle<L, N> {
+    /// Create a handle to an existing map.
+    /// # Note
+    /// The map should have been spawned with [`TileCommandExt::spawn_map_with_layers`] with the same layers.
+    pub fn from_id(map_id: Entity) -> Self {
+        Self {
+            map_id,
+            layers: PhantomData,
+        }
+    }
+
+    /// Get the entity of the map.
+    pub fn id(&self) -> Entity {
+        self.map_id
+    }
+
+    /// Get commands for the map.
+    pub fn commands<'a>(&self, commands: &'a mut Commands) -> Option<LayeredMapCommands<'a, L, N>> {
+        TileCommandExt::<N>::tile_map(commands, self.map_id).map(|commands| LayeredMapCommands {
+            commands,
+            layers: PhantomData,
+        })
+    }
+
+    /// Gets the query for this map.
+    pub fn get_map<'q, 'w, 's, Q, MF, I>(
+        &self,
+        query: &'q TileMapQuery<'w, 's, Q, MF, N>,
+    ) -> Result<TileQuery<'q, 'q, 's, Q::ReadOnly, N>, MapQueryError>
+    where
+        Q: TileData + QueriedLayer + 'static,
+        MF: QueryFilter + 'static,
+        L: HasLayer<Q::Layer, I>,
+    {
+        query.get_map(self.map_id)
+    }
+
+    /// Gets the mutable query for this map.
+    pub fn get_map_mut<'q, 'w, 's, Q, MF, I>(
+        &self,
+        query: &'q mut TileMapQuery<'w, 's, Q, MF, N>,
+    ) -> Result<TileQuery<'q, 'q, 's, Q, N>, MapQueryError>
+    where
+        Q: TileData + QueriedLayer + 'static,
+        MF: QueryFilter + 'static,
+        L: HasLayer<Q::Layer, I>,
+    {
+        query.get_map_mut(self.map_id)
+    }
+}
+
+/// Applies commands to a map with a declared [`LayerSet`], see [`TileMapHandle::commands`].
+pub struct LayeredMapCommands<'a, L, const N: usize> {
+    commands: TileMapCommands<'a, N>,
+    layers: PhantomData<fn() -> L>,
+}
+
+impl<'a, L: LayerSet, const N: usize> LayeredMapCommands<'a, L, N> {
+    /// Get a handle to the map.
+    pub fn handle(&self) -> TileMapHandle<L, N> {
+        TileMapHandle::from_id(self.commands.id())
+    }
+
+    /// Inserts a tile into a declared layer.
+    pub fn insert_tile<T: TileComponent, I>(
+        &mut self,
+        tile_c: impl Into<[i32; N]>,
+        tile: T,
+    ) -> &mut Self
+    where
+        L: HasLayer<T, I>,
+    {
+        self.commands.insert_tile(tile_c, tile);
+        self
+    }
+
+    /// Removes a tile from a declared layer.
+    pub fn remove_tile<T: TileComponent, I>(&mut self, tile_c: impl Into<[i32; N]>) -> &mut Self
+    where
+        L: HasLayer<T, I>,
+    {
+        self.commands.remove_tile::<T>(tile_c);
+        self
+    }
+
+    /// Replaces all the data of a chunk for a declared layer, see [`TileMapCommands::set_chunk_data`].
+    pub fn set_chunk_data<T: TileComponent, I>(
+        &mut self,
+        chunk_c: impl Into<[i32; N]>,
+        tiles: Vec<Option<T>>,
+    ) -> &mut Self
+    where
+        L: HasLayer<T, I>,
+    {
+        self.commands.set_chunk_data(chunk_c, tiles);
+        self
+    }
+
+    /// Spawns a chunk with empty storage for every declared layer.
+    pub fn spawn_chunk(&mut self, chunk_c: impl Into<[i32; N]>) -> &mut Self {
+        self.commands.spawn_chunk(chunk_c);
+        self
+    }
+
+    /// Recursively despawn a chunk and all it's tiles.
+    pub fn despawn_chunk(&mut self, chunk_c: impl Into<[i32; N]>) -> &mut Self {
+        self.commands.despawn_chunk(chunk_c);
+        self
+    }
+
+    /// Get the untyped commands for the map.
+    pub fn untyped(&mut self) -> &mut TileMapCommands<'a, N> {
+        &mut self.commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{system::SystemState, world::World};
+
+    use crate::{chunks::ChunkData, maps::TileMap, testing};
+
+    use super::*;
+
+    #[test]
+    fn handle_reads_and_writes_declared_layers() {
+        let mut world = World::new();
+        let handle = testing::apply(&mut world, |commands| {
+            let handle = TileCommandExt::<2>::spawn_map_with_layers::<(u8, bool)>(commands, 4);
+            handle
+                .commands(commands)
+                .unwrap()
+                .insert_tile([1, 1], 3u8)
+                .insert_tile([2, 1], true)
+                .remove_tile::<bool, _>([2, 1]);
+            handle
+        });
+
+        let chunk_id = world
+            .get::<TileMap<2>>(handle.id())
+            .unwrap()
+            .get_from_tile([0, 0])
+            .unwrap();
+        assert!(world.get::<ChunkData<bool>>(chunk_id).is_some());
+
+        let mut state = SystemState::<TileMapQuery<&u8>>::new(&mut world);
+        let heights = state.get(&world);
+        assert_eq!(handle.get_map(&heights).unwrap().get_at([1, 1]), Some(&3));
+    }
+}
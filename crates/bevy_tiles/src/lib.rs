@@ -22,31 +22,102 @@
 
 use bevy::app::Plugin;
 
+/// Provides tile based audio occlusion helpers.
+pub mod acoustics;
+/// Provides parallel chunk processing with reads padded by neighboring chunks.
+pub mod apron;
+/// Provides helpers for carving paths (rivers, roads) into tile layers.
+pub mod carve;
 /// Provides chunk level utilities.
 pub mod chunks;
+/// Provides copying regions of maps and pasting them elsewhere.
+pub mod clipboard;
 /// Provides commands for interacting with tilemaps.
 pub mod commands;
+/// Provides coordinate conversion between maps with different tile sizes.
+pub mod convert;
 /// Provides helper functions for interacting with coordiantes.
 pub mod coords;
+/// Provides layers kept up to date from the tiles of other layers.
+pub mod derived;
+/// Provides diffs between maps for merging concurrent edits.
+pub mod diff;
+/// Provides distance transforms over tile layers.
+pub mod distance;
+/// Provides smoothing and erosion filters for numeric tile layers.
+pub mod filters;
+/// Provides pipelines of stages for generating chunks.
+pub mod generation;
+/// Provides reverse indexes from tile values to coordinates.
+pub mod index;
+/// Provides an egui inspector for maps and tile data.
+#[cfg(feature = "inspector")]
+pub mod inspector;
+/// Provides detection of enclosed interiors and hiding the roofs above them.
+pub mod interiors;
+/// Provides connected region labeling for tile layers.
+pub mod labels;
+/// Provides typed layer sets for maps.
+pub mod layers;
+/// Provides lua scripting access to tiles.
+#[cfg(feature = "lua")]
+pub mod lua;
 /// Provides map level utilities.
 pub mod maps;
+/// Provides merging of maps into each other.
+pub mod merge;
+/// Provides deterministic noise for procedural generation.
+pub mod noise;
+/// Provides observer triggers fired as tiles and chunks are inserted and removed.
+pub mod observers;
+/// Provides tile orientation data for directional tiles.
+pub mod orientation;
+/// Provides a floating origin for very large worlds.
+pub mod origin;
+/// Provides post-processing for paths along the grid.
+pub mod paths;
+/// Provides raycasting into tile maps for picking tiles.
+pub mod picking;
 /// Provides traits for accessing tile data.
 pub mod queries;
+/// Provides a scheduler that spreads expensive chunk recomputation across frames.
+pub mod recompute;
+/// Provides updates scheduled for single tiles at future ticks.
+pub mod scheduler;
+/// Provides a small text command interpreter for editing tiles.
+pub mod script;
+/// Provides stacks of 2d maps used as floors.
+pub mod stack;
+/// Provides steering vectors for continuous movers from tile fields.
+pub mod steering;
+/// Provides bitset tag layers with fast boolean operations.
+pub mod tags;
+/// Provides territory ownership layers with border tracking.
+pub mod territory;
+/// Provides random ticks that pick tiles of a layer to update each frame.
+pub mod ticks;
 /// Provides tile level utilities.
 pub mod tiles;
+/// Provides tile edits that can be rolled back as a whole.
+pub mod transaction;
+
+/// Provides setup shared by the unit tests of this crate and crates built on it.
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 /// Helper aliases for working with 2d grids
 pub mod tiles_2d {
     use bevy::ecs::system::Commands;
 
     /// 2d [crate::tiles::TileMapQuery] alias.
-    pub type TileMapQuery<'w, 's, Q> = crate::tiles::TileMapQuery<'w, 's, Q, 2>;
+    pub type TileMapQuery<'w, 's, Q, MF = ()> = crate::tiles::TileMapQuery<'w, 's, Q, MF, 2>;
 
     /// 2d [crate::chunks::ChunkCoord] alias.
     pub type ChunkCoord = crate::chunks::ChunkCoord<2>;
 
     /// 2d [crate::chunks::ChunkMapQuery] alias.
-    pub type ChunkMapQuery<'w, 's, Q, F = ()> = crate::chunks::ChunkMapQuery<'w, 's, Q, F, 2>;
+    pub type ChunkMapQuery<'w, 's, Q, F = (), MF = (), M = ()> =
+        crate::chunks::ChunkMapQuery<'w, 's, Q, F, MF, M, 2>;
 
     /// 2d [crate::commands::TileMapCommands] alias.
     pub type TileMapCommands<'a, const N: usize> = crate::commands::TileMapCommands<'a, 2>;
@@ -68,13 +139,14 @@ pub mod tiles_3d {
     use bevy::ecs::system::Commands;
 
     /// 3d [crate::tiles::TileMapQuery] alias.
-    pub type TileMapQuery<'w, 's, Q> = crate::tiles::TileMapQuery<'w, 's, Q, 3>;
+    pub type TileMapQuery<'w, 's, Q, MF = ()> = crate::tiles::TileMapQuery<'w, 's, Q, MF, 3>;
 
     /// 3d [crate::chunks::ChunkCoord] alias.
     pub type ChunkCoord = crate::chunks::ChunkCoord<3>;
 
     /// 3d [crate::chunks::ChunkMapQuery] alias.
-    pub type ChunkMapQuery<'w, 's, Q, F = ()> = crate::chunks::ChunkMapQuery<'w, 's, Q, F, 3>;
+    pub type ChunkMapQuery<'w, 's, Q, F = (), MF = (), M = ()> =
+        crate::chunks::ChunkMapQuery<'w, 's, Q, F, MF, M, 3>;
 
     /// 3d [crate::commands::TileMapCommands] alias.
     pub type TileMapCommands<'a, const N: usize> = crate::commands::TileMapCommands<'a, 3>;
@@ -91,6 +163,12 @@ pub mod tiles_3d {
     pub type TileSpacing = crate::maps::TileSpacing<3>;
 }
 
+/// Items used by code generated by the derive macros.
+#[doc(hidden)]
+pub mod __private {
+    pub use bevy::ecs::world::EntityWorldMut;
+}
+
 /// Adds Tiles dependencies to the App.
 pub struct TilesPlugin;
 
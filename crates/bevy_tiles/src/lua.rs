@@ -0,0 +1,190 @@
+use std::cell::RefCell;
+
+use bevy::ecs::world::World;
+use mlua::{Lua, Table};
+
+use crate::{
+    coords::CoordIterator,
+    maps::TileMap,
+    script::{find_map, script_layer},
+};
+
+/// Runs a lua script that can read and write the tiles of maps with `N` dimensions.
+/// # Note
+/// Layers are accessed by the names registered in [`crate::script::TileScriptLayers`], and maps by [`bevy::prelude::Name`]
+/// or entity id, the same as [`crate::script::run_tile_script`]. Coordinates are tables of `N` numbers, and values are
+/// passed as strings (numbers are converted automatically).
+///
+/// The script gets a global `tiles` table with:
+/// * `tiles.get(map, coord, layer)` returns the value of a tile, or `nil`.
+/// * `tiles.set(map, coord, layer, value)` sets a tile.
+/// * `tiles.fill(map, corner_1, corner_2, layer, value)` sets every tile between two corners (inclusive).
+/// * `tiles.clear(map, coord, layer)` removes a tile.
+/// * `tiles.query(map, corner_1, corner_2, layer)` returns a list of `{ coord, value }` for every tile between two corners.
+/// * `tiles.chunks(map)` returns a list of the coordinates of every chunk in the map.
+///   Chunks are spawned by setting tiles in them.
+pub fn run_lua_tile_script<const N: usize>(world: &mut World, source: &str) -> mlua::Result<()> {
+    let lua = Lua::new();
+    let world = RefCell::new(world);
+    let world = &world;
+
+    lua.scope(|scope| {
+        let tiles = lua.create_table()?;
+
+        tiles.set(
+            "get",
+            scope.create_function(|_, (map, tile_c, layer): (String, Vec<i32>, String)| {
+                let mut world = world.borrow_mut();
+                let map_id = find_map::<N>(&mut world, &map).map_err(mlua::Error::external)?;
+                let layer = script_layer::<N>(&world, &layer).map_err(mlua::Error::external)?;
+                Ok((layer.get)(&world, map_id, coord::<N>(tile_c)?))
+            })?,
+        )?;
+
+        tiles.set(
+            "set",
+            scope.create_function(
+                |_, (map, tile_c, layer, value): (String, Vec<i32>, String, String)| {
+                    let mut world = world.borrow_mut();
+                    let map_id = find_map::<N>(&mut world, &map).map_err(mlua::Error::external)?;
+                    let layer = script_layer::<N>(&world, &layer).map_err(mlua::Error::external)?;
+                    (layer.set)(&mut world, map_id, coord::<N>(tile_c)?, None, &value)
+                        .map_err(mlua::Error::external)
+                },
+            )?,
+        )?;
+
+        tiles.set(
+            "fill",
+            scope.create_function(
+                |_,
+                 (map, corner_1, corner_2, layer, value): (
+                    String,
+                    Vec<i32>,
+                    Vec<i32>,
+                    String,
+                    String,
+                )| {
+                    let mut world = world.borrow_mut();
+                    let map_id = find_map::<N>(&mut world, &map).map_err(mlua::Error::external)?;
+                    let layer = script_layer::<N>(&world, &layer).map_err(mlua::Error::external)?;
+                    (layer.set)(
+                        &mut world,
+                        map_id,
+                        coord::<N>(corner_1)?,
+                        Some(coord::<N>(corner_2)?),
+                        &value,
+                    )
+                    .map_err(mlua::Error::external)
+                },
+            )?,
+        )?;
+
+        tiles.set(
+            "clear",
+            scope.create_function(|_, (map, tile_c, layer): (String, Vec<i32>, String)| {
+                let mut world = world.borrow_mut();
+                let map_id = find_map::<N>(&mut world, &map).map_err(mlua::Error::external)?;
+                let layer = script_layer::<N>(&world, &layer).map_err(mlua::Error::external)?;
+                (layer.clear)(&mut world, map_id, coord::<N>(tile_c)?);
+                Ok(())
+            })?,
+        )?;
+
+        tiles.set(
+            "query",
+            scope.create_function(
+                |lua, (map, corner_1, corner_2, layer): (String, Vec<i32>, Vec<i32>, String)| {
+                    let mut world = world.borrow_mut();
+                    let map_id = find_map::<N>(&mut world, &map).map_err(mlua::Error::external)?;
+                    let layer = script_layer::<N>(&world, &layer).map_err(mlua::Error::external)?;
+                    let found = lua.create_table()?;
+                    for tile_c in CoordIterator::new(coord::<N>(corner_1)?, coord::<N>(corner_2)?) {
+                        if let Some(value) = (layer.get)(&world, map_id, tile_c) {
+                            let entry = lua.create_table()?;
+                            entry.set("coord", tile_c.to_vec())?;
+                            entry.set("value", value)?;
+                            found.push(entry)?;
+                        }
+                    }
+                    Ok(found)
+                },
+            )?,
+        )?;
+
+        tiles.set(
+            "chunks",
+            scope.create_function(|lua, map: String| {
+                let mut world = world.borrow_mut();
+                let map_id = find_map::<N>(&mut world, &map).map_err(mlua::Error::external)?;
+                let chunks: Table = lua.create_table()?;
+                if let Some(map) = world.get::<TileMap<N>>(map_id) {
+                    for chunk_c in map.get_chunks().keys() {
+                        chunks.push(chunk_c.to_vec())?;
+                    }
+                }
+                Ok(chunks)
+            })?,
+        )?;
+
+        lua.globals().set("tiles", tiles)?;
+        lua.load(source).exec()
+    })
+}
+
+#[inline]
+fn coord<const N: usize>(tile_c: Vec<i32>) -> mlua::Result<[i32; N]> {
+    let len = tile_c.len();
+    tile_c.try_into().map_err(|_| {
+        mlua::Error::RuntimeError(format!(
+            "expected a coordinate with {N} values, found {len}"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Name;
+
+    use crate::{script::TileScriptLayers, testing};
+
+    use super::*;
+
+    #[test]
+    fn lua_edits_tiles() {
+        let mut world = World::new();
+        let mut layers = TileScriptLayers::<2>::default();
+        layers.register::<u8>("height");
+        world.insert_resource(layers);
+        testing::spawn_map(&mut world, 4, |map| {
+            map.insert(Name::new("overworld"));
+        });
+
+        run_lua_tile_script::<2>(
+            &mut world,
+            r#"
+            tiles.fill("overworld", {0, 0}, {2, 2}, "height", 1)
+            for i = 0, 2 do
+                tiles.set("overworld", {i, i}, "height", i * 2)
+            end
+            tiles.clear("overworld", {2, 0}, "height")
+            assert(tiles.get("overworld", {1, 1}, "height") == "2")
+            assert(tiles.get("overworld", {2, 0}, "height") == nil)
+            assert(#tiles.query("overworld", {0, 0}, {2, 2}, "height") == 8)
+            assert(#tiles.chunks("overworld") == 1)
+            "#,
+        )
+        .unwrap();
+
+        assert!(run_lua_tile_script::<2>(
+            &mut world,
+            r#"tiles.set("overworld", {0}, "height", 1)"#
+        )
+        .is_err());
+        assert!(run_lua_tile_script::<2>(
+            &mut world,
+            r#"tiles.set("overworld", {0, 0}, "water", 1)"#
+        )
+        .is_err());
+    }
+}
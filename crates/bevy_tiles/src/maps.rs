@@ -1,10 +1,23 @@
+use std::{any::TypeId, collections::BTreeMap};
+
 use bevy::{
-    ecs::{component::Component, entity::Entity},
+    ecs::{
+        bundle::Bundle,
+        component::Component,
+        entity::Entity,
+        system::{Commands, Query},
+        world::EntityWorldMut,
+    },
     prelude::{Deref, DerefMut},
-    utils::HashMap,
+    utils::{HashMap, HashSet},
 };
 
-use crate::{chunks::ChunkCoord, coords::calculate_chunk_coordinate};
+use crate::{
+    chunks::{ChunkCoord, ChunkData, ChunkTypes, FixedLayers},
+    commands::{TileCommandExt, TileMapCommands},
+    coords::{calculate_chunk_coordinate, in_tile_bounds, tile_bounds},
+    noise::{splitmix, TileRng},
+};
 
 /// Holds handles to all the chunks in a map.
 /// # Note
@@ -15,13 +28,25 @@ pub struct TileMap<const N: usize = 2> {
     chunks: HashMap<ChunkCoord<N>, Entity>,
     /// The size of a chunk in one direction.
     chunk_size: usize,
+    occupied: HashMap<TypeId, OccupiedChunks<N>>,
 }
 
+/// The most tiles a single chunk can hold, larger chunk sizes are rejected when spawning a map.
+pub const MAX_CHUNK_TILES: usize = 1 << 24;
+
 impl<const N: usize> TileMap<N> {
     pub(crate) fn with_chunk_size(chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "Chunk size must be greater than 0.");
+        assert!(
+            chunk_size
+                .checked_pow(N as u32)
+                .is_some_and(|tiles| tiles <= MAX_CHUNK_TILES),
+            "Chunk size {chunk_size} is too large, chunks can hold at most {MAX_CHUNK_TILES} tiles."
+        );
         Self {
             chunks: Default::default(),
             chunk_size,
+            occupied: Default::default(),
         }
     }
 
@@ -52,6 +77,174 @@ impl<const N: usize> TileMap<N> {
     pub fn get_chunk_size(&self) -> usize {
         self.chunk_size
     }
+
+    /// Get the lowest and highest tile coordinate (on every axis) this map can hold, see [`tile_bounds`].
+    #[inline]
+    pub fn tile_bounds(&self) -> (i32, i32) {
+        tile_bounds(self.chunk_size)
+    }
+
+    /// Check if a tile coordinate is within the bounds of this map.
+    #[inline]
+    pub fn in_bounds(&self, tile_c: impl Into<[i32; N]>) -> bool {
+        in_tile_bounds(tile_c, self.chunk_size)
+    }
+
+    /// Get a snapshot of the size of this map.
+    /// # Note
+    /// This only walks the chunk table, so it's cheap enough to call every frame.
+    pub fn stats(&self) -> MapStats<N> {
+        let mut bounds: Option<([i32; N], [i32; N])> = None;
+        for chunk_c in self.chunks.keys() {
+            let (min, max) = bounds.get_or_insert((chunk_c.0, chunk_c.0));
+            for i in 0..N {
+                min[i] = min[i].min(chunk_c[i]);
+                max[i] = max[i].max(chunk_c[i]);
+            }
+        }
+        MapStats {
+            chunk_count: self.chunks.len(),
+            tile_capacity: self.chunks.len() * self.chunk_size.pow(N as u32),
+            chunk_bounds: bounds,
+        }
+    }
+
+    /// Get a snapshot of how much of this map a layer of tile data is using.
+    /// # Note
+    /// This walks every chunk of the map, but not the tiles in them.
+    pub fn layer_stats<T: Send + Sync + 'static>(
+        &self,
+        layer: &Query<&ChunkData<T>>,
+    ) -> LayerStats {
+        let mut stats = LayerStats::default();
+        for chunk in self.chunks.values().filter_map(|id| layer.get(*id).ok()) {
+            stats.chunk_count += 1;
+            stats.occupied += chunk.get_count();
+            stats.bytes += chunk.memory_estimate();
+        }
+        stats
+    }
+
+    /// Get the lowest and highest tile coordinates of the chunks with `T` data, if there are any.
+    /// # Note
+    /// This is kept up to date as tiles are inserted and removed, so it's cheap enough to call every frame.
+    /// The bounds cover whole chunks, so they can be up to a chunk larger than the occupied tiles on each side.
+    pub fn occupied_bounds<T: Send + Sync + 'static>(&self) -> Option<([i32; N], [i32; N])> {
+        let occupied = self.occupied.get(&TypeId::of::<T>())?;
+        let chunk_size = self.chunk_size as i32;
+        let mut min = [0; N];
+        let mut max = [0; N];
+        for (i, axis) in occupied.axes.iter().enumerate() {
+            min[i] = axis.first_key_value()?.0 * chunk_size;
+            max[i] = (axis.last_key_value()?.0 + 1) * chunk_size - 1;
+        }
+        Some((min, max))
+    }
+
+    /// Record whether a chunk has `T` data, for [`TileMap::occupied_bounds`].
+    pub(crate) fn set_occupied<T: Send + Sync + 'static>(
+        &mut self,
+        chunk_c: [i32; N],
+        occupied: bool,
+    ) {
+        let layer = TypeId::of::<T>();
+        if occupied {
+            self.occupied.entry(layer).or_default().insert(chunk_c);
+        } else if let Some(chunks) = self.occupied.get_mut(&layer) {
+            chunks.remove(chunk_c);
+            if chunks.chunks.is_empty() {
+                self.occupied.remove(&layer);
+            }
+        }
+    }
+
+    /// Swap which layers two chunks are recorded as having data for.
+    pub(crate) fn swap_occupied(&mut self, chunk_c_0: [i32; N], chunk_c_1: [i32; N]) {
+        for chunks in self.occupied.values_mut() {
+            match (
+                chunks.chunks.contains(&chunk_c_0),
+                chunks.chunks.contains(&chunk_c_1),
+            ) {
+                (true, false) => {
+                    chunks.remove(chunk_c_0);
+                    chunks.insert(chunk_c_1);
+                }
+                (false, true) => {
+                    chunks.remove(chunk_c_1);
+                    chunks.insert(chunk_c_0);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Record that a chunk no longer has data for any layer.
+    pub(crate) fn clear_occupied(&mut self, chunk_c: [i32; N]) {
+        for chunks in self.occupied.values_mut() {
+            chunks.remove(chunk_c);
+        }
+        self.occupied.retain(|_, chunks| !chunks.chunks.is_empty());
+    }
+}
+
+/// The chunks with data for a layer, and how many of them are in each slice along every axis.
+struct OccupiedChunks<const N: usize> {
+    chunks: HashSet<[i32; N]>,
+    axes: [BTreeMap<i32, usize>; N],
+}
+
+impl<const N: usize> Default for OccupiedChunks<N> {
+    fn default() -> Self {
+        Self {
+            chunks: Default::default(),
+            axes: std::array::from_fn(|_| BTreeMap::new()),
+        }
+    }
+}
+
+impl<const N: usize> OccupiedChunks<N> {
+    fn insert(&mut self, chunk_c: [i32; N]) {
+        if self.chunks.insert(chunk_c) {
+            for (axis, c) in self.axes.iter_mut().zip(chunk_c) {
+                *axis.entry(c).or_default() += 1;
+            }
+        }
+    }
+
+    fn remove(&mut self, chunk_c: [i32; N]) {
+        if self.chunks.remove(&chunk_c) {
+            for (axis, c) in self.axes.iter_mut().zip(chunk_c) {
+                if let Some(count) = axis.get_mut(&c) {
+                    *count -= 1;
+                    if *count == 0 {
+                        axis.remove(&c);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Size information about a map, see [`TileMap::stats`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MapStats<const N: usize> {
+    /// Number of chunks in the map.
+    pub chunk_count: usize,
+    /// Number of tiles the existing chunks can hold.
+    pub tile_capacity: usize,
+    /// The lowest and highest chunk coordinates in the map, if there are any chunks.
+    pub chunk_bounds: Option<([i32; N], [i32; N])>,
+}
+
+/// Size information about a layer of tile data, see [`TileMap::layer_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LayerStats {
+    /// Number of chunks with data for this layer.
+    pub chunk_count: usize,
+    /// Number of tiles with data for this layer.
+    pub occupied: usize,
+    /// Estimated heap and component memory used by this layer.
+    pub bytes: usize,
 }
 
 /// Marker component for whether or not this map should use transforms.
@@ -60,6 +253,14 @@ impl<const N: usize> TileMap<N> {
 #[derive(Component, Copy, Clone, Debug)]
 pub struct UseTransforms;
 
+/// Marker component for maps whose chunks are despawned once all their tile data is removed.
+/// # Note:
+/// Only chunks emptied by removing tiles are despawned, chunks spawned without any tiles (ex: with
+/// [`TileMapCommands::spawn_chunk`]) or with [`crate::chunks::FixedLayers`] are kept.
+/// Components added to a chunk are lost when it's despawned.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct AutoDespawnEmptyChunks;
+
 /// The size of a tile along each axis.  Add this to a [`TileMap`] for child chunks
 /// and tiles to have proper spacing based on tile size.
 #[derive(Component, Copy, Clone, Debug, Deref, DerefMut)]
@@ -69,3 +270,261 @@ pub struct TileDims<const N: usize>(pub [f32; N]);
 /// and tiles to have proper spacing based on tile spacing.
 #[derive(Component, Copy, Clone, Debug, Deref, DerefMut)]
 pub struct TileSpacing<const N: usize>(pub [f32; N]);
+
+/// The tile coordinate the transforms of a map's chunks are placed relative to, instead of the map's origin.
+/// Add this to a map using [`UseTransforms`] to let [`crate::origin::FloatingOriginPlugin`] rebase chunk
+/// transforms near the camera, keeping them small in very large worlds.
+/// # Note
+/// Tile and chunk coordinates are unaffected, but positions in the map's local space are relative to this tile,
+/// so add it to tile coordinates calculated from them (ex: with [`crate::coords::world_to_tile`]).
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Deref)]
+pub struct MapOrigin<const N: usize>(pub(crate) [i32; N]);
+
+impl<const N: usize> Default for MapOrigin<N> {
+    fn default() -> Self {
+        Self([0; N])
+    }
+}
+
+impl<const N: usize> MapOrigin<N> {
+    /// Convert a tile coordinate calculated from a map local position to an absolute tile coordinate.
+    #[inline]
+    pub fn to_absolute(&self, local_c: impl Into<[i32; N]>) -> [i32; N] {
+        let mut tile_c = local_c.into();
+        for (c, o) in tile_c.iter_mut().zip(self.0) {
+            *c += o;
+        }
+        tile_c
+    }
+}
+
+/// The layers every chunk of a map is spawned with.  Add this to a [`TileMap`] before spawning chunks
+/// to keep chunks in the same archetype as tiles are edited.
+/// # Note
+/// Chunks of a map with declared layers spawn with empty [`ChunkData`] for each layer, and never remove
+/// [`ChunkData`] when the last tile of a layer is removed, so edits don't move big chunks between archetypes.
+/// Layers that weren't declared still work, they're just added to chunks on first use.
+#[derive(Component, Clone, Default)]
+pub struct MapLayers {
+    layers: Vec<(TypeId, fn(&mut EntityWorldMut<'_>, usize))>,
+}
+
+impl MapLayers {
+    /// Create an empty set of layers.
+    pub fn new() -> Self {
+        Self { layers: Vec::new() }
+    }
+
+    /// Declare a layer of `T` data.
+    pub fn with<T: Send + Sync + 'static>(mut self) -> Self {
+        if !self.contains::<T>() {
+            self.layers.push((TypeId::of::<T>(), |chunk, tiles| {
+                chunk.insert(ChunkData::<T>::new(tiles));
+            }));
+        }
+        self
+    }
+
+    /// Check if a layer of `T` data is declared.
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.layers
+            .iter()
+            .any(|(layer, _)| *layer == TypeId::of::<T>())
+    }
+
+    /// Iterate over the types of the declared layers.
+    pub fn layers(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.layers.iter().map(|(layer, _)| *layer)
+    }
+
+    /// Add empty storage for every declared layer to a newly spawned chunk holding `tiles` tiles.
+    pub(crate) fn insert_into(&self, chunk: &mut EntityWorldMut<'_>, tiles: usize) {
+        for (_, insert) in &self.layers {
+            insert(chunk, tiles);
+        }
+        chunk.insert(FixedLayers);
+        if let Some(mut types) = chunk.get_mut::<ChunkTypes>() {
+            types.0.extend(self.layers());
+        }
+    }
+}
+
+/// The seed procedural helpers use for a map.  Add this to a [`TileMap`] so regenerating
+/// the same chunk always yields the same content.
+/// # Note
+/// When present, seeds passed to generator commands (ex: [`crate::noise::NoiseConfig::seed`]) are mixed with this seed.
+#[derive(Component, Copy, Clone, Debug, Default, PartialEq, Eq, Deref, DerefMut)]
+pub struct MapSeed(pub u64);
+
+impl MapSeed {
+    /// Combine another seed with the map seed.
+    #[inline]
+    pub fn mix(&self, seed: u64) -> u64 {
+        splitmix(self.0 ^ splitmix(seed))
+    }
+
+    /// Get a random number generator for a chunk of this map.
+    pub fn chunk_rng<const N: usize>(&self, chunk_c: impl Into<[i32; N]>) -> TileRng {
+        TileRng::for_chunk(self.0, chunk_c)
+    }
+
+    /// Get a random number generator for a tile of this map.
+    pub fn tile_rng<const N: usize>(&self, tile_c: impl Into<[i32; N]>) -> TileRng {
+        TileRng::for_tile(self.0, tile_c)
+    }
+}
+
+/// Configures a map before spawning it (ex: `MapBuilder::new(16).with_dims([16.0, 16.0]).spawn(&mut commands)`),
+/// so the map is set up before any other command sees it.
+/// # Note
+/// Every setting adds to the bundle the map is spawned with, so each one can only be given once.
+#[derive(Clone)]
+pub struct MapBuilder<const N: usize = 2, B: Bundle = ()> {
+    chunk_size: usize,
+    bundle: B,
+}
+
+impl<const N: usize> MapBuilder<N> {
+    /// Start configuring a map with the given chunk size.
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size,
+            bundle: (),
+        }
+    }
+}
+
+impl<const N: usize, B: Bundle> MapBuilder<N, B> {
+    /// Give chunks and tiles transforms, see [`UseTransforms`].
+    pub fn use_transforms(self) -> MapBuilder<N, (B, UseTransforms)> {
+        self.with(UseTransforms)
+    }
+
+    /// Set the size of the map's tiles, see [`TileDims`].
+    pub fn with_dims(self, dims: impl Into<[f32; N]>) -> MapBuilder<N, (B, TileDims<N>)> {
+        self.with(TileDims(dims.into()))
+    }
+
+    /// Set the space between the map's tiles, see [`TileSpacing`].
+    pub fn with_spacing(self, spacing: impl Into<[f32; N]>) -> MapBuilder<N, (B, TileSpacing<N>)> {
+        self.with(TileSpacing(spacing.into()))
+    }
+
+    /// Set the seed of the map, see [`MapSeed`].
+    pub fn with_seed(self, seed: u64) -> MapBuilder<N, (B, MapSeed)> {
+        self.with(MapSeed(seed))
+    }
+
+    /// Declare the layers of the map, see [`MapLayers`].
+    pub fn with_layers(self, layers: MapLayers) -> MapBuilder<N, (B, MapLayers)> {
+        self.with(layers)
+    }
+
+    fn with<C: Bundle>(self, component: C) -> MapBuilder<N, (B, C)> {
+        MapBuilder {
+            chunk_size: self.chunk_size,
+            bundle: (self.bundle, component),
+        }
+    }
+
+    /// Spawn the map.
+    /// # Panics
+    /// If the chunk size is 0, or a chunk would hold more than [`MAX_CHUNK_TILES`] tiles.
+    pub fn spawn<'a>(self, commands: &'a mut Commands) -> TileMapCommands<'a, N> {
+        TileCommandExt::<N>::spawn_map_with(commands, self.chunk_size, self.bundle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{system::SystemState, world::World};
+
+    use crate::testing;
+
+    use super::*;
+
+    #[test]
+    fn map_and_layer_stats() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            map.insert_tile([0, 0], 1u8);
+            map.insert_tile([1, 0], 1u8);
+            map.insert_tile([-5, 9], 2u16);
+        });
+
+        let map = world.get::<TileMap<2>>(map_id).unwrap();
+        let stats = map.stats();
+        assert_eq!(stats.chunk_count, 2);
+        assert_eq!(stats.tile_capacity, 32);
+        assert_eq!(stats.chunk_bounds, Some(([-2, 0], [0, 2])));
+
+        let mut state = SystemState::<Query<&ChunkData<u8>>>::new(&mut world);
+        let layer = state.get(&world);
+        let map = world.get::<TileMap<2>>(map_id).unwrap();
+        let stats = map.layer_stats(&layer);
+        assert_eq!(stats.chunk_count, 1);
+        assert_eq!(stats.occupied, 2);
+        assert!(stats.bytes >= 16 * size_of::<Option<u8>>());
+    }
+
+    #[test]
+    fn occupied_bounds_follow_edits() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            map.insert_tile([0, 0], 1u8);
+            map.insert_tile([9, -1], 1u8);
+            map.insert_tile([-5, 9], 2u16);
+        });
+
+        let map = world.get::<TileMap<2>>(map_id).unwrap();
+        assert_eq!(map.occupied_bounds::<u8>(), Some(([0, -4], [11, 3])));
+        assert_eq!(map.occupied_bounds::<u16>(), Some(([-8, 8], [-5, 11])));
+        assert_eq!(map.occupied_bounds::<bool>(), None);
+
+        testing::apply_map(&mut world, map_id, |map| {
+            map.remove_tile::<u8>([9, -1]);
+            map.despawn_chunk([-2, 2]);
+        });
+        let map = world.get::<TileMap<2>>(map_id).unwrap();
+        assert_eq!(map.occupied_bounds::<u8>(), Some(([0, 0], [3, 3])));
+        assert_eq!(map.occupied_bounds::<u16>(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Chunk size must be greater than 0.")]
+    fn zero_chunk_size() {
+        TileMap::<2>::with_chunk_size(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "is too large")]
+    fn huge_chunk_size() {
+        TileMap::<3>::with_chunk_size(1 << 10);
+    }
+
+    #[test]
+    fn declared_layers_keep_archetype() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            map.insert(MapLayers::new().with::<u8>().with::<bool>());
+            map.insert_tile([0, 0], 1u8);
+        });
+
+        let chunk_id = world
+            .get::<TileMap<2>>(map_id)
+            .unwrap()
+            .get_from_tile([0, 0])
+            .unwrap();
+        let archetype = world.entity(chunk_id).archetype().id();
+        assert!(world.get::<ChunkData<bool>>(chunk_id).is_some());
+
+        testing::apply_map(&mut world, map_id, |map| {
+            map.insert_tile([1, 0], true);
+            map.remove_tile::<u8>([0, 0]);
+        });
+        assert_eq!(world.entity(chunk_id).archetype().id(), archetype);
+        assert_eq!(world.get::<ChunkData<u8>>(chunk_id).unwrap().get_count(), 0);
+        let map = world.get::<TileMap<2>>(map_id).unwrap();
+        assert_eq!(map.occupied_bounds::<u8>(), None);
+    }
+}
@@ -0,0 +1,332 @@
+use std::fmt;
+
+use bevy::{
+    app::App,
+    ecs::{entity::Entity, system::Resource, world::World},
+    prelude::DespawnRecursiveExt,
+};
+
+use crate::{
+    commands::{insert_tile_batch, take_tile_batch, TempRemove},
+    coords::{calculate_tile_index, CoordIterator},
+    maps::TileMap,
+    queries::TileComponent,
+};
+
+/// Which tile is kept when both maps have a tile at the same coordinate while merging.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the tile already in the destination map.
+    KeepDestination,
+    /// Replace the destination tile with the tile from the source map.
+    #[default]
+    KeepSource,
+}
+
+/// Why [`merge_maps`] didn't merge two maps, the source map is left untouched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeError {
+    /// The entity doesn't exist, or isn't a map with the merged dimensions.
+    NotAMap(Entity),
+    /// The source and destination are the same map.
+    SameMap,
+    /// No layers were registered with [`MergeLayers`], so merging would only despawn the source map.
+    NoMergeLayers,
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::NotAMap(map_id) => write!(f, "entity {map_id} isn't a map"),
+            MergeError::SameMap => write!(f, "can't merge a map into itself"),
+            MergeError::NoMergeLayers => write!(f, "no merge layers are registered"),
+        }
+    }
+}
+
+/// Merges one layer of a source map into a destination map, see [`merge_maps`].
+pub type MergeFn<const N: usize> = fn(&mut World, Entity, Entity, [i32; N], MergePolicy);
+
+/// The layers [`merge_maps`] moves between maps with `N` dimensions.
+#[derive(Resource)]
+pub struct MergeLayers<const N: usize> {
+    layers: Vec<MergeFn<N>>,
+}
+
+impl<const N: usize> Default for MergeLayers<N> {
+    fn default() -> Self {
+        Self { layers: Vec::new() }
+    }
+}
+
+impl<const N: usize> MergeLayers<N> {
+    /// Move tiles of type `T` when merging, tiles that lose a conflict are dropped.
+    pub fn register<T: TileComponent>(&mut self) -> &mut Self {
+        self.register_with(merge_plain_layer::<T, N>)
+    }
+
+    /// Merge a layer with a custom function (ex: one that despawns entities that lose a conflict).
+    pub fn register_with(&mut self, merge: MergeFn<N>) -> &mut Self {
+        self.layers.push(merge);
+        self
+    }
+}
+
+fn merge_plain_layer<T: TileComponent, const N: usize>(
+    world: &mut World,
+    dst_id: Entity,
+    src_id: Entity,
+    offset: [i32; N],
+    policy: MergePolicy,
+) {
+    merge_layer::<T, N>(world, dst_id, src_id, offset, policy);
+}
+
+/// Moves every `T` tile from the source map into the destination map, shifted by `offset`.
+/// Returns the tiles that lost a conflict, from either map.
+/// # Note
+/// The maps don't need to have the same chunk size.  Tiles are taken out of the source map like any other removal,
+/// so its indexes and observers see them go.
+pub fn merge_layer<T: TileComponent, const N: usize>(
+    world: &mut World,
+    dst_id: Entity,
+    src_id: Entity,
+    offset: [i32; N],
+    policy: MergePolicy,
+) -> Vec<T> {
+    if dst_id == src_id {
+        return Vec::new();
+    }
+    let Some(mut src) = world.temp_remove::<TileMap<N>>(src_id) else {
+        return Vec::new();
+    };
+    let chunk_size = src.get_chunk_size() as i32;
+    let tile_cs: Vec<[i32; N]> = src
+        .get_chunks()
+        .keys()
+        .flat_map(|chunk_c| {
+            let min = chunk_c.map(|c| c * chunk_size);
+            CoordIterator::new(min, min.map(|c| c + chunk_size - 1))
+        })
+        .collect();
+    let tiles: Vec<([i32; N], T)> = take_tile_batch::<T, N>(&mut src, tile_cs)
+        .map(|(mut tile_c, value)| {
+            for (c, o) in tile_c.iter_mut().zip(offset) {
+                *c += o;
+            }
+            (tile_c, value)
+        })
+        .collect();
+    drop(src);
+
+    let Some(mut dst) = world.temp_remove::<TileMap<N>>(dst_id) else {
+        return tiles.into_iter().map(|(_, value)| value).collect();
+    };
+    let dst_chunk_size = dst.get_chunk_size();
+    let mut dropped = Vec::new();
+    let mut tile_cs = Vec::with_capacity(tiles.len());
+    let mut values = Vec::with_capacity(tiles.len());
+    for (tile_c, value) in tiles {
+        let occupied = policy == MergePolicy::KeepDestination
+            && dst
+                .get_from_tile(tile_c)
+                .and_then(|chunk_id| dst.get_world_mut().get_entity_mut(chunk_id).ok())
+                .is_some_and(|chunk| {
+                    T::chunk_has_tile(&chunk, calculate_tile_index(tile_c, dst_chunk_size))
+                });
+        if occupied {
+            dropped.push(value);
+        } else {
+            tile_cs.push(tile_c);
+            values.push(value);
+        }
+    }
+    dropped.extend(insert_tile_batch::<T, N>(&mut dst, tile_cs, values));
+    dropped
+}
+
+/// Moves every registered layer (see [`MergeLayers`]) of the source map into the destination map,
+/// shifted by `offset`, then despawns the source map.
+/// # Note
+/// Used for stitching together separately generated sections of a world, or flattening editor layers.
+/// Tiles in layers that weren't registered are despawned with the source map.
+/// Nothing is merged or despawned if either entity isn't a map, or no layers are registered.
+pub fn merge_maps<const N: usize>(
+    world: &mut World,
+    dst_id: Entity,
+    src_id: Entity,
+    offset: [i32; N],
+    policy: MergePolicy,
+) -> Result<(), MergeError> {
+    if dst_id == src_id {
+        return Err(MergeError::SameMap);
+    }
+    for map_id in [dst_id, src_id] {
+        if world.get::<TileMap<N>>(map_id).is_none() {
+            return Err(MergeError::NotAMap(map_id));
+        }
+    }
+    let layers = world
+        .get_resource::<MergeLayers<N>>()
+        .map(|layers| layers.layers.clone())
+        .unwrap_or_default();
+    if layers.is_empty() {
+        return Err(MergeError::NoMergeLayers);
+    }
+    for merge in layers {
+        merge(world, dst_id, src_id, offset, policy);
+    }
+    world.entity_mut(src_id).despawn_recursive();
+    Ok(())
+}
+
+/// Adds merge layer registration to [`App`].
+pub trait MergeAppExt {
+    /// Move tiles of type `T` on maps with `N` dimensions when merging maps.
+    fn register_merge_layer<T: TileComponent, const N: usize>(&mut self) -> &mut Self;
+}
+
+impl MergeAppExt for App {
+    fn register_merge_layer<T: TileComponent, const N: usize>(&mut self) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(MergeLayers::<N>::default)
+            .register::<T>();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use bevy::ecs::{observer::Trigger, system::ResMut};
+
+    use crate::{
+        commands::{TileCommandExt, TileWorldExt},
+        index::LayerIndex,
+        observers::OnTileRemoved,
+        testing,
+        tiles::TileMapQuery,
+    };
+
+    use super::*;
+
+    #[test]
+    fn merge_with_offset() {
+        let mut world = World::new();
+        let mut layers = MergeLayers::<2>::default();
+        layers.register::<u8>().register::<bool>();
+        world.insert_resource(layers);
+        let dst_id = testing::spawn_map(&mut world, 4, |dst| {
+            dst.insert_tile([10, 0], 1u8);
+            dst.insert_tile([11, 0], 1u8);
+        });
+        let src_id = testing::spawn_map(&mut world, 3, |src| {
+            src.insert_tile([0, 0], 2u8);
+            src.insert_tile([1, 0], 2u8);
+            src.insert_tile([-1, -1], true);
+        });
+        testing::apply(&mut world, |commands| {
+            let policy = MergePolicy::KeepDestination;
+            TileCommandExt::<2>::merge_maps(commands, dst_id, src_id, [10, 0], policy);
+        });
+
+        assert!(world.get_entity(src_id).is_err());
+        let mut state = SystemState::<(TileMapQuery<&u8>, TileMapQuery<&bool>)>::new(&mut world);
+        let (heights, flags) = state.get(&world);
+        let heights = heights.get_map(dst_id).unwrap();
+        assert_eq!(heights.get_at([10, 0]), Some(&1));
+        assert_eq!(heights.get_at([11, 0]), Some(&1));
+        assert_eq!(flags.get_map(dst_id).unwrap().get_at([9, -1]), Some(&true));
+
+        // Keeping the source replaces conflicting tiles.
+        let src_id = testing::spawn_map(&mut world, 4, |src| {
+            src.insert_tile([0, 0], 3u8);
+        });
+        merge_maps::<2>(&mut world, dst_id, src_id, [11, 0], MergePolicy::KeepSource).unwrap();
+        let (heights, _) = state.get(&world);
+        let heights = heights.get_map(dst_id).unwrap();
+        assert_eq!(heights.get_at([10, 0]), Some(&1));
+        assert_eq!(heights.get_at([11, 0]), Some(&3));
+    }
+
+    #[test]
+    fn merge_without_layers_keeps_source() {
+        let mut world = World::new();
+        let dst_id = testing::spawn_map(&mut world, 4, |_| {});
+        let src_id = testing::spawn_map(&mut world, 4, |src| {
+            src.insert_tile([0, 0], 2u8);
+        });
+
+        let policy = MergePolicy::KeepSource;
+        assert_eq!(
+            merge_maps::<2>(&mut world, dst_id, src_id, [0, 0], policy),
+            Err(MergeError::NoMergeLayers)
+        );
+        assert_eq!(
+            merge_maps::<2>(&mut world, src_id, src_id, [0, 0], policy),
+            Err(MergeError::SameMap)
+        );
+        world.init_resource::<MergeLayers<2>>();
+        world.resource_mut::<MergeLayers<2>>().register::<u8>();
+        world.despawn(dst_id);
+        assert_eq!(
+            merge_maps::<2>(&mut world, dst_id, src_id, [0, 0], policy),
+            Err(MergeError::NotAMap(dst_id))
+        );
+
+        let mut state = SystemState::<TileMapQuery<&u8>>::new(&mut world);
+        let heights = state.get(&world);
+        assert_eq!(heights.get_map(src_id).unwrap().get_at([0, 0]), Some(&2));
+    }
+
+    #[derive(Resource, Default)]
+    struct Removed(Vec<[i32; 2]>);
+
+    #[test]
+    fn merge_tuple_layer() {
+        let mut world = World::new();
+        world.init_resource::<Removed>();
+        world.add_observer(
+            |trigger: Trigger<OnTileRemoved<u8>>, mut removed: ResMut<Removed>| {
+                removed.0.push(trigger.tile_c);
+            },
+        );
+        let dst_id = TileWorldExt::<2>::spawn_map(&mut world, 4);
+        let src_id = TileWorldExt::<2>::spawn_map(&mut world, 2);
+        world
+            .entity_mut(src_id)
+            .insert(LayerIndex::<u8, 2>::new(|value| Some(*value as u64)));
+        TileWorldExt::<2>::insert_tile(&mut world, src_id, [0, 0], (1u8, true));
+        TileWorldExt::<2>::insert_tile(&mut world, src_id, [3, 0], (2u8, false));
+        TileWorldExt::<2>::insert_tile(&mut world, src_id, [1, 0], 3u8);
+        TileWorldExt::<2>::insert_tile(&mut world, dst_id, [8, 0], (4u8, true));
+
+        let dropped = merge_layer::<(u8, bool), 2>(
+            &mut world,
+            dst_id,
+            src_id,
+            [5, 0],
+            MergePolicy::KeepDestination,
+        );
+        world.flush();
+
+        assert_eq!(dropped, vec![(2, false)]);
+        let tile = |world: &World, map_id, tile_c| {
+            TileWorldExt::<2>::get_tile::<u8>(world, map_id, tile_c).copied()
+        };
+        assert_eq!(tile(&world, dst_id, [5, 0]), Some(1));
+        assert_eq!(tile(&world, dst_id, [8, 0]), Some(4));
+        // The partial tile isn't part of the layer, so it stays behind.
+        assert_eq!(tile(&world, src_id, [1, 0]), Some(3));
+        assert_eq!(tile(&world, src_id, [0, 0]), None);
+        let index = world.get::<LayerIndex<u8, 2>>(src_id).unwrap();
+        assert_eq!(index.key_of([0, 0]), None);
+        assert_eq!(index.key_of([1, 0]), Some(3));
+        let mut removed = world.resource::<Removed>().0.clone();
+        removed.sort();
+        assert_eq!(removed, vec![[0, 0], [3, 0]]);
+        let src = world.get::<TileMap<2>>(src_id).unwrap();
+        assert_eq!(src.occupied_bounds::<bool>(), None);
+    }
+}
@@ -0,0 +1,221 @@
+use std::ops::Range;
+
+/// Configuration for fractal gradient (Perlin) noise.
+/// # Note
+/// Sampling is deterministic, the same config and coordinate will always produce the same value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseConfig {
+    /// Seed used to pick the lattice gradients.
+    pub seed: u64,
+    /// Frequency of the first octave, in noise cycles per tile.
+    pub frequency: f32,
+    /// Number of octaves summed together.
+    pub octaves: u32,
+    /// Frequency multiplier applied between octaves.
+    pub lacunarity: f32,
+    /// Amplitude multiplier applied between octaves.
+    pub persistence: f32,
+}
+
+impl Default for NoiseConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            frequency: 1.0 / 16.0,
+            octaves: 4,
+            lacunarity: 2.0,
+            persistence: 0.5,
+        }
+    }
+}
+
+impl NoiseConfig {
+    /// Create a default config with the given seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// Sample the noise at the center of a tile, returns a value in `[-1, 1]`.
+    #[inline]
+    pub fn sample_tile<const N: usize>(&self, tile_c: impl Into<[i32; N]>) -> f32 {
+        self.sample(tile_c.into().map(|c| c as f32 + 0.5))
+    }
+
+    /// Sample the noise at a point in tile space, returns a value in `[-1, 1]`.
+    pub fn sample<const N: usize>(&self, point: [f32; N]) -> f32 {
+        let mut total = 0.0;
+        let mut max_amplitude = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = self.frequency;
+        for octave in 0..self.octaves.max(1) {
+            let seed = self.seed.wrapping_add(octave as u64);
+            total += amplitude * perlin(seed, point.map(|p| p * frequency));
+            max_amplitude += amplitude;
+            amplitude *= self.persistence;
+            frequency *= self.lacunarity;
+        }
+        (total / max_amplitude).clamp(-1.0, 1.0)
+    }
+}
+
+/// Single octave N dimensional gradient noise, returns a value in roughly `[-1, 1]`.
+pub fn perlin<const N: usize>(seed: u64, point: [f32; N]) -> f32 {
+    let cell = point.map(|p| p.floor() as i32);
+    let mut frac = [0.0; N];
+    let mut fade = [0.0; N];
+    for i in 0..N {
+        frac[i] = point[i] - cell[i] as f32;
+        fade[i] = smootherstep(frac[i]);
+    }
+
+    let mut total = 0.0;
+    for corner_mask in 0..(1usize << N) {
+        let mut corner = cell;
+        for (i, c) in corner.iter_mut().enumerate() {
+            if corner_mask & (1 << i) != 0 {
+                *c += 1;
+            }
+        }
+        let hash = hash_coord(seed, corner);
+
+        let mut weight = 1.0;
+        let mut dot = 0.0;
+        for i in 0..N {
+            let set = corner_mask & (1 << i) != 0;
+            let offset = if set { frac[i] - 1.0 } else { frac[i] };
+            weight *= if set { fade[i] } else { 1.0 - fade[i] };
+            dot += gradient_component(hash, i) * offset;
+        }
+        total += weight * dot;
+    }
+
+    // The largest possible magnitude of a dot product with a unit lattice offset.
+    total * 2.0 / (N as f32).sqrt()
+}
+
+/// Hashes a coordinate with a seed, used for deterministic per tile randomness.
+#[inline]
+pub fn hash_coord<const N: usize>(seed: u64, coord: [i32; N]) -> u64 {
+    let mut hash = splitmix(seed);
+    for c in coord {
+        hash = splitmix(hash ^ (c as u32 as u64));
+    }
+    hash
+}
+
+/// A small seedable random number generator (splitmix64) for procedural helpers.
+/// # Note
+/// The sequence only depends on the seed, so generating from [`TileRng::for_tile`] or [`TileRng::for_chunk`]
+/// always yields the same content for the same coordinate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileRng {
+    state: u64,
+}
+
+impl TileRng {
+    /// Create a generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Create a generator for a tile, the same seed and tile always give the same sequence.
+    pub fn for_tile<const N: usize>(seed: u64, tile_c: impl Into<[i32; N]>) -> Self {
+        Self::new(hash_coord(seed, tile_c.into()))
+    }
+
+    /// Create a generator for a chunk, the same seed and chunk always give the same sequence.
+    /// # Note
+    /// Chunk and tile generators with the same coordinate are independent of each other.
+    pub fn for_chunk<const N: usize>(seed: u64, chunk_c: impl Into<[i32; N]>) -> Self {
+        Self::new(hash_coord(splitmix(seed), chunk_c.into()))
+    }
+
+    /// Get the next random `u64`.
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        splitmix(self.state)
+    }
+
+    /// Get a random value in `[0, 1)`.
+    #[inline]
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Get a random value in `range`, returns `range.start` for empty ranges.
+    #[inline]
+    pub fn range(&mut self, range: Range<i32>) -> i32 {
+        let span = (range.end as i64 - range.start as i64).max(0) as u64;
+        if span == 0 {
+            return range.start;
+        }
+        (range.start as i64 + (self.next_u64() % span) as i64) as i32
+    }
+
+    /// Returns true with the given probability.
+    #[inline]
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+}
+
+#[inline]
+pub(crate) fn splitmix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+#[inline]
+fn gradient_component(hash: u64, dim: usize) -> f32 {
+    let bits = splitmix(hash.wrapping_add(dim as u64)) >> 40;
+    (bits as f32 / (1u64 << 24) as f32) * 2.0 - 1.0
+}
+
+#[inline]
+fn smootherstep(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coords::CoordIterator;
+
+    #[test]
+    fn noise_is_deterministic_and_bounded() {
+        let config = NoiseConfig::with_seed(42);
+        for tile_c in CoordIterator::new([-40, -40], [40, 40]) {
+            let value = config.sample_tile(tile_c);
+            assert_eq!(value, config.sample_tile(tile_c));
+            assert!((-1.0..=1.0).contains(&value));
+        }
+    }
+
+    #[test]
+    fn noise_depends_on_seed() {
+        let a = NoiseConfig::with_seed(1);
+        let b = NoiseConfig::with_seed(2);
+        assert!(CoordIterator::new([0, 0, 0], [8, 8, 8])
+            .any(|tile_c| a.sample_tile(tile_c) != b.sample_tile(tile_c)));
+    }
+
+    #[test]
+    fn rng_is_reproducible() {
+        let mut a = TileRng::for_chunk(9, [3, -2]);
+        let mut b = TileRng::for_chunk(9, [3, -2]);
+        let mut c = TileRng::for_tile(9, [3, -2]);
+        let a: Vec<i32> = (0..16).map(|_| a.range(-5..5)).collect();
+        let b: Vec<i32> = (0..16).map(|_| b.range(-5..5)).collect();
+        let c: Vec<i32> = (0..16).map(|_| c.range(-5..5)).collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(a.iter().all(|v| (-5..5).contains(v)));
+        assert_eq!(TileRng::new(1).range(4..4), 4);
+    }
+}
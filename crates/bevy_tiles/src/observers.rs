@@ -0,0 +1,101 @@
+use std::marker::PhantomData;
+
+use bevy::ecs::{entity::Entity, event::Event, world::World};
+
+/// Triggered on a map after `B` data is inserted into one of its tiles, including when it replaces existing data.
+/// # Note
+/// Observers run once the command that inserted the tile is applied, or right away for [`crate::commands::TileWorldExt`].
+/// Nothing is triggered (or allocated) until an observer for the event is added.
+/// Writes that skip the tile commands (ex: [`crate::commands::TileMapCommands::set_chunk_data`] or mutable queries)
+/// don't trigger it.
+#[derive(Event, Debug)]
+pub struct OnTileInserted<B, const N: usize = 2> {
+    /// The coordinate of the tile.
+    pub tile_c: [i32; N],
+    layer: PhantomData<fn() -> B>,
+}
+
+impl<B, const N: usize> OnTileInserted<B, N> {
+    /// Create the event for a tile.
+    pub fn new(tile_c: [i32; N]) -> Self {
+        Self {
+            tile_c,
+            layer: PhantomData,
+        }
+    }
+}
+
+/// Triggered on a map after `B` data is removed from one of its tiles, see [`OnTileInserted`].
+#[derive(Event, Debug)]
+pub struct OnTileRemoved<B, const N: usize = 2> {
+    /// The coordinate of the tile.
+    pub tile_c: [i32; N],
+    layer: PhantomData<fn() -> B>,
+}
+
+impl<B, const N: usize> OnTileRemoved<B, N> {
+    /// Create the event for a tile.
+    pub fn new(tile_c: [i32; N]) -> Self {
+        Self {
+            tile_c,
+            layer: PhantomData,
+        }
+    }
+}
+
+/// Triggered on a map after one of its chunks is spawned, see [`OnTileInserted`].
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OnChunkSpawned<const N: usize = 2> {
+    /// The coordinate of the chunk.
+    pub chunk_c: [i32; N],
+    /// The chunk entity.
+    pub chunk_id: Entity,
+}
+
+/// Triggered on a map after one of its chunks is despawned, see [`OnTileInserted`].
+/// # Note
+/// The chunk entity is already gone by the time observers run.
+/// Chunks despawned along with their map don't trigger this.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OnChunkDespawned<const N: usize = 2> {
+    /// The coordinate of the chunk.
+    pub chunk_c: [i32; N],
+    /// The despawned chunk entity.
+    pub chunk_id: Entity,
+}
+
+/// Triggered on a map after one of its chunks is moved to another coordinate, see [`OnTileInserted`].
+/// # Note
+/// Tiles move along with their chunk (ex: [`crate::commands::TileMapCommands::swap_chunks`]), so tile data that
+/// stores its own coordinate can be updated by observing this.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OnChunkMoved<const N: usize = 2> {
+    /// The coordinate the chunk was moved from.
+    pub from: [i32; N],
+    /// The coordinate the chunk was moved to.
+    pub to: [i32; N],
+    /// The chunk entity.
+    pub chunk_id: Entity,
+}
+
+/// Whether anything could be observing `E`, events are only registered once an observer for them is added.
+#[inline]
+pub(crate) fn observed<E: Event>(world: &World) -> bool {
+    world.component_id::<E>().is_some()
+}
+
+/// Queue triggering `events` on a map, if anything is observing them.
+#[inline]
+pub(crate) fn trigger_on_map<E: Event>(
+    world: &mut World,
+    map_id: Entity,
+    events: impl IntoIterator<Item = E>,
+) {
+    if !observed::<E>(world) {
+        return;
+    }
+    let mut commands = world.commands();
+    for event in events {
+        commands.trigger_targets(event, map_id);
+    }
+}
@@ -0,0 +1,98 @@
+use std::f32::consts::FRAC_PI_4;
+
+use bevy::{
+    ecs::component::Component,
+    math::{Mat2, Quat},
+};
+
+use crate::coords::{Dir4, Dir8};
+
+/// Which way a tile is facing, for directional tiles like conveyor belts, pipes, and machines.
+/// # Note
+/// Can be stored as a data layer or put on tile entities, where [`Orientation::rotation`] is turned
+/// around the map's z axis with north (`+y`) unrotated.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Orientation(pub Dir8);
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Self(Dir8::North)
+    }
+}
+
+impl Orientation {
+    /// The direction the tile is facing.
+    #[inline]
+    pub fn facing(&self) -> Dir8 {
+        self.0
+    }
+
+    /// The orientation a quarter turn clockwise from this one.
+    #[inline]
+    pub fn rotate_cw(self) -> Self {
+        Self(self.0.rotate_cw().rotate_cw())
+    }
+
+    /// The orientation a quarter turn counter clockwise from this one.
+    #[inline]
+    pub fn rotate_ccw(self) -> Self {
+        Self(self.0.rotate_ccw().rotate_ccw())
+    }
+
+    /// The number of quarter turns clockwise from north, or [`None`] when facing a diagonal.
+    #[inline]
+    pub fn quarter_turns(&self) -> Option<u8> {
+        (!self.0.is_diagonal()).then_some(self.0 as u8 / 2)
+    }
+
+    /// The counter clockwise angle from north in radians.
+    #[inline]
+    pub fn angle(&self) -> f32 {
+        -(self.0 as u8 as f32) * FRAC_PI_4
+    }
+
+    /// The rotation of the tile around the z axis.
+    #[inline]
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_z(self.angle())
+    }
+
+    /// The rotation to apply to texture coordinates centered on the tile (`-0.5..0.5`) when drawing it.
+    #[inline]
+    pub fn uv_rotation(&self) -> Mat2 {
+        Mat2::from_angle(self.angle())
+    }
+}
+
+impl From<Dir4> for Orientation {
+    fn from(value: Dir4) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<Dir8> for Orientation {
+    fn from(value: Dir8) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::{Vec2, Vec3};
+
+    use super::*;
+
+    #[test]
+    fn turns() {
+        let east = Orientation::from(Dir4::East);
+        assert_eq!(Orientation::default().rotate_cw(), east);
+        assert_eq!(east.rotate_ccw().rotate_ccw(), Dir4::West.into());
+        assert_eq!(east.quarter_turns(), Some(1));
+        assert_eq!(Orientation(Dir8::SouthWest).quarter_turns(), None);
+
+        let forward = east.rotation() * Vec3::Y;
+        assert!(forward.abs_diff_eq(Vec3::X, 1e-6));
+        let forward = Orientation(Dir8::NorthWest).uv_rotation() * Vec2::Y;
+        assert!(forward.abs_diff_eq(Vec2::new(-1.0, 1.0).normalize(), 1e-6));
+    }
+}
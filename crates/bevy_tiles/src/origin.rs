@@ -0,0 +1,240 @@
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    ecs::{
+        component::Component,
+        event::{Event, EventReader, EventWriter},
+        query::{Has, With, Without},
+        schedule::IntoSystemConfigs,
+        system::{Query, ResMut, Resource},
+    },
+    hierarchy::Parent,
+    math::{DVec3, Vec3},
+    prelude::Transform,
+    transform::TransformSystem,
+};
+
+use crate::{
+    chunks::ChunkCoord,
+    coords::{calculate_chunk_coordinate, world_to_tile},
+    maps::{MapOrigin, TileDims, TileMap, TileSpacing},
+};
+
+/// Marks the entity (usually the camera) the world is kept centered on by the [`FloatingOriginPlugin`].
+#[derive(Component, Copy, Clone, Debug, Default)]
+pub struct FloatingOrigin;
+
+/// Keeps the world centered on the [`FloatingOrigin`] entity to avoid `f32` precision issues in very large worlds.
+/// # Note
+/// When the floating origin strays further than `threshold` from the world origin, every root entity with a
+/// [`Transform`] (maps, the camera, etc.) is moved back by the same offset and an [`OriginShifted`] event is sent,
+/// so other integrations (ex: physics) can apply the same shift.  Tile and chunk coordinates stay absolute.
+///
+/// Maps with a [`MapOrigin`] also get their chunk transforms rebased near the floating origin.
+/// The floating origin entity and maps are expected to be root entities.
+pub struct FloatingOriginPlugin {
+    /// Distance from the world origin the floating origin can stray before the world is shifted.
+    pub threshold: f32,
+}
+
+impl Default for FloatingOriginPlugin {
+    fn default() -> Self {
+        Self { threshold: 4096.0 }
+    }
+}
+
+impl Plugin for FloatingOriginPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WorldOrigin {
+            offset: DVec3::ZERO,
+            threshold: self.threshold,
+        })
+        .add_event::<OriginShifted>()
+        .add_systems(
+            PostUpdate,
+            (
+                shift_origin,
+                (
+                    rebase_map_chunks::<1>,
+                    rebase_map_chunks::<2>,
+                    rebase_map_chunks::<3>,
+                ),
+            )
+                .chain()
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// How far the world has been shifted by the [`FloatingOriginPlugin`].
+#[derive(Resource, Copy, Clone, Debug)]
+pub struct WorldOrigin {
+    /// The total offset the world has been shifted by, add this to a translation to get its absolute position.
+    pub offset: DVec3,
+    /// Distance from the world origin the floating origin can stray before the world is shifted.
+    pub threshold: f32,
+}
+
+impl WorldOrigin {
+    /// Get the absolute position of a translation.
+    pub fn to_absolute(&self, translation: Vec3) -> DVec3 {
+        self.offset + translation.as_dvec3()
+    }
+
+    /// Get the translation of an absolute position.
+    pub fn to_relative(&self, position: DVec3) -> Vec3 {
+        (position - self.offset).as_vec3()
+    }
+}
+
+/// Sent when the world is shifted, every root [`Transform`] was moved by `-offset`.
+#[derive(Event, Copy, Clone, Debug, PartialEq)]
+pub struct OriginShifted {
+    /// The amount the world origin moved by.
+    pub offset: Vec3,
+}
+
+/// Shifts every root entity back towards the world origin when the floating origin strays too far.
+pub fn shift_origin(
+    mut world_origin: ResMut<WorldOrigin>,
+    mut shifted: EventWriter<OriginShifted>,
+    mut roots: Query<(&mut Transform, Has<FloatingOrigin>), Without<Parent>>,
+) {
+    let Some(offset) = roots
+        .iter()
+        .find_map(|(transform, is_origin)| is_origin.then_some(transform.translation))
+    else {
+        return;
+    };
+    if offset.length() <= world_origin.threshold {
+        return;
+    }
+
+    for (mut transform, _) in roots.iter_mut() {
+        transform.translation -= offset;
+    }
+    world_origin.offset += offset.as_dvec3();
+    shifted.send(OriginShifted { offset });
+}
+
+/// Moves the chunk transforms of maps with a [`MapOrigin`] so the chunk the floating origin is over sits at the
+/// map's local origin, without moving anything in world space.
+pub fn rebase_map_chunks<const N: usize>(
+    mut shifted: EventReader<OriginShifted>,
+    origins: Query<&Transform, (With<FloatingOrigin>, Without<TileMap<N>>)>,
+    mut maps: Query<
+        (
+            &TileMap<N>,
+            &mut MapOrigin<N>,
+            &mut Transform,
+            &TileDims<N>,
+            Option<&TileSpacing<N>>,
+        ),
+        Without<FloatingOrigin>,
+    >,
+    mut chunks: Query<
+        &mut Transform,
+        (
+            With<ChunkCoord<N>>,
+            Without<TileMap<N>>,
+            Without<FloatingOrigin>,
+        ),
+    >,
+) {
+    if shifted.read().count() == 0 || N > 3 {
+        return;
+    }
+    let Ok(origin_t) = origins.get_single() else {
+        return;
+    };
+
+    for (map, mut map_origin, mut map_t, tile_dims, tile_spacing) in maps.iter_mut() {
+        let local = map_t
+            .compute_affine()
+            .inverse()
+            .transform_point3(origin_t.translation);
+        let mut local_c = [0.0; N];
+        for (i, c) in local_c.iter_mut().enumerate() {
+            *c = local[i];
+        }
+        let tile_c =
+            map_origin.to_absolute(world_to_tile(local_c, *tile_dims, tile_spacing.cloned()));
+        let chunk_size = map.get_chunk_size() as i32;
+        let new_origin =
+            calculate_chunk_coordinate(tile_c, chunk_size as usize).map(|c| c * chunk_size);
+        if new_origin == map_origin.0 {
+            continue;
+        }
+
+        // Move every chunk back by the change in origin, and the map forward by the same amount.
+        let mut delta = Vec3::ZERO;
+        for i in 0..N {
+            let step = tile_dims[i] + tile_spacing.map(|spacing| spacing[i]).unwrap_or(0.0);
+            delta[i] = (new_origin[i] - map_origin.0[i]) as f32 * step;
+        }
+        for chunk_id in map.get_chunks().values() {
+            if let Ok(mut chunk_t) = chunks.get_mut(*chunk_id) {
+                chunk_t.translation -= delta;
+            }
+        }
+        let shift = map_t.rotation * (map_t.scale * delta);
+        map_t.translation += shift;
+        map_origin.0 = new_origin;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::App, ecs::world::World, transform::TransformPlugin};
+
+    use crate::{maps::UseTransforms, testing};
+
+    use super::*;
+
+    fn chunk_world_x(world: &World, map_id: bevy::ecs::entity::Entity, chunk_c: [i32; 2]) -> f32 {
+        let map = world.get::<TileMap<2>>(map_id).unwrap();
+        let chunk_id = map.get_from_chunk(ChunkCoord(chunk_c)).unwrap();
+        world.get::<Transform>(map_id).unwrap().translation.x
+            + world.get::<Transform>(chunk_id).unwrap().translation.x
+    }
+
+    #[test]
+    fn shift_keeps_tiles_in_place() {
+        let mut app = App::new();
+        app.add_plugins((TransformPlugin, FloatingOriginPlugin { threshold: 100.0 }));
+        let camera = app
+            .world_mut()
+            .spawn((Transform::from_xyz(90.0, 0.0, 0.0), FloatingOrigin))
+            .id();
+        let map_id = testing::spawn_map(app.world_mut(), 4, |map| {
+            map.insert((
+                UseTransforms,
+                TileDims([1.0, 1.0]),
+                MapOrigin::<2>::default(),
+            ));
+            map.insert_tile([0, 0], 1u8);
+            map.insert_tile([120, 0], 1u8);
+        });
+        app.update();
+        assert_eq!(app.world().resource::<WorldOrigin>().offset, DVec3::ZERO);
+
+        app.world_mut()
+            .get_mut::<Transform>(camera)
+            .unwrap()
+            .translation
+            .x = 121.0;
+        app.update();
+
+        let world = app.world();
+        assert_eq!(world.resource::<WorldOrigin>().offset.x, 121.0);
+        assert_eq!(world.get::<Transform>(camera).unwrap().translation.x, 0.0);
+        assert_eq!(world.get::<MapOrigin<2>>(map_id).unwrap().0, [120, 0]);
+        assert_eq!(chunk_world_x(world, map_id, [30, 0]), -1.0);
+        assert_eq!(chunk_world_x(world, map_id, [0, 0]), -121.0);
+        let chunk_id = world
+            .get::<TileMap<2>>(map_id)
+            .unwrap()
+            .get_from_chunk(ChunkCoord([30, 0]))
+            .unwrap();
+        assert_eq!(world.get::<Transform>(chunk_id).unwrap().translation.x, 0.0);
+    }
+}
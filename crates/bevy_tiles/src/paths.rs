@@ -0,0 +1,141 @@
+use bevy::math::Vec3;
+
+use crate::{convert::MapSpace, coords::LineIterator};
+
+/// True if an agent can move in a straight line from `start` to `end` without entering a tile where
+/// `is_blocked` returns true.
+/// # Note
+/// Diagonal steps also need the tiles beside them to be open, so lines never squeeze between two blocked corners.
+pub fn line_of_sight<const N: usize>(
+    start: impl Into<[i32; N]>,
+    end: impl Into<[i32; N]>,
+    is_blocked: impl Fn([i32; N]) -> bool,
+) -> bool {
+    let mut prev: Option<[i32; N]> = None;
+    for tile_c in LineIterator::new(start, end) {
+        if is_blocked(tile_c) {
+            return false;
+        }
+        if let Some(prev) = prev {
+            let axes = (0..N).filter(|i| prev[*i] != tile_c[*i]).count();
+            if axes > 1 {
+                for i in (0..N).filter(|i| prev[*i] != tile_c[*i]) {
+                    let mut side_c = prev;
+                    side_c[i] = tile_c[i];
+                    if is_blocked(side_c) {
+                        return false;
+                    }
+                }
+            }
+        }
+        prev = Some(tile_c);
+    }
+    true
+}
+
+/// Removes the waypoints of a path that are in the middle of a straight run, keeping the ends
+/// and every tile where the path turns.
+pub fn simplify_path<const N: usize>(path: &[[i32; N]]) -> Vec<[i32; N]> {
+    let direction = |from: [i32; N], to: [i32; N]| {
+        let mut dir = [0; N];
+        for i in 0..N {
+            dir[i] = (to[i] - from[i]).signum();
+        }
+        dir
+    };
+
+    let mut simplified: Vec<[i32; N]> = Vec::with_capacity(path.len());
+    for (i, tile_c) in path.iter().enumerate() {
+        if i == 0 || i == path.len() - 1 {
+            simplified.push(*tile_c);
+            continue;
+        }
+        if direction(path[i - 1], *tile_c) != direction(*tile_c, path[i + 1]) {
+            simplified.push(*tile_c);
+        }
+    }
+    simplified
+}
+
+/// Pulls a path tight by skipping every waypoint that the waypoint before it can see past,
+/// so agents walk straight across open areas instead of zigzagging along the grid.
+/// # Note
+/// This is greedy, each waypoint is joined to the farthest later waypoint it has [`line_of_sight`] to.
+pub fn smooth_path<const N: usize>(
+    path: &[[i32; N]],
+    is_blocked: impl Fn([i32; N]) -> bool,
+) -> Vec<[i32; N]> {
+    let Some(first) = path.first() else {
+        return Vec::new();
+    };
+
+    let mut smoothed = vec![*first];
+    let mut anchor = 0;
+    while anchor < path.len() - 1 {
+        let next = (anchor + 2..path.len())
+            .rev()
+            .find(|i| line_of_sight(path[anchor], path[*i], &is_blocked))
+            .unwrap_or(anchor + 1);
+        smoothed.push(path[next]);
+        anchor = next;
+    }
+    smoothed
+}
+
+/// Converts a path to world space, placing each waypoint at `anchor` within its tile
+/// (see [`MapSpace::tile_anchor_to_world`], `[0.5; N]` is the center of the tile).
+pub fn path_to_world<const N: usize>(
+    path: &[[i32; N]],
+    space: &MapSpace<N>,
+    anchor: [f32; N],
+) -> Vec<Vec3> {
+    path.iter()
+        .map(|tile_c| space.tile_anchor_to_world(*tile_c, anchor))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::transform::components::GlobalTransform;
+
+    use crate::maps::{MapOrigin, TileDims};
+
+    use super::*;
+
+    #[test]
+    fn smooth_around_wall() {
+        // A wall at x = 2 from y = 0 to 3, the path goes under it.
+        let is_blocked = |[x, y]: [i32; 2]| x == 2 && (0..=3).contains(&y);
+        let path: Vec<[i32; 2]> = vec![
+            [0, 2],
+            [0, 1],
+            [0, 0],
+            [1, -1],
+            [2, -1],
+            [3, -1],
+            [4, 0],
+            [4, 1],
+            [4, 2],
+        ];
+
+        assert_eq!(
+            simplify_path(&path),
+            vec![[0, 2], [0, 0], [1, -1], [3, -1], [4, 0], [4, 2]]
+        );
+        assert!(!line_of_sight([1, 0], [2, -1], |c| c == [2, 0] || c == [1, -1]));
+
+        let smoothed = smooth_path(&path, is_blocked);
+        assert_eq!(smoothed, vec![[0, 2], [1, -1], [3, -1], [4, 2]]);
+
+        let space = MapSpace {
+            transform: GlobalTransform::IDENTITY,
+            dims: TileDims([2.0, 2.0]),
+            spacing: None,
+            origin: MapOrigin::default(),
+        };
+        assert_eq!(
+            path_to_world(&smoothed[..2], &space, [0.5; 2]),
+            vec![Vec3::new(1.0, 5.0, 0.0), Vec3::new(3.0, -1.0, 0.0)]
+        );
+    }
+}
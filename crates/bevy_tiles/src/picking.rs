@@ -0,0 +1,147 @@
+use bevy::math::{Ray3d, Vec3};
+
+use crate::{convert::MapSpace, coords::Dir6, tiles::TileQuery};
+
+/// Cast a ray into a 3d map and find the first tile with `T` data it enters, along with the face of the tile it
+/// entered through and the distance along the ray (in world units) to that face.
+/// Returns [`None`] if no tile is hit within `max_distance`.
+/// # Note
+/// The ray is traced through the map's local space, so it works with maps that are moved, rotated, or scaled.
+/// If the ray starts inside a tile, that tile is hit at a distance of `0.0` on the face facing back along the ray.
+///
+/// Tiles are placed against the hit face with `tile_c + face.offset()`.
+pub fn raycast_tiles_3d<T: Send + Sync + 'static>(
+    ray: Ray3d,
+    max_distance: f32,
+    space: &MapSpace<3>,
+    tiles: &TileQuery<'_, '_, '_, &T, 3>,
+) -> Option<([i32; 3], Dir6, f32)> {
+    raycast_grid_3d(ray, max_distance, space, |tile_c| {
+        tiles.get_at(tile_c).is_some()
+    })
+}
+
+/// Cast a ray into a 3d map and find the first tile where `is_solid` returns true, see [`raycast_tiles_3d`].
+pub fn raycast_grid_3d(
+    ray: Ray3d,
+    max_distance: f32,
+    space: &MapSpace<3>,
+    is_solid: impl Fn([i32; 3]) -> bool,
+) -> Option<([i32; 3], Dir6, f32)> {
+    let start = Vec3::from(space.world_to_tile_pos(ray.origin));
+    let mut dir = space
+        .transform
+        .affine()
+        .inverse()
+        .transform_vector3(*ray.direction);
+    for i in 0..3 {
+        dir[i] /= space.pitch(i);
+    }
+
+    let mut tile_c = start.floor().as_ivec3().to_array();
+    if is_solid(tile_c) {
+        let axis = (0..3)
+            .max_by(|a, b| dir[*a].abs().total_cmp(&dir[*b].abs()))
+            .unwrap_or(0);
+        return Some((tile_c, entry_face(axis, dir[axis]), 0.0));
+    }
+
+    // The distance along the ray to the next tile boundary on each axis, and between boundaries on each axis.
+    let mut next = [f32::INFINITY; 3];
+    let mut delta = [f32::INFINITY; 3];
+    for i in 0..3 {
+        if dir[i] > 0.0 {
+            next[i] = (tile_c[i] as f32 + 1.0 - start[i]) / dir[i];
+            delta[i] = 1.0 / dir[i];
+        } else if dir[i] < 0.0 {
+            next[i] = (start[i] - tile_c[i] as f32) / -dir[i];
+            delta[i] = 1.0 / -dir[i];
+        }
+    }
+
+    loop {
+        let axis = (0..3).min_by(|a, b| next[*a].total_cmp(&next[*b]))?;
+        let distance = next[axis];
+        if !distance.is_finite() || distance > max_distance {
+            return None;
+        }
+        tile_c[axis] += dir[axis].signum() as i32;
+        next[axis] += delta[axis];
+        if is_solid(tile_c) {
+            return Some((tile_c, entry_face(axis, dir[axis]), distance));
+        }
+    }
+}
+
+/// The face of a tile a ray moving along `axis` enters through.
+fn entry_face(axis: usize, dir: f32) -> Dir6 {
+    Dir6::ALL[if dir > 0.0 { axis + 3 } else { axis }]
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::{system::SystemState, world::World},
+        math::{Dir3, Quat},
+        transform::components::{GlobalTransform, Transform},
+    };
+
+    use crate::{
+        commands::TileCommandExt,
+        maps::{MapOrigin, TileDims},
+        testing,
+        tiles::TileMapQuery,
+    };
+
+    use super::*;
+
+    #[test]
+    fn picks_first_solid_tile() {
+        let mut world = World::new();
+        let map_id = testing::apply(&mut world, |commands| {
+            let mut map = TileCommandExt::<3>::spawn_map(commands, 4);
+            map.insert_tile([5, 0, 0], true);
+            map.insert_tile([8, 0, 0], true);
+            map.insert_tile([2, -3, 1], true);
+            map.id()
+        });
+
+        let mut state = SystemState::<TileMapQuery<&bool, (), 3>>::new(&mut world);
+        let tile_maps = state.get(&world);
+        let tiles = tile_maps.get_map(map_id).unwrap();
+        let mut space = MapSpace {
+            transform: GlobalTransform::IDENTITY,
+            dims: TileDims([1.0; 3]),
+            spacing: None,
+            origin: MapOrigin::default(),
+        };
+
+        let ray = Ray3d::new(Vec3::new(0.5, 0.5, 0.5), Dir3::X);
+        assert_eq!(
+            raycast_tiles_3d(ray, 100.0, &space, &tiles),
+            Some(([5, 0, 0], Dir6::NegX, 4.5))
+        );
+        assert_eq!(raycast_tiles_3d(ray, 4.0, &space, &tiles), None);
+        let ray = Ray3d::new(Vec3::new(2.5, 2.5, 1.5), Dir3::NEG_Y);
+        assert_eq!(
+            raycast_tiles_3d(ray, 100.0, &space, &tiles),
+            Some(([2, -3, 1], Dir6::PosY, 4.5))
+        );
+        let ray = Ray3d::new(Vec3::new(5.5, 0.5, 0.5), Dir3::NEG_X);
+        assert_eq!(
+            raycast_tiles_3d(ray, 100.0, &space, &tiles),
+            Some(([5, 0, 0], Dir6::PosX, 0.0))
+        );
+
+        // Twice as large tiles, rotated a half turn around z and moved up.
+        space.dims = TileDims([2.0; 3]);
+        space.transform = GlobalTransform::from(
+            Transform::from_xyz(0.0, 0.0, 4.0)
+                .with_rotation(Quat::from_rotation_z(std::f32::consts::PI)),
+        );
+        let ray = Ray3d::new(Vec3::new(-1.0, -1.0, 5.0), Dir3::NEG_X);
+        let (tile_c, face, distance) = raycast_tiles_3d(ray, 100.0, &space, &tiles).unwrap();
+        assert_eq!((tile_c, face), ([5, 0, 0], Dir6::NegX));
+        assert!((distance - 9.0).abs() < 1e-4);
+    }
+}
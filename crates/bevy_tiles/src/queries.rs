@@ -2,14 +2,21 @@ use std::any::TypeId;
 
 use bevy::{
     ecs::query::{QueryData, WorldQuery},
-    prelude::{Bundle, Component, Entity, EntityWorldMut},
+    prelude::{EntityWorldMut, Mut, World},
 };
 
 use crate::{
-    chunks::{ChunkData, ChunkTypes},
-    maps::{TileDims, TileSpacing},
+    chunks::{ChunkData, ChunkTypes, FixedLayers},
+    commands::{
+        trigger_inserted, trigger_removed, update_layer_index, update_occupied, TempRemoved,
+    },
+    maps::{TileDims, TileMap, TileSpacing},
+    orientation::Orientation,
 };
 
+/// Derive [`TileComponent`] for plain data types, see [`bevy_tiles_macros::TileComponent`].
+pub use bevy_tiles_macros::TileComponent;
+
 /// Marks a data type as.
 pub trait TileDataQuery {
     /// The item returned from a tile query.
@@ -22,6 +29,11 @@ pub trait TileDataQuery {
         source: <<Self as TileDataQuery>::Source as WorldQuery>::Item<'_>,
         index: usize,
     ) -> Option<Self::Item<'_>>;
+
+    /// Get every tile in a chunk, indexed by tile index.
+    fn get_all(
+        source: <<Self as TileDataQuery>::Source as WorldQuery>::Item<'_>,
+    ) -> Vec<Option<Self::Item<'_>>>;
 }
 
 /// Mark type as usable in tiles.
@@ -54,13 +66,19 @@ impl<T: Send + Sync + 'static> TileDataQuery for &T {
     ) -> Option<Self::Item<'_>> {
         source.get(index)
     }
+
+    fn get_all(
+        source: <<Self as TileDataQuery>::Source as WorldQuery>::Item<'_>,
+    ) -> Vec<Option<Self::Item<'_>>> {
+        source.iter().collect()
+    }
 }
 
 impl<'w, T: Send + Sync + 'static> TileData for &'w mut T {
     type ReadOnly = &'w T;
 }
 
-impl<'w, T: Send + Sync + 'static> TileDataQuery for &'w mut T {
+impl<T: Send + Sync + 'static> TileDataQuery for &mut T {
     type Item<'a> = &'a mut T;
 
     type Source = &'static mut ChunkData<T>;
@@ -71,12 +89,27 @@ impl<'w, T: Send + Sync + 'static> TileDataQuery for &'w mut T {
     ) -> Option<Self::Item<'_>> {
         source.into_inner().get_mut(index)
     }
+
+    fn get_all(
+        source: <<Self as TileDataQuery>::Source as WorldQuery>::Item<'_>,
+    ) -> Vec<Option<Self::Item<'_>>> {
+        source.into_inner().iter_mut().collect()
+    }
 }
 
 /// The tiled version of a component bundle.
+/// # Note
+/// Tuples of tile components are tile components as well, filling several layers of a tile at once.
+/// A tuple is only taken when every one of its elements is there, and only counts as replaced when every one
+/// of its elements was, the replaced elements of a partly replaced tuple are passed to [`TileComponent::discard_tile`].
 /// # Safety
 /// Easy to screw this up.
 pub unsafe trait TileComponent: Sized + Send + Sync + 'static {
+    /// Whether this is plain data kept as is in a [`ChunkData<Self>`], with no cleanup or observers of its own.
+    /// Whole chunks of plain data are moved in at once by [`crate::commands::ChunkWriter::set_data`], skipping
+    /// [`TileComponent::discard_tile`] and the `trigger_*` hooks, so only set this for plain data.
+    const PLAIN: bool = false;
+
     /// Inserts a bundle and returns all the replaced values.
     fn insert_tile_into_chunk<const N: usize>(
         self,
@@ -104,74 +137,298 @@ pub unsafe trait TileComponent: Sized + Send + Sync + 'static {
 
     /// Try to remove a bundle.
     fn take_tile_from_chunk(chunk: &mut EntityWorldMut<'_>, tile_i: usize) -> Option<Self>;
+
+    /// Record whether a chunk has data for the layers of this bundle, called after the chunk is edited.
+    fn update_occupied<const N: usize>(map: &mut TempRemoved<'_, TileMap<N>>, chunk_c: [i32; N]) {
+        update_occupied::<Self, N>(map, chunk_c);
+    }
+
+    /// Whether a chunk has data for every layer of this bundle at a tile, this must agree with
+    /// [`TileComponent::take_tile_from_chunk`].
+    fn chunk_has_tile(chunk: &EntityWorldMut<'_>, tile_i: usize) -> bool {
+        chunk
+            .get::<ChunkData<Self>>()
+            .is_some_and(|data| data.get(tile_i).is_some())
+    }
+
+    /// Cleans up a value that was replaced but can't be returned (ex: when the rest of its tuple wasn't replaced, or
+    /// by [`crate::commands::ChunkWriter::set_data`]).
+    /// # Note
+    /// Drops the value by default, implement this for tiles that need cleaning up (ex: entities).
+    fn discard_tile<const N: usize>(self, _world: &mut World) {}
+
+    /// Update any [`crate::index::LayerIndex`] of the layers of this bundle, called after tiles are edited.
+    fn update_layer_index<const N: usize>(
+        map: &mut TempRemoved<'_, TileMap<N>>,
+        tile_cs: &[[i32; N]],
+    ) {
+        update_layer_index::<Self, N>(map, tile_cs.iter().copied());
+    }
+
+    /// Trigger [`crate::observers::OnTileInserted`] for the layers of this bundle, called after tiles are inserted.
+    fn trigger_inserted<const N: usize>(
+        map: &mut TempRemoved<'_, TileMap<N>>,
+        tile_cs: &[[i32; N]],
+    ) {
+        trigger_inserted::<Self, N>(map, tile_cs.iter().copied());
+    }
+
+    /// Trigger [`crate::observers::OnTileRemoved`] for the layers of this bundle, called after tiles are taken.
+    fn trigger_removed<const N: usize>(
+        map: &mut TempRemoved<'_, TileMap<N>>,
+        tile_cs: &[[i32; N]],
+    ) {
+        trigger_removed::<Self, N>(map, tile_cs.iter().copied());
+    }
+}
+
+/// Inserts plain tile data into a chunk, creating the [`ChunkData<T>`] storage if needed.
+pub fn insert_plain_tile_into_chunk<T: Send + Sync + 'static, const N: usize>(
+    value: T,
+    mut chunk: EntityWorldMut<'_>,
+    chunk_size: usize,
+    tile_i: usize,
+) -> Option<T> {
+    get_or_insert_chunk_data::<T, N>(&mut chunk, chunk_size).insert(tile_i, value)
+}
+
+/// Inserts a batch of plain tile data into a chunk, creating the [`ChunkData<T>`] storage if needed.
+pub fn insert_plain_tile_batch_into_chunk<T: Send + Sync + 'static, const N: usize>(
+    tiles: impl Iterator<Item = T>,
+    mut chunk: EntityWorldMut<'_>,
+    chunk_size: usize,
+    tile_is: impl Iterator<Item = ([i32; N], usize)>,
+) -> impl Iterator<Item = T> {
+    let mut chunk_data = get_or_insert_chunk_data::<T, N>(&mut chunk, chunk_size);
+    let mut removed = Vec::new();
+    for ((_, tile_i), tile) in tile_is.zip(tiles) {
+        if let Some(replaced) = chunk_data.insert(tile_i, tile) {
+            removed.push(replaced);
+        }
+    }
+    removed.into_iter()
+}
+
+/// Takes plain tile data out of a chunk, removing the [`ChunkData<T>`] storage once it's empty
+/// (unless the chunk has [`FixedLayers`]).
+pub fn take_plain_tile_from_chunk<T: Send + Sync + 'static>(
+    chunk: &mut EntityWorldMut<'_>,
+    tile_i: usize,
+) -> Option<T> {
+    let mut chunk_data = chunk.get_mut::<ChunkData<T>>()?;
+    let removed = chunk_data.take(tile_i);
+    if chunk_data.get_count() == 0 && !chunk.contains::<FixedLayers>() {
+        chunk
+            .get_mut::<ChunkTypes>()
+            .unwrap()
+            .0
+            .remove(&TypeId::of::<T>());
+        chunk.remove::<ChunkData<T>>();
+    }
+    removed
+}
+
+#[inline]
+fn get_or_insert_chunk_data<'a, T: Send + Sync + 'static, const N: usize>(
+    chunk: &'a mut EntityWorldMut<'_>,
+    chunk_size: usize,
+) -> Mut<'a, ChunkData<T>> {
+    if !chunk.contains::<ChunkData<T>>() {
+        chunk
+            .get_mut::<ChunkTypes>()
+            .unwrap()
+            .0
+            .insert(TypeId::of::<T>());
+        chunk.insert(ChunkData::<T>::new(chunk_size.pow(N as u32)));
+    }
+    chunk.get_mut::<ChunkData<T>>().unwrap()
+}
+
+macro_rules! impl_plain_tile_component {
+    ($($t:ty),*) => {
+        $(
+            /// # Safety:
+            /// Plain data, no other components are touched.
+            unsafe impl TileComponent for $t {
+                const PLAIN: bool = true;
+
+                fn insert_tile_into_chunk<const N: usize>(
+                    self,
+                    chunk: EntityWorldMut<'_>,
+                    _chunk_c: [i32; N],
+                    chunk_size: usize,
+                    _use_transforms: bool,
+                    _tile_dims: Option<TileDims<N>>,
+                    _tile_spacing: Option<TileSpacing<N>>,
+                    _tile_c: [i32; N],
+                    tile_i: usize,
+                ) -> Option<Self> {
+                    insert_plain_tile_into_chunk::<Self, N>(self, chunk, chunk_size, tile_i)
+                }
+
+                fn insert_tile_batch_into_chunk<const N: usize>(
+                    tiles: impl Iterator<Item = Self>,
+                    chunk: EntityWorldMut<'_>,
+                    _chunk_c: [i32; N],
+                    chunk_size: usize,
+                    _use_transforms: bool,
+                    _tile_dims: Option<TileDims<N>>,
+                    _tile_spacing: Option<TileSpacing<N>>,
+                    tile_is: impl Iterator<Item = ([i32; N], usize)>,
+                ) -> impl Iterator<Item = Self> {
+                    insert_plain_tile_batch_into_chunk::<Self, N>(tiles, chunk, chunk_size, tile_is)
+                }
+
+                fn take_tile_from_chunk(
+                    chunk: &mut EntityWorldMut<'_>,
+                    tile_i: usize,
+                ) -> Option<Self> {
+                    take_plain_tile_from_chunk::<Self>(chunk, tile_i)
+                }
+            }
+        )*
+    };
+}
+
+impl_plain_tile_component!(
+    bool,
+    u8,
+    u16,
+    u32,
+    u64,
+    i8,
+    i16,
+    i32,
+    i64,
+    f32,
+    f64,
+    Orientation
+);
+
+macro_rules! impl_tuple_tile_component {
+    ($(($t:ident, $v:ident)),*) => {
+        /// # Safety:
+        /// Each element is inserted into and taken from the chunk by its own implementation, one after another.
+        unsafe impl<$($t: TileComponent),*> TileComponent for ($($t,)*) {
+            fn insert_tile_into_chunk<const N: usize>(
+                self,
+                chunk: EntityWorldMut<'_>,
+                chunk_c: [i32; N],
+                chunk_size: usize,
+                use_transforms: bool,
+                tile_dims: Option<TileDims<N>>,
+                tile_spacing: Option<TileSpacing<N>>,
+                tile_c: [i32; N],
+                tile_i: usize,
+            ) -> Option<Self> {
+                let chunk_id = chunk.id();
+                let world = chunk.into_world_mut();
+                let ($($v,)*) = self;
+                $(
+                    let $v = $v.insert_tile_into_chunk::<N>(
+                        world.entity_mut(chunk_id),
+                        chunk_c,
+                        chunk_size,
+                        use_transforms,
+                        tile_dims,
+                        tile_spacing,
+                        tile_c,
+                        tile_i,
+                    );
+                )*
+                match ($($v,)*) {
+                    ($(Some($v),)*) => Some(($($v,)*)),
+                    ($($v,)*) => {
+                        $(
+                            if let Some($v) = $v {
+                                $v.discard_tile::<N>(world);
+                            }
+                        )*
+                        None
+                    }
+                }
+            }
+
+            fn insert_tile_batch_into_chunk<const N: usize>(
+                tiles: impl Iterator<Item = Self>,
+                chunk: EntityWorldMut<'_>,
+                chunk_c: [i32; N],
+                chunk_size: usize,
+                use_transforms: bool,
+                tile_dims: Option<TileDims<N>>,
+                tile_spacing: Option<TileSpacing<N>>,
+                tile_is: impl Iterator<Item = ([i32; N], usize)>,
+            ) -> impl Iterator<Item = Self> {
+                // Tiles are inserted one at a time so replaced elements can be matched back up into tuples.
+                let chunk_id = chunk.id();
+                let world = chunk.into_world_mut();
+                let mut removed = Vec::new();
+                for ((tile_c, tile_i), tile) in tile_is.zip(tiles) {
+                    removed.extend(tile.insert_tile_into_chunk::<N>(
+                        world.entity_mut(chunk_id),
+                        chunk_c,
+                        chunk_size,
+                        use_transforms,
+                        tile_dims,
+                        tile_spacing,
+                        tile_c,
+                        tile_i,
+                    ));
+                }
+                removed.into_iter()
+            }
+
+            fn take_tile_from_chunk(chunk: &mut EntityWorldMut<'_>, tile_i: usize) -> Option<Self> {
+                // Nothing is taken unless every element is there, so no element is lost.
+                if !Self::chunk_has_tile(chunk, tile_i) {
+                    return None;
+                }
+                $(let $v = $t::take_tile_from_chunk(chunk, tile_i);)*
+                Some(($($v?,)*))
+            }
+
+            fn update_occupied<const N: usize>(
+                map: &mut TempRemoved<'_, TileMap<N>>,
+                chunk_c: [i32; N],
+            ) {
+                $($t::update_occupied::<N>(map, chunk_c);)*
+            }
+
+            fn chunk_has_tile(chunk: &EntityWorldMut<'_>, tile_i: usize) -> bool {
+                $($t::chunk_has_tile(chunk, tile_i))&&*
+            }
+
+            fn discard_tile<const N: usize>(self, world: &mut World) {
+                let ($($v,)*) = self;
+                $($v.discard_tile::<N>(world);)*
+            }
+
+            fn update_layer_index<const N: usize>(
+                map: &mut TempRemoved<'_, TileMap<N>>,
+                tile_cs: &[[i32; N]],
+            ) {
+                $($t::update_layer_index::<N>(map, tile_cs);)*
+            }
+
+            fn trigger_inserted<const N: usize>(
+                map: &mut TempRemoved<'_, TileMap<N>>,
+                tile_cs: &[[i32; N]],
+            ) {
+                $($t::trigger_inserted::<N>(map, tile_cs);)*
+            }
+
+            fn trigger_removed<const N: usize>(
+                map: &mut TempRemoved<'_, TileMap<N>>,
+                tile_cs: &[[i32; N]],
+            ) {
+                $($t::trigger_removed::<N>(map, tile_cs);)*
+            }
+        }
+    };
 }
 
-// /// # Safety:
-// /// Probably safe.
-// /// MAKE THIS NOT A DEFAULT IMPL
-// unsafe impl<T: Sized + Send + Sync + 'static> TileComponent for T {
-//     fn insert_tile_into_chunk<const N: usize>(
-//         self,
-//         mut chunk: EntityWorldMut<'_>,
-//         chunk_size: usize,
-//         tile_i: usize,
-//     ) -> Option<T> {
-//         let location = match chunk.get_mut::<ChunkData<Self>>() {
-//             Some(data) => data,
-//             None => {
-//                 chunk
-//                     .get_mut::<ChunkTypes>()
-//                     .unwrap()
-//                     .0
-//                     .insert(TypeId::of::<Self>());
-//                 let chunk = chunk.insert(ChunkData::<Self>::new(
-//                     chunk_size.pow(N.try_into().unwrap()),
-//                 ));
-//                 chunk.get_mut::<ChunkData<Self>>().unwrap()
-//             }
-//         };
-//         let mut binding = location;
-//         binding.insert(tile_i, self)
-//     }
-
-//     fn take_tile_from_chunk(chunk: &mut EntityWorldMut<'_>, tile_i: usize) -> Option<Self> {
-//         let location = chunk.get_mut::<ChunkData<Self>>();
-//         let mut binding = location?;
-//         let removed = binding.take(tile_i);
-//         if binding.count == 0 {
-//             chunk
-//                 .get_mut::<ChunkTypes>()
-//                 .unwrap()
-//                 .0
-//                 .remove(&TypeId::of::<Self>());
-//             chunk.remove::<ChunkData<Self>>();
-//         }
-//         removed
-//     }
-
-//     fn insert_tile_batch_into_chunk<const N: usize>(
-//         tiles: impl Iterator<Item = Self>,
-//         mut chunk: EntityWorldMut<'_>,
-//         chunk_size: usize,
-//         tile_is: impl Iterator<Item = usize>,
-//     ) {
-//         let location = match chunk.get_mut::<ChunkData<Self>>() {
-//             Some(data) => data,
-//             None => {
-//                 chunk
-//                     .get_mut::<ChunkTypes>()
-//                     .unwrap()
-//                     .0
-//                     .insert(TypeId::of::<Self>());
-//                 let chunk = chunk.insert(ChunkData::<Self>::new(
-//                     chunk_size.pow(N.try_into().unwrap()),
-//                 ));
-//                 chunk.get_mut::<ChunkData<Self>>().unwrap()
-//             }
-//         };
-//         let mut binding = location;
-//         for (tile_i, tile) in tile_is.zip(tiles) {
-//             binding.insert(tile_i, tile);
-//         }
-//     }
-// }
+impl_tuple_tile_component!((A, a), (B, b));
+impl_tuple_tile_component!((A, a), (B, b), (C, c));
+impl_tuple_tile_component!((A, a), (B, b), (C, c), (D, d));
+impl_tuple_tile_component!((A, a), (B, b), (C, c), (D, d), (E, e));
+impl_tuple_tile_component!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f));
@@ -0,0 +1,243 @@
+use std::{marker::PhantomData, time::Duration};
+
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    ecs::{
+        component::Component, entity::Entity, query::With, schedule::IntoSystemConfigs,
+        system::Resource, world::World,
+    },
+    transform::{components::GlobalTransform, TransformSystem},
+    utils::{HashSet, Instant},
+};
+#[cfg(feature = "trace")]
+use bevy::{log::info_span, utils::tracing::field};
+
+use crate::{chunks::ChunkCoord, maps::TileMap};
+
+/// Recomputes derived data for a single chunk of a map.
+pub type RecomputeFn<const N: usize> = fn(&mut World, Entity, [i32; N]);
+
+/// A job registered with a [`RecomputeScheduler`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RecomputeJob(usize);
+
+/// Marks the entity (usually the camera) chunks closest to are recomputed first.
+#[derive(Component, Copy, Clone, Debug, Default)]
+pub struct RecomputeFocus;
+
+/// Spreads expensive per chunk work (lighting, distance fields, autotiling, etc.) across frames.
+/// # Note
+/// Chunks are marked dirty for a job, and each frame dirty chunks are recomputed closest to the [`RecomputeFocus`]
+/// first until `budget` runs out.  At least one chunk is recomputed every frame so the queue always makes progress.
+///
+/// Distances use chunk [`GlobalTransform`]s, chunks without them are recomputed after the rest.
+/// Marking a chunk dirty again before it's recomputed does nothing, so big edits only queue each chunk once.
+#[derive(Resource)]
+pub struct RecomputeScheduler<const N: usize> {
+    /// How much time can be spent recomputing chunks each frame.
+    pub budget: Duration,
+    jobs: Vec<RecomputeFn<N>>,
+    pending: HashSet<(RecomputeJob, Entity, [i32; N])>,
+}
+
+impl<const N: usize> RecomputeScheduler<N> {
+    /// Create a scheduler with a per frame time budget.
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            jobs: Vec::new(),
+            pending: Default::default(),
+        }
+    }
+
+    /// Register a job chunks can be marked dirty for.
+    pub fn register(&mut self, job: RecomputeFn<N>) -> RecomputeJob {
+        self.jobs.push(job);
+        RecomputeJob(self.jobs.len() - 1)
+    }
+
+    /// Queue a chunk to be recomputed by a job.
+    pub fn mark_dirty(&mut self, job: RecomputeJob, map_id: Entity, chunk_c: impl Into<[i32; N]>) {
+        self.pending.insert((job, map_id, chunk_c.into()));
+    }
+
+    /// Queue every chunk in an iterator to be recomputed by a job.
+    pub fn mark_dirty_batch(
+        &mut self,
+        job: RecomputeJob,
+        map_id: Entity,
+        chunk_cs: impl IntoIterator<Item = [i32; N]>,
+    ) {
+        self.pending
+            .extend(chunk_cs.into_iter().map(|chunk_c| (job, map_id, chunk_c)));
+    }
+
+    /// Check if a chunk is waiting to be recomputed by a job.
+    pub fn is_dirty(
+        &self,
+        job: RecomputeJob,
+        map_id: Entity,
+        chunk_c: impl Into<[i32; N]>,
+    ) -> bool {
+        self.pending.contains(&(job, map_id, chunk_c.into()))
+    }
+
+    /// Number of chunks waiting to be recomputed, over every job.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+/// Adds a [`RecomputeScheduler`] for maps with `N` dimensions.
+pub struct RecomputeSchedulerPlugin<const N: usize> {
+    /// How much time can be spent recomputing chunks each frame.
+    pub budget: Duration,
+    dims: PhantomData<[(); N]>,
+}
+
+impl<const N: usize> RecomputeSchedulerPlugin<N> {
+    /// Create a plugin with a per frame time budget.
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            dims: PhantomData,
+        }
+    }
+}
+
+impl<const N: usize> Default for RecomputeSchedulerPlugin<N> {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(2))
+    }
+}
+
+impl<const N: usize> Plugin for RecomputeSchedulerPlugin<N> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RecomputeScheduler::<N>::new(self.budget))
+            .add_systems(
+                PostUpdate,
+                run_recompute_jobs::<N>.after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Recomputes dirty chunks, closest to the [`RecomputeFocus`] first, until the budget runs out.
+pub fn run_recompute_jobs<const N: usize>(world: &mut World) {
+    let start = Instant::now();
+    let Some(mut scheduler) = world.get_resource_mut::<RecomputeScheduler<N>>() else {
+        return;
+    };
+    if scheduler.pending.is_empty() {
+        return;
+    }
+    let budget = scheduler.budget;
+    let jobs = scheduler.jobs.clone();
+    let pending = std::mem::take(&mut scheduler.pending);
+
+    let focus = world
+        .query_filtered::<&GlobalTransform, With<RecomputeFocus>>()
+        .iter(world)
+        .next()
+        .map(GlobalTransform::translation);
+
+    let mut queue: Vec<(f32, (RecomputeJob, Entity, [i32; N]))> = pending
+        .into_iter()
+        .map(|(job, map_id, chunk_c)| {
+            let distance = focus
+                .zip(
+                    world
+                        .get::<TileMap<N>>(map_id)
+                        .and_then(|map| map.get_from_chunk(ChunkCoord(chunk_c)))
+                        .and_then(|chunk_id| world.get::<GlobalTransform>(chunk_id)),
+                )
+                .map(|(focus, chunk_t)| chunk_t.translation().distance_squared(focus))
+                .unwrap_or(f32::INFINITY);
+            (distance, (job, map_id, chunk_c))
+        })
+        .collect();
+    queue.sort_by(|(d_1, k_1), (d_2, k_2)| d_1.total_cmp(d_2).then(k_1.cmp(k_2)));
+
+    #[cfg(feature = "trace")]
+    let _span = info_span!(
+        "run_recompute_jobs",
+        ran = field::Empty,
+        left = field::Empty
+    )
+    .entered();
+    #[cfg(feature = "trace")]
+    let queued = queue.len();
+
+    let mut queue = queue.into_iter();
+    for (_, (job, map_id, chunk_c)) in queue.by_ref() {
+        (jobs[job.0])(world, map_id, chunk_c);
+        if start.elapsed() >= budget {
+            break;
+        }
+    }
+    #[cfg(feature = "trace")]
+    _span
+        .record("ran", queued - queue.len())
+        .record("left", queue.len());
+
+    // Anything left over (and anything marked dirty by the jobs) waits for the next frame.
+    let mut scheduler = world.resource_mut::<RecomputeScheduler<N>>();
+    scheduler.pending.extend(queue.map(|(_, key)| key));
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::Resource, prelude::Transform, transform::TransformPlugin};
+
+    use crate::{
+        maps::{TileDims, UseTransforms},
+        testing,
+    };
+
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Recomputed(Vec<[i32; 2]>);
+
+    fn record(world: &mut World, _map_id: Entity, chunk_c: [i32; 2]) {
+        world.resource_mut::<Recomputed>().0.push(chunk_c);
+    }
+
+    #[test]
+    fn closest_chunks_first() {
+        let mut app = App::new();
+        app.add_plugins((
+            TransformPlugin,
+            RecomputeSchedulerPlugin::<2>::new(Duration::ZERO),
+        ))
+        .init_resource::<Recomputed>();
+        app.world_mut()
+            .spawn((Transform::from_xyz(20.0, 0.0, 0.0), RecomputeFocus));
+        let map_id = testing::spawn_map(app.world_mut(), 4, |map| {
+            map.insert((UseTransforms, TileDims([1.0, 1.0])));
+            for x in 0..4 {
+                map.insert_tile([x * 4, 0], 1u8);
+            }
+        });
+        app.update();
+
+        let mut scheduler = app.world_mut().resource_mut::<RecomputeScheduler<2>>();
+        let job = scheduler.register(record);
+        scheduler.mark_dirty_batch(job, map_id, [[0, 0], [1, 0], [2, 0], [3, 0]]);
+        scheduler.mark_dirty(job, map_id, [0, 0]);
+        scheduler.mark_dirty(job, map_id, [9, 9]);
+        assert_eq!(scheduler.pending(), 5);
+
+        // With no budget, one chunk is recomputed a frame.
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<Recomputed>().0, vec![[3, 0], [2, 0]]);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().resource::<Recomputed>().0,
+            vec![[3, 0], [2, 0], [1, 0], [0, 0], [9, 9]]
+        );
+        assert_eq!(app.world().resource::<RecomputeScheduler<2>>().pending(), 0);
+    }
+}
@@ -0,0 +1,239 @@
+use std::{cmp::Reverse, collections::BinaryHeap, marker::PhantomData};
+
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        entity::Entity,
+        schedule::{InternedScheduleLabel, ScheduleLabel},
+        system::Resource,
+        world::{Mut, World},
+    },
+    utils::HashMap,
+};
+
+use crate::{coords::calculate_chunk_coordinate, maps::TileMap};
+
+/// Runs a scheduled update for a single tile of a map.
+pub type TileUpdateFn<const N: usize> = fn(&mut World, Entity, [i32; N]);
+
+/// An update registered with a [`TileScheduler`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TileUpdate(usize);
+
+/// Runs updates for single tiles at a future tick (crops growing, fires burning out) without an entity
+/// and timer per tile.
+/// # Note
+/// Scheduled updates are grouped by the chunk of their tile, so the scheduler only keeps a small heap per chunk
+/// with pending updates.  Each tick the due updates run in order of their due tick, then map, then coordinate.
+///
+/// Updates can schedule more updates (ex: a crop scheduling its next growth stage), those are never run
+/// in the same tick.  Updates for maps that don't exist (or were despawned since) are dropped.
+#[derive(Resource)]
+pub struct TileScheduler<const N: usize> {
+    tick: u64,
+    updates: Vec<TileUpdateFn<N>>,
+    incoming: Vec<(u64, TileUpdate, Entity, [i32; N])>,
+    chunks: HashMap<(Entity, [i32; N]), BinaryHeap<Reverse<(u64, [i32; N], TileUpdate)>>>,
+}
+
+impl<const N: usize> Default for TileScheduler<N> {
+    fn default() -> Self {
+        Self {
+            tick: 0,
+            updates: Vec::new(),
+            incoming: Vec::new(),
+            chunks: Default::default(),
+        }
+    }
+}
+
+impl<const N: usize> TileScheduler<N> {
+    /// Register an update tiles can be scheduled for.
+    pub fn register(&mut self, update: TileUpdateFn<N>) -> TileUpdate {
+        self.updates.push(update);
+        TileUpdate(self.updates.len() - 1)
+    }
+
+    /// The number of ticks that have run.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Schedule an update for a tile `delay` ticks from now, delays of `0` and `1` both run on the next tick.
+    pub fn schedule(
+        &mut self,
+        update: TileUpdate,
+        map_id: Entity,
+        tile_c: impl Into<[i32; N]>,
+        delay: u64,
+    ) {
+        let due = self.tick + delay.max(1);
+        self.incoming.push((due, update, map_id, tile_c.into()));
+    }
+
+    /// Number of updates waiting to run.
+    pub fn pending(&self) -> usize {
+        self.incoming.len() + self.chunks.values().map(BinaryHeap::len).sum::<usize>()
+    }
+
+    /// Number of chunks with updates waiting to run.
+    pub fn pending_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Move newly scheduled updates into the heaps of their chunks.
+    fn sort_incoming(&mut self, world: &World) {
+        for (due, update, map_id, tile_c) in self.incoming.drain(..) {
+            let Some(map) = world.get::<TileMap<N>>(map_id) else {
+                continue;
+            };
+            let chunk_c = calculate_chunk_coordinate(tile_c, map.get_chunk_size());
+            self.chunks
+                .entry((map_id, chunk_c))
+                .or_default()
+                .push(Reverse((due, tile_c, update)));
+        }
+    }
+
+    /// Take every update that's due by the current tick, dropping the heaps of despawned maps.
+    fn take_due(&mut self, world: &World) -> Vec<(u64, Entity, [i32; N], TileUpdate)> {
+        let mut due = Vec::new();
+        self.chunks.retain(|(map_id, _), heap| {
+            if world.get::<TileMap<N>>(*map_id).is_none() {
+                return false;
+            }
+            while heap.peek().is_some_and(|Reverse(next)| next.0 <= self.tick) {
+                let Reverse((tick, tile_c, update)) = heap.pop().unwrap();
+                due.push((tick, *map_id, tile_c, update));
+            }
+            !heap.is_empty()
+        });
+        due.sort();
+        due
+    }
+}
+
+/// Adds a [`TileScheduler`] for maps with `N` dimensions, ticking once per run of a schedule.
+pub struct TileSchedulerPlugin<const N: usize> {
+    /// The schedule ticks run in.
+    pub schedule: InternedScheduleLabel,
+    dims: PhantomData<[(); N]>,
+}
+
+impl<const N: usize> TileSchedulerPlugin<N> {
+    /// Create a plugin that ticks in the given schedule (ex: [`bevy::app::FixedUpdate`]).
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+            dims: PhantomData,
+        }
+    }
+}
+
+impl<const N: usize> Default for TileSchedulerPlugin<N> {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl<const N: usize> Plugin for TileSchedulerPlugin<N> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TileScheduler<N>>()
+            .add_systems(self.schedule, run_tile_updates::<N>);
+    }
+}
+
+/// Advances the [`TileScheduler`] a tick and runs every update that's due.
+pub fn run_tile_updates<const N: usize>(world: &mut World) {
+    if !world.contains_resource::<TileScheduler<N>>() {
+        return;
+    }
+    let (updates, due) = world.resource_scope(|world, mut scheduler: Mut<TileScheduler<N>>| {
+        scheduler.tick += 1;
+        scheduler.sort_incoming(world);
+        (scheduler.updates.clone(), scheduler.take_due(world))
+    });
+
+    for (_, map_id, tile_c, update) in due {
+        // Earlier updates can despawn the map.
+        if world.get::<TileMap<N>>(map_id).is_none() {
+            continue;
+        }
+        (updates[update.0])(world, map_id, tile_c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::Resource;
+
+    use crate::commands::TileWorldExt;
+
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Burned(Vec<(u64, [i32; 2])>);
+
+    fn burn_out(world: &mut World, map_id: Entity, tile_c: [i32; 2]) {
+        let tick = world.resource::<TileScheduler<2>>().tick();
+        world.resource_mut::<Burned>().0.push((tick, tile_c));
+        // Fire spreads east until x = 2.
+        if tile_c[0] < 2 {
+            let mut scheduler = world.resource_mut::<TileScheduler<2>>();
+            scheduler.schedule(TileUpdate(0), map_id, [tile_c[0] + 1, tile_c[1]], 2);
+        }
+    }
+
+    #[test]
+    fn updates_run_when_due() {
+        let mut app = App::new();
+        app.add_plugins(TileSchedulerPlugin::<2>::default())
+            .init_resource::<Burned>();
+        let map_id = TileWorldExt::<2>::spawn_map(app.world_mut(), 4);
+
+        let mut scheduler = app.world_mut().resource_mut::<TileScheduler<2>>();
+        let burn = scheduler.register(burn_out);
+        scheduler.schedule(burn, map_id, [0, 0], 1);
+        scheduler.schedule(burn, map_id, [9, -9], 3);
+        scheduler.schedule(burn, Entity::PLACEHOLDER, [0, 0], 1);
+        assert_eq!(scheduler.pending(), 3);
+
+        for _ in 0..6 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().resource::<Burned>().0,
+            vec![(1, [0, 0]), (3, [1, 0]), (3, [9, -9]), (5, [2, 0])]
+        );
+        let scheduler = app.world().resource::<TileScheduler<2>>();
+        assert_eq!(scheduler.pending(), 0);
+        assert_eq!(scheduler.pending_chunks(), 0);
+    }
+
+    fn despawn_map(world: &mut World, map_id: Entity, _tile_c: [i32; 2]) {
+        world.resource_mut::<Burned>().0.push((0, [0, 0]));
+        TileWorldExt::<2>::despawn_map(world, map_id);
+    }
+
+    #[test]
+    fn updates_for_despawned_maps_are_dropped() {
+        let mut app = App::new();
+        app.add_plugins(TileSchedulerPlugin::<2>::default())
+            .init_resource::<Burned>();
+        let map_id = TileWorldExt::<2>::spawn_map(app.world_mut(), 4);
+
+        let mut scheduler = app.world_mut().resource_mut::<TileScheduler<2>>();
+        let burn = scheduler.register(burn_out);
+        let despawn = scheduler.register(despawn_map);
+        scheduler.schedule(despawn, map_id, [0, 0], 1);
+        scheduler.schedule(burn, map_id, [5, 0], 1);
+        scheduler.schedule(burn, map_id, [9, 9], 4);
+        app.update();
+        assert_eq!(app.world().resource::<Burned>().0, vec![(0, [0, 0])]);
+
+        app.update();
+        let scheduler = app.world().resource::<TileScheduler<2>>();
+        assert_eq!(scheduler.pending(), 0);
+        assert_eq!(scheduler.pending_chunks(), 0);
+    }
+}
@@ -0,0 +1,331 @@
+use std::{fmt, str::FromStr};
+
+use bevy::{
+    app::App,
+    ecs::{entity::Entity, system::Resource, world::World},
+    log::warn,
+    prelude::{Command, Commands, Name},
+    utils::HashMap,
+};
+
+use crate::{
+    chunks::ChunkData,
+    commands::{insert_tile, insert_tile_batch, take_tile, TempRemove},
+    coords::{calculate_tile_index, CoordIterator},
+    maps::TileMap,
+    queries::TileComponent,
+};
+
+/// Something that went wrong while running a tile script.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TileScriptError {
+    /// The line didn't start with a known command.
+    UnknownCommand(String),
+    /// The line had the wrong number of arguments for its command.
+    WrongArgumentCount {
+        /// The command on the line.
+        command: String,
+        /// Number of arguments the command takes.
+        expected: usize,
+        /// Number of arguments on the line.
+        found: usize,
+    },
+    /// No map has the given name or id.
+    UnknownMap(String),
+    /// No layer was registered with the given name.
+    UnknownLayer(String),
+    /// An argument couldn't be parsed.
+    InvalidArgument(String),
+}
+
+impl fmt::Display for TileScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TileScriptError::UnknownCommand(command) => write!(f, "unknown command `{command}`"),
+            TileScriptError::WrongArgumentCount {
+                command,
+                expected,
+                found,
+            } => write!(f, "`{command}` takes {expected} arguments, found {found}"),
+            TileScriptError::UnknownMap(map) => write!(f, "unknown map `{map}`"),
+            TileScriptError::UnknownLayer(layer) => write!(f, "unknown layer `{layer}`"),
+            TileScriptError::InvalidArgument(arg) => write!(f, "invalid argument `{arg}`"),
+        }
+    }
+}
+
+impl std::error::Error for TileScriptError {}
+
+type SetFn<const N: usize> =
+    fn(&mut World, Entity, [i32; N], Option<[i32; N]>, &str) -> Result<(), TileScriptError>;
+type ClearFn<const N: usize> = fn(&mut World, Entity, [i32; N]);
+type GetFn<const N: usize> = fn(&World, Entity, [i32; N]) -> Option<String>;
+
+#[derive(Clone, Copy)]
+pub(crate) struct ScriptLayer<const N: usize> {
+    pub(crate) set: SetFn<N>,
+    pub(crate) clear: ClearFn<N>,
+    #[cfg_attr(not(feature = "lua"), allow(dead_code))]
+    pub(crate) get: GetFn<N>,
+}
+
+/// The layers tile scripts can edit on maps with `N` dimensions, by name.
+#[derive(Resource)]
+pub struct TileScriptLayers<const N: usize> {
+    layers: HashMap<String, ScriptLayer<N>>,
+}
+
+impl<const N: usize> Default for TileScriptLayers<N> {
+    fn default() -> Self {
+        Self {
+            layers: Default::default(),
+        }
+    }
+}
+
+impl<const N: usize> TileScriptLayers<N> {
+    /// Allow scripts to edit tiles of type `T` under the given name, values are parsed with [`FromStr`]
+    /// and printed with [`ToString`].
+    pub fn register<T>(&mut self, name: impl Into<String>) -> &mut Self
+    where
+        T: TileComponent + FromStr + ToString + Clone,
+    {
+        self.layers.insert(
+            name.into(),
+            ScriptLayer {
+                set: set_tiles::<T, N>,
+                clear: clear_tile::<T, N>,
+                get: get_tile_string::<T, N>,
+            },
+        );
+        self
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Result<ScriptLayer<N>, TileScriptError> {
+        self.layers
+            .get(name)
+            .copied()
+            .ok_or_else(|| TileScriptError::UnknownLayer(name.into()))
+    }
+
+    /// Iterate over the names of registered layers.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.layers.keys().map(String::as_str)
+    }
+}
+
+fn set_tiles<T, const N: usize>(
+    world: &mut World,
+    map_id: Entity,
+    corner_1: [i32; N],
+    corner_2: Option<[i32; N]>,
+    value: &str,
+) -> Result<(), TileScriptError>
+where
+    T: TileComponent + FromStr + Clone,
+{
+    let value = T::from_str(value).map_err(|_| TileScriptError::InvalidArgument(value.into()))?;
+    let Some(mut map) = world.temp_remove::<TileMap<N>>(map_id) else {
+        return Err(TileScriptError::UnknownMap(map_id.to_string()));
+    };
+    match corner_2 {
+        None => {
+            insert_tile::<T, N>(&mut map, corner_1, value);
+        }
+        Some(corner_2) => {
+            let tile_cs: Vec<[i32; N]> = CoordIterator::new(corner_1, corner_2).collect();
+            let values = std::iter::repeat_n(value, tile_cs.len());
+            let _ = insert_tile_batch::<T, N>(&mut map, tile_cs, values);
+        }
+    }
+    Ok(())
+}
+
+fn clear_tile<T: TileComponent, const N: usize>(
+    world: &mut World,
+    map_id: Entity,
+    tile_c: [i32; N],
+) {
+    if let Some(mut map) = world.temp_remove::<TileMap<N>>(map_id) {
+        take_tile::<T, N>(&mut map, tile_c);
+    }
+}
+
+fn get_tile_string<T: TileComponent + ToString, const N: usize>(
+    world: &World,
+    map_id: Entity,
+    tile_c: [i32; N],
+) -> Option<String> {
+    let map = world.get::<TileMap<N>>(map_id)?;
+    let tile_i = calculate_tile_index(tile_c, map.get_chunk_size());
+    let chunk = world.get::<ChunkData<T>>(map.get_from_tile(tile_c)?)?;
+    chunk.get(tile_i).map(ToString::to_string)
+}
+
+/// Runs a tile script against the world, returning the number of commands run.
+/// # Note
+/// Commands are separated by new lines or `;`, and blank lines and lines starting with `#` are ignored.
+/// Maps are found by [`Name`], or by entity id (ex: `12v1`).
+/// * `set <map> <coord...> <layer> <value>` sets a single tile.
+/// * `fill <map> <corner_1...> <corner_2...> <layer> <value>` sets every tile between two corners (inclusive).
+/// * `clear <map> <coord...> <layer>` removes a single tile.
+///
+/// Commands before an error are still applied.
+pub fn run_tile_script<const N: usize>(
+    world: &mut World,
+    script: &str,
+) -> Result<usize, TileScriptError> {
+    let mut count = 0;
+    for line in script.split(['\n', ';']) {
+        let args: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = args.split_first() else {
+            continue;
+        };
+        if command.starts_with('#') {
+            continue;
+        }
+
+        let expected = match *command {
+            "set" => N + 3,
+            "fill" => 2 * N + 3,
+            "clear" => N + 2,
+            _ => return Err(TileScriptError::UnknownCommand(command.to_string())),
+        };
+        if args.len() != expected {
+            return Err(TileScriptError::WrongArgumentCount {
+                command: command.to_string(),
+                expected,
+                found: args.len(),
+            });
+        }
+
+        let map_id = find_map::<N>(world, args[0])?;
+        let corner_1 = parse_coord::<N>(&args[1..])?;
+        let corner_2 = match *command {
+            "fill" => Some(parse_coord::<N>(&args[N + 1..])?),
+            _ => None,
+        };
+        let layer_name = args[expected - 1 - usize::from(*command != "clear")];
+        let layer = script_layer::<N>(world, layer_name)?;
+
+        match *command {
+            "clear" => (layer.clear)(world, map_id, corner_1),
+            _ => (layer.set)(world, map_id, corner_1, corner_2, args[expected - 1])?,
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+pub(crate) fn script_layer<const N: usize>(
+    world: &World,
+    name: &str,
+) -> Result<ScriptLayer<N>, TileScriptError> {
+    world
+        .get_resource::<TileScriptLayers<N>>()
+        .ok_or_else(|| TileScriptError::UnknownLayer(name.into()))?
+        .get(name)
+}
+
+pub(crate) fn find_map<const N: usize>(
+    world: &mut World,
+    map: &str,
+) -> Result<Entity, TileScriptError> {
+    world
+        .query::<(Entity, Option<&Name>, &TileMap<N>)>()
+        .iter(world)
+        .find(|(id, name, _)| {
+            name.is_some_and(|name| name.as_str() == map) || id.to_string() == map
+        })
+        .map(|(id, _, _)| id)
+        .ok_or_else(|| TileScriptError::UnknownMap(map.into()))
+}
+
+fn parse_coord<const N: usize>(args: &[&str]) -> Result<[i32; N], TileScriptError> {
+    let mut tile_c = [0; N];
+    for (c, arg) in tile_c.iter_mut().zip(args) {
+        *c = arg
+            .parse()
+            .map_err(|_| TileScriptError::InvalidArgument(arg.to_string()))?;
+    }
+    Ok(tile_c)
+}
+
+struct TileScript<const N: usize>(String);
+
+impl<const N: usize> Command for TileScript<N> {
+    fn apply(self, world: &mut World) {
+        if let Err(err) = run_tile_script::<N>(world, &self.0) {
+            warn!("Tile script failed: {err}");
+        }
+    }
+}
+
+/// Queues a tile script to run, see [`run_tile_script`] for the syntax.
+/// Errors are logged instead of returned.
+pub fn apply_tile_script<const N: usize>(commands: &mut Commands, script: impl Into<String>) {
+    commands.queue(TileScript::<N>(script.into()));
+}
+
+/// Adds tile script layer registration to [`App`].
+pub trait TileScriptAppExt {
+    /// Allow scripts to edit tiles of type `T` on maps with `N` dimensions under the given name.
+    fn register_script_layer<T, const N: usize>(&mut self, name: impl Into<String>) -> &mut Self
+    where
+        T: TileComponent + FromStr + ToString + Clone;
+}
+
+impl TileScriptAppExt for App {
+    fn register_script_layer<T, const N: usize>(&mut self, name: impl Into<String>) -> &mut Self
+    where
+        T: TileComponent + FromStr + ToString + Clone,
+    {
+        self.world_mut()
+            .get_resource_or_insert_with(TileScriptLayers::<N>::default)
+            .register::<T>(name);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{testing, tiles::TileMapQuery};
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+
+    #[test]
+    fn script_edits_tiles() {
+        let mut world = World::new();
+        let mut layers = TileScriptLayers::<2>::default();
+        layers.register::<u8>("height");
+        world.insert_resource(layers);
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            map.insert(Name::new("overworld"));
+        });
+
+        let script = "# raise a hill
+            fill overworld -1 -1 1 1 height 3
+            set overworld 0 0 height 9; clear overworld 1 1 height";
+        assert_eq!(run_tile_script::<2>(&mut world, script), Ok(3));
+        assert_eq!(
+            run_tile_script::<2>(&mut world, "set overworld 0 0 water 1"),
+            Err(TileScriptError::UnknownLayer("water".into()))
+        );
+        assert_eq!(
+            run_tile_script::<2>(&mut world, "set overworld 0 height 1"),
+            Err(TileScriptError::WrongArgumentCount {
+                command: "set".into(),
+                expected: 5,
+                found: 4
+            })
+        );
+
+        let mut state = SystemState::<TileMapQuery<&u8>>::new(&mut world);
+        let tile_maps = state.get(&world);
+        let tiles = tile_maps.get_map(map_id).unwrap();
+        assert_eq!(tiles.get_at([0, 0]), Some(&9));
+        assert_eq!(tiles.get_at([-1, 1]), Some(&3));
+        assert_eq!(tiles.get_at([1, 1]), None);
+    }
+}
@@ -0,0 +1,136 @@
+use bevy::{
+    ecs::{component::Component, entity::Entity, system::Query},
+    prelude::Visibility,
+    utils::HashMap,
+};
+
+/// An ordered stack of 2d maps used as the floors of a building or dungeon,
+/// addressed with `[x, y, floor]` coordinates.
+/// # Note
+/// Floors are regular 2d maps, the stack only keeps track of their order and the links between them.
+#[derive(Component, Clone, Debug, Default)]
+pub struct MapStack {
+    floors: Vec<Entity>,
+    links: HashMap<[i32; 3], [i32; 3]>,
+}
+
+impl MapStack {
+    /// Create an empty stack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a map as the next floor up, returns the index of the floor.
+    pub fn push_floor(&mut self, map_id: Entity) -> usize {
+        self.floors.push(map_id);
+        self.floors.len() - 1
+    }
+
+    /// Get the map of a floor.
+    pub fn floor(&self, floor: usize) -> Option<Entity> {
+        self.floors.get(floor).copied()
+    }
+
+    /// Get the index of the floor for a map.
+    pub fn floor_of(&self, map_id: Entity) -> Option<usize> {
+        self.floors.iter().position(|floor| *floor == map_id)
+    }
+
+    /// Get the maps of every floor, from the bottom up.
+    pub fn floors(&self) -> &[Entity] {
+        &self.floors
+    }
+
+    /// Get the map and 2d tile coordinate for a `[x, y, floor]` coordinate.
+    pub fn resolve(&self, tile_c: impl Into<[i32; 3]>) -> Option<(Entity, [i32; 2])> {
+        let [x, y, floor] = tile_c.into();
+        let floor = usize::try_from(floor).ok()?;
+        Some((self.floor(floor)?, [x, y]))
+    }
+
+    /// Link one tile to another (usually on another floor), such as a staircase or portal.
+    pub fn link(&mut self, from_c: impl Into<[i32; 3]>, to_c: impl Into<[i32; 3]>) -> &mut Self {
+        self.links.insert(from_c.into(), to_c.into());
+        self
+    }
+
+    /// Link two tiles both ways.
+    pub fn link_both(
+        &mut self,
+        tile_c_1: impl Into<[i32; 3]>,
+        tile_c_2: impl Into<[i32; 3]>,
+    ) -> &mut Self {
+        let tile_c_1 = tile_c_1.into();
+        let tile_c_2 = tile_c_2.into();
+        self.link(tile_c_1, tile_c_2).link(tile_c_2, tile_c_1)
+    }
+
+    /// Remove the link leaving a tile, returning where it went.
+    pub fn unlink(&mut self, from_c: impl Into<[i32; 3]>) -> Option<[i32; 3]> {
+        self.links.remove(&from_c.into())
+    }
+
+    /// Get where the link on a tile leads, if there is one.
+    pub fn follow(&self, from_c: impl Into<[i32; 3]>) -> Option<[i32; 3]> {
+        self.links.get(&from_c.into()).copied()
+    }
+
+    /// Iterate over every link in the stack.
+    pub fn links(&self) -> impl Iterator<Item = ([i32; 3], [i32; 3])> + '_ {
+        self.links.iter().map(|(from_c, to_c)| (*from_c, *to_c))
+    }
+
+    /// Set the [`Visibility`] of every floor map, showing the floors where `visible` returns true.
+    /// # Note
+    /// Floors without a [`Visibility`] component are skipped.
+    pub fn set_visibility(
+        &self,
+        maps: &mut Query<&mut Visibility>,
+        visible: impl Fn(usize) -> bool,
+    ) {
+        for (floor, map_id) in self.floors.iter().enumerate() {
+            if let Ok(mut visibility) = maps.get_mut(*map_id) {
+                *visibility = if visible(floor) {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{system::SystemState, world::World};
+
+    use super::*;
+
+    #[test]
+    fn floors_and_links() {
+        let mut world = World::new();
+        let ground = world.spawn(Visibility::default()).id();
+        let upstairs = world.spawn(Visibility::default()).id();
+
+        let mut stack = MapStack::new();
+        assert_eq!(stack.push_floor(ground), 0);
+        assert_eq!(stack.push_floor(upstairs), 1);
+        stack.link_both([3, 4, 0], [3, 4, 1]);
+
+        assert_eq!(stack.resolve([3, 4, 1]), Some((upstairs, [3, 4])));
+        assert_eq!(stack.resolve([3, 4, 2]), None);
+        assert_eq!(stack.resolve([3, 4, -1]), None);
+        assert_eq!(stack.follow([3, 4, 0]), Some([3, 4, 1]));
+        assert_eq!(stack.follow([3, 4, 1]), Some([3, 4, 0]));
+        assert_eq!(stack.floor_of(upstairs), Some(1));
+
+        let mut state = SystemState::<Query<&mut Visibility>>::new(&mut world);
+        let mut maps = state.get_mut(&mut world);
+        stack.set_visibility(&mut maps, |floor| floor == 1);
+        assert_eq!(world.get::<Visibility>(ground), Some(&Visibility::Hidden));
+        assert_eq!(
+            world.get::<Visibility>(upstairs),
+            Some(&Visibility::Inherited)
+        );
+    }
+}
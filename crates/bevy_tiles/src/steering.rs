@@ -0,0 +1,163 @@
+use bevy::math::{Vec3, Vec3A};
+
+use crate::{convert::MapSpace, coords::CoordIterator, tiles::TileQuery};
+
+/// Interpolates a numeric layer between tile centers at a position in tile units (see [`MapSpace::world_to_tile_pos`]).
+/// Returns [`None`] if none of the surrounding tiles have data.
+/// # Note
+/// Empty tiles are skipped, weights are renormalized over the tiles that exist.
+pub fn sample_tile_pos<T, const N: usize>(
+    tiles: &TileQuery<'_, '_, '_, &T, N>,
+    tile_pos: [f32; N],
+) -> Option<f32>
+where
+    T: Copy + Into<f32> + Send + Sync + 'static,
+{
+    let mut base = [0; N];
+    let mut frac = [0.0; N];
+    for i in 0..N {
+        let p = tile_pos[i] - 0.5;
+        base[i] = p.floor() as i32;
+        frac[i] = p - p.floor();
+    }
+
+    let mut total = 0.0;
+    let mut weights = 0.0;
+    for corner in CoordIterator::new([0; N], [1; N]) {
+        let mut tile_c = base;
+        let mut weight = 1.0;
+        for i in 0..N {
+            tile_c[i] += corner[i];
+            weight *= if corner[i] == 1 {
+                frac[i]
+            } else {
+                1.0 - frac[i]
+            };
+        }
+        if let Some(value) = tiles.get_at(tile_c) {
+            total += weight * (*value).into();
+            weights += weight;
+        }
+    }
+    (weights > 0.0).then(|| total / weights)
+}
+
+/// Interpolates a numeric layer (such as one written by [`crate::commands::TileMapCommands::distance_field`])
+/// between tile centers at a world space position.
+pub fn sample_field<T, const N: usize>(
+    tiles: &TileQuery<'_, '_, '_, &T, N>,
+    space: &MapSpace<N>,
+    world: Vec3,
+) -> Option<f32>
+where
+    T: Copy + Into<f32> + Send + Sync + 'static,
+{
+    sample_tile_pos(tiles, space.world_to_tile_pos(world))
+}
+
+/// The world space gradient of a numeric layer at a world space position, in layer units per world unit.
+/// Returns [`None`] if the layer has no data around the position.
+pub fn field_gradient<T, const N: usize>(
+    tiles: &TileQuery<'_, '_, '_, &T, N>,
+    space: &MapSpace<N>,
+    world: Vec3,
+) -> Option<Vec3>
+where
+    T: Copy + Into<f32> + Send + Sync + 'static,
+{
+    let tile_pos = space.world_to_tile_pos(world);
+    let center = sample_tile_pos(tiles, tile_pos)?;
+
+    let mut local = Vec3::ZERO;
+    for i in 0..N.min(3) {
+        let sample = |offset: f32| {
+            let mut pos = tile_pos;
+            pos[i] += offset;
+            sample_tile_pos(tiles, pos)
+        };
+        // Central differences half a tile to each side, falling back to one sided ones at the edge of the data.
+        let per_tile = match (sample(-0.5), sample(0.5)) {
+            (Some(low), Some(high)) => high - low,
+            (Some(low), None) => 2.0 * (center - low),
+            (None, Some(high)) => 2.0 * (high - center),
+            (None, None) => 0.0,
+        };
+        local[i] = per_tile / space.pitch(i);
+    }
+
+    // Gradients transform by the inverse transpose, so non-uniform scales keep them perpendicular to contours.
+    let normal_matrix = space.transform.affine().matrix3.inverse().transpose();
+    Some(Vec3::from(normal_matrix * Vec3A::from(local)))
+}
+
+/// A unit world space direction that moves down a numeric layer, so agents on a distance field head to its sources.
+/// Returns [`Vec3::ZERO`] where the layer is flat or has no data.
+pub fn steer_downhill<T, const N: usize>(
+    tiles: &TileQuery<'_, '_, '_, &T, N>,
+    space: &MapSpace<N>,
+    world: Vec3,
+) -> Vec3
+where
+    T: Copy + Into<f32> + Send + Sync + 'static,
+{
+    field_gradient(tiles, space, world)
+        .map(|gradient| -gradient.normalize_or_zero())
+        .unwrap_or(Vec3::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::{system::SystemState, world::World},
+        transform::components::GlobalTransform,
+    };
+
+    use crate::{
+        maps::{MapOrigin, TileDims},
+        testing,
+        tiles::TileMapQuery,
+    };
+
+    use super::*;
+
+    #[test]
+    fn steer_toward_low_values() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            // Distance from the column x = 0.
+            for x in 0..6 {
+                for y in 0..6 {
+                    map.insert_tile([x, y], x as f32);
+                }
+            }
+        });
+
+        let space = MapSpace {
+            transform: GlobalTransform::IDENTITY,
+            dims: TileDims([2.0, 2.0]),
+            spacing: None,
+            origin: MapOrigin::default(),
+        };
+        let mut state = SystemState::<TileMapQuery<&f32>>::new(&mut world);
+        let tile_maps = state.get(&world);
+        let tiles = tile_maps.get_map(map_id).unwrap();
+
+        // Halfway between the centers of tiles 1 and 2.
+        assert_eq!(
+            sample_field(&tiles, &space, Vec3::new(4.0, 5.0, 0.0)),
+            Some(1.5)
+        );
+        assert_eq!(
+            field_gradient(&tiles, &space, Vec3::new(4.0, 5.0, 0.0)),
+            Some(Vec3::new(0.5, 0.0, 0.0))
+        );
+        assert_eq!(
+            steer_downhill(&tiles, &space, Vec3::new(6.3, 3.1, 0.0)),
+            Vec3::NEG_X
+        );
+        assert_eq!(
+            steer_downhill(&tiles, &space, Vec3::new(-10.0, 3.0, 0.0)),
+            Vec3::ZERO
+        );
+    }
+}
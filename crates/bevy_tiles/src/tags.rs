@@ -0,0 +1,386 @@
+use std::borrow::Cow;
+
+use bevy::{
+    ecs::{component::Component, system::Query},
+    utils::HashMap,
+};
+
+use crate::{
+    chunks::{ChunkCoord, ChunkData},
+    coords::{calculate_chunk_coordinate, calculate_tile_index, CoordIterator},
+    maps::TileMap,
+};
+
+/// A set of tiles stored as one bit per tile, chunk by chunk.
+/// # Note
+/// Boolean operations between layers run a word (64 tiles) at a time, so masks like
+/// "walkable AND explored AND NOT reserved" are cheap to build every frame.
+/// Layers can only be combined with layers using the same chunk size.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagLayer<const N: usize> {
+    chunk_size: usize,
+    chunks: HashMap<ChunkCoord<N>, Vec<u64>>,
+}
+
+impl<const N: usize> TagLayer<N> {
+    /// Create an empty layer with the given chunk size.
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size,
+            chunks: Default::default(),
+        }
+    }
+
+    /// Create a layer from the tiles of a map matching a predicate.
+    pub fn from_layer<T: Send + Sync + 'static>(
+        map: &TileMap<N>,
+        chunks: &Query<&ChunkData<T>>,
+        predicate: impl Fn(&T) -> bool,
+    ) -> Self {
+        let mut layer = Self::new(map.get_chunk_size());
+        for (chunk_c, chunk_id) in map.get_chunks() {
+            let Ok(chunk) = chunks.get(*chunk_id) else {
+                continue;
+            };
+            let mut bits = layer.empty_chunk();
+            for (tile_i, tile) in chunk.tiles.iter().enumerate() {
+                if tile.as_ref().is_some_and(&predicate) {
+                    bits[tile_i / 64] |= 1 << (tile_i % 64);
+                }
+            }
+            if bits.iter().any(|word| *word != 0) {
+                layer.chunks.insert(*chunk_c, bits);
+            }
+        }
+        layer
+    }
+
+    /// Get the chunk size of the layer.
+    pub fn get_chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Whether a tile is in the set.
+    pub fn get(&self, tile_c: impl Into<[i32; N]>) -> bool {
+        let tile_c = tile_c.into();
+        let chunk_c = ChunkCoord(calculate_chunk_coordinate(tile_c, self.chunk_size));
+        let tile_i = calculate_tile_index(tile_c, self.chunk_size);
+        self.chunks
+            .get(&chunk_c)
+            .is_some_and(|bits| bits[tile_i / 64] & (1 << (tile_i % 64)) != 0)
+    }
+
+    /// Add or remove a tile from the set.
+    pub fn set(&mut self, tile_c: impl Into<[i32; N]>, value: bool) -> &mut Self {
+        let tile_c = tile_c.into();
+        let chunk_c = ChunkCoord(calculate_chunk_coordinate(tile_c, self.chunk_size));
+        let tile_i = calculate_tile_index(tile_c, self.chunk_size);
+        let bit = 1 << (tile_i % 64);
+        if value {
+            let empty = self.empty_chunk();
+            self.chunks.entry(chunk_c).or_insert(empty)[tile_i / 64] |= bit;
+        } else if let Some(bits) = self.chunks.get_mut(&chunk_c) {
+            bits[tile_i / 64] &= !bit;
+        }
+        self
+    }
+
+    /// Add or remove every tile between two corners (inclusive).
+    pub fn fill(
+        &mut self,
+        corner_1: impl Into<[i32; N]>,
+        corner_2: impl Into<[i32; N]>,
+        value: bool,
+    ) -> &mut Self {
+        for tile_c in CoordIterator::new(corner_1, corner_2) {
+            self.set(tile_c, value);
+        }
+        self
+    }
+
+    /// Number of tiles in the set.
+    pub fn count(&self) -> usize {
+        self.chunks
+            .values()
+            .flatten()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.values().flatten().all(|word| *word == 0)
+    }
+
+    /// Iterate over the coordinates of every tile in the set.
+    pub fn iter(&self) -> impl Iterator<Item = [i32; N]> + '_ {
+        let chunk_size = self.chunk_size as i32;
+        self.chunks.iter().flat_map(move |(chunk_c, bits)| {
+            let min = chunk_c.map(|c| c * chunk_size);
+            let max = min.map(|c| c + chunk_size - 1);
+            CoordIterator::new(min, max).filter(move |tile_c| {
+                let tile_i = calculate_tile_index(*tile_c, chunk_size as usize);
+                bits[tile_i / 64] & (1 << (tile_i % 64)) != 0
+            })
+        })
+    }
+
+    /// Add every tile of another layer to this one.
+    pub fn union_with(&mut self, other: &TagLayer<N>) -> &mut Self {
+        self.assert_compatible(other);
+        for (chunk_c, other_bits) in other.chunks.iter() {
+            match self.chunks.get_mut(chunk_c) {
+                Some(bits) => combine(bits, other_bits, |a, b| a | b),
+                None => {
+                    self.chunks.insert(*chunk_c, other_bits.clone());
+                }
+            }
+        }
+        self
+    }
+
+    /// Remove every tile that isn't in another layer from this one.
+    pub fn intersect_with(&mut self, other: &TagLayer<N>) -> &mut Self {
+        self.assert_compatible(other);
+        self.chunks
+            .retain(|chunk_c, bits| match other.chunks.get(chunk_c) {
+                Some(other_bits) => {
+                    combine(bits, other_bits, |a, b| a & b);
+                    true
+                }
+                None => false,
+            });
+        self
+    }
+
+    /// Remove every tile in another layer from this one.
+    pub fn difference_with(&mut self, other: &TagLayer<N>) -> &mut Self {
+        self.assert_compatible(other);
+        for (chunk_c, bits) in self.chunks.iter_mut() {
+            if let Some(other_bits) = other.chunks.get(chunk_c) {
+                combine(bits, other_bits, |a, b| a & !b);
+            }
+        }
+        self
+    }
+
+    /// Run an operation between this layer and another only between two corners (inclusive),
+    /// tiles outside of the region are left as is.
+    pub fn apply_in(
+        &mut self,
+        op: TagOp,
+        other: &TagLayer<N>,
+        corner_1: impl Into<[i32; N]>,
+        corner_2: impl Into<[i32; N]>,
+    ) -> &mut Self {
+        self.assert_compatible(other);
+        let mut min = corner_1.into();
+        let mut max = corner_2.into();
+        for i in 0..N {
+            if min[i] > max[i] {
+                std::mem::swap(&mut min[i], &mut max[i]);
+            }
+        }
+
+        let chunk_size = self.chunk_size as i32;
+        let empty = self.empty_chunk();
+        for chunk_c in CoordIterator::new(
+            calculate_chunk_coordinate(min, self.chunk_size),
+            calculate_chunk_coordinate(max, self.chunk_size),
+        ) {
+            let other_bits = other.chunks.get(&ChunkCoord(chunk_c)).unwrap_or(&empty);
+            let bits = self
+                .chunks
+                .entry(ChunkCoord(chunk_c))
+                .or_insert_with(|| empty.clone());
+
+            // Mask of the tiles in this chunk that are inside the region.
+            let mut chunk_min = [0; N];
+            let mut chunk_max = [0; N];
+            let mut whole = true;
+            for i in 0..N {
+                let origin = chunk_c[i] * chunk_size;
+                chunk_min[i] = origin.max(min[i]);
+                chunk_max[i] = (origin + chunk_size - 1).min(max[i]);
+                whole &= chunk_min[i] == origin && chunk_max[i] == origin + chunk_size - 1;
+            }
+            let mask = if whole {
+                vec![u64::MAX; bits.len()]
+            } else {
+                let mut mask = empty.clone();
+                for tile_c in CoordIterator::new(chunk_min, chunk_max) {
+                    let tile_i = calculate_tile_index(tile_c, self.chunk_size);
+                    mask[tile_i / 64] |= 1 << (tile_i % 64);
+                }
+                mask
+            };
+
+            for ((word, other_word), mask) in bits.iter_mut().zip(other_bits).zip(mask) {
+                let result = match op {
+                    TagOp::Union => *word | other_word,
+                    TagOp::Intersect => *word & other_word,
+                    TagOp::Difference => *word & !other_word,
+                };
+                *word = (*word & !mask) | (result & mask);
+            }
+        }
+        self
+    }
+
+    /// Remove chunks without any tiles in the set.
+    pub fn shrink(&mut self) {
+        self.chunks
+            .retain(|_, bits| bits.iter().any(|word| *word != 0));
+    }
+
+    #[inline]
+    fn empty_chunk(&self) -> Vec<u64> {
+        vec![0; self.chunk_size.pow(N as u32).div_ceil(64)]
+    }
+
+    /// The bits of a chunk, one per tile in tile index order.
+    #[inline]
+    pub(crate) fn chunk_bits(&self, chunk_c: [i32; N]) -> Option<&[u64]> {
+        self.chunks.get(&ChunkCoord(chunk_c)).map(Vec::as_slice)
+    }
+
+    #[inline]
+    fn assert_compatible(&self, other: &TagLayer<N>) {
+        assert_eq!(
+            self.chunk_size, other.chunk_size,
+            "Tag layers must have the same chunk size to be combined."
+        );
+    }
+}
+
+#[inline]
+fn combine(bits: &mut [u64], other_bits: &[u64], op: impl Fn(u64, u64) -> u64) {
+    for (word, other_word) in bits.iter_mut().zip(other_bits) {
+        *word = op(*word, *other_word);
+    }
+}
+
+/// A boolean operation between two [`TagLayer`]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagOp {
+    /// Tiles in either layer.
+    Union,
+    /// Tiles in both layers.
+    Intersect,
+    /// Tiles in the first layer but not the second.
+    Difference,
+}
+
+/// Named [`TagLayer`]s for a map.  Add this to a [`TileMap`] to keep masks like "walkable" or "explored"
+/// next to the map they describe.
+#[derive(Component, Clone, Debug)]
+pub struct MapTags<const N: usize> {
+    chunk_size: usize,
+    layers: HashMap<Cow<'static, str>, TagLayer<N>>,
+}
+
+impl<const N: usize> MapTags<N> {
+    /// Create an empty set of tag layers, the chunk size should match the map's.
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size,
+            layers: Default::default(),
+        }
+    }
+
+    /// Get a tag layer by name.
+    pub fn get(&self, name: &str) -> Option<&TagLayer<N>> {
+        self.layers.get(name)
+    }
+
+    /// Get a tag layer by name, creating an empty one if needed.
+    pub fn get_or_insert(&mut self, name: impl Into<Cow<'static, str>>) -> &mut TagLayer<N> {
+        let chunk_size = self.chunk_size;
+        self.layers
+            .entry(name.into())
+            .or_insert_with(|| TagLayer::new(chunk_size))
+    }
+
+    /// Replace a tag layer, returning the old one.
+    pub fn insert(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        layer: TagLayer<N>,
+    ) -> Option<TagLayer<N>> {
+        self.layers.insert(name.into(), layer)
+    }
+
+    /// Remove a tag layer.
+    pub fn remove(&mut self, name: &str) -> Option<TagLayer<N>> {
+        self.layers.remove(name)
+    }
+
+    /// Iterate over the names of every tag layer.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.layers.keys().map(|name| name.as_ref())
+    }
+
+    /// Combine named layers into a new mask, starting with the first layer and applying each operation in order.
+    /// Missing layers are treated as empty.
+    pub fn combine<'a>(
+        &self,
+        first: &str,
+        ops: impl IntoIterator<Item = (TagOp, &'a str)>,
+    ) -> TagLayer<N> {
+        let mut mask = self
+            .get(first)
+            .cloned()
+            .unwrap_or_else(|| TagLayer::new(self.chunk_size));
+        let empty = TagLayer::new(self.chunk_size);
+        for (op, name) in ops {
+            let other = self.get(name).unwrap_or(&empty);
+            match op {
+                TagOp::Union => mask.union_with(other),
+                TagOp::Intersect => mask.intersect_with(other),
+                TagOp::Difference => mask.difference_with(other),
+            };
+        }
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boolean_masks() {
+        let mut tags = MapTags::<2>::new(8);
+        tags.get_or_insert("walkable")
+            .fill([-10, -10], [10, 10], true);
+        tags.get_or_insert("explored").fill([0, 0], [20, 20], true);
+        tags.get_or_insert("reserved").set([5, 5], true);
+
+        let mask = tags.combine(
+            "walkable",
+            [
+                (TagOp::Intersect, "explored"),
+                (TagOp::Difference, "reserved"),
+            ],
+        );
+        assert_eq!(mask.count(), 11 * 11 - 1);
+        assert!(mask.get([0, 0]));
+        assert!(!mask.get([5, 5]));
+        assert!(!mask.get([-1, 0]));
+        assert!(!mask.get([11, 11]));
+        assert_eq!(mask.iter().count(), mask.count());
+
+        let mut region = TagLayer::<2>::new(8);
+        region.apply_in(
+            TagOp::Union,
+            tags.get("walkable").unwrap(),
+            [-2, -2],
+            [1, 1],
+        );
+        assert_eq!(region.count(), 16);
+        region.apply_in(TagOp::Difference, &mask, [-20, -20], [20, 20]);
+        assert_eq!(region.count(), 16 - 4);
+        region.shrink();
+        assert!(!region.is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+use bevy::{
+    ecs::{component::Component, system::Query},
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    chunks::ChunkData,
+    coords::{calculate_tile_index, CoordIterator},
+    maps::TileMap,
+};
+
+/// Who owns each tile of a map (ex: the player id in a strategy game), add this to a [`TileMap`] for
+/// territory overlays.
+/// # Note
+/// The border tiles of every owner (owned tiles next to a tile with a different or no owner) are kept up to date
+/// as tiles change hands, so only the changed tiles and their neighbors are checked on each edit.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Territory<const N: usize> {
+    owners: HashMap<[i32; N], u32>,
+    areas: HashMap<u32, usize>,
+    borders: HashMap<u32, HashSet<[i32; N]>>,
+}
+
+impl<const N: usize> Territory<N> {
+    /// Create an empty territory.
+    pub fn new() -> Self {
+        Self {
+            owners: Default::default(),
+            areas: Default::default(),
+            borders: Default::default(),
+        }
+    }
+
+    /// Create a territory from the tiles of a map, tiles with a [`None`] owner aren't owned.
+    pub fn from_layer<T: Send + Sync + 'static>(
+        map: &TileMap<N>,
+        chunks: &Query<&ChunkData<T>>,
+        owner: impl Fn(&T) -> Option<u32>,
+    ) -> Self {
+        let mut territory = Self::new();
+        let chunk_size = map.get_chunk_size() as i32;
+        for (chunk_c, chunk_id) in map.get_chunks() {
+            let Ok(chunk) = chunks.get(*chunk_id) else {
+                continue;
+            };
+            let min = chunk_c.map(|c| c * chunk_size);
+            let max = min.map(|c| c + chunk_size - 1);
+            for tile_c in CoordIterator::new(min, max) {
+                if let Some(owner) = chunk
+                    .get(calculate_tile_index(tile_c, chunk_size as usize))
+                    .and_then(&owner)
+                {
+                    territory.set_owner(tile_c, Some(owner));
+                }
+            }
+        }
+        territory
+    }
+
+    /// Get the owner of a tile.
+    pub fn owner(&self, tile_c: impl Into<[i32; N]>) -> Option<u32> {
+        self.owners.get(&tile_c.into()).copied()
+    }
+
+    /// Change the owner of a tile, [`None`] makes it unowned.  Returns the previous owner.
+    pub fn set_owner(&mut self, tile_c: impl Into<[i32; N]>, owner: Option<u32>) -> Option<u32> {
+        let tile_c = tile_c.into();
+        let old = self.owner(tile_c);
+        if old == owner {
+            return old;
+        }
+
+        if let Some(old) = old {
+            self.remove_border(old, tile_c);
+            if let Some(area) = self.areas.get_mut(&old) {
+                *area -= 1;
+                if *area == 0 {
+                    self.areas.remove(&old);
+                }
+            }
+        }
+        match owner {
+            Some(owner) => {
+                self.owners.insert(tile_c, owner);
+                *self.areas.entry(owner).or_default() += 1;
+            }
+            None => {
+                self.owners.remove(&tile_c);
+            }
+        }
+
+        self.refresh_border(tile_c);
+        for neighbor in neighbors(tile_c) {
+            self.refresh_border(neighbor);
+        }
+        old
+    }
+
+    /// Change the owner of every tile between two corners (inclusive).
+    pub fn fill(
+        &mut self,
+        corner_1: impl Into<[i32; N]>,
+        corner_2: impl Into<[i32; N]>,
+        owner: Option<u32>,
+    ) -> &mut Self {
+        for tile_c in CoordIterator::new(corner_1, corner_2) {
+            self.set_owner(tile_c, owner);
+        }
+        self
+    }
+
+    /// Number of tiles an owner has.
+    pub fn area(&self, owner: u32) -> usize {
+        self.areas.get(&owner).copied().unwrap_or(0)
+    }
+
+    /// Iterate over every owner with at least one tile.
+    pub fn owners(&self) -> impl Iterator<Item = u32> + '_ {
+        self.areas.keys().copied()
+    }
+
+    /// Iterate over the tiles an owner has.
+    pub fn tiles(&self, owner: u32) -> impl Iterator<Item = [i32; N]> + '_ {
+        self.owners
+            .iter()
+            .filter(move |(_, o)| **o == owner)
+            .map(|(tile_c, _)| *tile_c)
+    }
+
+    /// Iterate over the border tiles of an owner's territory.
+    pub fn borders(&self, owner: u32) -> impl Iterator<Item = [i32; N]> + '_ {
+        self.borders.get(&owner).into_iter().flatten().copied()
+    }
+
+    /// Whether a tile is on the border of its owner's territory.
+    pub fn is_border(&self, tile_c: impl Into<[i32; N]>) -> bool {
+        let tile_c = tile_c.into();
+        self.owner(tile_c)
+            .and_then(|owner| self.borders.get(&owner))
+            .is_some_and(|borders| borders.contains(&tile_c))
+    }
+
+    fn refresh_border(&mut self, tile_c: [i32; N]) {
+        let Some(owner) = self.owner(tile_c) else {
+            return;
+        };
+        let border = neighbors(tile_c).any(|neighbor| self.owner(neighbor) != Some(owner));
+        if border {
+            self.borders.entry(owner).or_default().insert(tile_c);
+        } else {
+            self.remove_border(owner, tile_c);
+        }
+    }
+
+    fn remove_border(&mut self, owner: u32, tile_c: [i32; N]) {
+        if let Some(borders) = self.borders.get_mut(&owner) {
+            borders.remove(&tile_c);
+            if borders.is_empty() {
+                self.borders.remove(&owner);
+            }
+        }
+    }
+}
+
+impl Territory<2> {
+    /// Trace the outlines of an owner's territory as closed polylines, one per border (including holes).
+    /// # Note
+    /// Points are tile corners, the tile `[x, y]` spans from `[x, y]` to `[x + 1, y + 1]`.
+    /// Outer borders wind counter clockwise and holes clockwise, the last point connects back to the first.
+    pub fn border_loops(&self, owner: u32) -> Vec<Vec<[i32; 2]>> {
+        // Directed edges with the territory on their left.
+        let mut edges: HashMap<[i32; 2], Vec<[i32; 2]>> = HashMap::new();
+        for [x, y] in self.borders(owner) {
+            let sides = [
+                ([x, y - 1], [x, y], [x + 1, y]),
+                ([x + 1, y], [x + 1, y], [x + 1, y + 1]),
+                ([x, y + 1], [x + 1, y + 1], [x, y + 1]),
+                ([x - 1, y], [x, y + 1], [x, y]),
+            ];
+            for (neighbor, start, end) in sides {
+                if self.owner(neighbor) != Some(owner) {
+                    edges.entry(start).or_default().push(end);
+                }
+            }
+        }
+
+        let mut loops = Vec::new();
+        while let Some(start) = edges.keys().next().copied() {
+            let mut points = vec![start];
+            let mut current = start;
+            while let Some(next) = edges.get_mut(&current).and_then(Vec::pop) {
+                if edges.get(&current).is_some_and(Vec::is_empty) {
+                    edges.remove(&current);
+                }
+                if next == start {
+                    break;
+                }
+                points.push(next);
+                current = next;
+            }
+            loops.push(simplify_loop(points));
+        }
+        loops
+    }
+}
+
+/// Drop the points in the middle of straight lines.
+fn simplify_loop(points: Vec<[i32; 2]>) -> Vec<[i32; 2]> {
+    let len = points.len();
+    (0..len)
+        .filter(|i| {
+            let prev = points[(i + len - 1) % len];
+            let point = points[*i];
+            let next = points[(i + 1) % len];
+            (point[0] - prev[0]) * (next[1] - point[1])
+                != (point[1] - prev[1]) * (next[0] - point[0])
+        })
+        .map(|i| points[i])
+        .collect()
+}
+
+fn neighbors<const N: usize>(tile_c: [i32; N]) -> impl Iterator<Item = [i32; N]> {
+    (0..N).flat_map(move |axis| {
+        [-1, 1].into_iter().map(move |step| {
+            let mut neighbor = tile_c;
+            neighbor[axis] += step;
+            neighbor
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borders_follow_ownership() {
+        let mut territory = Territory::<2>::new();
+        territory.fill([0, 0], [2, 2], Some(1));
+        territory.set_owner([3, 0], Some(2));
+        assert_eq!(territory.area(1), 9);
+        assert_eq!(territory.borders(1).count(), 8);
+        assert!(!territory.is_border([1, 1]));
+        assert_eq!(territory.border_loops(1).len(), 1);
+        assert_eq!(territory.border_loops(1)[0].len(), 4);
+
+        // Losing the center punches a hole in the territory.
+        assert_eq!(territory.set_owner([1, 1], Some(2)), Some(1));
+        assert!(territory.is_border([1, 1]));
+        assert_eq!(territory.area(1), 8);
+        assert_eq!(territory.area(2), 2);
+        let loops = territory.border_loops(1);
+        assert_eq!(loops.len(), 2);
+        assert!(loops.iter().all(|points| points.len() == 4));
+        assert!(loops
+            .iter()
+            .any(|points| points.contains(&[1, 1]) && points.contains(&[2, 2])));
+
+        territory.fill([0, 0], [3, 2], None);
+        assert_eq!(territory.owners().count(), 0);
+        assert_eq!(territory.borders(1).count(), 0);
+    }
+}
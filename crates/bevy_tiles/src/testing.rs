@@ -0,0 +1,39 @@
+//! Setup shared by unit tests, the same as `tests/harness` but on a bare [`World`].
+
+use bevy::prelude::*;
+
+use crate::commands::{TileCommandExt, TileMapCommands};
+
+/// Queue commands and apply them right away, the same as the end of a system.
+pub fn apply<R>(world: &mut World, f: impl FnOnce(&mut Commands) -> R) -> R {
+    let out = {
+        let mut commands = world.commands();
+        f(&mut commands)
+    };
+    world.flush();
+    out
+}
+
+/// Queue commands for a 2d map and apply them right away.
+pub fn apply_map<R>(
+    world: &mut World,
+    map_id: Entity,
+    f: impl FnOnce(&mut TileMapCommands<'_, 2>) -> R,
+) -> R {
+    apply(world, |commands| {
+        f(&mut TileCommandExt::<2>::tile_map(commands, map_id).unwrap())
+    })
+}
+
+/// Spawn a 2d map, queue commands for it, and apply them right away.
+pub fn spawn_map(
+    world: &mut World,
+    chunk_size: usize,
+    f: impl FnOnce(&mut TileMapCommands<'_, 2>),
+) -> Entity {
+    apply(world, |commands| {
+        let mut map = TileCommandExt::<2>::spawn_map(commands, chunk_size);
+        f(&mut map);
+        map.id()
+    })
+}
@@ -0,0 +1,173 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        entity::Entity,
+        event::{Event, EventWriter},
+        schedule::{InternedScheduleLabel, ScheduleLabel},
+        system::{Query, ResMut, Resource},
+    },
+};
+
+use crate::{
+    chunks::ChunkData,
+    coords::calculate_tile_coordinate,
+    maps::{MapSeed, TileMap},
+    noise::TileRng,
+};
+
+/// Sent for every tile picked by a random tick (ex: grass spreading, ice melting).
+#[derive(Event)]
+pub struct RandomTick<T, const N: usize = 2> {
+    /// The map the tile is in.
+    pub map_id: Entity,
+    /// The coordinate of the tile.
+    pub tile_c: [i32; N],
+    layer: PhantomData<fn() -> T>,
+}
+
+/// Settings and state for random ticks of the `T` layer of maps with `N` dimensions.
+#[derive(Resource)]
+pub struct RandomTicks<T, const N: usize = 2> {
+    /// How many tiles are picked from each chunk per tick.
+    pub per_chunk: usize,
+    tick: u64,
+    layer: PhantomData<fn() -> T>,
+}
+
+impl<T, const N: usize> RandomTicks<T, N> {
+    /// Create settings that pick `per_chunk` tiles from each chunk per tick.
+    pub fn new(per_chunk: usize) -> Self {
+        Self {
+            per_chunk,
+            tick: 0,
+            layer: PhantomData,
+        }
+    }
+
+    /// The number of ticks that have run.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+}
+
+/// Picks random tiles of a layer every tick and sends a [`RandomTick`] for each of them, so slow mechanics
+/// can touch a few tiles of every chunk instead of scanning the whole map.
+/// # Note
+/// Each tick, `per_chunk` tiles are picked (with replacement) from the tiles of each chunk that have `T` data.
+/// Picks are deterministic, they only depend on the tick count, the chunk, and the map's [`MapSeed`].
+pub struct RandomTickPlugin<T, const N: usize = 2> {
+    /// How many tiles are picked from each chunk per tick.
+    pub per_chunk: usize,
+    /// The schedule ticks run in.
+    pub schedule: InternedScheduleLabel,
+    layer: PhantomData<fn() -> T>,
+}
+
+impl<T, const N: usize> RandomTickPlugin<T, N> {
+    /// Create a plugin that picks `per_chunk` tiles from each chunk every [`Update`].
+    pub fn new(per_chunk: usize) -> Self {
+        Self {
+            per_chunk,
+            schedule: Update.intern(),
+            layer: PhantomData,
+        }
+    }
+
+    /// Tick in a different schedule (ex: [`bevy::app::FixedUpdate`]).
+    pub fn in_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.schedule = schedule.intern();
+        self
+    }
+}
+
+impl<T: Send + Sync + 'static, const N: usize> Plugin for RandomTickPlugin<T, N> {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RandomTick<T, N>>()
+            .insert_resource(RandomTicks::<T, N>::new(self.per_chunk))
+            .add_systems(self.schedule, random_ticks::<T, N>);
+    }
+}
+
+/// Advances the [`RandomTicks`] for a layer and sends a [`RandomTick`] for every tile it picks.
+pub fn random_ticks<T: Send + Sync + 'static, const N: usize>(
+    mut ticks: ResMut<RandomTicks<T, N>>,
+    maps: Query<(Entity, &TileMap<N>, Option<&MapSeed>)>,
+    chunks: Query<&ChunkData<T>>,
+    mut picked: EventWriter<RandomTick<T, N>>,
+) {
+    ticks.tick += 1;
+    if ticks.per_chunk == 0 {
+        return;
+    }
+
+    for (map_id, map, seed) in maps.iter() {
+        let seed = seed.copied().unwrap_or_default().mix(ticks.tick);
+        for (chunk_c, chunk_id) in map.get_chunks() {
+            let Ok(data) = chunks.get(*chunk_id) else {
+                continue;
+            };
+            if data.get_count() == 0 {
+                continue;
+            }
+            let occupied: Vec<usize> = data
+                .iter()
+                .enumerate()
+                .filter_map(|(tile_i, tile)| tile.map(|_| tile_i))
+                .collect();
+
+            let mut rng = TileRng::for_chunk(seed, chunk_c.0);
+            for _ in 0..ticks.per_chunk {
+                let tile_i = occupied[rng.range(0..occupied.len() as i32) as usize];
+                picked.send(RandomTick {
+                    map_id,
+                    tile_c: calculate_tile_coordinate(chunk_c.0, tile_i, map.get_chunk_size()),
+                    layer: PhantomData,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+
+    use crate::{coords::CoordIterator, testing};
+
+    use super::*;
+
+    fn run_ticks(seed: u64) -> Vec<[i32; 2]> {
+        let mut app = App::new();
+        app.add_plugins(RandomTickPlugin::<u8, 2>::new(3));
+        testing::spawn_map(app.world_mut(), 4, |map| {
+            map.insert(MapSeed(seed));
+            // One full chunk, and one with a single grass tile.
+            map.insert_tile_batch_cloned(CoordIterator::new([0, 0], [3, 3]), 1u8);
+            map.insert_tile([-2, 5], 1u8);
+            map.insert_tile([-3, 6], 1u16);
+        });
+        app.update();
+        app.update();
+
+        let events = app.world().resource::<Events<RandomTick<u8, 2>>>();
+        events
+            .iter_current_update_events()
+            .map(|tick| tick.tile_c)
+            .collect()
+    }
+
+    #[test]
+    fn picks_occupied_tiles() {
+        let picked = run_ticks(7);
+        assert_eq!(picked.len(), 6);
+        assert_eq!(
+            picked.iter().filter(|tile_c| **tile_c == [-2, 5]).count(),
+            3
+        );
+        assert!(picked.iter().all(|tile_c| *tile_c == [-2, 5]
+            || (0..4).contains(&tile_c[0]) && (0..4).contains(&tile_c[1])));
+        assert_eq!(run_ticks(7), picked);
+    }
+}
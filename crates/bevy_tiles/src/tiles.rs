@@ -1,5 +1,3 @@
-use bevy::prelude::*;
-
 mod tile_query;
 
 pub use tile_query::*;
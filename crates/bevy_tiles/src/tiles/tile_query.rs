@@ -1,7 +1,16 @@
-use bevy::ecs::{entity::Entity, query::With, system::SystemParam};
+#[cfg(feature = "strict-safety")]
+use std::marker::PhantomData;
+
+use bevy::ecs::{
+    entity::Entity,
+    query::{QueryFilter, With},
+    system::SystemParam,
+};
+#[cfg(feature = "strict-safety")]
+use bevy::{ecs::query::WorldQuery, utils::HashMap};
 
 use crate::{
-    chunks::{ChunkMapQuery, ChunkQuery, InMap},
+    chunks::{ChunkMapQuery, ChunkQuery, InMap, MapQueryError},
     coords::{
         calculate_chunk_coordinate, calculate_tile_coordinate, calculate_tile_index,
         max_tile_index, CoordIterator,
@@ -12,30 +21,76 @@ use crate::{
 /// Used to query individual tiles from a tile map.
 /// This query also implicitly queries chunks and maps
 /// in order to properly resolve tiles.
+/// # Note
+/// `MF` filters the maps that can be queried (ex: `With<GameLayer>`), so
+/// [`TileMapQuery::get_single_map`] can resolve one map out of many.
 #[derive(SystemParam)]
-pub struct TileMapQuery<'w, 's, Q, const N: usize = 2>
+pub struct TileMapQuery<'w, 's, Q, MF = (), const N: usize = 2>
 where
     Q: TileData + 'static,
+    MF: QueryFilter + 'static,
 {
-    chunk_q: ChunkMapQuery<'w, 's, <Q as TileDataQuery>::Source, With<InMap>, N>,
+    chunk_q: ChunkMapQuery<'w, 's, <Q as TileDataQuery>::Source, With<InMap>, MF, (), N>,
 }
 
-impl<'w, 's, Q, const N: usize> TileMapQuery<'w, 's, Q, N>
+impl<'w, 's, Q, MF, const N: usize> TileMapQuery<'w, 's, Q, MF, N>
 where
     Q: TileData + 'static,
+    MF: QueryFilter + 'static,
 {
     /// Gets the query for a given map.
-    pub fn get_map(&self, map_id: Entity) -> Option<TileQuery<'_, '_, 's, Q::ReadOnly, N>> {
+    pub fn get_map(
+        &self,
+        map_id: Entity,
+    ) -> Result<TileQuery<'_, '_, 's, Q::ReadOnly, N>, MapQueryError> {
         let chunk_q = self.chunk_q.get_map(map_id)?;
 
-        Some(TileQuery { chunk_q })
+        Ok(TileQuery { chunk_q })
     }
 
     /// Gets the query for a given map.
-    pub fn get_map_mut(&mut self, map_id: Entity) -> Option<TileQuery<'_, '_, 's, Q, N>> {
+    pub fn get_map_mut(
+        &mut self,
+        map_id: Entity,
+    ) -> Result<TileQuery<'_, '_, 's, Q, N>, MapQueryError> {
         let chunk_q = self.chunk_q.get_map_mut(map_id)?;
 
-        Some(TileQuery { chunk_q })
+        Ok(TileQuery { chunk_q })
+    }
+
+    /// Gets the query for the only map, returns an error if there isn't exactly one.
+    /// # Note
+    /// The returned query holds onto the map, keep it around for the rest of the system
+    /// instead of looking the map up again.
+    pub fn get_single_map(&self) -> Result<TileQuery<'_, '_, 's, Q::ReadOnly, N>, MapQueryError> {
+        let chunk_q = self.chunk_q.get_single_map()?;
+
+        Ok(TileQuery { chunk_q })
+    }
+
+    /// Gets the query for the only map, returns an error if there isn't exactly one.
+    pub fn get_single_map_mut(&mut self) -> Result<TileQuery<'_, '_, 's, Q, N>, MapQueryError> {
+        let chunk_q = self.chunk_q.get_single_map_mut()?;
+
+        Ok(TileQuery { chunk_q })
+    }
+
+    /// Gets the query for the only map.
+    /// # Panics
+    /// Panics if there isn't exactly one map.
+    pub fn single_map(&self) -> TileQuery<'_, '_, 's, Q::ReadOnly, N> {
+        TileQuery {
+            chunk_q: self.chunk_q.single_map(),
+        }
+    }
+
+    /// Gets the query for the only map.
+    /// # Panics
+    /// Panics if there isn't exactly one map.
+    pub fn single_map_mut(&mut self) -> TileQuery<'_, '_, 's, Q, N> {
+        TileQuery {
+            chunk_q: self.chunk_q.single_map_mut(),
+        }
     }
 }
 
@@ -65,6 +120,12 @@ where
         }
     }
 
+    /// Get the size of chunks in the queried map.
+    #[inline]
+    pub fn get_chunk_size(&self) -> usize {
+        self.chunk_q.map.get_chunk_size()
+    }
+
     /// Gets the readonly query item for the given tile.
     pub fn get_at(
         &self,
@@ -94,6 +155,8 @@ where
     /// Gets the query item for the given tile.
     /// # Safety
     /// This function makes it possible to violate Rust's aliasing guarantees: please use responsibly.
+    /// # Note
+    /// Prefer [`TileQuery::get_at_mut`] where possible.
     pub unsafe fn get_at_unchecked(
         &self,
         tile_c: impl Into<[i32; N]>,
@@ -115,8 +178,18 @@ where
     ) -> TileQueryIter<'_, 's, Q::ReadOnly, N> {
         let corner_1 = corner_1.into();
         let corner_2 = corner_2.into();
+        #[cfg(not(feature = "strict-safety"))]
         // SAFETY: This thing is uses manual mem management
-        unsafe { TileQueryIter::from_owned(self.to_readonly(), corner_1, corner_2) }
+        return unsafe { TileQueryIter::from_owned(self.to_readonly(), corner_1, corner_2) };
+        #[cfg(feature = "strict-safety")]
+        return {
+            let chunk_size = self.get_chunk_size();
+            let chunks = self.chunk_q.collect_in(
+                calculate_chunk_coordinate(corner_1, chunk_size),
+                calculate_chunk_coordinate(corner_2, chunk_size),
+            );
+            TileQueryIter::from_chunks(chunks, chunk_size, corner_1, corner_2)
+        };
     }
 
     /// Iterate over all the tiles in a given space, starting at `corner_1`
@@ -128,8 +201,18 @@ where
     ) -> TileQueryIter<'_, 's, Q, N> {
         let corner_1 = corner_1.into();
         let corner_2 = corner_2.into();
+        #[cfg(not(feature = "strict-safety"))]
         // SAFETY: This thing is uses manual mem management
-        unsafe { TileQueryIter::from_owned(self.reborrow(), corner_1, corner_2) }
+        return unsafe { TileQueryIter::from_owned(self.reborrow(), corner_1, corner_2) };
+        #[cfg(feature = "strict-safety")]
+        return {
+            let chunk_size = self.get_chunk_size();
+            let chunks = self.chunk_q.collect_in_mut(
+                calculate_chunk_coordinate(corner_1, chunk_size),
+                calculate_chunk_coordinate(corner_2, chunk_size),
+            );
+            TileQueryIter::from_chunks(chunks, chunk_size, corner_1, corner_2)
+        };
     }
 
     /// Iter all tiles in a given chunk.
@@ -208,8 +291,10 @@ where
 // Everything below here is astoundingly unsafe but I think it's sound
 // If we're iterating over a readonly query, we're manually managing the lifetime of
 // the readonly query by making the TileQueryIter own it as a reference.
+// With the `strict-safety` feature the items are collected up front instead.
 
 /// Iterates over all the tiles in a region.
+#[cfg(not(feature = "strict-safety"))]
 pub struct TileQueryIter<'a, 's, Q, const N: usize>
 where
     Q: TileData + 'static,
@@ -217,6 +302,18 @@ where
     coord_iter: CoordIterator<N>,
     tile_q: TileQuery<'a, 'a, 's, Q, N>,
 }
+
+/// Iterates over all the tiles in a region.
+#[cfg(feature = "strict-safety")]
+pub struct TileQueryIter<'a, 's, Q, const N: usize>
+where
+    Q: TileData + 'static,
+{
+    tiles: std::vec::IntoIter<<Q as TileDataQuery>::Item<'a>>,
+    marker: PhantomData<fn() -> (&'s (), [(); N])>,
+}
+
+#[cfg(not(feature = "strict-safety"))]
 impl<'a, 's, Q, const N: usize> TileQueryIter<'a, 's, Q, N>
 where
     Q: TileData + 'static,
@@ -233,6 +330,40 @@ where
     }
 }
 
+#[cfg(feature = "strict-safety")]
+impl<'a, 's, Q, const N: usize> TileQueryIter<'a, 's, Q, N>
+where
+    Q: TileData + 'static,
+{
+    fn from_chunks(
+        chunks: Vec<(
+            [i32; N],
+            <<Q as TileDataQuery>::Source as WorldQuery>::Item<'a>,
+        )>,
+        chunk_size: usize,
+        corner_1: [i32; N],
+        corner_2: [i32; N],
+    ) -> Self {
+        let mut chunks: HashMap<[i32; N], Vec<Option<<Q as TileDataQuery>::Item<'a>>>> = chunks
+            .into_iter()
+            .map(|(chunk_c, chunk)| (chunk_c, Q::get_all(chunk)))
+            .collect();
+        let tiles: Vec<_> = CoordIterator::new(corner_1, corner_2)
+            .filter_map(|tile_c| {
+                chunks
+                    .get_mut(&calculate_chunk_coordinate(tile_c, chunk_size))?
+                    .get_mut(calculate_tile_index(tile_c, chunk_size))?
+                    .take()
+            })
+            .collect();
+        Self {
+            tiles: tiles.into_iter(),
+            marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(not(feature = "strict-safety"))]
 impl<'a, 's, Q, const N: usize> Iterator for TileQueryIter<'a, 's, Q, N>
 where
     Q: TileData + 'static,
@@ -261,3 +392,94 @@ where
         None
     }
 }
+
+#[cfg(feature = "strict-safety")]
+impl<'a, 's, Q, const N: usize> Iterator for TileQueryIter<'a, 's, Q, N>
+where
+    Q: TileData + 'static,
+{
+    type Item = <Q as TileDataQuery>::Item<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tiles.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{component::Component, system::SystemState, world::World};
+
+    use crate::testing;
+
+    use super::*;
+
+    #[test]
+    fn single_map_errors() {
+        let mut world = World::new();
+        let mut state = SystemState::<TileMapQuery<&u8>>::new(&mut world);
+        assert_eq!(
+            state.get(&world).get_single_map().err(),
+            Some(MapQueryError::NoMaps)
+        );
+
+        let map_ids = [0, 1].map(|_| {
+            testing::spawn_map(&mut world, 4, |map| {
+                map.insert_tile([0, 0], 1u8);
+            })
+        });
+        let tile = world.spawn_empty().id();
+        let tile_maps = state.get(&world);
+        assert_eq!(
+            tile_maps.get_single_map().err(),
+            Some(MapQueryError::MultipleMaps)
+        );
+        assert_eq!(
+            tile_maps.get_map(tile).err(),
+            Some(MapQueryError::NotAMap(tile))
+        );
+
+        world.despawn(map_ids[1]);
+        let tile_maps = state.get(&world);
+        assert_eq!(tile_maps.single_map().get_at([0, 0]), Some(&1));
+    }
+
+    #[derive(Component)]
+    struct GameLayer;
+
+    #[test]
+    fn map_filter_selects_map() {
+        let mut world = World::new();
+        for (value, game_layer) in [(1u8, false), (2u8, true), (3u8, false)] {
+            testing::spawn_map(&mut world, 4, |map| {
+                map.insert_tile([0, 0], value);
+                if game_layer {
+                    map.insert(GameLayer);
+                }
+            });
+        }
+
+        let mut state = SystemState::<TileMapQuery<&u8, With<GameLayer>>>::new(&mut world);
+        let tile_maps = state.get(&world);
+        assert_eq!(tile_maps.single_map().get_at([0, 0]), Some(&2));
+    }
+
+    #[test]
+    fn iter_in_across_chunks() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            for x in -2..3 {
+                map.insert_tile([x, 3], x);
+                map.insert_tile([x, 5], 10 * x);
+            }
+        });
+
+        let mut state = SystemState::<TileMapQuery<&mut i32>>::new(&mut world);
+        let mut tile_maps = state.get_mut(&mut world);
+        let mut tiles = tile_maps.get_map_mut(map_id).unwrap();
+        for tile in tiles.iter_in_mut([-1, 3], [2, 5]) {
+            *tile += 1;
+        }
+        let values: Vec<i32> = tiles.iter_in([2, 5], [-2, 3]).copied().collect();
+        assert_eq!(values, vec![-2, 0, 1, 2, 3, -20, -9, 1, 11, 21]);
+    }
+}
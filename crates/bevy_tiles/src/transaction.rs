@@ -0,0 +1,233 @@
+use std::fmt::Display;
+
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    log::warn,
+    prelude::{Command, Commands},
+};
+
+use crate::{
+    chunks::{ChunkData, MapQueryError},
+    commands::{insert_tile, take_tile, TempRemove},
+    coords::calculate_tile_index,
+    maps::TileMap,
+    queries::TileComponent,
+};
+
+type UndoFn = Box<dyn FnOnce(&mut World)>;
+
+/// A set of tile edits across any number of layers and maps that can be rolled back, see [`try_edit`].
+pub struct TileEdit<'w> {
+    world: &'w mut World,
+    undo: Vec<UndoFn>,
+}
+
+impl<'w> TileEdit<'w> {
+    /// Get readonly access to the world being edited.
+    /// # Note
+    /// Edits have to go through the transaction to be rolled back, so mutable access isn't given out.
+    pub fn world(&self) -> &World {
+        self.world
+    }
+
+    /// Number of edits made so far.
+    pub fn len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Whether no edits have been made.
+    pub fn is_empty(&self) -> bool {
+        self.undo.is_empty()
+    }
+
+    /// Get a tile from a map.
+    pub fn get<T: TileComponent, const N: usize>(
+        &self,
+        map_id: Entity,
+        tile_c: impl Into<[i32; N]>,
+    ) -> Option<&T> {
+        let map = self.world.get::<TileMap<N>>(map_id)?;
+        let tile_c = tile_c.into();
+        self.world
+            .get::<ChunkData<T>>(map.get_from_tile(tile_c)?)?
+            .get(calculate_tile_index(tile_c, map.get_chunk_size()))
+    }
+
+    /// Insert a tile into a map, returning the replaced value.
+    /// Fails without recording an edit if the entity isn't a map.
+    pub fn insert<T: TileComponent + Clone, const N: usize>(
+        &mut self,
+        map_id: Entity,
+        tile_c: impl Into<[i32; N]>,
+        value: T,
+    ) -> Result<Option<T>, MapQueryError> {
+        let tile_c = tile_c.into();
+        let mut map = self
+            .world
+            .temp_remove::<TileMap<N>>(map_id)
+            .ok_or(MapQueryError::NotAMap(map_id))?;
+        let replaced = insert_tile::<T, N>(&mut map, tile_c, value);
+        drop(map);
+
+        let old = replaced.clone();
+        self.undo.push(Box::new(move |world| {
+            if let Some(mut map) = world.temp_remove::<TileMap<N>>(map_id) {
+                match old {
+                    Some(old) => {
+                        insert_tile::<T, N>(&mut map, tile_c, old);
+                    }
+                    None => {
+                        take_tile::<T, N>(&mut map, tile_c);
+                    }
+                }
+            }
+        }));
+        Ok(replaced)
+    }
+
+    /// Remove a tile from a map, returning its value.
+    /// Fails without recording an edit if the entity isn't a map.
+    pub fn remove<T: TileComponent + Clone, const N: usize>(
+        &mut self,
+        map_id: Entity,
+        tile_c: impl Into<[i32; N]>,
+    ) -> Result<Option<T>, MapQueryError> {
+        let tile_c = tile_c.into();
+        let mut map = self
+            .world
+            .temp_remove::<TileMap<N>>(map_id)
+            .ok_or(MapQueryError::NotAMap(map_id))?;
+        let Some(taken) = take_tile::<T, N>(&mut map, tile_c) else {
+            return Ok(None);
+        };
+        drop(map);
+
+        let old = taken.clone();
+        self.undo.push(Box::new(move |world| {
+            if let Some(mut map) = world.temp_remove::<TileMap<N>>(map_id) {
+                insert_tile::<T, N>(&mut map, tile_c, old);
+            }
+        }));
+        Ok(Some(taken))
+    }
+
+    /// Check that a tile exists in a map, useful for validating invariants before returning from [`try_edit`].
+    pub fn contains<T: TileComponent, const N: usize>(
+        &self,
+        map_id: Entity,
+        tile_c: impl Into<[i32; N]>,
+    ) -> bool {
+        self.get::<T, N>(map_id, tile_c).is_some()
+    }
+
+    fn rollback(self) {
+        for undo in self.undo.into_iter().rev() {
+            undo(self.world);
+        }
+    }
+}
+
+/// Run a set of tile edits, undoing all of them if the closure returns an error.
+/// # Note
+/// Edits are undone in reverse order by writing back the values they replaced, so only edits made through the
+/// [`TileEdit`] are rolled back.  Chunks spawned by a rolled back edit are left in place (without the tile data).
+pub fn try_edit<R, E>(
+    world: &mut World,
+    edit: impl FnOnce(&mut TileEdit) -> Result<R, E>,
+) -> Result<R, E> {
+    let mut transaction = TileEdit {
+        world,
+        undo: Vec::new(),
+    };
+    match edit(&mut transaction) {
+        Ok(value) => Ok(value),
+        Err(err) => {
+            transaction.rollback();
+            Err(err)
+        }
+    }
+}
+
+/// Runs [`try_edit`] as a command, logging the error if the edit was rolled back.
+pub struct TryEdit<F>(pub F);
+
+impl<F, E> Command for TryEdit<F>
+where
+    F: FnOnce(&mut TileEdit) -> Result<(), E> + Send + 'static,
+    E: Display,
+{
+    fn apply(self, world: &mut World) {
+        if let Err(err) = try_edit(world, self.0) {
+            warn!("Tile edit rolled back: {err}");
+        }
+    }
+}
+
+/// Queues a [`try_edit`] to run, errors are logged instead of returned.
+pub fn queue_try_edit<F, E>(commands: &mut Commands, edit: F)
+where
+    F: FnOnce(&mut TileEdit) -> Result<(), E> + Send + 'static,
+    E: Display,
+{
+    commands.queue(TryEdit(edit));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing;
+
+    use super::*;
+
+    #[test]
+    fn failed_edits_roll_back() {
+        let mut world = World::new();
+        let map_id = testing::spawn_map(&mut world, 4, |map| {
+            map.insert_tile([0, 0], 1u8);
+            map.insert_tile([1, 0], true);
+        });
+
+        let not_a_map = world.spawn_empty().id();
+        let result: Result<(), &str> = try_edit(&mut world, |edit| {
+            assert_eq!(edit.insert::<u8, 2>(map_id, [0, 0], 5), Ok(Some(1)));
+            assert_eq!(edit.insert::<u8, 2>(map_id, [2, 2], 7), Ok(None));
+            assert_eq!(edit.remove::<bool, 2>(map_id, [1, 0]), Ok(Some(true)));
+            assert_eq!(edit.remove::<bool, 2>(map_id, [1, 0]), Ok(None));
+            edit.insert::<u16, 2>(map_id, [3, 3], 1).unwrap();
+            assert_eq!(
+                edit.insert::<u8, 2>(not_a_map, [0, 0], 1),
+                Err(MapQueryError::NotAMap(not_a_map))
+            );
+            assert_eq!(
+                edit.remove::<u8, 2>(not_a_map, [0, 0]),
+                Err(MapQueryError::NotAMap(not_a_map))
+            );
+            assert_eq!(edit.len(), 4);
+            if !edit.contains::<bool, 2>(map_id, [3, 3]) {
+                return Err("doors need a collider");
+            }
+            Ok(())
+        });
+        assert_eq!(result, Err("doors need a collider"));
+
+        let edit = TileEdit {
+            world: &mut world,
+            undo: Vec::new(),
+        };
+        assert_eq!(edit.get::<u8, 2>(map_id, [0, 0]), Some(&1));
+        assert_eq!(edit.get::<u8, 2>(map_id, [2, 2]), None);
+        assert_eq!(edit.get::<bool, 2>(map_id, [1, 0]), Some(&true));
+        assert!(!edit.contains::<u16, 2>(map_id, [3, 3]));
+
+        let result: Result<usize, &str> = try_edit(&mut world, |edit| {
+            edit.insert::<u16, 2>(map_id, [3, 3], 1).unwrap();
+            edit.insert::<bool, 2>(map_id, [3, 3], true).unwrap();
+            Ok(edit.len())
+        });
+        assert_eq!(result, Ok(2));
+        let edit = TileEdit {
+            world: &mut world,
+            undo: Vec::new(),
+        };
+        assert_eq!(edit.get::<u16, 2>(map_id, [3, 3]), Some(&1));
+    }
+}
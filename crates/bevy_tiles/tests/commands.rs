@@ -0,0 +1,935 @@
+use bevy::prelude::*;
+use bevy_tiles::{
+    chunks::{ChunkCoord, ChunkData},
+    clipboard::copy_region,
+    commands::{MissingMapPolicy, TempRemove, TileCommandExt, TileWorldExt},
+    index::LayerIndex,
+    maps::{AutoDespawnEmptyChunks, MapBuilder, MapSeed, TileDims, TileMap, UseTransforms},
+    observers::{OnChunkDespawned, OnChunkSpawned, OnTileInserted, OnTileRemoved},
+    queries::TileComponent,
+    tiles::TileMapQuery,
+    TilesPlugin,
+};
+
+mod harness;
+
+use harness::Harness;
+
+#[derive(Resource)]
+struct Map(Entity);
+
+#[derive(Resource, Default)]
+struct Seen(Option<u8>);
+
+#[test]
+fn edit_is_seen_by_next_system() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.app.insert_resource(Map(map_id));
+    harness.app.init_resource::<Seen>();
+    harness.app.add_systems(
+        Update,
+        (
+            |mut commands: Commands, map: Res<Map>| {
+                TileCommandExt::<2>::tile_map(&mut commands, map.0)
+                    .unwrap()
+                    .insert_tile([5, 5], 7u8);
+            },
+            |tile_maps: TileMapQuery<&u8>, map: Res<Map>, mut seen: ResMut<Seen>| {
+                seen.0 = tile_maps.get_map(map.0).unwrap().get_at([5, 5]).cloned();
+            },
+        )
+            .chain(),
+    );
+
+    harness.app.update();
+    assert_eq!(harness.world().resource::<Seen>().0, Some(7));
+}
+
+#[test]
+fn insert_replace_remove() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+
+    harness.apply_map(map_id, |map| map.insert_tile([1, 2], 1u8));
+    assert_eq!(harness.tile::<u8>(map_id, [1, 2]), Some(1));
+
+    harness.apply_map(map_id, |map| map.insert_tile([1, 2], 2u8));
+    assert_eq!(harness.tile::<u8>(map_id, [1, 2]), Some(2));
+
+    // Commands apply in the order they were queued.
+    harness.apply_map(map_id, |map| {
+        map.remove_tile::<u8>([1, 2]);
+        map.insert_tile([1, 3], 3u8);
+    });
+    assert_eq!(harness.tile::<u8>(map_id, [1, 2]), None);
+    assert_eq!(harness.tile::<u8>(map_id, [1, 3]), Some(3));
+
+    harness.apply_map(map_id, |map| {
+        map.remove_tile::<u8>([1, 3]);
+    });
+    let chunk_id = harness.chunk(map_id, [1, 3]).unwrap();
+    assert!(harness.world().get::<ChunkData<u8>>(chunk_id).is_none());
+}
+
+#[test]
+fn chunk_lifecycle() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+
+    harness.apply_map(map_id, |map| map.spawn_chunk([0, 0]));
+    let chunk_id = harness.chunk(map_id, [0, 0]).unwrap();
+    assert!(harness.world().get::<ChunkData<u8>>(chunk_id).is_none());
+
+    // Spawning an existing chunk keeps it.
+    harness.apply_map(map_id, |map| {
+        map.spawn_chunk([0, 0]);
+        map.insert_tile([3, 3], 1u8);
+    });
+    assert_eq!(harness.chunk(map_id, [3, 3]), Some(chunk_id));
+
+    harness.apply_map(map_id, |map| {
+        map.despawn_chunk([0, 0]);
+    });
+    assert!(harness.world().get_entity(chunk_id).is_err());
+    assert_eq!(harness.chunk(map_id, [3, 3]), None);
+    assert_eq!(harness.tile::<u8>(map_id, [3, 3]), None);
+
+    harness.apply_map(map_id, |map| map.insert_tile([3, 3], 2u8));
+    assert_ne!(harness.chunk(map_id, [3, 3]), Some(chunk_id));
+    assert_eq!(harness.tile::<u8>(map_id, [3, 3]), Some(2));
+}
+
+#[test]
+#[should_panic(expected = "No tilemap found!")]
+fn map_despawned_before_edit() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+
+    harness.apply(|commands| {
+        let mut map = TileCommandExt::<2>::tile_map(commands, map_id).unwrap();
+        map.insert_tile([0, 0], 1u8);
+        TileCommandExt::<2>::despawn_map(commands, map_id);
+        TileCommandExt::<2>::tile_map(commands, map_id)
+            .unwrap()
+            .insert_tile([1, 0], 1u8);
+    });
+}
+
+#[test]
+fn missing_map_policy_skips_commands() {
+    let mut harness = Harness::new(TilesPlugin);
+    harness.world().insert_resource(MissingMapPolicy::Warn);
+    let map_id = harness.spawn_map(4);
+
+    harness.apply(|commands| {
+        TileCommandExt::<2>::despawn_map(commands, map_id);
+        let mut map = TileCommandExt::<2>::tile_map(commands, map_id).unwrap();
+        map.insert_tile([1, 0], 1u8);
+        map.spawn_chunk([2, 2]);
+        map.generate_chunk([0, 0]);
+    });
+    assert!(harness.world().get_entity(map_id).is_err());
+}
+
+#[test]
+fn temp_remove_restores_on_drop() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| map.insert_tile([0, 0], 1u8));
+
+    {
+        let mut map = harness.world().temp_remove::<TileMap<2>>(map_id).unwrap();
+        assert_eq!(map.get_chunk_size(), 4);
+        let world = map.get_world_mut();
+        assert!(world.get::<TileMap<2>>(map_id).is_none());
+        // The map can't be taken twice.
+        assert!(world.temp_remove::<TileMap<2>>(map_id).is_none());
+    }
+
+    assert!(harness.world().get::<TileMap<2>>(map_id).is_some());
+    assert_eq!(harness.tile::<u8>(map_id, [0, 0]), Some(1));
+}
+
+#[test]
+fn nested_commands_apply() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+
+    harness.apply(|commands| {
+        commands.queue(move |world: &mut World| {
+            let mut commands = world.commands();
+            TileCommandExt::<2>::tile_map(&mut commands, map_id)
+                .unwrap()
+                .insert_tile([2, 2], 4u8);
+        });
+    });
+    assert_eq!(harness.tile::<u8>(map_id, [2, 2]), Some(4));
+}
+
+#[test]
+fn batch_insert_across_chunks() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+
+    let tile_cs: Vec<[i32; 2]> = (-6..6).flat_map(|x| (-6..6).map(move |y| [x, y])).collect();
+    harness.apply_map(map_id, |map| {
+        map.insert_tile_batch(tile_cs, |[x, y]| x * 100 + y);
+        map.insert_tile_batch_cloned([[7, 7], [-7, -7]], 1i32);
+    });
+
+    let chunks = harness
+        .world()
+        .get::<TileMap<2>>(map_id)
+        .unwrap()
+        .get_chunks()
+        .len();
+    assert_eq!(chunks, 16);
+    for tile_c in [[-6, -6], [-1, 3], [0, 0], [5, -4], [3, 5]] {
+        assert_eq!(
+            harness.tile::<i32>(map_id, tile_c),
+            Some(tile_c[0] * 100 + tile_c[1])
+        );
+    }
+    assert_eq!(harness.tile::<i32>(map_id, [-7, -7]), Some(1));
+}
+
+#[test]
+fn batch_remove_across_chunks() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+
+    let tile_cs: Vec<[i32; 2]> = (-6..6).flat_map(|x| (-6..6).map(move |y| [x, y])).collect();
+    harness.apply_map(map_id, |map| {
+        map.insert_tile_batch_cloned(tile_cs, 1i32);
+    });
+    // Remove the left half, along with coordinates in chunks that don't exist.
+    let removed: Vec<[i32; 2]> = (-8..0).flat_map(|x| (-6..6).map(move |y| [x, y])).collect();
+    harness.apply_map(map_id, |map| {
+        map.remove_tile_batch::<i32, _>(removed);
+    });
+
+    for tile_c in [[-6, -6], [-1, 3], [-4, 5]] {
+        assert_eq!(harness.tile::<i32>(map_id, tile_c), None);
+    }
+    for tile_c in [[0, 0], [5, -6], [3, 5]] {
+        assert_eq!(harness.tile::<i32>(map_id, tile_c), Some(1));
+    }
+}
+
+#[test]
+fn chunk_batch_lifecycle() {
+    #[derive(Component, PartialEq, Debug)]
+    struct Ring([i32; 2]);
+
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+
+    let ring: Vec<[i32; 2]> = (-1..=1)
+        .flat_map(|x| (-1..=1).map(move |y| [x, y]))
+        .collect();
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([0, 0], 1u8);
+        map.spawn_chunk_batch_with(ring, Ring);
+    });
+    let chunk_id = harness.chunk(map_id, [0, 0]).unwrap();
+    assert_eq!(harness.tile::<u8>(map_id, [0, 0]), Some(1));
+    assert_eq!(harness.world().get::<Ring>(chunk_id), Some(&Ring([0, 0])));
+
+    harness.apply_map(map_id, |map| {
+        map.despawn_chunk_batch([[0, 0], [1, 1], [5, 5]]);
+        map.spawn_chunk_batch([[2, 2]]);
+    });
+    assert!(harness.world().get_entity(chunk_id).is_err());
+    assert_eq!(harness.tile::<u8>(map_id, [0, 0]), None);
+    let chunks = harness
+        .world()
+        .get::<TileMap<2>>(map_id)
+        .unwrap()
+        .get_chunks()
+        .len();
+    assert_eq!(chunks, 8);
+}
+
+#[test]
+fn despawn_map_from_map_commands() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| map.insert_tile([0, 0], 1u8));
+    let chunk_id = harness.chunk(map_id, [0, 0]).unwrap();
+
+    harness.apply(|commands| {
+        let map = TileCommandExt::<2>::tile_map(commands, map_id).unwrap();
+        assert_eq!(map.id(), map_id);
+        map.despawn_map();
+    });
+    assert!(harness.world().get_entity(map_id).is_err());
+    assert!(harness.world().get_entity(chunk_id).is_err());
+}
+
+#[test]
+fn move_and_swap_tile_data() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([0, 0], 1u8);
+        map.insert_tile([1, 0], 2u8);
+        map.insert_tile([1, 0], 7u16);
+    });
+
+    harness.apply(|commands| {
+        // The destination chunk doesn't exist yet.
+        TileCommandExt::<2>::move_tile::<u8>(commands, map_id, [0, 0], [9, 9]);
+        // Moving an empty tile leaves the destination alone.
+        TileCommandExt::<2>::move_tile::<u8>(commands, map_id, [5, 5], [1, 0]);
+    });
+    assert_eq!(harness.tile::<u8>(map_id, [0, 0]), None);
+    assert_eq!(harness.tile::<u8>(map_id, [9, 9]), Some(1));
+    assert_eq!(harness.tile::<u8>(map_id, [1, 0]), Some(2));
+
+    harness.apply(|commands| {
+        TileCommandExt::<2>::swap_tiles::<u8>(commands, map_id, [9, 9], [1, 0]);
+        TileCommandExt::<2>::swap_tiles::<u16>(commands, map_id, [1, 0], [-3, 2]);
+    });
+    assert_eq!(harness.tile::<u8>(map_id, [9, 9]), Some(2));
+    assert_eq!(harness.tile::<u8>(map_id, [1, 0]), Some(1));
+    assert_eq!(harness.tile::<u16>(map_id, [1, 0]), None);
+    assert_eq!(harness.tile::<u16>(map_id, [-3, 2]), Some(7));
+}
+
+#[test]
+fn paste_copied_region_into_other_map() {
+    let mut harness = Harness::new(TilesPlugin);
+    let src = harness.spawn_map(4);
+    let dst = harness.spawn_map(8);
+    harness.apply_map(src, |map| {
+        map.insert_tile([0, 0], 1u8);
+        map.insert_tile([2, 1], 2u8);
+    });
+
+    let clipboard = copy_region::<(u8,), 2>(harness.world(), src, [0, 0], [2, 1]).unwrap();
+    harness.apply_map(dst, |map| {
+        map.paste_region(clipboard.clone(), [-1, -1]);
+        map.paste_region(clipboard, [20, 0]);
+    });
+    assert_eq!(harness.tile::<u8>(dst, [-1, -1]), Some(1));
+    assert_eq!(harness.tile::<u8>(dst, [1, 0]), Some(2));
+    assert_eq!(harness.tile::<u8>(dst, [22, 1]), Some(2));
+    assert_eq!(harness.tile::<u8>(src, [2, 1]), Some(2));
+}
+
+#[test]
+fn update_region_mutates_existing_tiles() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([-1, -1], 10i32);
+        map.insert_tile([2, 5], 10i32);
+        map.insert_tile([3, 3], 10i32);
+    });
+
+    harness.apply_map(map_id, |map| {
+        map.update_region::<i32, _>([2, 5], [-1, -2], |tile_c, moisture| {
+            *moisture += tile_c[1] + 1;
+        });
+    });
+    assert_eq!(harness.tile::<i32>(map_id, [-1, -1]), Some(10));
+    assert_eq!(harness.tile::<i32>(map_id, [2, 5]), Some(16));
+    assert_eq!(harness.tile::<i32>(map_id, [3, 3]), Some(10));
+    assert_eq!(harness.tile::<i32>(map_id, [0, 0]), None);
+}
+
+#[test]
+fn update_region_keeps_indexes_and_edits_tuples() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| {
+        map.insert(LayerIndex::<u8, 2>::new(|ore| Some(*ore as u64)));
+        map.insert_tile([0, 0], 1u8);
+        map.insert_tile([5, 0], (1u8, true));
+    });
+
+    harness.apply_map(map_id, |map| {
+        map.update_region::<u8, _>([0, 0], [1, 0], |_, ore| *ore = 2)
+            .update_region::<(u8, bool), _>([0, 0], [7, 0], |_, (ore, open)| {
+                *ore += 4;
+                *open = false;
+            });
+    });
+    // The tile without a `bool` isn't part of the tuple layer.
+    assert_eq!(harness.tile::<u8>(map_id, [0, 0]), Some(2));
+    assert_eq!(harness.tile::<u8>(map_id, [5, 0]), Some(5));
+    assert_eq!(harness.tile::<bool>(map_id, [5, 0]), Some(false));
+    let index = harness.world().get::<LayerIndex<u8, 2>>(map_id).unwrap();
+    assert_eq!(index.count(1), 0);
+    assert_eq!(index.coords(2).collect::<Vec<_>>(), vec![[0, 0]]);
+    assert_eq!(index.coords(5).collect::<Vec<_>>(), vec![[5, 0]]);
+}
+
+#[test]
+fn hide_and_show_chunk_region() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([0, 0], 1u8);
+        map.insert_tile([5, 5], 1u8);
+        map.insert_tile([-5, 0], 1u8);
+    });
+    let chunks = [[0, 0], [5, 5], [-5, 0]].map(|tile_c| harness.chunk(map_id, tile_c).unwrap());
+
+    harness.apply_map(map_id, |map| {
+        map.set_chunks_visible([7, 7], [0, 0], false);
+    });
+    let visibility =
+        |harness: &mut Harness, chunk_id| harness.world().get::<Visibility>(chunk_id).copied();
+    assert_eq!(
+        visibility(&mut harness, chunks[0]),
+        Some(Visibility::Hidden)
+    );
+    assert_eq!(
+        visibility(&mut harness, chunks[1]),
+        Some(Visibility::Hidden)
+    );
+    assert_eq!(visibility(&mut harness, chunks[2]), None);
+
+    harness.apply_map(map_id, |map| {
+        map.set_chunks_visible([0, 0], [0, 0], true);
+    });
+    assert_eq!(
+        visibility(&mut harness, chunks[0]),
+        Some(Visibility::Inherited)
+    );
+    assert_eq!(
+        visibility(&mut harness, chunks[1]),
+        Some(Visibility::Hidden)
+    );
+}
+
+#[test]
+fn spawn_configured_maps() {
+    let mut harness = Harness::new(TilesPlugin);
+    let (with_id, built_id) = harness.apply(|commands| {
+        let mut map =
+            TileCommandExt::<2>::spawn_map_with(commands, 4, (UseTransforms, TileDims([8.0, 8.0])));
+        map.insert_tile([5, 0], 1u8);
+        let with_id = map.id();
+        let built_id = MapBuilder::<2>::new(8)
+            .use_transforms()
+            .with_dims([16.0, 16.0])
+            .with_seed(7)
+            .spawn(commands)
+            .id();
+        (with_id, built_id)
+    });
+
+    let chunk_id = harness.chunk(with_id, [5, 0]).unwrap();
+    assert_eq!(
+        harness
+            .world()
+            .get::<Transform>(chunk_id)
+            .unwrap()
+            .translation,
+        Vec3::new(32.0, 0.0, 0.0)
+    );
+    let built = harness.world().entity(built_id);
+    assert_eq!(built.get::<TileMap<2>>().unwrap().get_chunk_size(), 8);
+    assert!(built.contains::<UseTransforms>());
+    assert_eq!(**built.get::<TileDims<2>>().unwrap(), [16.0, 16.0]);
+    assert_eq!(built.get::<MapSeed>(), Some(&MapSeed(7)));
+}
+
+#[test]
+fn tuple_tiles_fill_several_layers() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([0, 0], (1u8, 2i32));
+        map.insert_tile_batch([[5, 5], [6, 5]], |tile_c| (tile_c[0] as u8, true));
+        map.insert_tile([9, 9], 3u8);
+    });
+    assert_eq!(harness.tile::<u8>(map_id, [0, 0]), Some(1));
+    assert_eq!(harness.tile::<i32>(map_id, [0, 0]), Some(2));
+    assert_eq!(harness.tile::<u8>(map_id, [6, 5]), Some(6));
+    assert_eq!(harness.tile::<bool>(map_id, [5, 5]), Some(true));
+
+    harness.apply_map(map_id, |map| {
+        map.remove_tile::<(u8, i32)>([0, 0]);
+        map.remove_tile::<(u8, i32)>([9, 9]);
+    });
+    assert_eq!(harness.tile::<i32>(map_id, [0, 0]), None);
+    // The tile had no i32, so the tuple wasn't there to take.
+    assert_eq!(harness.tile::<u8>(map_id, [9, 9]), Some(3));
+    let map = harness.world().get::<TileMap<2>>(map_id).unwrap();
+    assert_eq!(map.occupied_bounds::<u8>(), Some(([4, 4], [11, 11])));
+    assert_eq!(map.occupied_bounds::<i32>(), None);
+}
+
+#[test]
+fn tuple_tiles_edit_each_layer() {
+    #[derive(Resource, Default)]
+    struct Edits(Vec<String>);
+
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    let world = harness.world();
+    world.init_resource::<Edits>();
+    world
+        .entity_mut(map_id)
+        .insert(LayerIndex::<u8, 2>::new(|value| Some(*value as u64)));
+    world.add_observer(
+        |trigger: Trigger<OnTileInserted<u8>>, mut edits: ResMut<Edits>| {
+            edits.0.push(format!("insert u8 {:?}", trigger.tile_c));
+        },
+    );
+    world.add_observer(
+        |trigger: Trigger<OnTileRemoved<i32>>, mut edits: ResMut<Edits>| {
+            edits.0.push(format!("remove i32 {:?}", trigger.tile_c));
+        },
+    );
+    world.flush();
+
+    TileWorldExt::<2>::insert_tile(world, map_id, [0, 0], 1u8);
+    // Only part of the tile was replaced, so there's no whole tuple to return.
+    let replaced = TileWorldExt::<2>::insert_tile(world, map_id, [0, 0], (2u8, 3i32));
+    assert_eq!(replaced, None);
+    let replaced =
+        TileWorldExt::<2>::insert_tile_batch(world, map_id, [[0, 0], [1, 0]], |_| (4u8, 5i32));
+    assert_eq!(replaced, [(2, 3)]);
+    let index = world.get::<LayerIndex<u8, 2>>(map_id).unwrap();
+    assert_eq!(index.count(4), 2);
+
+    TileWorldExt::<2>::take_tile::<u8>(world, map_id, [1, 0]);
+    assert_eq!(
+        TileWorldExt::<2>::take_tile::<(u8, i32)>(world, map_id, [1, 0]),
+        None
+    );
+    assert_eq!(world.get_tile::<i32>(map_id, [1, 0]), Some(&5));
+    assert_eq!(
+        TileWorldExt::<2>::take_tile::<(u8, i32)>(world, map_id, [0, 0]),
+        Some((4, 5))
+    );
+
+    let index = world.get::<LayerIndex<u8, 2>>(map_id).unwrap();
+    assert_eq!(index.len(), 0);
+    world.flush();
+    assert_eq!(
+        world.resource::<Edits>().0,
+        [
+            "insert u8 [0, 0]",
+            "insert u8 [0, 0]",
+            "insert u8 [0, 0]",
+            "insert u8 [1, 0]",
+            "remove i32 [0, 0]",
+        ]
+    );
+}
+
+#[derive(TileComponent, Clone, Copy, Debug, PartialEq)]
+struct Height(f32);
+
+#[derive(TileComponent, Clone, Copy, Debug, PartialEq)]
+enum Terrain<T> {
+    Grass,
+    Water(T),
+}
+
+#[test]
+fn derived_tiles_are_stored_as_plain_data() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([0, 0], Height(2.5));
+        map.insert_tile([0, 0], (Terrain::Water(3u8), true));
+        map.insert_tile_batch([[1, 0], [2, 0]], |_| Terrain::<u8>::Grass);
+    });
+    assert_eq!(harness.tile::<Height>(map_id, [0, 0]), Some(Height(2.5)));
+    assert_eq!(
+        harness.tile::<Terrain<u8>>(map_id, [0, 0]),
+        Some(Terrain::Water(3))
+    );
+    assert_eq!(
+        harness.tile::<Terrain<u8>>(map_id, [2, 0]),
+        Some(Terrain::Grass)
+    );
+
+    harness.apply_map(map_id, |map| {
+        map.remove_tile::<Height>([0, 0]);
+    });
+    assert_eq!(harness.tile::<Height>(map_id, [0, 0]), None);
+    let map = harness.world().get::<TileMap<2>>(map_id).unwrap();
+    let chunk_id = map.get_from_tile([0, 0]).unwrap();
+    assert!(harness.world().get::<ChunkData<Height>>(chunk_id).is_none());
+}
+
+#[test]
+fn world_ext_edits_maps_immediately() {
+    let mut harness = Harness::new(TilesPlugin);
+    let world = harness.world();
+    let map_id = TileWorldExt::<2>::spawn_map(world, 4);
+    assert_eq!(world.insert_tile(map_id, [1, 1], 3u8), None);
+    assert_eq!(world.insert_tile(map_id, [1, 1], 4u8), Some(3));
+    let replaced =
+        world.insert_tile_batch(map_id, [[0, 0], [1, 1], [9, 9]], |tile_c| tile_c[0] as u8);
+    assert_eq!(replaced, vec![4]);
+    assert_eq!(world.get_tile::<u8>(map_id, [9, 9]), Some(&9));
+
+    assert_eq!(world.take_tile::<u8>(map_id, [1, 1]), Some(1));
+    let mut taken = world.take_tile_batch::<u8, _>(map_id, [[0, 0], [1, 1]]);
+    taken.sort();
+    assert_eq!(taken, vec![([0, 0], 0)]);
+    assert_eq!(world.get_tile::<u8>(map_id, [0, 0]), None);
+
+    let chunk_id = world.spawn_chunk(map_id, [-3, 0]).unwrap();
+    assert_eq!(world.spawn_chunk(map_id, [-3, 0]), Some(chunk_id));
+    world.despawn_chunk(map_id, [-3, 0]);
+    assert!(world.get_entity(chunk_id).is_err());
+
+    TileWorldExt::<2>::despawn_map(world, map_id);
+    assert!(world.get_entity(map_id).is_err());
+    world.insert_resource(MissingMapPolicy::Ignore);
+    assert_eq!(world.insert_tile(map_id, [0, 0], 1u8), None);
+}
+
+#[test]
+fn break_and_place_blocks_under_ray() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.apply(|commands| {
+        let mut map = TileCommandExt::<3>::spawn_map(commands, 4);
+        map.insert(TileDims([1.0; 3]));
+        map.insert_tile_batch_cloned([[0, 0, 0], [1, 0, 0], [2, 0, 0]], 1u8);
+        map.id()
+    });
+    let ray = Ray3d::new(Vec3::new(0.5, 0.5, 5.0), Dir3::NEG_Z);
+    let tile = |harness: &mut Harness, tile_c: [i32; 3]| {
+        harness.world().get_tile::<u8>(map_id, tile_c).copied()
+    };
+
+    harness.apply(move |commands| {
+        let mut map = TileCommandExt::<3>::tile_map(commands, map_id).unwrap();
+        map.place_tile_at_ray(ray, 10.0, 2u8);
+    });
+    assert_eq!(tile(&mut harness, [0, 0, 1]), Some(2));
+
+    harness.apply(move |commands| {
+        let mut map = TileCommandExt::<3>::tile_map(commands, map_id).unwrap();
+        map.break_tile_at_ray::<u8>(ray, 10.0)
+            .break_tile_at_ray::<u8>(ray, 10.0);
+        // Too short to reach anything.
+        map.break_tile_at_ray::<u8>(Ray3d::new(Vec3::new(2.5, 0.5, 5.0), Dir3::NEG_Z), 3.0);
+    });
+    assert_eq!(tile(&mut harness, [0, 0, 1]), None);
+    assert_eq!(tile(&mut harness, [0, 0, 0]), None);
+    assert_eq!(tile(&mut harness, [1, 0, 0]), Some(1));
+    assert_eq!(tile(&mut harness, [2, 0, 0]), Some(1));
+}
+
+#[derive(Component)]
+struct Biome;
+
+#[test]
+fn reserve_chunks_before_tiles() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([0, 0], 1u8);
+    });
+    let kept = harness.chunk(map_id, [0, 0]).unwrap();
+    harness.apply_map(map_id, |map| {
+        map.reserve_chunks_around([1, 0], 1);
+    });
+
+    let map = harness.world().get::<TileMap<2>>(map_id).unwrap();
+    assert_eq!(map.get_chunks().len(), 9);
+    assert!(map.get_from_chunk(ChunkCoord::new([2, -1])).is_some());
+    assert!(map.get_from_chunk(ChunkCoord::new([-1, 0])).is_none());
+    assert_eq!(harness.chunk(map_id, [0, 0]), Some(kept));
+    assert_eq!(harness.tile::<u8>(map_id, [0, 0]), Some(1));
+
+    // Reserved chunks can be set up before any tiles are added to them.
+    let chunk_id = harness.chunk(map_id, [8, 4]).unwrap();
+    harness.world().entity_mut(chunk_id).insert(Biome);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([9, 5], 2u8);
+    });
+    assert_eq!(harness.chunk(map_id, [9, 5]), Some(chunk_id));
+    assert!(harness.world().get::<Biome>(chunk_id).is_some());
+}
+
+#[test]
+fn take_tile_returns_removed_data() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([2, 3], 7u8);
+    });
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    harness.apply_map(map_id, move |map| {
+        let other = sender.clone();
+        map.take_tile::<u8, _>([2, 3], move |tile| sender.send(tile).unwrap())
+            .take_tile::<u8, _>([2, 3], move |tile| other.send(tile).unwrap());
+    });
+    assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![Some(7), None]);
+    assert_eq!(harness.tile::<u8>(map_id, [2, 3]), None);
+}
+
+#[test]
+fn move_tile_batch_shifts_a_row() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile_batch((0..6).map(|x| [x, 0]), |tile_c| tile_c[0] as u8);
+        map.insert_tile([6, 0], 100u8);
+    });
+
+    // Shift the conveyor at x = 0..6 one tile to the right, across the chunk border.
+    harness.apply_map(map_id, |map| {
+        map.move_tile_batch::<u8, _>((0..6).map(|x| ([x, 0], [x + 1, 0])));
+    });
+    assert_eq!(harness.tile::<u8>(map_id, [0, 0]), None);
+    for x in 1..=6 {
+        assert_eq!(harness.tile::<u8>(map_id, [x, 0]), Some(x as u8 - 1));
+    }
+}
+
+#[test]
+fn move_tile_batch_overlapping_moves() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile_batch((0..4).map(|x| [x, 0]), |tile_c| tile_c[0] as u8);
+    });
+
+    // [0, 0] and [1, 0] both move to [5, 0], so the later move wins.
+    // [2, 0] is a source twice, so it only moves along its first pair.
+    harness.apply_map(map_id, |map| {
+        map.move_tile_batch::<u8, _>([
+            ([0, 0], [5, 0]),
+            ([1, 0], [5, 0]),
+            ([2, 0], [6, 0]),
+            ([2, 0], [7, 0]),
+            ([3, 0], [6, 0]),
+        ]);
+    });
+    for x in 0..4 {
+        assert_eq!(harness.tile::<u8>(map_id, [x, 0]), None);
+    }
+    assert_eq!(harness.tile::<u8>(map_id, [5, 0]), Some(1));
+    assert_eq!(harness.tile::<u8>(map_id, [6, 0]), Some(3));
+    assert_eq!(harness.tile::<u8>(map_id, [7, 0]), None);
+}
+
+#[test]
+fn stamp_prefab_relative_to_origin() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    let stamp = |map: &mut bevy_tiles::commands::OffsetTileMapCommands<'_, '_, 2>| {
+        map.insert_tile_batch([[0, 0], [1, 0], [0, 1]], |tile_c| {
+            (tile_c[0] + tile_c[1] * 2) as u8
+        })
+        .insert_tile([1, 1], 9u8);
+    };
+    harness.apply_map(map_id, |map| {
+        stamp(&mut map.with_origin([10, -3]));
+        stamp(&mut map.with_origin([-1, -1]));
+    });
+    assert_eq!(harness.tile::<u8>(map_id, [10, -3]), Some(0));
+    assert_eq!(harness.tile::<u8>(map_id, [11, -3]), Some(1));
+    assert_eq!(harness.tile::<u8>(map_id, [10, -2]), Some(2));
+    assert_eq!(harness.tile::<u8>(map_id, [11, -2]), Some(9));
+    assert_eq!(harness.tile::<u8>(map_id, [-1, 0]), Some(2));
+
+    harness.apply_map(map_id, |map| {
+        map.with_origin([10, -3])
+            .update_region::<u8, _>([0, 0], [1, 1], |tile_c, tile| {
+                *tile += (tile_c[0] * 10) as u8
+            })
+            .remove_tile::<u8>([0, 1]);
+    });
+    assert_eq!(harness.tile::<u8>(map_id, [11, -3]), Some(11));
+    assert_eq!(harness.tile::<u8>(map_id, [10, -2]), None);
+    assert_eq!(harness.tile::<u8>(map_id, [0, 0]), Some(9));
+}
+
+#[test]
+fn auto_despawn_emptied_chunks() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness
+        .world()
+        .entity_mut(map_id)
+        .insert(AutoDespawnEmptyChunks);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([0, 0], 1u8);
+        map.insert_tile([1, 0], true);
+        map.insert_tile_batch_cloned([[8, 8], [9, 9]], 2u8);
+        map.spawn_chunk([-5, -5]);
+    });
+    let chunk_id = harness.chunk(map_id, [0, 0]).unwrap();
+
+    harness.apply_map(map_id, |map| {
+        map.remove_tile::<u8>([0, 0]);
+        map.remove_tile_batch::<u8, _>([[8, 8], [9, 9]]);
+    });
+    // The chunk still has a `bool` layer.
+    assert_eq!(harness.chunk(map_id, [0, 0]), Some(chunk_id));
+    assert_eq!(harness.chunk(map_id, [8, 8]), None);
+
+    harness.apply_map(map_id, |map| {
+        map.remove_tile::<bool>([1, 0]);
+    });
+    assert_eq!(harness.chunk(map_id, [0, 0]), None);
+    assert!(harness.world().get_entity(chunk_id).is_err());
+    let map = harness.world().get::<TileMap<2>>(map_id).unwrap();
+    assert_eq!(map.get_chunks().len(), 1);
+    assert_eq!(map.occupied_bounds::<u8>(), None);
+}
+
+#[test]
+fn insert_tile_only_if_empty() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([0, 0], 1u8);
+        map.insert_tile_if_empty([0, 0], 2u8)
+            .insert_tile_if_empty([1, 0], 2u8)
+            .insert_tile_if_empty([1, 0], 3u8);
+    });
+    assert_eq!(harness.tile::<u8>(map_id, [0, 0]), Some(1));
+    assert_eq!(harness.tile::<u8>(map_id, [1, 0]), Some(2));
+
+    let world = harness.world();
+    *world.get_or_insert_tile(map_id, [5, 5], || 0u8).unwrap() += 1;
+    *world.get_or_insert_tile(map_id, [5, 5], || 0u8).unwrap() += 1;
+    assert_eq!(
+        world.get_or_insert_tile(map_id, [0, 0], || 9u8),
+        Some(&mut 1)
+    );
+    assert_eq!(harness.tile::<u8>(map_id, [5, 5]), Some(2));
+
+    // Tuples are only empty when any of their layers is.
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([2, 0], (4u8, true));
+        map.insert_tile_if_empty([2, 0], (5u8, false))
+            .insert_tile_if_empty([3, 0], (6u8, false));
+    });
+    assert_eq!(harness.tile::<u8>(map_id, [2, 0]), Some(4));
+    assert_eq!(harness.tile::<bool>(map_id, [2, 0]), Some(true));
+    assert_eq!(harness.tile::<bool>(map_id, [3, 0]), Some(false));
+
+    let world = harness.world();
+    assert_eq!(
+        world.get_or_insert_tile(map_id, [2, 0], || (9u8, false)),
+        None
+    );
+    assert_eq!(harness.tile::<u8>(map_id, [2, 0]), Some(4));
+    assert_eq!(harness.tile::<bool>(map_id, [2, 0]), Some(true));
+}
+
+#[derive(Component, Debug, PartialEq)]
+struct BiomeTag(u8);
+
+#[test]
+fn chunk_scoped_commands() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([1, 1], true);
+        map.chunk([0, 0]).insert_chunk_bundle(BiomeTag(3)).fill(7u8);
+        map.chunk([1, 1]).fill(2u8).clear::<u8>();
+        map.chunk([-1, 0]).spawn();
+    });
+    let chunk_id = harness.chunk(map_id, [0, 0]).unwrap();
+    assert_eq!(
+        harness.world().get::<BiomeTag>(chunk_id),
+        Some(&BiomeTag(3))
+    );
+    assert_eq!(harness.tile::<u8>(map_id, [0, 0]), Some(7));
+    assert_eq!(harness.tile::<u8>(map_id, [3, 3]), Some(7));
+    assert_eq!(harness.tile::<u8>(map_id, [4, 4]), None);
+    assert!(harness.chunk(map_id, [-1, 0]).is_some());
+
+    harness.apply_map(map_id, |map| {
+        let mut chunk = map.chunk([0, 0]);
+        chunk.remove_chunk_bundle::<BiomeTag>().clear::<u8>();
+    });
+    assert_eq!(harness.world().get::<BiomeTag>(chunk_id), None);
+    assert_eq!(harness.tile::<u8>(map_id, [0, 0]), None);
+    assert_eq!(harness.tile::<bool>(map_id, [1, 1]), Some(true));
+
+    harness.apply_map(map_id, |map| map.chunk([0, 0]).despawn());
+    assert_eq!(harness.chunk(map_id, [0, 0]), None);
+}
+
+#[derive(Resource, Default)]
+struct Observed(Vec<String>);
+
+#[test]
+fn observers_see_tile_and_chunk_changes() {
+    let mut harness = Harness::new(TilesPlugin);
+    harness.world().init_resource::<Observed>();
+    let map_id = harness.spawn_map(4);
+    harness
+        .world()
+        .entity_mut(map_id)
+        .insert(AutoDespawnEmptyChunks);
+    let world = harness.world();
+    world.add_observer(
+        |trigger: Trigger<OnTileInserted<u8>>, mut observed: ResMut<Observed>| {
+            observed.0.push(format!("insert {:?}", trigger.tile_c));
+        },
+    );
+    world.add_observer(
+        |trigger: Trigger<OnTileRemoved<u8>>, mut observed: ResMut<Observed>| {
+            observed.0.push(format!("remove {:?}", trigger.tile_c));
+        },
+    );
+    world.add_observer(
+        |trigger: Trigger<OnChunkSpawned>, mut observed: ResMut<Observed>| {
+            observed.0.push(format!("spawn {:?}", trigger.chunk_c));
+        },
+    );
+    world.entity_mut(map_id).observe(
+        |trigger: Trigger<OnChunkDespawned>,
+         maps: Query<&TileMap>,
+         mut observed: ResMut<Observed>| {
+            // The map is back in place by the time observers run.
+            assert!(maps.get(trigger.entity()).is_ok());
+            observed.0.push(format!("despawn {:?}", trigger.chunk_c));
+        },
+    );
+
+    harness.apply_map(map_id, |map| {
+        map.insert_tile([0, 0], 1u8);
+        map.insert_tile_batch_cloned([[1, 0], [9, 9]], 2u8);
+        map.insert_tile([5, 0], true);
+        map.remove_tile::<u8>([9, 9]);
+        map.remove_tile::<u8>([9, 9]);
+    });
+    let removed = TileWorldExt::<2>::take_tile::<u8>(harness.world(), map_id, [1, 0]);
+    assert_eq!(removed, Some(2));
+    assert_eq!(
+        harness.world().resource::<Observed>().0.last().unwrap(),
+        "remove [1, 0]"
+    );
+    harness.apply_map(map_id, |map| {
+        map.despawn_chunk([0, 0]);
+    });
+
+    let observed = harness.world().resource::<Observed>();
+    assert_eq!(
+        observed.0,
+        [
+            "spawn [0, 0]",
+            "insert [0, 0]",
+            "spawn [2, 2]",
+            "insert [1, 0]",
+            "insert [9, 9]",
+            "spawn [1, 0]",
+            "remove [9, 9]",
+            "despawn [2, 2]",
+            "remove [1, 0]",
+            "despawn [0, 0]",
+        ]
+    );
+}
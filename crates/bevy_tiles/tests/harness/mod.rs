@@ -0,0 +1,70 @@
+//! A small [`App`] harness for testing how tile commands apply.
+#![allow(dead_code)]
+
+use bevy::{app::Plugins, ecs::system::SystemState, prelude::*};
+use bevy_tiles::{
+    commands::{TileCommandExt, TileMapCommands},
+    maps::TileMap,
+    tiles::TileMapQuery,
+};
+
+/// An [`App`] with [`MinimalPlugins`] and the plugin under test.
+pub struct Harness {
+    pub app: App,
+}
+
+impl Harness {
+    /// Create a harness with the given plugins on top of [`MinimalPlugins`].
+    pub fn new<M>(plugins: impl Plugins<M>) -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, plugins));
+        Self { app }
+    }
+
+    /// Get the world of the app.
+    pub fn world(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    /// Queue commands and apply them right away, the same as the end of a system.
+    pub fn apply<R>(&mut self, f: impl FnOnce(&mut Commands) -> R) -> R {
+        let world = self.app.world_mut();
+        let out = {
+            let mut commands = world.commands();
+            f(&mut commands)
+        };
+        world.flush();
+        out
+    }
+
+    /// Queue commands for a map and apply them right away.
+    pub fn apply_map(&mut self, map_id: Entity, f: impl FnOnce(&mut TileMapCommands<'_, 2>)) {
+        self.apply(|commands| {
+            f(&mut TileCommandExt::<2>::tile_map(commands, map_id).unwrap());
+        });
+    }
+
+    /// Spawn a 2d map.
+    pub fn spawn_map(&mut self, chunk_size: usize) -> Entity {
+        self.apply(|commands| TileCommandExt::<2>::spawn_map(commands, chunk_size).id())
+    }
+
+    /// Read a tile of a 2d map.
+    pub fn tile<T: Clone + Send + Sync + 'static>(
+        &mut self,
+        map_id: Entity,
+        tile_c: [i32; 2],
+    ) -> Option<T> {
+        let world = self.app.world_mut();
+        let mut state = SystemState::<TileMapQuery<&T>>::new(world);
+        let tile_maps = state.get(world);
+        tile_maps.get_map(map_id).ok()?.get_at(tile_c).cloned()
+    }
+
+    /// Get the chunk entity holding a tile of a 2d map.
+    pub fn chunk(&mut self, map_id: Entity, tile_c: [i32; 2]) -> Option<Entity> {
+        self.world()
+            .get::<TileMap<2>>(map_id)?
+            .get_from_tile(tile_c)
+    }
+}
@@ -1,25 +1,37 @@
-use std::{cmp::Eq, hash::Hash};
-
 use bevy::{
     ecs::system::EntityCommands,
-    prelude::{Bundle, Commands, Entity, World},
-    utils::{hashbrown::hash_map::Entry, HashMap},
+    prelude::{Bundle, Entity},
 };
 
 mod tile_batch;
 mod tile_single;
+mod tile_turn;
 
-use bevy_tiles::{commands::TileMapCommands, queries::TileComponent};
+use bevy_tiles::commands::TileMapCommands;
 use tile_batch::*;
 use tile_single::*;
+use tile_turn::*;
 
-use crate::EntityTile;
+use crate::{pool::TilePool, EntityTile};
 
 /// ECS extensions for bevy_tiles.
 pub trait TileMapCommandsECSExt<const N: usize> {
     /// Spawns a tile and returns a handle to the underlying entity.
     /// This will despawn any tile that already exists in this coordinate
-    fn spawn_tile(&mut self, tile_c: impl Into<[i32; N]>, bundle: impl Bundle) -> EntityCommands;
+    fn spawn_tile(
+        &mut self,
+        tile_c: impl Into<[i32; N]>,
+        bundle: impl Bundle,
+    ) -> EntityCommands<'_>;
+
+    /// Spawns a tile reusing an entity from the [`TilePool`] if it has any, and returns a handle to the entity.
+    /// This will despawn any tile that already exists in this coordinate
+    fn spawn_pooled_tile(
+        &mut self,
+        pool: &mut TilePool,
+        tile_c: impl Into<[i32; N]>,
+        bundle: impl Bundle,
+    ) -> EntityCommands<'_>;
 
     /// Spawns a tile and returns a handle to the underlying entity.
     /// This will despawn any tile that already exists in this coordinate
@@ -41,12 +53,23 @@ pub trait TileMapCommandsECSExt<const N: usize> {
         tile_c_1: impl Into<[i32; N]>,
         tile_c_2: impl Into<[i32; N]>,
     ) -> &mut Self;
+
+    /// Moves a tile entity to the coordinate its [`bevy::prelude::Transform`] is closest to.
+    /// Use this to reconcile the grid after moving a tile entity freely.
+    fn reindex_tile(&mut self, tile_id: Entity) -> &mut Self;
+
+    /// Resolves every intent in the map's [`crate::turns::TurnQueue`] in initiative order.
+    fn resolve_turn(&mut self) -> &mut Self;
 }
 
 impl<'a, const N: usize> TileMapCommandsECSExt<N> for TileMapCommands<'a, N> {
     /// Spawns a tile and returns a handle to the underlying entity.
     /// This will despawn any tile that already exists at the coordinate.
-    fn spawn_tile(&mut self, tile_c: impl Into<[i32; N]>, bundle: impl Bundle) -> EntityCommands {
+    fn spawn_tile(
+        &mut self,
+        tile_c: impl Into<[i32; N]>,
+        bundle: impl Bundle,
+    ) -> EntityCommands<'_> {
         let tile_c = tile_c.into();
         let tile_id = self.commands().spawn(bundle).id();
         let map_id = self.id();
@@ -58,6 +81,31 @@ impl<'a, const N: usize> TileMapCommandsECSExt<N> for TileMapCommands<'a, N> {
         self.commands_mut().entity(tile_id)
     }
 
+    /// Spawns a tile reusing an entity from the [`TilePool`] if it has any, and returns a handle to the entity.
+    /// This will despawn any tile that already exists at the coordinate.
+    fn spawn_pooled_tile(
+        &mut self,
+        pool: &mut TilePool,
+        tile_c: impl Into<[i32; N]>,
+        bundle: impl Bundle,
+    ) -> EntityCommands<'_> {
+        // Skip pooled entities that were despawned by something else.
+        let Some(tile_id) = std::iter::from_fn(|| pool.take())
+            .find(|tile_id| self.commands().get_entity(*tile_id).is_some())
+        else {
+            return self.spawn_tile(tile_c, bundle);
+        };
+        let tile_c = tile_c.into();
+        self.commands().entity(tile_id).insert(bundle);
+        let map_id = self.id();
+        self.commands().queue(SpawnTile::<N> {
+            map_id,
+            tile_c,
+            tile_id: EntityTile(tile_id),
+        });
+        self.commands_mut().entity(tile_id)
+    }
+
     /// Despawns a tile.
     fn despawn_tile(&mut self, tile_c: impl Into<[i32; N]>) -> &mut Self {
         let tile_c = tile_c.into();
@@ -99,6 +147,28 @@ impl<'a, const N: usize> TileMapCommandsECSExt<N> for TileMapCommands<'a, N> {
         self
     }
 
+    /// Moves a tile entity to the coordinate its transform is closest to, overwriting and despawning any tile in the new coordinate.
+    /// # Note
+    /// This does nothing if the map doesn't have [`bevy_tiles::maps::UseTransforms`] and [`bevy_tiles::maps::TileDims`].
+    fn reindex_tile(&mut self, tile_id: Entity) -> &mut Self {
+        let map_id = self.id();
+        self.commands().queue(ReindexTile::<N> { map_id, tile_id });
+
+        self
+    }
+
+    /// Resolves every intent in the map's [`crate::turns::TurnQueue`] in initiative order, all at once,
+    /// sending an [`crate::turns::IntentResolved`] for each.
+    /// # Note
+    /// Intents resolve against the map as left by the intents before them,
+    /// so a move into a tile vacated earlier in the turn succeeds while a move into a tile taken earlier is blocked.
+    fn resolve_turn(&mut self) -> &mut Self {
+        let map_id = self.id();
+        self.commands().queue(ResolveTurn::<N> { map_id });
+
+        self
+    }
+
     fn spawn_tile_batch(
         &mut self,
         tile_cs: impl IntoIterator<Item = [i32; N]> + Send + 'static,
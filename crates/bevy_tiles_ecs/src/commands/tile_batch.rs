@@ -1,12 +1,12 @@
-use std::iter::repeat;
+use std::iter::repeat_n;
 
 use bevy::prelude::{Bundle, Command, Entity, World};
 use bevy_tiles::{
-    commands::{insert_tile_batch, TempRemove},
+    commands::{insert_tile_batch, missing_map, TempRemove},
     maps::TileMap,
 };
 
-use crate::EntityTile;
+use crate::{pool::despawn_or_pool, EntityTile};
 
 pub struct SpawnTileBatch<TC, TB, const N: usize> {
     pub map_id: Entity,
@@ -22,7 +22,7 @@ where
     fn apply(self, world: &mut World) {
         let replaced = {
             let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
-                panic!("No tilemap found!")
+                return missing_map(world, self.map_id);
             };
 
             let mut tile_cs = Vec::new();
@@ -32,7 +32,7 @@ where
 
             let spawned: Vec<EntityTile> = map
                 .get_world_mut()
-                .spawn_batch(repeat(self.tile_b).take(tile_cs.len()))
+                .spawn_batch(repeat_n(self.tile_b, tile_cs.len()))
                 .map(EntityTile)
                 .collect();
 
@@ -40,7 +40,7 @@ where
         };
 
         for replaced in replaced {
-            world.despawn(*replaced);
+            despawn_or_pool::<N>(world, *replaced);
         }
     }
 }
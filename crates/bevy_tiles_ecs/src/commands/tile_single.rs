@@ -1,13 +1,17 @@
 use bevy::{
     ecs::{entity::Entity, world::World},
-    prelude::Command,
+    prelude::{Command, Transform},
 };
 use bevy_tiles::{
-    commands::{insert_tile, take_tile, TempRemove},
-    maps::TileMap,
+    commands::{insert_tile, missing_map, take_tile, TempRemove},
+    maps::{MapOrigin, TileDims, TileMap, TileSpacing, UseTransforms},
 };
 
-use crate::EntityTile;
+use crate::{
+    entity_tile::{transform_to_tile_coord, InChunk, TileCoord},
+    pool::despawn_or_pool,
+    EntityTile,
+};
 
 pub struct SpawnTile<const N: usize> {
     pub map_id: Entity,
@@ -19,14 +23,14 @@ impl<const N: usize> Command for SpawnTile<N> {
     fn apply(self, world: &mut World) {
         let replaced = {
             let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
-                panic!("No tilemap found!")
+                return missing_map(world, self.map_id);
             };
 
             insert_tile::<EntityTile, N>(&mut map, self.tile_c, self.tile_id)
         };
 
         if let Some(replaced) = replaced {
-            world.despawn(*replaced);
+            despawn_or_pool::<N>(world, *replaced);
         }
     }
 }
@@ -40,12 +44,12 @@ impl<const N: usize> Command for DespawnTile<N> {
     fn apply(self, world: &mut World) {
         if let Some(id) = {
             let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
-                panic!("No tilemap found!")
+                return missing_map(world, self.map_id);
             };
 
             take_tile::<EntityTile, N>(&mut map, self.tile_c)
         } {
-            world.despawn(*id);
+            despawn_or_pool::<N>(world, *id);
         }
     }
 }
@@ -63,26 +67,20 @@ impl<const N: usize> Command for SwapTile<N> {
         }
 
         let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
-            panic!("No tilemap found!")
+            return missing_map(world, self.map_id);
         };
 
         let tile_id_0 = take_tile::<EntityTile, N>(&mut map, self.tile_c_0);
 
         let tile_id_1 = take_tile::<EntityTile, N>(&mut map, self.tile_c_1);
 
-        let res_0 = tile_id_0.map(|tile_id_0| {
-            (
-                tile_id_0,
-                insert_tile::<EntityTile, N>(&mut map, self.tile_c_1, tile_id_0),
-            )
-        });
-
-        let res_1 = tile_id_1.map(|tile_id_1| {
-            (
-                tile_id_1,
-                insert_tile::<EntityTile, N>(&mut map, self.tile_c_0, tile_id_1),
-            )
-        });
+        if let Some(tile_id_0) = tile_id_0 {
+            insert_tile::<EntityTile, N>(&mut map, self.tile_c_1, tile_id_0);
+        }
+
+        if let Some(tile_id_1) = tile_id_1 {
+            insert_tile::<EntityTile, N>(&mut map, self.tile_c_0, tile_id_1);
+        }
     }
 }
 
@@ -96,7 +94,7 @@ impl<const N: usize> Command for MoveTile<N> {
     fn apply(self, world: &mut World) {
         let replaced = {
             let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
-                panic!("No tilemap found!")
+                return missing_map(world, self.map_id);
             };
 
             let Some(id) = take_tile::<EntityTile, N>(&mut map, self.old_c) else {
@@ -107,7 +105,69 @@ impl<const N: usize> Command for MoveTile<N> {
         };
 
         if let Some(replaced) = replaced {
-            world.despawn(*replaced);
+            despawn_or_pool::<N>(world, *replaced);
         }
     }
 }
+
+pub struct ReindexTile<const N: usize> {
+    pub map_id: Entity,
+    pub tile_id: Entity,
+}
+
+impl<const N: usize> Command for ReindexTile<N> {
+    fn apply(self, world: &mut World) {
+        let Some(new_c) = derive_tile_coord::<N>(world, self.map_id, self.tile_id) else {
+            return;
+        };
+        let Some(old_c) = world
+            .get::<TileCoord<N>>(self.tile_id)
+            .map(|tile_c| **tile_c)
+        else {
+            return;
+        };
+        if old_c == new_c {
+            return;
+        }
+
+        MoveTile {
+            map_id: self.map_id,
+            old_c,
+            new_c,
+        }
+        .apply(world);
+    }
+}
+
+/// Get the coordinate a tile should have based on its transform, if the map uses transforms.
+pub(crate) fn derive_tile_coord<const N: usize>(
+    world: &mut World,
+    map_id: Entity,
+    tile_id: Entity,
+) -> Option<[i32; N]> {
+    let (_, tile_dims, tile_spacing, map_origin) = world
+        .query::<(
+            &UseTransforms,
+            &TileDims<N>,
+            Option<&TileSpacing<N>>,
+            Option<&MapOrigin<N>>,
+        )>()
+        .get(world, map_id)
+        .ok()?;
+    let (tile_dims, tile_spacing, map_origin) = (
+        *tile_dims,
+        tile_spacing.cloned(),
+        map_origin.cloned().unwrap_or_default(),
+    );
+    let (tile_t, in_chunk) = world
+        .query::<(&Transform, &InChunk)>()
+        .get(world, tile_id)
+        .ok()?;
+    let chunk_t = world.get::<Transform>(**in_chunk)?;
+    Some(map_origin.to_absolute(transform_to_tile_coord(
+        chunk_t,
+        tile_t,
+        tile_dims,
+        tile_spacing,
+    )))
+}
@@ -0,0 +1,79 @@
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    prelude::Command,
+    utils::HashMap,
+};
+use bevy_tiles::{
+    commands::{get_tile, insert_tile, missing_map, take_tile, TempRemove},
+    maps::TileMap,
+};
+
+use crate::{
+    entity_tile::TileCoord,
+    turns::{Intent, IntentResolved, Outcome, TurnQueue},
+    EntityTile,
+};
+
+pub struct ResolveTurn<const N: usize> {
+    pub map_id: Entity,
+}
+
+impl<const N: usize> Command for ResolveTurn<N> {
+    fn apply(self, world: &mut World) {
+        let Some(mut queue) = world.get_mut::<TurnQueue<N>>(self.map_id) else {
+            return;
+        };
+        let intents = queue.take_ordered();
+
+        // Moves only happen through this command while resolving, so track actors locally.
+        let mut actor_cs: HashMap<Entity, [i32; N]> = intents
+            .iter()
+            .filter_map(|queued| {
+                let tile_c = world.get::<TileCoord<N>>(queued.actor)?;
+                Some((queued.actor, **tile_c))
+            })
+            .collect();
+
+        let mut resolved = Vec::with_capacity(intents.len());
+        {
+            let Some(mut map) = world.temp_remove::<TileMap<N>>(self.map_id) else {
+                return missing_map(world, self.map_id);
+            };
+
+            for queued in intents {
+                let actor_c = actor_cs.get(&queued.actor).copied().filter(|tile_c| {
+                    get_tile::<EntityTile, N>(&map, *tile_c)
+                        .is_some_and(|tile| **tile == queued.actor)
+                });
+
+                let outcome = match (actor_c, queued.intent) {
+                    (None, _) => Outcome::Missing,
+                    (Some(_), Intent::Wait) => Outcome::Waited,
+                    (Some(_), Intent::Attack(target_c)) => Outcome::Attacked(
+                        get_tile::<EntityTile, N>(&map, target_c).map(|tile| **tile),
+                    ),
+                    (Some(actor_c), Intent::Move(target_c)) => {
+                        match get_tile::<EntityTile, N>(&map, target_c) {
+                            Some(tile) if **tile != queued.actor => Outcome::Blocked(**tile),
+                            Some(_) => Outcome::Moved,
+                            None => {
+                                let tile = take_tile::<EntityTile, N>(&mut map, actor_c).unwrap();
+                                insert_tile::<EntityTile, N>(&mut map, target_c, tile);
+                                actor_cs.insert(queued.actor, target_c);
+                                Outcome::Moved
+                            }
+                        }
+                    }
+                };
+
+                resolved.push(IntentResolved {
+                    actor: queued.actor,
+                    intent: queued.intent,
+                    outcome,
+                });
+            }
+        }
+
+        world.send_event_batch(resolved);
+    }
+}
@@ -4,20 +4,24 @@ use bevy::{
     ecs::query::WorldQuery,
     math::{IVec2, IVec3, Vec2, Vec3},
     prelude::{
-        BuildChildren, BuildChildrenTransformExt, Component, Deref, DerefMut, Entity,
-        EntityWorldMut, InheritedVisibility, Transform, Visibility,
+        BuildChildren, Component, Deref, DerefMut, Entity, EntityWorldMut, InheritedVisibility,
+        Query, Transform, Trigger, Visibility, World,
     },
 };
 use bevy_tiles::{
-    chunks::{ChunkData, ChunkTypes},
-    coords::{
-        calculate_chunk_relative_tile_coordinate,
-        calculate_chunk_relative_tile_coordinate_from_index,
-    },
-    maps::{TileDims, TileSpacing},
+    chunks::{ChunkData, ChunkTypes, FixedLayers, InMap},
+    coords::{calculate_chunk_relative_tile_coordinate_from_index, calculate_tile_coordinate},
+    maps::{TileDims, TileMap, TileSpacing},
+    merge::{merge_layer, MergePolicy},
+    observers::OnChunkMoved,
     queries::{ReadOnlyTileData, TileComponent, TileData, TileDataQuery},
 };
 
+use crate::{
+    lean::{LeanChunk, LeanTiles},
+    pool::despawn_or_pool,
+};
+
 #[derive(Deref, DerefMut, Clone, Copy, Debug, PartialEq, Eq)]
 /// TileComponent for tracking entities.
 pub struct EntityTile(pub Entity);
@@ -40,6 +44,12 @@ impl TileDataQuery for EntityTile {
     ) -> Option<Self::Item<'_>> {
         source.get(index).cloned()
     }
+
+    fn get_all(
+        source: <<Self as TileDataQuery>::Source as WorldQuery>::Item<'_>,
+    ) -> Vec<Option<Self::Item<'_>>> {
+        source.iter().map(|tile| tile.cloned()).collect()
+    }
 }
 
 /// # Safety:
@@ -48,7 +58,7 @@ unsafe impl TileComponent for EntityTile {
     fn insert_tile_into_chunk<const N: usize>(
         self,
         mut chunk: EntityWorldMut<'_>,
-        chunk_c: [i32; N],
+        _chunk_c: [i32; N],
         chunk_size: usize,
         use_transforms: bool,
         tile_dims: Option<TileDims<N>>,
@@ -79,18 +89,7 @@ unsafe impl TileComponent for EntityTile {
             calc_tile_transform(use_transforms, tile_dims, tile_spacing, tile_i, chunk_size);
 
         chunk.world_scope(|world| {
-            world
-                .get_entity_mut(*self)
-                .unwrap()
-                .insert((
-                    tile_t.unwrap_or_default(),
-                    Visibility::default(),
-                    InheritedVisibility::default(),
-                    TileIndex(tile_i),
-                    TileCoord(tile_c),
-                    InChunk(chunk_id),
-                ))
-                .set_parent(chunk_id);
+            place_tile_entity(world, *self, chunk_id, tile_t, tile_i, tile_c);
         });
 
         res
@@ -100,7 +99,7 @@ unsafe impl TileComponent for EntityTile {
         let location = chunk.get_mut::<ChunkData<Self>>();
         let mut binding = location?;
         let removed = binding.take(tile_i);
-        if binding.get_count() == 0 {
+        if binding.get_count() == 0 && !chunk.contains::<FixedLayers>() {
             chunk
                 .get_mut::<ChunkTypes>()
                 .unwrap()
@@ -119,7 +118,7 @@ unsafe impl TileComponent for EntityTile {
     fn insert_tile_batch_into_chunk<const N: usize>(
         tiles: impl Iterator<Item = Self>,
         mut chunk: EntityWorldMut<'_>,
-        chunk_c: [i32; N],
+        _chunk_c: [i32; N],
         chunk_size: usize,
         use_transforms: bool,
         tile_dims: Option<TileDims<N>>,
@@ -147,18 +146,7 @@ unsafe impl TileComponent for EntityTile {
                 calc_tile_transform(use_transforms, tile_dims, tile_spacing, tile_i, chunk_size);
 
             chunk.world_scope(|world| {
-                world
-                    .get_entity_mut(*tile)
-                    .unwrap()
-                    .insert((
-                        tile_t.unwrap_or_default(),
-                        Visibility::default(),
-                        InheritedVisibility::default(),
-                        TileIndex(tile_i),
-                        TileCoord(tile_c),
-                        InChunk(chunk_id),
-                    ))
-                    .set_parent(chunk_id);
+                place_tile_entity(world, *tile, chunk_id, tile_t, tile_i, tile_c);
             });
 
             if let Some(res) = res {
@@ -169,6 +157,82 @@ unsafe impl TileComponent for EntityTile {
         chunk.insert(chunk_data);
         removed.into_iter()
     }
+
+    fn discard_tile<const N: usize>(self, world: &mut World) {
+        // The entity may have been written back into the map (ex: setting a chunk's data to what it already holds).
+        let placed = world
+            .get::<InChunk>(*self)
+            .zip(world.get::<TileIndex>(*self))
+            .and_then(|(in_chunk, tile_i)| world.get::<ChunkData<Self>>(**in_chunk)?.get(**tile_i))
+            .is_some_and(|tile| *tile == self);
+        if !placed {
+            despawn_or_pool::<N>(world, *self);
+        }
+    }
+}
+
+/// Moves the tile entities of a source map into a destination map, despawning (or pooling) the tiles that lose a
+/// conflict.
+/// # Note
+/// Register this with [`bevy_tiles::merge::MergeLayers::register_with`] to move tile entities when merging maps.
+pub fn merge_entity_tiles<const N: usize>(
+    world: &mut World,
+    dst_id: Entity,
+    src_id: Entity,
+    offset: [i32; N],
+    policy: MergePolicy,
+) {
+    for tile in merge_layer::<EntityTile, N>(world, dst_id, src_id, offset, policy) {
+        despawn_or_pool::<N>(world, *tile);
+    }
+}
+
+/// Updates the [`TileCoord`] of the tile entities of a chunk that moved to another coordinate.
+/// # Note
+/// Added for 2d and 3d maps by [`crate::TilesPlugin`].
+pub fn move_chunk_tiles<const N: usize>(
+    trigger: Trigger<OnChunkMoved<N>>,
+    maps: Query<&TileMap<N>>,
+    chunks: Query<&ChunkData<EntityTile>>,
+    mut tiles: Query<&mut TileCoord<N>>,
+) {
+    let (Ok(map), Ok(chunk_data)) = (maps.get(trigger.entity()), chunks.get(trigger.chunk_id))
+    else {
+        return;
+    };
+    let chunk_size = map.get_chunk_size();
+    for (tile_i, tile_id) in chunk_data.iter().enumerate() {
+        let Some(mut tile_c) = tile_id.and_then(|tile_id| tiles.get_mut(**tile_id).ok()) else {
+            continue;
+        };
+        tile_c.0 = calculate_tile_coordinate(trigger.to, tile_i, chunk_size);
+    }
+}
+
+/// Adds the tile components to a tile entity, and parents it to its chunk unless the map has [`LeanTiles`].
+fn place_tile_entity<const N: usize>(
+    world: &mut World,
+    tile_id: Entity,
+    chunk_id: Entity,
+    tile_t: Option<Transform>,
+    tile_i: usize,
+    tile_c: [i32; N],
+) {
+    let lean = world
+        .get::<InMap>(chunk_id)
+        .is_some_and(|in_map| world.get::<LeanTiles>(**in_map).is_some());
+    let mut tile = world.get_entity_mut(tile_id).unwrap();
+    tile.insert((TileIndex(tile_i), TileCoord(tile_c), InChunk(chunk_id)));
+    if !lean {
+        tile.insert((
+            tile_t.unwrap_or_default(),
+            Visibility::default(),
+            InheritedVisibility::default(),
+        ))
+        .set_parent(chunk_id);
+    } else if !world.entity(chunk_id).contains::<LeanChunk<N>>() {
+        world.entity_mut(chunk_id).insert(LeanChunk::<N>);
+    }
 }
 
 #[inline]
@@ -214,6 +278,29 @@ fn calc_tile_transform<const N: usize>(
     }
 }
 
+/// Calculate the tile coordinate a tile entity sits on from its transform and the transform of its chunk,
+/// snapping to the closest tile.
+/// # Note
+/// Only the first `N` axes of the translations are used.
+#[inline]
+pub fn transform_to_tile_coord<const N: usize>(
+    chunk_t: &Transform,
+    tile_t: &Transform,
+    tile_dims: TileDims<N>,
+    tile_spacing: Option<TileSpacing<N>>,
+) -> [i32; N] {
+    if N > 3 {
+        panic!("Can't use transforms on tilemaps with more than 3 dimensions :)");
+    }
+    let translation = chunk_t.translation + tile_t.translation;
+    let mut tile_c = [0; N];
+    for (i, c) in tile_c.iter_mut().enumerate() {
+        let step = tile_dims.0[i] + tile_spacing.map(|spacing| spacing.0[i]).unwrap_or(0.0);
+        *c = (translation[i] / step).round() as i32;
+    }
+    tile_c
+}
+
 #[inline]
 fn calc_tile_trans_dim<const N: usize>(
     dim: usize,
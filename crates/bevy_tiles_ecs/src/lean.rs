@@ -0,0 +1,272 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    ecs::{
+        component::{Component, ComponentHooks, StorageType},
+        entity::Entity,
+        query::{Changed, With},
+        schedule::IntoSystemConfigs,
+        system::{Commands, Query},
+        world::World,
+    },
+    math::Vec3,
+    prelude::Transform,
+    transform::{components::GlobalTransform, TransformSystem},
+    utils::HashSet,
+};
+use bevy_tiles::{
+    chunks::{ChunkData, InMap},
+    convert::{MapSpace, MapSpaces},
+    coords::{calculate_tile_index, CoordIterator},
+    maps::TileMap,
+};
+
+use crate::{
+    entity_tile::{InChunk, TileCoord},
+    pool::despawn_or_pool,
+    EntityTile,
+};
+
+/// Keeps the tile entities of a map as light as possible, for maps with a very large number of them.
+/// # Note
+/// Tile entities of maps with this component only get [`crate::entity_tile::TileIndex`], [`TileCoord`],
+/// and [`InChunk`].  They aren't children of their chunk and don't get [`Transform`] or visibility components,
+/// use the [`TileActivationPlugin`] to give tiles near a [`TileActivator`] a [`Transform`].
+///
+/// Tile entities aren't children of their chunk, but despawning the map or one of its chunks still despawns them
+/// (or puts them in the [`crate::pool::TilePool`]).
+/// Add this before spawning any tiles, tiles that are already in the map are left as is.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct LeanTiles;
+
+/// Marks a chunk of a map with [`LeanTiles`], so the tile entities in its [`ChunkData<EntityTile>`] are despawned
+/// (or pooled) along with it.
+pub(crate) struct LeanChunk<const N: usize>;
+
+impl<const N: usize> Component for LeanChunk<N> {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        // Nothing removes this from a chunk, so it's only removed when the chunk is despawned.
+        hooks.on_remove(|mut world, chunk_id, _| {
+            let Some(chunk_data) = world.get::<ChunkData<EntityTile>>(chunk_id) else {
+                return;
+            };
+            let tiles: Vec<Entity> = chunk_data.iter().flatten().map(|tile| **tile).collect();
+            let mut commands = world.commands();
+            for tile_id in tiles {
+                commands.queue(move |world: &mut World| despawn_or_pool::<N>(world, tile_id));
+            }
+        });
+    }
+}
+
+/// Gives the tile entities of maps with [`LeanTiles`] a [`Transform`] while they're within `radius` (in world units)
+/// of this entity's [`GlobalTransform`] (ex: put this on a camera).
+#[derive(Component, Clone, Copy, Debug)]
+pub struct TileActivator {
+    /// The distance from the activator to the corner of a tile it activates.
+    pub radius: f32,
+}
+
+/// Marks a tile entity of a map with [`LeanTiles`] that was given a [`Transform`] by a [`TileActivator`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ActiveTile;
+
+/// Adds and removes [`Transform`]s on the tile entities of maps with [`LeanTiles`] as [`TileActivator`]s move around.
+/// # Note
+/// Active tiles are placed in world space directly, maps need [`bevy_tiles::maps::TileDims`] for tiles to be activated.
+#[derive(Default)]
+pub struct TileActivationPlugin<const N: usize> {
+    dims: PhantomData<[(); N]>,
+}
+
+impl<const N: usize> Plugin for TileActivationPlugin<N> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            activate_tiles::<N>.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+type ActiveTiles<'w, 's, const N: usize> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static TileCoord<N>,
+        &'static InChunk,
+        &'static mut Transform,
+    ),
+    With<ActiveTile>,
+>;
+
+/// Activates the tiles of maps with [`LeanTiles`] that are near a [`TileActivator`], and deactivates the tiles
+/// that are no longer near one.
+pub fn activate_tiles<const N: usize>(
+    mut commands: Commands,
+    activators: Query<(&TileActivator, &GlobalTransform)>,
+    maps: Query<(Entity, &TileMap<N>), With<LeanTiles>>,
+    spaces: MapSpaces<N>,
+    chunks: Query<&ChunkData<EntityTile>>,
+    in_maps: Query<&InMap>,
+    mut active: ActiveTiles<N>,
+    moved: Query<(), Changed<TileCoord<N>>>,
+) {
+    let mut in_range = HashSet::new();
+    for (map_id, map) in maps.iter() {
+        let Some(space) = spaces.get(map_id) else {
+            continue;
+        };
+        let chunk_size = map.get_chunk_size();
+        for (activator, activator_t) in activators.iter() {
+            let center = activator_t.translation();
+            let reach: [i32; N] =
+                std::array::from_fn(|i| (activator.radius / space.pitch(i)).ceil() as i32 + 1);
+            let center_c = space.world_to_tile(center);
+            let min = std::array::from_fn(|i| center_c[i] - reach[i]);
+            let max = std::array::from_fn(|i| center_c[i] + reach[i]);
+            for tile_c in CoordIterator::new(min, max) {
+                let Some(tile_id) = map
+                    .get_from_tile(tile_c)
+                    .and_then(|chunk_id| chunks.get(chunk_id).ok())
+                    .and_then(|data| data.get(calculate_tile_index(tile_c, chunk_size)))
+                else {
+                    continue;
+                };
+                let corner = tile_corner(&space, tile_c);
+                if corner.distance(center) <= activator.radius
+                    && in_range.insert(**tile_id)
+                    && !active.contains(**tile_id)
+                {
+                    commands
+                        .entity(**tile_id)
+                        .insert((Transform::from_translation(corner), ActiveTile));
+                }
+            }
+        }
+    }
+
+    for (tile_id, tile_c, in_chunk, mut tile_t) in active.iter_mut() {
+        if !in_range.contains(&tile_id) {
+            commands
+                .entity(tile_id)
+                .remove::<(Transform, GlobalTransform, ActiveTile)>();
+        } else if moved.contains(tile_id) {
+            let space = in_maps
+                .get(**in_chunk)
+                .ok()
+                .and_then(|in_map| spaces.get(**in_map));
+            if let Some(space) = space {
+                tile_t.translation = tile_corner(&space, **tile_c);
+            }
+        }
+    }
+}
+
+/// The world space position of a tile's lowest corner, matching where tiles of other maps are placed.
+fn tile_corner<const N: usize>(space: &MapSpace<N>, tile_c: [i32; N]) -> Vec3 {
+    space.tile_anchor_to_world(tile_c, [0.0; N])
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{hierarchy::Parent, render::view::Visibility};
+    use bevy_tiles::{commands::TileCommandExt, maps::TileDims, testing};
+
+    use crate::{commands::TileMapCommandsECSExt, pool::TilePool};
+
+    use super::*;
+
+    #[test]
+    fn activates_tiles_near_activator() {
+        let mut app = App::new();
+        app.add_plugins(TileActivationPlugin::<2>::default());
+
+        let map_id = testing::spawn_map(app.world_mut(), 4, |map| {
+            map.insert((LeanTiles, TileDims([16.0, 16.0])));
+        });
+        let (near, far) = testing::apply_map(app.world_mut(), map_id, |map| {
+            (
+                map.spawn_tile([1, 0], ()).id(),
+                map.spawn_tile([20, 0], ()).id(),
+            )
+        });
+        let activator = app
+            .world_mut()
+            .spawn((TileActivator { radius: 40.0 }, GlobalTransform::IDENTITY))
+            .id();
+        app.update();
+
+        for tile_id in [near, far] {
+            let tile = app.world().entity(tile_id);
+            assert!(!tile.contains::<Parent>());
+            assert!(!tile.contains::<Visibility>());
+        }
+        assert_eq!(
+            app.world().get::<Transform>(near).unwrap().translation,
+            Vec3::new(16.0, 0.0, 0.0)
+        );
+        assert!(app.world().get::<Transform>(far).is_none());
+
+        *app.world_mut()
+            .get_mut::<GlobalTransform>(activator)
+            .unwrap() = GlobalTransform::from_xyz(320.0, 0.0, 0.0);
+        app.update();
+        assert!(app.world().get::<Transform>(near).is_none());
+        assert!(app.world().get::<ActiveTile>(far).is_some());
+    }
+
+    #[test]
+    fn despawns_lean_tiles_with_their_chunk_and_map() {
+        let mut app = App::new();
+        let map_id = testing::spawn_map(app.world_mut(), 4, |map| {
+            map.insert(LeanTiles);
+        });
+        let tiles = testing::apply_map(app.world_mut(), map_id, |map| {
+            [[1, 0], [2, 3], [20, 0]].map(|tile_c| map.spawn_tile(tile_c, ()).id())
+        });
+        app.update();
+
+        testing::apply_map(app.world_mut(), map_id, |map| {
+            map.despawn_chunk([0, 0]);
+        });
+        app.update();
+        assert!(app.world().get_entity(tiles[0]).is_err());
+        assert!(app.world().get_entity(tiles[1]).is_err());
+        assert!(app.world().get_entity(tiles[2]).is_ok());
+
+        TileCommandExt::<2>::despawn_map(&mut app.world_mut().commands(), map_id);
+        app.update();
+        assert!(app.world().get_entity(tiles[2]).is_err());
+        assert!(app
+            .world_mut()
+            .query::<&TileCoord<2>>()
+            .iter(app.world())
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn pools_lean_tiles_with_their_chunk() {
+        let mut app = App::new();
+        app.insert_resource(TilePool::new(4));
+        let map_id = testing::spawn_map(app.world_mut(), 4, |map| {
+            map.insert(LeanTiles);
+        });
+        let tiles = testing::apply_map(app.world_mut(), map_id, |map| {
+            [[1, 0], [2, 3]].map(|tile_c| map.spawn_tile(tile_c, ()).id())
+        });
+
+        testing::apply_map(app.world_mut(), map_id, |map| {
+            map.despawn_chunk([0, 0]);
+        });
+        app.update();
+        assert_eq!(app.world().resource::<TilePool>().len(), 2);
+        for tile_id in tiles {
+            assert!(!app.world().entity(tile_id).contains::<TileCoord<2>>());
+        }
+    }
+}
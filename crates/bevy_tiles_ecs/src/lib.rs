@@ -13,8 +13,20 @@ use bevy::app::Plugin;
 pub mod commands;
 /// The entity tracking tile component.
 pub mod entity_tile;
+/// Provides lightweight tile entities for maps with a very large number of them.
+pub mod lean;
+/// Provides smooth movement of tile entities between coordinates.
+pub mod movement;
+/// Provides reuse of despawned tile entities.
+pub mod pool;
+/// Provides syncing of tile coordinates and orientations with transforms.
+pub mod sync;
 /// Provides tile level utilities.
 pub mod tiles;
+/// Provides enter and leave events for tile entities moving across regions.
+pub mod triggers;
+/// Provides turn based intent resolution for tile entities.
+pub mod turns;
 
 pub(crate) use entity_tile::EntityTile;
 
@@ -28,6 +40,12 @@ pub mod tiles_2d {
     /// 2d [crate::tiles::TileEntityMapQuery] alias.
     pub type TileEntityMapQuery<'w, 's, Q, F> = crate::tiles::TileEntityMapQuery<'w, 's, Q, F, 2>;
 
+    /// 2d [crate::triggers::TileTrigger] alias.
+    pub type TileTrigger = crate::triggers::TileTrigger<2>;
+
+    /// 2d [crate::triggers::TileRegion] alias.
+    pub type TileRegion = crate::triggers::TileRegion<2>;
+
     /// 2d [crate::commands::TileCommandExt] alias.
     pub trait TileMapCommandsECSExt: crate::commands::TileMapCommandsECSExt<2> {}
 
@@ -44,6 +62,12 @@ pub mod tiles_3d {
     /// 2d [crate::tiles::TileEntityMapQuery] alias.
     pub type TileEntityMapQuery<'w, 's, Q, F> = crate::tiles::TileEntityMapQuery<'w, 's, Q, F, 3>;
 
+    /// 3d [crate::triggers::TileTrigger] alias.
+    pub type TileTrigger = crate::triggers::TileTrigger<3>;
+
+    /// 3d [crate::triggers::TileRegion] alias.
+    pub type TileRegion = crate::triggers::TileRegion<3>;
+
     /// 2d [crate::commands::TileCommandExt] alias.
     pub trait TileMapCommandsECSExt: crate::commands::TileMapCommandsECSExt<3> {}
 
@@ -54,5 +78,8 @@ pub mod tiles_3d {
 pub struct TilesPlugin;
 
 impl Plugin for TilesPlugin {
-    fn build(&self, _app: &mut bevy::prelude::App) {}
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_observer(entity_tile::move_chunk_tiles::<2>)
+            .add_observer(entity_tile::move_chunk_tiles::<3>);
+    }
 }
@@ -0,0 +1,310 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    app::{App, Plugin, PostUpdate, Update},
+    ecs::{
+        component::Component,
+        query::{With, Without},
+        schedule::IntoSystemConfigs,
+        system::{Commands, Query, Res},
+    },
+    math::{
+        curve::{Curve, EaseFunction, EasingCurve},
+        Vec3,
+    },
+    prelude::Transform,
+    time::Time,
+    transform::TransformSystem,
+};
+use bevy_tiles::{
+    chunks::InMap,
+    commands::TileCommandExt,
+    maps::{TileDims, TileSpacing, UseTransforms},
+};
+
+use crate::{
+    commands::TileMapCommandsECSExt,
+    entity_tile::{InChunk, TileCoord},
+};
+
+/// When a [`TileMover`] moves its tile in the grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CommitMove {
+    /// Move the tile in the grid as soon as the movement starts, the tile occupies its destination while it slides in.
+    #[default]
+    Start,
+    /// Move the tile in the grid once the movement finishes, the tile occupies its origin while it slides out.
+    End,
+}
+
+/// Smoothly slides a tile entity between coordinates instead of snapping its [`Transform`].
+/// # Note
+/// Only works on maps with [`UseTransforms`] and [`TileDims`], on other maps moves are committed immediately.
+/// Don't combine this with [`crate::sync::TransformSync::Snap`], or tiles will be reindexed halfway through their movement.
+#[derive(Component, Clone, Debug)]
+pub struct TileMover<const N: usize> {
+    /// Time in seconds a move takes.
+    pub duration: f32,
+    /// Easing applied over the duration of the move.
+    pub easing: EaseFunction,
+    /// When the grid is updated.
+    pub commit: CommitMove,
+    target: Option<[i32; N]>,
+    active: Option<ActiveMove<N>>,
+}
+
+#[derive(Clone, Debug)]
+struct ActiveMove<const N: usize> {
+    from_c: [i32; N],
+    to_c: [i32; N],
+    /// Start and end translations relative to the map.
+    curve: EasingCurve<Vec3>,
+    elapsed: f32,
+}
+
+impl<const N: usize> TileMover<N> {
+    /// Create a mover with linear easing that commits moves at the start.
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            easing: EaseFunction::Linear,
+            commit: CommitMove::default(),
+            target: None,
+            active: None,
+        }
+    }
+
+    /// Set the easing of this mover.
+    pub fn with_easing(mut self, easing: EaseFunction) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Set when this mover updates the grid.
+    pub fn with_commit(mut self, commit: CommitMove) -> Self {
+        self.commit = commit;
+        self
+    }
+
+    /// Request a move to the given coordinate, starting once the current move (if any) finishes.
+    /// Requesting another move before this one starts replaces it.
+    pub fn move_to(&mut self, tile_c: impl Into<[i32; N]>) {
+        self.target = Some(tile_c.into());
+    }
+
+    /// True if a move is in progress.
+    pub fn is_moving(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// True if a move is in progress or waiting to start.
+    pub fn is_busy(&self) -> bool {
+        self.active.is_some() || self.target.is_some()
+    }
+}
+
+/// Adds [`TileMover`] support for maps with `N` dimensions.
+pub struct TileMoverPlugin<const N: usize>(PhantomData<[(); N]>);
+
+impl<const N: usize> Default for TileMoverPlugin<N> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<const N: usize> Plugin for TileMoverPlugin<N> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, start_tile_moves::<N>).add_systems(
+            PostUpdate,
+            tween_tile_movers::<N>.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Starts requested moves, committing them to the grid if the mover uses [`CommitMove::Start`].
+pub fn start_tile_moves<const N: usize>(
+    mut commands: Commands,
+    mut movers: Query<(&mut TileMover<N>, &TileCoord<N>, &Transform, &InChunk)>,
+    chunks: Query<(&Transform, &InMap)>,
+    maps: Query<(&TileDims<N>, Option<&TileSpacing<N>>), With<UseTransforms>>,
+) {
+    for (mut mover, tile_c, tile_t, in_chunk) in movers.iter_mut() {
+        if mover.active.is_some() {
+            continue;
+        }
+        let Some(to_c) = mover.target.take() else {
+            continue;
+        };
+        let from_c = **tile_c;
+        if from_c == to_c {
+            continue;
+        }
+        let Ok((chunk_t, in_map)) = chunks.get(**in_chunk) else {
+            continue;
+        };
+        let map_id = **in_map;
+
+        let Ok((tile_dims, tile_spacing)) = maps.get(map_id) else {
+            if let Some(mut map) = TileCommandExt::<N>::tile_map(&mut commands, map_id) {
+                map.move_tile(from_c, to_c);
+            }
+            continue;
+        };
+
+        let from = chunk_t.translation + tile_t.translation;
+        let mut to = from;
+        for i in 0..N.min(3) {
+            let step = tile_dims.0[i] + tile_spacing.map(|spacing| spacing.0[i]).unwrap_or(0.0);
+            to[i] += (to_c[i] - from_c[i]) as f32 * step;
+        }
+
+        if mover.commit == CommitMove::Start {
+            if let Some(mut map) = TileCommandExt::<N>::tile_map(&mut commands, map_id) {
+                map.move_tile(from_c, to_c);
+            }
+        }
+        let curve = EasingCurve::new(from, to, mover.easing);
+        mover.active = Some(ActiveMove {
+            from_c,
+            to_c,
+            curve,
+            elapsed: 0.0,
+        });
+    }
+}
+
+/// Advances moves in progress, committing them to the grid if the mover uses [`CommitMove::End`].
+pub fn tween_tile_movers<const N: usize>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut movers: Query<(&mut TileMover<N>, &mut Transform, &InChunk)>,
+    chunks: Query<(&Transform, &InMap), Without<TileMover<N>>>,
+) {
+    for (mut mover, mut tile_t, in_chunk) in movers.iter_mut() {
+        let duration = mover.duration;
+        let commit = mover.commit;
+        let Some(active) = mover.active.as_mut() else {
+            continue;
+        };
+        let Ok((chunk_t, in_map)) = chunks.get(**in_chunk) else {
+            continue;
+        };
+
+        active.elapsed += time.delta_secs();
+        let t = if duration > 0.0 {
+            (active.elapsed / duration).min(1.0)
+        } else {
+            1.0
+        };
+        tile_t.translation = active.curve.sample_clamped(t) - chunk_t.translation;
+
+        if t >= 1.0 {
+            let (from_c, to_c) = (active.from_c, active.to_c);
+            mover.active = None;
+            if commit == CommitMove::End {
+                if let Some(mut map) = TileCommandExt::<N>::tile_map(&mut commands, **in_map) {
+                    map.move_tile(from_c, to_c);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy_tiles::testing;
+
+    use super::*;
+
+    fn step(app: &mut App, secs: f32) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(secs));
+        app.update();
+    }
+
+    #[test]
+    fn mover_commits_at_start() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_plugins(TileMoverPlugin::<2>::default());
+
+        let map_id = testing::spawn_map(app.world_mut(), 4, |map| {
+            map.insert((UseTransforms, TileDims([16.0, 16.0])));
+        });
+        let tile_id = testing::apply_map(app.world_mut(), map_id, |map| {
+            map.spawn_tile([0, 0], TileMover::<2>::new(1.0)).id()
+        });
+        step(&mut app, 0.0);
+
+        // Move across the chunk border, the tile is in its new chunk while it slides in.
+        app.world_mut()
+            .get_mut::<TileMover<2>>(tile_id)
+            .unwrap()
+            .move_to([5, 0]);
+        step(&mut app, 0.0);
+        assert_eq!(**app.world().get::<TileCoord<2>>(tile_id).unwrap(), [5, 0]);
+        assert_eq!(
+            app.world().get::<Transform>(tile_id).unwrap().translation.x,
+            -64.0
+        );
+
+        step(&mut app, 0.5);
+        assert_eq!(
+            app.world().get::<Transform>(tile_id).unwrap().translation.x,
+            -24.0
+        );
+
+        step(&mut app, 0.6);
+        assert!(!app
+            .world()
+            .get::<TileMover<2>>(tile_id)
+            .unwrap()
+            .is_moving());
+        assert_eq!(
+            app.world().get::<Transform>(tile_id).unwrap().translation.x,
+            16.0
+        );
+    }
+
+    #[test]
+    fn mover_commits_at_end() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_plugins(TileMoverPlugin::<2>::default());
+
+        let map_id = testing::spawn_map(app.world_mut(), 4, |map| {
+            map.insert((UseTransforms, TileDims([16.0, 16.0])));
+        });
+        let tile_id = testing::apply_map(app.world_mut(), map_id, |map| {
+            let mover = TileMover::<2>::new(1.0).with_commit(CommitMove::End);
+            map.spawn_tile([0, 0], mover).id()
+        });
+        step(&mut app, 0.0);
+
+        app.world_mut()
+            .get_mut::<TileMover<2>>(tile_id)
+            .unwrap()
+            .move_to([2, 0]);
+        step(&mut app, 0.5);
+        assert_eq!(
+            app.world().get::<Transform>(tile_id).unwrap().translation.x,
+            16.0
+        );
+        assert_eq!(**app.world().get::<TileCoord<2>>(tile_id).unwrap(), [0, 0]);
+
+        step(&mut app, 0.6);
+        assert!(!app
+            .world()
+            .get::<TileMover<2>>(tile_id)
+            .unwrap()
+            .is_moving());
+        assert_eq!(**app.world().get::<TileCoord<2>>(tile_id).unwrap(), [2, 0]);
+        assert_eq!(
+            app.world().get::<Transform>(tile_id).unwrap().translation.x,
+            32.0
+        );
+    }
+}
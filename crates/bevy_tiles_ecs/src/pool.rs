@@ -0,0 +1,79 @@
+use bevy::{
+    ecs::{entity::Entity, system::Resource, world::World},
+    prelude::{BuildChildren, DespawnRecursiveExt, EntityWorldMut},
+};
+
+use crate::entity_tile::{InChunk, TileCoord, TileIndex};
+
+/// Resets a tile entity before it's put in a [`TilePool`].
+pub type TileResetFn = fn(&mut EntityWorldMut);
+
+/// Keeps despawned tile entities around so they can be reused by
+/// [`crate::commands::TileMapCommandsECSExt::spawn_pooled_tile`] instead of spawning new ones.
+/// # Note
+/// Pooling is opt-in, while this resource exists tiles despawned by commands (including tiles that are replaced)
+/// are put in the pool until it's full, and only despawned after that.
+///
+/// Pooled entities stay alive without any tile components, by default their children are despawned and every other
+/// component is removed as well.  Use [`TilePool::with_reset`] to keep some components around and reset them instead.
+#[derive(Resource)]
+pub struct TilePool {
+    /// The most entities the pool will hold.
+    pub capacity: usize,
+    reset: TileResetFn,
+    free: Vec<Entity>,
+}
+
+impl TilePool {
+    /// Create a pool that holds up to `capacity` entities.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            reset: |tile| {
+                tile.despawn_descendants().clear();
+            },
+            free: Vec::new(),
+        }
+    }
+
+    /// Reset tile entities with the given function when they're pooled, instead of removing all their components.
+    pub fn with_reset(mut self, reset: TileResetFn) -> Self {
+        self.reset = reset;
+        self
+    }
+
+    /// Take an entity out of the pool.
+    pub fn take(&mut self) -> Option<Entity> {
+        self.free.pop()
+    }
+
+    /// The number of entities in the pool.
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Check if the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+}
+
+/// Puts a tile entity that was taken out of a map in the [`TilePool`], or despawns it if there's no pool or it's full.
+pub(crate) fn despawn_or_pool<const N: usize>(world: &mut World, tile_id: Entity) {
+    let reset = match world.get_resource::<TilePool>() {
+        Some(pool) if pool.free.len() < pool.capacity => pool.reset,
+        _ => {
+            if let Ok(tile) = world.get_entity_mut(tile_id) {
+                tile.despawn_recursive();
+            }
+            return;
+        }
+    };
+    let Ok(mut tile) = world.get_entity_mut(tile_id) else {
+        return;
+    };
+    tile.remove_parent()
+        .remove::<(TileIndex, TileCoord<N>, InChunk)>();
+    reset(&mut tile);
+    world.resource_mut::<TilePool>().free.push(tile_id);
+}
@@ -0,0 +1,220 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    ecs::{
+        entity::Entity,
+        query::{Changed, With},
+        system::{Commands, Query},
+    },
+    log::warn,
+    prelude::Transform,
+};
+use bevy_tiles::{
+    chunks::InMap,
+    commands::TileCommandExt,
+    maps::{MapOrigin, TileDims, TileSpacing, UseTransforms},
+    orientation::Orientation,
+};
+
+use crate::{
+    commands::TileMapCommandsECSExt,
+    entity_tile::{transform_to_tile_coord, InChunk, TileCoord},
+};
+
+/// What to do when a tile entity's transform no longer matches its [`TileCoord`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TransformSync {
+    /// Move the tile in the grid to the coordinate closest to its transform.
+    #[default]
+    Snap,
+    /// Log a warning and leave the grid alone.
+    Warn,
+}
+
+/// Keeps [`TileCoord`] in sync with tile entities that are moved by their transforms
+/// on maps with [`UseTransforms`] and [`TileDims`].
+#[derive(Default)]
+pub struct TileTransformSyncPlugin<const N: usize> {
+    /// How mismatches are handled.
+    pub mode: TransformSync,
+    dims: PhantomData<[(); N]>,
+}
+
+impl<const N: usize> TileTransformSyncPlugin<N> {
+    /// Create a plugin with the given mode.
+    pub fn new(mode: TransformSync) -> Self {
+        Self {
+            mode,
+            dims: PhantomData,
+        }
+    }
+}
+
+impl<const N: usize> Plugin for TileTransformSyncPlugin<N> {
+    fn build(&self, app: &mut App) {
+        match self.mode {
+            TransformSync::Snap => app.add_systems(PostUpdate, snap_tile_coords::<N>),
+            TransformSync::Warn => app.add_systems(PostUpdate, warn_tile_coords::<N>),
+        };
+    }
+}
+
+type MovedTiles<'w, 's, const N: usize> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static TileCoord<N>,
+        &'static Transform,
+        &'static InChunk,
+    ),
+    Changed<Transform>,
+>;
+type TileChunks<'w, 's> = Query<'w, 's, (&'static Transform, &'static InMap)>;
+type TransformMaps<'w, 's, const N: usize> = Query<
+    'w,
+    's,
+    (
+        &'static TileDims<N>,
+        Option<&'static TileSpacing<N>>,
+        Option<&'static MapOrigin<N>>,
+    ),
+    With<UseTransforms>,
+>;
+
+/// Queues a [`TileMapCommandsECSExt::reindex_tile`] for every tile entity that was moved off of its coordinate.
+pub fn snap_tile_coords<const N: usize>(
+    mut commands: Commands,
+    moved: MovedTiles<N>,
+    chunks: TileChunks,
+    maps: TransformMaps<N>,
+) {
+    for (map_id, tile_id, _, _) in mismatched_tiles(&moved, &chunks, &maps) {
+        if let Some(mut map) = TileCommandExt::<N>::tile_map(&mut commands, map_id) {
+            map.reindex_tile(tile_id);
+        }
+    }
+}
+
+/// Logs a warning for every tile entity that was moved off of its coordinate.
+pub fn warn_tile_coords<const N: usize>(
+    moved: MovedTiles<N>,
+    chunks: TileChunks,
+    maps: TransformMaps<N>,
+) {
+    for (map_id, tile_id, tile_c, transform_c) in mismatched_tiles(&moved, &chunks, &maps) {
+        warn!(
+            "Tile {tile_id} in map {map_id} is indexed at {tile_c:?} but its transform is at {transform_c:?}"
+        );
+    }
+}
+
+fn mismatched_tiles<'a, const N: usize>(
+    moved: &'a MovedTiles<N>,
+    chunks: &'a TileChunks,
+    maps: &'a TransformMaps<N>,
+) -> impl Iterator<Item = (Entity, Entity, [i32; N], [i32; N])> + 'a {
+    moved
+        .iter()
+        .filter_map(|(tile_id, tile_c, tile_t, in_chunk)| {
+            let (chunk_t, in_map) = chunks.get(**in_chunk).ok()?;
+            let (tile_dims, tile_spacing, map_origin) = maps.get(**in_map).ok()?;
+            let transform_c =
+                map_origin
+                    .cloned()
+                    .unwrap_or_default()
+                    .to_absolute(transform_to_tile_coord(
+                        chunk_t,
+                        tile_t,
+                        *tile_dims,
+                        tile_spacing.cloned(),
+                    ));
+            (transform_c != **tile_c).then_some((**in_map, tile_id, **tile_c, transform_c))
+        })
+}
+
+/// Turns the [`Transform`] of tile entities to match their [`Orientation`] whenever it changes.
+pub struct TileOrientationPlugin;
+
+impl Plugin for TileOrientationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, sync_tile_orientations);
+    }
+}
+
+/// Sets the rotation of tile entities with a changed [`Orientation`].
+pub fn sync_tile_orientations(
+    mut tiles: Query<(&Orientation, &mut Transform), (Changed<Orientation>, With<InChunk>)>,
+) {
+    for (orientation, mut transform) in tiles.iter_mut() {
+        transform.rotation = orientation.rotation();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::system::SystemState,
+        math::{Vec2, Vec3},
+    };
+    use bevy_tiles::{
+        coords::{Dir4, Dir8},
+        testing,
+    };
+
+    use crate::tiles::TileEntityMapQuery;
+
+    use super::*;
+
+    #[test]
+    fn snap_moves_tile_in_grid() {
+        let mut app = App::new();
+        app.add_plugins(TileTransformSyncPlugin::<2>::default());
+
+        let map_id = testing::spawn_map(app.world_mut(), 4, |map| {
+            map.insert((UseTransforms, TileDims([16.0, 16.0])));
+        });
+        let tile_id = testing::apply_map(app.world_mut(), map_id, |map| {
+            map.spawn_tile([3, 0], ()).id()
+        });
+        app.update();
+
+        // Slide the tile most of the way into the next chunk over.
+        app.world_mut()
+            .get_mut::<Transform>(tile_id)
+            .unwrap()
+            .translation += Vec3::new(30.0, -17.0, 0.0);
+        app.update();
+
+        assert_eq!(**app.world().get::<TileCoord<2>>(tile_id).unwrap(), [5, -1]);
+        let mut state = SystemState::<TileEntityMapQuery<Entity, (), 2>>::new(app.world_mut());
+        let tile_maps = state.get(app.world());
+        let tiles = tile_maps.get_map(map_id).unwrap();
+        assert_eq!(tiles.get_at([3, 0]), None);
+        assert_eq!(tiles.get_at([5, -1]), Some(tile_id));
+    }
+
+    #[test]
+    fn orientation_turns_transform() {
+        let mut app = App::new();
+        app.add_plugins(TileOrientationPlugin);
+
+        let map_id = testing::spawn_map(app.world_mut(), 4, |map| {
+            map.insert((UseTransforms, TileDims([16.0, 16.0])));
+        });
+        let tile_id = testing::apply_map(app.world_mut(), map_id, |map| {
+            map.spawn_tile([1, 2], Orientation::from(Dir4::East)).id()
+        });
+        app.update();
+
+        let transform = *app.world().get::<Transform>(tile_id).unwrap();
+        assert!((transform.rotation * Vec3::Y).abs_diff_eq(Vec3::X, 1e-6));
+        assert_eq!(transform.translation.truncate(), Vec2::new(16.0, 32.0));
+
+        app.world_mut().get_mut::<Orientation>(tile_id).unwrap().0 = Dir8::South;
+        app.update();
+        let transform = *app.world().get::<Transform>(tile_id).unwrap();
+        assert!((transform.rotation * Vec3::Y).abs_diff_eq(Vec3::NEG_Y, 1e-6));
+    }
+}
@@ -1,3 +1,8 @@
+#[cfg(feature = "strict-safety")]
+use std::marker::PhantomData;
+
+#[cfg(feature = "strict-safety")]
+use bevy::utils::HashMap;
 use bevy::{
     ecs::{
         entity::Entity,
@@ -7,7 +12,7 @@ use bevy::{
     prelude::Query,
 };
 use bevy_tiles::{
-    chunks::{ChunkMapQuery, ChunkQuery, InMap},
+    chunks::{ChunkMapQuery, ChunkQuery, InMap, MapQueryError},
     coords::{
         calculate_chunk_coordinate, calculate_tile_coordinate, calculate_tile_index,
         max_tile_index, CoordIterator,
@@ -26,8 +31,8 @@ where
     Q: QueryData + 'static,
     F: QueryFilter + 'static,
 {
-    tile_q: Query<'w, 's, Q, (F, With<InChunk>)>,
-    chunk_q: ChunkMapQuery<'w, 's, <EntityTile as TileDataQuery>::Source, With<InMap>, N>,
+    tile_q: Query<'w, 's, (Entity, Q), (F, With<InChunk>)>,
+    chunk_q: ChunkMapQuery<'w, 's, <EntityTile as TileDataQuery>::Source, With<InMap>, (), (), N>,
 }
 
 impl<'w, 's, Q, F, const N: usize> TileEntityMapQuery<'w, 's, Q, F, N>
@@ -39,20 +44,47 @@ where
     pub fn get_map(
         &self,
         map_id: Entity,
-    ) -> Option<TileEntityQuery<'_, '_, 's, Q::ReadOnly, F, N>> {
+    ) -> Result<TileEntityQuery<'_, '_, 's, Q::ReadOnly, F, N>, MapQueryError> {
         let chunk_q = self.chunk_q.get_map(map_id)?;
 
-        Some(TileEntityQuery {
+        Ok(TileEntityQuery {
             tile_q: self.tile_q.to_readonly(),
             chunk_q,
         })
     }
 
     /// Gets the query for a given map.
-    pub fn get_map_mut(&mut self, map_id: Entity) -> Option<TileEntityQuery<'_, '_, 's, Q, F, N>> {
+    pub fn get_map_mut(
+        &mut self,
+        map_id: Entity,
+    ) -> Result<TileEntityQuery<'_, '_, 's, Q, F, N>, MapQueryError> {
         let chunk_q = self.chunk_q.get_map_mut(map_id)?;
 
-        Some(TileEntityQuery {
+        Ok(TileEntityQuery {
+            tile_q: self.tile_q.reborrow(),
+            chunk_q,
+        })
+    }
+
+    /// Gets the query for the only map, returns an error if there isn't exactly one.
+    pub fn get_single_map(
+        &self,
+    ) -> Result<TileEntityQuery<'_, '_, 's, Q::ReadOnly, F, N>, MapQueryError> {
+        let chunk_q = self.chunk_q.get_single_map()?;
+
+        Ok(TileEntityQuery {
+            tile_q: self.tile_q.to_readonly(),
+            chunk_q,
+        })
+    }
+
+    /// Gets the query for the only map, returns an error if there isn't exactly one.
+    pub fn get_single_map_mut(
+        &mut self,
+    ) -> Result<TileEntityQuery<'_, '_, 's, Q, F, N>, MapQueryError> {
+        let chunk_q = self.chunk_q.get_single_map_mut()?;
+
+        Ok(TileEntityQuery {
             tile_q: self.tile_q.reborrow(),
             chunk_q,
         })
@@ -65,7 +97,7 @@ where
     Q: QueryData + 'static,
     F: QueryFilter + 'static,
 {
-    tile_q: Query<'w, 's, Q, (F, With<InChunk>)>,
+    tile_q: Query<'w, 's, (Entity, Q), (F, With<InChunk>)>,
     chunk_q: ChunkQuery<'a, 'w, 's, <EntityTile as TileDataQuery>::Source, With<InMap>, N>,
 }
 
@@ -100,7 +132,7 @@ where
         let chunk_c = calculate_chunk_coordinate(tile_c, self.chunk_q.map.get_chunk_size());
         let chunk_e = self.chunk_q.get_at(chunk_c)?;
         let tile_id = chunk_e.get(tile_i)?;
-        self.tile_q.get(**tile_id).ok()
+        self.tile_q.get(**tile_id).ok().map(|(_, tile)| tile)
     }
 
     /// Gets the query item for the given tile.
@@ -113,12 +145,14 @@ where
         let chunk_c = calculate_chunk_coordinate(tile_c, self.chunk_q.map.get_chunk_size());
         let chunk_e = self.chunk_q.get_at(chunk_c)?;
         let tile_id = chunk_e.get(tile_i)?;
-        self.tile_q.get_mut(**tile_id).ok()
+        self.tile_q.get_mut(**tile_id).ok().map(|(_, tile)| tile)
     }
 
     /// Gets the query item for the given tile.
     /// # Safety
     /// This function makes it possible to violate Rust's aliasing guarantees: please use responsibly.
+    /// # Note
+    /// Prefer [`TileEntityQuery::get_at_mut`] where possible.
     pub unsafe fn get_at_unchecked(
         &self,
         tile_c: impl Into<[i32; N]>,
@@ -128,7 +162,10 @@ where
         let chunk_c = calculate_chunk_coordinate(tile_c, self.chunk_q.map.get_chunk_size());
         let chunk_e = self.chunk_q.get_at(chunk_c)?;
         let tile_id = chunk_e.get(tile_i)?;
-        self.tile_q.get_unchecked(**tile_id).ok()
+        self.tile_q
+            .get_unchecked(**tile_id)
+            .ok()
+            .map(|(_, tile)| tile)
     }
 
     /// Iterate over all the tiles in a given space, starting at `corner_1`
@@ -140,8 +177,16 @@ where
     ) -> TileEntityQueryIter<'_, 's, Q::ReadOnly, F, N> {
         let corner_1 = corner_1.into();
         let corner_2 = corner_2.into();
+        #[cfg(not(feature = "strict-safety"))]
         // SAFETY: This thing is uses manual mem management
-        unsafe { TileEntityQueryIter::from_owned(self.to_readonly(), corner_1, corner_2) }
+        return unsafe { TileEntityQueryIter::from_owned(self.to_readonly(), corner_1, corner_2) };
+        #[cfg(feature = "strict-safety")]
+        return TileEntityQueryIter::from_tiles(
+            self.tile_ids(corner_1, corner_2)
+                .into_iter()
+                .filter_map(|tile_id| self.tile_q.get(tile_id).ok().map(|(_, tile)| tile))
+                .collect(),
+        );
     }
 
     /// Iterate over all the tiles in a given space, starting at `corner_1`
@@ -153,8 +198,41 @@ where
     ) -> TileEntityQueryIter<'_, 's, Q, F, N> {
         let corner_1 = corner_1.into();
         let corner_2 = corner_2.into();
+        #[cfg(not(feature = "strict-safety"))]
         // SAFETY: This thing is uses manual mem management
-        unsafe { TileEntityQueryIter::from_owned(self.reborrow(), corner_1, corner_2) }
+        return unsafe { TileEntityQueryIter::from_owned(self.reborrow(), corner_1, corner_2) };
+        #[cfg(feature = "strict-safety")]
+        return {
+            let order: HashMap<Entity, usize> = self
+                .tile_ids(corner_1, corner_2)
+                .into_iter()
+                .enumerate()
+                .map(|(i, tile_id)| (tile_id, i))
+                .collect();
+            let mut tiles: Vec<_> = self
+                .tile_q
+                .iter_mut()
+                .filter_map(|(tile_id, tile)| Some((*order.get(&tile_id)?, tile)))
+                .collect();
+            tiles.sort_unstable_by_key(|(i, _)| *i);
+            TileEntityQueryIter::from_tiles(tiles.into_iter().map(|(_, tile)| tile).collect())
+        };
+    }
+
+    /// The tile entities in a region, in iteration order.
+    #[cfg(feature = "strict-safety")]
+    fn tile_ids(&self, corner_1: [i32; N], corner_2: [i32; N]) -> Vec<Entity> {
+        let chunk_size = self.chunk_q.map.get_chunk_size();
+        CoordIterator::new(corner_1, corner_2)
+            .filter_map(|tile_c| {
+                let chunk_e = self
+                    .chunk_q
+                    .get_at(calculate_chunk_coordinate(tile_c, chunk_size))?;
+                chunk_e
+                    .get(calculate_tile_index(tile_c, chunk_size))
+                    .map(|tile_id| **tile_id)
+            })
+            .collect()
     }
 
     /// Iter all tiles in a given chunk.
@@ -233,8 +311,10 @@ where
 // Everything below here is astoundingly unsafe but I think it's sound
 // If we're iterating over a readonly query, we're manually managing the lifetime of
 // the readonly query by making the TileQueryIter own it as a reference.
+// With the `strict-safety` feature the items are collected up front instead.
 
 /// Iterates over all the tiles in a region.
+#[cfg(not(feature = "strict-safety"))]
 pub struct TileEntityQueryIter<'a, 's, Q, F, const N: usize>
 where
     Q: QueryData + 'static,
@@ -243,6 +323,19 @@ where
     coord_iter: CoordIterator<N>,
     tile_q: TileEntityQuery<'a, 'a, 's, Q, F, N>,
 }
+
+/// Iterates over all the tiles in a region.
+#[cfg(feature = "strict-safety")]
+pub struct TileEntityQueryIter<'a, 's, Q, F, const N: usize>
+where
+    Q: QueryData + 'static,
+    F: QueryFilter + 'static,
+{
+    tiles: std::vec::IntoIter<<Q as WorldQuery>::Item<'a>>,
+    marker: PhantomData<fn() -> (&'s (), F, [(); N])>,
+}
+
+#[cfg(not(feature = "strict-safety"))]
 impl<'a, 's, Q, F, const N: usize> TileEntityQueryIter<'a, 's, Q, F, N>
 where
     Q: QueryData + 'static,
@@ -260,6 +353,21 @@ where
     }
 }
 
+#[cfg(feature = "strict-safety")]
+impl<'a, 's, Q, F, const N: usize> TileEntityQueryIter<'a, 's, Q, F, N>
+where
+    Q: QueryData + 'static,
+    F: QueryFilter + 'static,
+{
+    fn from_tiles(tiles: Vec<<Q as WorldQuery>::Item<'a>>) -> Self {
+        Self {
+            tiles: tiles.into_iter(),
+            marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(not(feature = "strict-safety"))]
 impl<'a, 's, Q, F, const N: usize> Iterator for TileEntityQueryIter<'a, 's, Q, F, N>
 where
     Q: QueryData + 'static,
@@ -289,3 +397,16 @@ where
         None
     }
 }
+
+#[cfg(feature = "strict-safety")]
+impl<'a, 's, Q, F, const N: usize> Iterator for TileEntityQueryIter<'a, 's, Q, F, N>
+where
+    Q: QueryData + 'static,
+    F: QueryFilter + 'static,
+{
+    type Item = <Q as WorldQuery>::Item<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tiles.next()
+    }
+}
@@ -0,0 +1,251 @@
+use std::{borrow::Cow, marker::PhantomData};
+
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventWriter},
+        query::Changed,
+        system::{Commands, Query},
+    },
+    prelude::Deref,
+    utils::HashSet,
+};
+use bevy_tiles::{chunks::InMap, coords::CoordIterator, tiles::TileQuery};
+
+use crate::entity_tile::{InChunk, TileCoord};
+
+/// The tiles covered by a [`TileTrigger`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TileRegion<const N: usize> {
+    /// Every tile between two corners (inclusive).
+    Rect {
+        /// The lowest corner of the region.
+        min: [i32; N],
+        /// The highest corner of the region.
+        max: [i32; N],
+    },
+    /// An arbitrary set of tiles.
+    Coords(HashSet<[i32; N]>),
+}
+
+impl<const N: usize> TileRegion<N> {
+    /// Create a rectangular region between two corners (inclusive).
+    pub fn rect(corner_1: impl Into<[i32; N]>, corner_2: impl Into<[i32; N]>) -> Self {
+        let mut min = corner_1.into();
+        let mut max = corner_2.into();
+        for i in 0..N {
+            if min[i] > max[i] {
+                std::mem::swap(&mut min[i], &mut max[i]);
+            }
+        }
+        Self::Rect { min, max }
+    }
+
+    /// Create a region from the tiles of a marker layer matching `predicate` between two corners (inclusive).
+    /// # Note
+    /// This is a snapshot of the layer, so the region won't follow later edits to the layer.
+    pub fn from_layer<T, F>(
+        tiles: &TileQuery<'_, '_, '_, &T, N>,
+        corner_1: impl Into<[i32; N]>,
+        corner_2: impl Into<[i32; N]>,
+        predicate: F,
+    ) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) -> bool,
+    {
+        Self::Coords(
+            CoordIterator::new(corner_1, corner_2)
+                .filter(|tile_c| tiles.get_at(*tile_c).is_some_and(&predicate))
+                .collect(),
+        )
+    }
+
+    /// Check if a tile is part of this region.
+    #[inline]
+    pub fn contains(&self, tile_c: impl Into<[i32; N]>) -> bool {
+        let tile_c = tile_c.into();
+        match self {
+            TileRegion::Rect { min, max } => {
+                (0..N).all(|i| min[i] <= tile_c[i] && tile_c[i] <= max[i])
+            }
+            TileRegion::Coords(coords) => coords.contains(&tile_c),
+        }
+    }
+}
+
+/// A named region of a tilemap that sends [`EnteredRegion`] and [`LeftRegion`] events
+/// when tile entities move across its boundary.
+#[derive(Component, Clone, Debug)]
+pub struct TileTrigger<const N: usize> {
+    /// Name of the trigger, for telling triggers apart in event handlers.
+    pub name: Cow<'static, str>,
+    /// The map this trigger is placed in.
+    pub map_id: Entity,
+    /// The tiles covered by this trigger.
+    pub region: TileRegion<N>,
+}
+
+impl<const N: usize> TileTrigger<N> {
+    /// Create a new trigger.
+    pub fn new(name: impl Into<Cow<'static, str>>, map_id: Entity, region: TileRegion<N>) -> Self {
+        Self {
+            name: name.into(),
+            map_id,
+            region,
+        }
+    }
+}
+
+/// The triggers a tile entity is currently inside of.
+/// # Note:
+/// This is only accurate when mutated by the plugin.
+#[derive(Component, Deref, Clone, Debug, Default)]
+pub struct InTriggers(pub(crate) Vec<Entity>);
+
+/// Sent when a tile entity moves into a [`TileTrigger`].
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnteredRegion {
+    /// The tile entity that moved.
+    pub entity: Entity,
+    /// The trigger that was entered.
+    pub trigger: Entity,
+}
+
+/// Sent when a tile entity moves out of a [`TileTrigger`].
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LeftRegion {
+    /// The tile entity that moved.
+    pub entity: Entity,
+    /// The trigger that was left.
+    pub trigger: Entity,
+}
+
+/// Adds [`TileTrigger`] tracking for maps with `N` dimensions.
+pub struct TileTriggerPlugin<const N: usize>(PhantomData<[(); N]>);
+
+impl<const N: usize> Default for TileTriggerPlugin<N> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<const N: usize> Plugin for TileTriggerPlugin<N> {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EnteredRegion>()
+            .add_event::<LeftRegion>()
+            .add_systems(Update, update_tile_triggers::<N>);
+    }
+}
+
+/// Sends trigger events for tile entities whose [`TileCoord`] changed.
+pub fn update_tile_triggers<const N: usize>(
+    mut commands: Commands,
+    mut moved: Query<
+        (Entity, &TileCoord<N>, &InChunk, Option<&mut InTriggers>),
+        Changed<TileCoord<N>>,
+    >,
+    chunks: Query<&InMap>,
+    triggers: Query<(Entity, &TileTrigger<N>)>,
+    mut entered: EventWriter<EnteredRegion>,
+    mut left: EventWriter<LeftRegion>,
+) {
+    for (entity, tile_c, in_chunk, in_triggers) in moved.iter_mut() {
+        let map_id = chunks.get(**in_chunk).ok().map(|in_map| **in_map);
+        let inside: Vec<Entity> = triggers
+            .iter()
+            .filter(|(_, trigger)| {
+                Some(trigger.map_id) == map_id && trigger.region.contains(**tile_c)
+            })
+            .map(|(trigger_id, _)| trigger_id)
+            .collect();
+
+        let previous = in_triggers.as_ref().map(|t| t.0.as_slice()).unwrap_or(&[]);
+        for trigger in previous.iter().filter(|t| !inside.contains(t)) {
+            left.send(LeftRegion {
+                entity,
+                trigger: *trigger,
+            });
+        }
+        for trigger in inside.iter().filter(|t| !previous.contains(t)) {
+            entered.send(EnteredRegion {
+                entity,
+                trigger: *trigger,
+            });
+        }
+
+        match in_triggers {
+            Some(mut in_triggers) => in_triggers.0 = inside,
+            None => {
+                commands.entity(entity).insert(InTriggers(inside));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+    use bevy_tiles::testing;
+
+    use crate::commands::TileMapCommandsECSExt;
+
+    use super::*;
+
+    fn drain<E: Event>(app: &mut App) -> Vec<E> {
+        app.world_mut()
+            .resource_mut::<Events<E>>()
+            .drain()
+            .collect()
+    }
+
+    fn move_tile(app: &mut App, map_id: Entity, old_c: [i32; 2], new_c: [i32; 2]) {
+        testing::apply_map(app.world_mut(), map_id, |map| {
+            map.move_tile(old_c, new_c);
+        });
+    }
+
+    #[test]
+    fn enter_and_leave() {
+        let mut app = App::new();
+        app.add_plugins(TileTriggerPlugin::<2>::default());
+
+        let map_id = testing::spawn_map(app.world_mut(), 4, |_| {});
+        let tile_id = testing::apply_map(app.world_mut(), map_id, |map| {
+            map.spawn_tile([0, 0], ()).id()
+        });
+        let trigger_id = app
+            .world_mut()
+            .spawn(TileTrigger::new(
+                "checkpoint",
+                map_id,
+                TileRegion::rect([2, -1], [5, 1]),
+            ))
+            .id();
+        app.update();
+        assert!(drain::<EnteredRegion>(&mut app).is_empty());
+
+        move_tile(&mut app, map_id, [0, 0], [3, 0]);
+        app.update();
+        let expected = EnteredRegion {
+            entity: tile_id,
+            trigger: trigger_id,
+        };
+        assert_eq!(drain::<EnteredRegion>(&mut app), vec![expected]);
+
+        move_tile(&mut app, map_id, [3, 0], [4, 1]);
+        app.update();
+        assert!(drain::<EnteredRegion>(&mut app).is_empty());
+        assert!(drain::<LeftRegion>(&mut app).is_empty());
+
+        move_tile(&mut app, map_id, [4, 1], [4, 2]);
+        app.update();
+        let expected = LeftRegion {
+            entity: tile_id,
+            trigger: trigger_id,
+        };
+        assert_eq!(drain::<LeftRegion>(&mut app), vec![expected]);
+    }
+}
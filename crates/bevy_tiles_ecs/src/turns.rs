@@ -0,0 +1,144 @@
+use bevy::{
+    app::{App, Plugin},
+    ecs::{component::Component, entity::Entity, event::Event},
+};
+
+/// Something a tile entity wants to do this turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Intent<const N: usize> {
+    /// Move to the given coordinate.
+    Move([i32; N]),
+    /// Act on whatever tile entity is at the given coordinate.
+    Attack([i32; N]),
+    /// Do nothing.
+    Wait,
+}
+
+/// How an [`Intent`] played out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The actor moved to its target.
+    Moved,
+    /// The actor couldn't move because its target was taken by the given tile entity.
+    Blocked(Entity),
+    /// The actor attacked the given tile entity, or nothing if the target was empty.
+    Attacked(Option<Entity>),
+    /// The actor waited.
+    Waited,
+    /// The actor wasn't a tile in the map when its intent was resolved (ex: it was despawned earlier in the turn).
+    Missing,
+}
+
+/// Sent for every intent resolved by [`crate::commands::TileMapCommandsECSExt::resolve_turn`], in resolution order.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IntentResolved<const N: usize> {
+    /// The tile entity that acted.
+    pub actor: Entity,
+    /// What the actor wanted to do.
+    pub intent: Intent<N>,
+    /// What happened.
+    pub outcome: Outcome,
+}
+
+/// Intents queued for the next turn of a map, add this to the map entity.
+#[derive(Component, Clone, Debug, Default)]
+pub struct TurnQueue<const N: usize> {
+    pub(crate) intents: Vec<QueuedIntent<N>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct QueuedIntent<const N: usize> {
+    pub(crate) actor: Entity,
+    pub(crate) initiative: i32,
+    pub(crate) intent: Intent<N>,
+}
+
+impl<const N: usize> TurnQueue<N> {
+    /// Queue an intent for a tile entity, intents with a higher initiative resolve first.
+    /// Intents with the same initiative resolve in the order they were queued.
+    pub fn push(&mut self, actor: Entity, initiative: i32, intent: Intent<N>) {
+        self.intents.push(QueuedIntent {
+            actor,
+            initiative,
+            intent,
+        });
+    }
+
+    /// Number of queued intents.
+    pub fn len(&self) -> usize {
+        self.intents.len()
+    }
+
+    /// True if no intents are queued.
+    pub fn is_empty(&self) -> bool {
+        self.intents.is_empty()
+    }
+
+    /// Take the queued intents in resolution order.
+    pub(crate) fn take_ordered(&mut self) -> Vec<QueuedIntent<N>> {
+        let mut intents = std::mem::take(&mut self.intents);
+        intents.sort_by_key(|queued| std::cmp::Reverse(queued.initiative));
+        intents
+    }
+}
+
+/// Adds [`IntentResolved`] events for maps with `N` dimensions.
+#[derive(Default)]
+pub struct TurnPlugin<const N: usize>;
+
+impl<const N: usize> Plugin for TurnPlugin<N> {
+    fn build(&self, app: &mut App) {
+        app.add_event::<IntentResolved<N>>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+    use bevy_tiles::testing;
+
+    use crate::commands::TileMapCommandsECSExt;
+
+    use super::*;
+
+    #[test]
+    fn resolves_in_initiative_order() {
+        let mut app = App::new();
+        app.add_plugins(TurnPlugin::<2>);
+
+        let map_id = testing::spawn_map(app.world_mut(), 4, |_| {});
+        let (a, b) = testing::apply_map(app.world_mut(), map_id, |map| {
+            let a = map.spawn_tile([0, 0], ()).id();
+            let b = map.spawn_tile([1, 0], ()).id();
+            let mut queue = TurnQueue::<2>::default();
+            // `a` queues first, but `b` has to get out of the way before `a` can follow.
+            queue.push(a, 1, Intent::Move([1, 0]));
+            queue.push(b, 5, Intent::Move([2, 0]));
+            queue.push(a, 0, Intent::Move([2, 0]));
+            queue.push(b, 0, Intent::Attack([1, 0]));
+            map.insert(queue);
+            (a, b)
+        });
+
+        testing::apply_map(app.world_mut(), map_id, |map| {
+            map.resolve_turn();
+        });
+
+        let outcomes: Vec<_> = app
+            .world_mut()
+            .resource_mut::<Events<IntentResolved<2>>>()
+            .drain()
+            .map(|resolved| (resolved.actor, resolved.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                (b, Outcome::Moved),
+                (a, Outcome::Moved),
+                (a, Outcome::Blocked(b)),
+                (b, Outcome::Attacked(Some(a))),
+            ]
+        );
+        assert!(app.world().get::<TurnQueue<2>>(map_id).unwrap().is_empty());
+    }
+}
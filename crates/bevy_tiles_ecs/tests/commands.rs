@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+use bevy_tiles::{
+    commands::{ChunkWriter, TempRemove, TileWorldExt},
+    maps::TileMap,
+    merge::{MergeLayers, MergePolicy},
+};
+use bevy_tiles_ecs::{
+    commands::TileMapCommandsECSExt,
+    entity_tile::{merge_entity_tiles, EntityTile, InChunk, TileCoord},
+    pool::TilePool,
+    TilesPlugin,
+};
+
+#[path = "../../bevy_tiles/tests/harness/mod.rs"]
+mod harness;
+
+use harness::Harness;
+
+fn coord(harness: &mut Harness, tile_id: Entity) -> Option<[i32; 2]> {
+    harness
+        .world()
+        .get::<TileCoord<2>>(tile_id)
+        .map(|tile_c| **tile_c)
+}
+
+#[test]
+fn move_overwrites_target() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    let mut ids = Vec::new();
+    harness.apply_map(map_id, |map| {
+        ids.push(map.spawn_tile([0, 0], ()).id());
+        ids.push(map.spawn_tile([6, 0], ()).id());
+    });
+
+    harness.apply_map(map_id, |map| {
+        map.move_tile([0, 0], [6, 0]);
+    });
+    assert_eq!(coord(&mut harness, ids[0]), Some([6, 0]));
+    assert!(harness.world().get_entity(ids[1]).is_err());
+}
+
+#[test]
+fn swap_and_swap_with_empty() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    let mut ids = Vec::new();
+    harness.apply_map(map_id, |map| {
+        ids.push(map.spawn_tile([0, 0], ()).id());
+        ids.push(map.spawn_tile([5, 5], ()).id());
+    });
+
+    harness.apply_map(map_id, |map| {
+        map.swap_tiles([0, 0], [5, 5]);
+    });
+    assert_eq!(coord(&mut harness, ids[0]), Some([5, 5]));
+    assert_eq!(coord(&mut harness, ids[1]), Some([0, 0]));
+
+    harness.apply_map(map_id, |map| {
+        map.swap_tiles([-3, 2], [0, 0]);
+    });
+    assert_eq!(coord(&mut harness, ids[1]), Some([-3, 2]));
+}
+
+#[test]
+fn swapped_chunks_move_tile_coords() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    let mut ids = Vec::new();
+    harness.apply_map(map_id, |map| {
+        ids.push(map.spawn_tile([1, 0], ()).id());
+        ids.push(map.spawn_tile([5, 1], ()).id());
+    });
+
+    harness.apply_map(map_id, |map| {
+        map.swap_chunks([0, 0], [1, 0]);
+    });
+    assert_eq!(coord(&mut harness, ids[0]), Some([5, 0]));
+    assert_eq!(coord(&mut harness, ids[1]), Some([1, 1]));
+
+    harness.apply_map(map_id, |map| {
+        map.swap_chunks([1, 0], [-1, 2]);
+    });
+    assert_eq!(coord(&mut harness, ids[0]), Some([-3, 8]));
+    assert_eq!(coord(&mut harness, ids[1]), Some([1, 1]));
+}
+
+#[test]
+fn set_chunk_data_places_and_despawns_tiles() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(2);
+    let mut ids = Vec::new();
+    harness.apply_map(map_id, |map| {
+        ids.push(map.spawn_tile([0, 0], ()).id());
+        ids.push(map.spawn_tile([1, 0], ()).id());
+        ids.push(map.spawn_tile([0, 1], ()).id());
+    });
+
+    let world = harness.world();
+    let tile_id = world.spawn_empty().id();
+    {
+        let mut map = world.temp_remove::<TileMap<2>>(map_id).unwrap();
+        ChunkWriter::new(&mut map, [0, 0]).set_data(vec![
+            Some(EntityTile(tile_id)),
+            Some(EntityTile(ids[1])),
+            None,
+            None,
+        ]);
+    }
+    assert!(world.get_entity(ids[0]).is_err());
+    assert!(world.get_entity(ids[2]).is_err());
+    assert_eq!(coord(&mut harness, ids[1]), Some([1, 0]));
+    assert_eq!(coord(&mut harness, tile_id), Some([0, 0]));
+}
+
+#[test]
+fn cleared_chunks_despawn_tiles() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    let mut ids = Vec::new();
+    harness.apply_map(map_id, |map| {
+        ids.push(map.spawn_tile([0, 0], ()).id());
+        ids.push(map.spawn_tile([3, 2], ()).id());
+        ids.push(map.spawn_tile([4, 0], ()).id());
+    });
+
+    harness.apply_map(map_id, |map| {
+        map.chunk([0, 0]).clear::<EntityTile>();
+    });
+    let world = harness.world();
+    assert!(world.get_entity(ids[0]).is_err());
+    assert!(world.get_entity(ids[1]).is_err());
+    assert_eq!(coord(&mut harness, ids[2]), Some([4, 0]));
+}
+
+#[test]
+fn overwritten_moves_despawn_tiles() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    let mut ids = Vec::new();
+    harness.apply_map(map_id, |map| {
+        ids.push(map.spawn_tile([0, 0], ()).id());
+        ids.push(map.spawn_tile([1, 0], ()).id());
+        ids.push(map.spawn_tile([5, 0], ()).id());
+    });
+
+    harness.apply_map(map_id, |map| {
+        map.move_tile_batch::<EntityTile, _>([([0, 0], [5, 0]), ([1, 0], [5, 0])]);
+    });
+    let world = harness.world();
+    assert!(world.get_entity(ids[0]).is_err());
+    assert!(world.get_entity(ids[2]).is_err());
+    assert_eq!(coord(&mut harness, ids[1]), Some([5, 0]));
+}
+
+#[test]
+fn partly_replaced_tuples_despawn_tiles() {
+    let mut harness = Harness::new(TilesPlugin);
+    let map_id = harness.spawn_map(4);
+    let mut ids = Vec::new();
+    harness.apply_map(map_id, |map| {
+        ids.push(map.spawn_tile([0, 0], ()).id());
+    });
+
+    let world = harness.world();
+    let tile_id = world.spawn_empty().id();
+    let replaced =
+        TileWorldExt::<2>::insert_tile(world, map_id, [0, 0], (EntityTile(tile_id), 1u8));
+    assert_eq!(replaced, None);
+    assert!(world.get_entity(ids[0]).is_err());
+    assert_eq!(coord(&mut harness, tile_id), Some([0, 0]));
+}
+
+#[test]
+fn despawned_tiles_are_pooled_and_reused() {
+    #[derive(Component)]
+    struct Ore;
+
+    let mut harness = Harness::new(TilesPlugin);
+    harness.world().insert_resource(TilePool::new(1));
+    let map_id = harness.spawn_map(4);
+    let mut ids = Vec::new();
+    harness.apply_map(map_id, |map| {
+        ids.push(map.spawn_tile([0, 0], Ore).id());
+        ids.push(map.spawn_tile([1, 0], Ore).id());
+    });
+    let children = [ids[0], ids[1]].map(|tile_id| {
+        let child_id = harness.world().spawn_empty().id();
+        harness.world().entity_mut(tile_id).add_child(child_id);
+        child_id
+    });
+
+    harness.apply_map(map_id, |map| {
+        map.despawn_tile([0, 0]).despawn_tile([1, 0]);
+    });
+    let world = harness.world();
+    assert_eq!(world.resource::<TilePool>().len(), 1);
+    assert!(world.get_entity(ids[1]).is_err());
+    assert!(children
+        .iter()
+        .all(|child| world.get_entity(*child).is_err()));
+    let pooled = world.entity(ids[0]);
+    assert!(!pooled.contains::<Ore>() && !pooled.contains::<TileCoord<2>>());
+
+    let mut pool = harness.world().remove_resource::<TilePool>().unwrap();
+    let mut reused = Vec::new();
+    harness.apply_map(map_id, |map| {
+        reused.push(map.spawn_pooled_tile(&mut pool, [3, 3], Ore).id());
+        reused.push(map.spawn_pooled_tile(&mut pool, [2, 3], Ore).id());
+    });
+    assert_eq!(reused[0], ids[0]);
+    assert_ne!(reused[1], ids[1]);
+    assert_eq!(coord(&mut harness, ids[0]), Some([3, 3]));
+    assert!(harness.world().entity(ids[0]).contains::<Ore>());
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn spawn_pooled_tile_skips_despawned_entities() {
+    let mut harness = Harness::new(TilesPlugin);
+    harness.world().insert_resource(TilePool::new(2));
+    let map_id = harness.spawn_map(4);
+    let mut ids = Vec::new();
+    harness.apply_map(map_id, |map| {
+        ids.push(map.spawn_tile([0, 0], ()).id());
+        ids.push(map.spawn_tile([1, 0], ()).id());
+    });
+    harness.apply_map(map_id, |map| {
+        map.despawn_tile([0, 0]).despawn_tile([1, 0]);
+    });
+
+    // Despawn the pooled entity that would be reused first.
+    harness.world().despawn(ids[1]);
+    let mut pool = harness.world().remove_resource::<TilePool>().unwrap();
+    let mut reused = Vec::new();
+    harness.apply_map(map_id, |map| {
+        reused.push(map.spawn_pooled_tile(&mut pool, [3, 3], ()).id());
+        reused.push(map.spawn_pooled_tile(&mut pool, [2, 3], ()).id());
+    });
+    assert_eq!(reused[0], ids[0]);
+    assert_ne!(reused[1], ids[1]);
+    assert_eq!(coord(&mut harness, reused[0]), Some([3, 3]));
+    assert_eq!(coord(&mut harness, reused[1]), Some([2, 3]));
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn merged_maps_move_tile_entities() {
+    for policy in [MergePolicy::KeepDestination, MergePolicy::KeepSource] {
+        let mut harness = Harness::new(TilesPlugin);
+        let mut layers = MergeLayers::<2>::default();
+        layers.register_with(merge_entity_tiles::<2>);
+        harness.world().insert_resource(layers);
+        let dst_id = harness.spawn_map(4);
+        let src_id = harness.spawn_map(2);
+        let mut dst_tile = None;
+        harness.apply_map(dst_id, |map| {
+            dst_tile = Some(map.spawn_tile([5, 0], ()).id());
+        });
+        let mut src_tiles = Vec::new();
+        harness.apply_map(src_id, |map| {
+            src_tiles.push(map.spawn_tile([0, 0], ()).id());
+            src_tiles.push(map.spawn_tile([1, 1], ()).id());
+        });
+
+        // [0, 0] lands on the destination tile at [5, 0].
+        harness.apply_map(dst_id, |map| {
+            map.merge_from(src_id, [5, 0], policy);
+        });
+        assert!(harness.world().get_entity(src_id).is_err());
+
+        let chunk_id = harness.chunk(dst_id, [6, 1]).unwrap();
+        let moved = src_tiles[1];
+        assert_eq!(coord(&mut harness, moved), Some([6, 1]));
+        let world = harness.world();
+        assert_eq!(
+            world.get::<InChunk>(moved).map(|in_chunk| **in_chunk),
+            Some(chunk_id)
+        );
+        assert_eq!(world.get::<Parent>(moved).map(Parent::get), Some(chunk_id));
+
+        let (kept, lost) = match policy {
+            MergePolicy::KeepDestination => (dst_tile.unwrap(), src_tiles[0]),
+            MergePolicy::KeepSource => (src_tiles[0], dst_tile.unwrap()),
+        };
+        assert!(harness.world().get_entity(lost).is_err());
+        assert_eq!(coord(&mut harness, kept), Some([5, 0]));
+    }
+}
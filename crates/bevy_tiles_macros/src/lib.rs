@@ -0,0 +1,74 @@
+//! Derive macros for `bevy_tiles`, re-exported from the `bevy_tiles` crate.
+
+#![deny(missing_docs)]
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput};
+
+/// Implements `bevy_tiles::queries::TileComponent` for a plain data type, storing it in a
+/// `ChunkData` of its own like the built in tile types.
+/// # Note
+/// The type has to be `Send + Sync + 'static`, and can't be a union.
+#[proc_macro_derive(TileComponent)]
+pub fn derive_tile_component(input: TokenStream) -> TokenStream {
+    let mut ast = parse_macro_input!(input as DeriveInput);
+    if let Data::Union(data) = &ast.data {
+        return syn::Error::new(
+            data.union_token.span,
+            "TileComponent can't be derived for unions",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    ast.generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(Self: Send + Sync + 'static));
+    let name = &ast.ident;
+    let (impl_generics, type_generics, where_clause) = ast.generics.split_for_impl();
+
+    quote! {
+        /// # Safety:
+        /// Plain data, no other components are touched.
+        unsafe impl #impl_generics ::bevy_tiles::queries::TileComponent for #name #type_generics #where_clause {
+            const PLAIN: bool = true;
+
+            fn insert_tile_into_chunk<const N: usize>(
+                self,
+                chunk: ::bevy_tiles::__private::EntityWorldMut<'_>,
+                _chunk_c: [i32; N],
+                chunk_size: usize,
+                _use_transforms: bool,
+                _tile_dims: Option<::bevy_tiles::maps::TileDims<N>>,
+                _tile_spacing: Option<::bevy_tiles::maps::TileSpacing<N>>,
+                _tile_c: [i32; N],
+                tile_i: usize,
+            ) -> Option<Self> {
+                ::bevy_tiles::queries::insert_plain_tile_into_chunk::<Self, N>(self, chunk, chunk_size, tile_i)
+            }
+
+            fn insert_tile_batch_into_chunk<const N: usize>(
+                tiles: impl Iterator<Item = Self>,
+                chunk: ::bevy_tiles::__private::EntityWorldMut<'_>,
+                _chunk_c: [i32; N],
+                chunk_size: usize,
+                _use_transforms: bool,
+                _tile_dims: Option<::bevy_tiles::maps::TileDims<N>>,
+                _tile_spacing: Option<::bevy_tiles::maps::TileSpacing<N>>,
+                tile_is: impl Iterator<Item = ([i32; N], usize)>,
+            ) -> impl Iterator<Item = Self> {
+                ::bevy_tiles::queries::insert_plain_tile_batch_into_chunk::<Self, N>(tiles, chunk, chunk_size, tile_is)
+            }
+
+            fn take_tile_from_chunk(
+                chunk: &mut ::bevy_tiles::__private::EntityWorldMut<'_>,
+                tile_i: usize,
+            ) -> Option<Self> {
+                ::bevy_tiles::queries::take_plain_tile_from_chunk::<Self>(chunk, tile_i)
+            }
+        }
+    }
+    .into()
+}